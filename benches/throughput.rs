@@ -0,0 +1,356 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tokio::runtime::Runtime;
+use tokio_actor::actors;
+
+const MSGS: u64 = 10_000;
+
+#[actors]
+mod plain {
+    pub enum CounterMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { n, resp } => {
+                    self.total += n;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[actors(batch = 32)]
+mod batched {
+    pub enum CounterMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { n, resp } => {
+                    self.total += n;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// same actor twice, once with the payload behind `#[shared]`
+#[actors]
+mod owned {
+    pub enum SinkMsg {
+        Data { payload: Vec<u8> },
+        Len { resp: usize },
+    }
+
+    pub struct Sink {
+        pub len: usize,
+    }
+
+    impl Sink {
+        async fn process(&mut self, msg: SinkMsg) {
+            match msg {
+                SinkMsg::Data { payload } => self.len += payload.len(),
+                SinkMsg::Len { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.len);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[actors]
+mod shared {
+    pub enum SinkMsg {
+        Data {
+            #[shared]
+            payload: Vec<u8>,
+        },
+        Len {
+            resp: usize,
+        },
+    }
+
+    pub struct Sink {
+        pub len: usize,
+    }
+
+    impl Sink {
+        async fn process(&mut self, msg: SinkMsg) {
+            match msg {
+                SinkMsg::Data { payload } => self.len += payload.len(),
+                SinkMsg::Len { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.len);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// the same 4 MB reply by value and through `#[resp(boxed)]`/`#[resp(boxed_public)]`
+#[actors]
+mod reply {
+    #[derive(Clone)]
+    pub struct Rows(pub [u8; 4 << 20]);
+
+    pub enum SnapshotMsg {
+        ByValue {
+            resp: Rows,
+        },
+        #[resp(boxed)]
+        Boxed {
+            resp: Rows,
+        },
+        #[resp(boxed_public)]
+        BoxedPublic {
+            resp: Rows,
+        },
+    }
+
+    pub struct Snapshot {
+        pub rows: Box<Rows>,
+    }
+
+    impl Snapshot {
+        async fn process(&mut self, msg: SnapshotMsg) {
+            match msg {
+                SnapshotMsg::ByValue { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(*self.rows.clone());
+                    }
+                }
+                SnapshotMsg::Boxed { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.rows.clone());
+                    }
+                }
+                SnapshotMsg::BoxedPublic { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.rows.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+const RECIPIENTS: usize = 100;
+const PAYLOAD: usize = 1 << 20;
+
+// both modules generate the same api, so the benches are written once
+macro_rules! tell {
+    ($m:ident, $producers:expr) => {
+        async {
+            let actor = $m::ActorCounter::spawn($m::Counter { total: 0 });
+            let per_producer = MSGS / $producers;
+            let mut producers = vec![];
+            for _ in 0..$producers {
+                let mut actor = actor.clone();
+                producers.push(tokio::spawn(async move {
+                    for _ in 0..per_producer {
+                        actor
+                            .add_no_wait($m::CounterMsg::Add { n: 1, resp: None })
+                            .await
+                            .unwrap();
+                    }
+                }));
+            }
+            for p in producers {
+                p.await.unwrap();
+            }
+            // the ask queues behind every tell, so it returns once all of them are processed
+            let mut actor = actor;
+            actor
+                .add($m::CounterMsg::Add { n: 0, resp: None })
+                .await
+                .unwrap()
+        }
+    };
+}
+
+fn tell_throughput(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("tell");
+    group.throughput(Throughput::Elements(MSGS));
+    group.bench_function("plain", |b| b.to_async(&rt).iter(|| tell!(plain, 1)));
+    group.bench_function("batch_32", |b| b.to_async(&rt).iter(|| tell!(batched, 1)));
+    group.finish();
+}
+
+fn ask_latency(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("ask");
+    let actor = rt.block_on(async { plain::ActorCounter::spawn(plain::Counter { total: 0 }) });
+    group.bench_function("plain", |b| {
+        b.to_async(&rt).iter(|| {
+            let mut actor = actor.clone();
+            async move {
+                actor
+                    .add(plain::CounterMsg::Add { n: 1, resp: None })
+                    .await
+                    .unwrap()
+            }
+        })
+    });
+    let actor_batched =
+        rt.block_on(async { batched::ActorCounter::spawn(batched::Counter { total: 0 }) });
+    group.bench_function("batch_32", |b| {
+        b.to_async(&rt).iter(|| {
+            let mut actor = actor_batched.clone();
+            async move {
+                actor
+                    .add(batched::CounterMsg::Add { n: 1, resp: None })
+                    .await
+                    .unwrap()
+            }
+        })
+    });
+    // the oneshot every ask allocates, on its own: its share of `ask/plain`
+    group.bench_function("oneshot_only", |b| {
+        b.to_async(&rt).iter(|| async {
+            let (s, r) = tokio::sync::oneshot::channel::<u64>();
+            let _ = s.send(1);
+            r.await.unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn contention(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("contention");
+    group.throughput(Throughput::Elements(MSGS));
+    for producers in [2u64, 4, 8] {
+        group.bench_with_input(BenchmarkId::new("plain", producers), &producers, |b, &n| {
+            b.to_async(&rt).iter(|| tell!(plain, n))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("batch_32", producers),
+            &producers,
+            |b, &n| b.to_async(&rt).iter(|| tell!(batched, n)),
+        );
+    }
+    group.finish();
+}
+
+// fans one 1 MB payload out to every actor and waits until all of them got it
+macro_rules! broadcast {
+    ($m:ident, $actors:expr, $payload:expr) => {{
+        let mut actors = $actors.clone();
+        let payload = $payload.clone();
+        async move {
+            for actor in &actors {
+                actor
+                    .send_into($m::SinkMsg::Data {
+                        payload: payload.clone(),
+                    })
+                    .await
+                    .unwrap();
+            }
+            for actor in &mut actors {
+                actor.len($m::SinkMsg::Len { resp: None }).await.unwrap();
+            }
+        }
+    }};
+}
+
+fn broadcast(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("broadcast_1mb");
+    group.throughput(Throughput::Elements(RECIPIENTS as u64));
+    let owned_actors: Vec<_> = rt.block_on(async {
+        (0..RECIPIENTS)
+            .map(|_| owned::ActorSink::spawn(owned::Sink { len: 0 }))
+            .collect()
+    });
+    let owned_payload = vec![7u8; PAYLOAD];
+    group.bench_function("owned", |b| {
+        b.to_async(&rt)
+            .iter(|| broadcast!(owned, owned_actors, owned_payload))
+    });
+    let shared_actors: Vec<_> = rt.block_on(async {
+        (0..RECIPIENTS)
+            .map(|_| shared::ActorSink::spawn(shared::Sink { len: 0 }))
+            .collect()
+    });
+    let shared_payload = std::sync::Arc::new(vec![7u8; PAYLOAD]);
+    group.bench_function("shared", |b| {
+        b.to_async(&rt)
+            .iter(|| broadcast!(shared, shared_actors, shared_payload))
+    });
+    group.finish();
+}
+
+// asks for a 4 MB reply from a worker thread, whose stack can hold one by value
+macro_rules! snapshot {
+    ($actor:expr, $method:ident, $variant:ident) => {{
+        let mut actor = $actor.clone();
+        async move {
+            tokio::spawn(async move {
+                let rows = actor
+                    .$method(reply::SnapshotMsg::$variant { resp: None })
+                    .await
+                    .unwrap();
+                rows.0[0]
+            })
+            .await
+            .unwrap()
+        }
+    }};
+}
+
+fn large_reply(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .thread_stack_size(64 << 20)
+        .enable_all()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("reply_4mb");
+    let actor = rt.block_on(async {
+        reply::ActorSnapshot::spawn(reply::Snapshot {
+            rows: Box::new(reply::Rows([7; 4 << 20])),
+        })
+    });
+    group.bench_function("by_value", |b| {
+        b.to_async(&rt).iter(|| snapshot!(actor, by_value, ByValue))
+    });
+    group.bench_function("boxed", |b| {
+        b.to_async(&rt).iter(|| snapshot!(actor, boxed, Boxed))
+    });
+    group.bench_function("boxed_public", |b| {
+        b.to_async(&rt)
+            .iter(|| snapshot!(actor, boxed_public, BoxedPublic))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    tell_throughput,
+    ask_latency,
+    contention,
+    broadcast,
+    large_reply
+);
+criterion_main!(benches);
@@ -0,0 +1,149 @@
+// two actors working together: a `Fetcher` asks a `Limiter` for a permit before
+// every fetch, so however many callers pile up, fetches never go faster than the
+// limiter allows. the backend is a map standing in for the network, unknown urls
+// fail through a `#[fallible]` variant.
+//
+// cargo run --example fetcher
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio_actor::actors;
+
+#[actors]
+pub mod fetching {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum FetchError {
+        NotFound(String),
+        // the limiter or the fetcher went away
+        Actor(ActorError),
+    }
+
+    impl From<ActorError> for FetchError {
+        fn from(e: ActorError) -> Self {
+            FetchError::Actor(e)
+        }
+    }
+
+    pub enum LimiterMsg {
+        // answers once a permit is free, permits are handed out one per `every`
+        Acquire { resp: () },
+        Granted { resp: u64 },
+    }
+
+    pub struct Limiter {
+        pub every: Duration,
+        pub last: Option<tokio::time::Instant>,
+        pub granted: u64,
+    }
+
+    impl Limiter {
+        pub fn new(every: Duration) -> Self {
+            Limiter {
+                every,
+                last: None,
+                granted: 0,
+            }
+        }
+
+        async fn process(&mut self, msg: LimiterMsg) {
+            match msg {
+                LimiterMsg::Acquire { resp } => {
+                    if let Some(last) = self.last {
+                        tokio::time::sleep_until(last + self.every).await;
+                    }
+                    self.last = Some(tokio::time::Instant::now());
+                    self.granted += 1;
+                    if let Some(ack) = resp {
+                        ack.ack();
+                    }
+                }
+                LimiterMsg::Granted { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.granted);
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum FetcherMsg {
+        #[fallible]
+        Fetch {
+            url: String,
+            resp: Result<String, FetchError>,
+        },
+    }
+
+    pub struct Fetcher {
+        pub limiter: ActorLimiter,
+        pub backend: HashMap<String, String>,
+    }
+
+    impl Fetcher {
+        async fn process(&mut self, msg: FetcherMsg) {
+            match msg {
+                FetcherMsg::Fetch { url, resp } => {
+                    let result = self.fetch(url).await;
+                    if let Some(r) = resp {
+                        let _ = r.send(result);
+                    }
+                }
+            }
+        }
+
+        async fn fetch(&mut self, url: String) -> Result<String, FetchError> {
+            self.limiter
+                .acquire(LimiterMsg::Acquire { resp: None })
+                .await?;
+            match self.backend.get(&url) {
+                Some(body) => Ok(body.clone()),
+                None => Err(FetchError::NotFound(url)),
+            }
+        }
+    }
+}
+
+use fetching::{ActorFetcher, ActorLimiter, Fetcher, FetcherMsg, Limiter};
+
+pub fn backend() -> HashMap<String, String> {
+    [
+        ("https://example.com/a", "alpha"),
+        ("https://example.com/b", "beta"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect()
+}
+
+pub fn fetch(url: &str) -> FetcherMsg {
+    FetcherMsg::Fetch {
+        url: url.into(),
+        resp: None,
+    }
+}
+
+pub fn spawn(every: Duration) -> (ActorFetcher, ActorLimiter) {
+    let limiter = ActorLimiter::spawn(Limiter::new(every));
+    let fetcher = ActorFetcher::spawn(Fetcher {
+        limiter: limiter.clone(),
+        backend: backend(),
+    });
+    (fetcher, limiter)
+}
+
+#[tokio::main]
+async fn main() {
+    let (mut fetcher, _limiter) = spawn(Duration::from_millis(100));
+    let start = tokio::time::Instant::now();
+    for url in [
+        "https://example.com/a",
+        "https://example.com/b",
+        "https://example.com/c",
+    ] {
+        let result = fetcher.fetch(fetch(url)).await;
+        println!("{:>5?} {} -> {:?}", start.elapsed(), url, result);
+    }
+}
@@ -0,0 +1,111 @@
+// a key-value store owned by a single actor: asks read and write it, `_no_wait`
+// tells write without waiting for the answer, `stop_within` shuts it down once
+// what's queued is done.
+//
+// cargo run --example kv_store
+use std::time::Duration;
+
+use tokio_actor::actors;
+
+#[actors]
+pub mod kv {
+    use std::collections::HashMap;
+
+    pub enum KvMsg {
+        Get {
+            key: String,
+            resp: Option<String>,
+        },
+        // answers with the value it replaced
+        Set {
+            key: String,
+            value: String,
+            resp: Option<String>,
+        },
+        Delete {
+            key: String,
+            resp: Option<String>,
+        },
+        Len {
+            resp: usize,
+        },
+    }
+
+    #[derive(Default)]
+    pub struct Kv {
+        pub entries: HashMap<String, String>,
+    }
+
+    impl Kv {
+        async fn process(&mut self, msg: KvMsg) {
+            match msg {
+                KvMsg::Get { key, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.entries.get(&key).cloned());
+                    }
+                }
+                KvMsg::Set { key, value, resp } => {
+                    let old = self.entries.insert(key, value);
+                    if let Some(r) = resp {
+                        let _ = r.send(old);
+                    }
+                }
+                KvMsg::Delete { key, resp } => {
+                    let old = self.entries.remove(&key);
+                    if let Some(r) = resp {
+                        let _ = r.send(old);
+                    }
+                }
+                KvMsg::Len { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.entries.len());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use kv::{ActorKv, Kv, KvMsg};
+
+pub fn set(key: &str, value: &str) -> KvMsg {
+    KvMsg::Set {
+        key: key.into(),
+        value: value.into(),
+        resp: None,
+    }
+}
+
+pub fn get(key: &str) -> KvMsg {
+    KvMsg::Get {
+        key: key.into(),
+        resp: None,
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut store = ActorKv::spawn(Kv::default());
+    store.set_no_wait(set("lang", "rust")).await.unwrap();
+    println!(
+        "replaced: {:?}",
+        store.set(set("lang", "rust 2021")).await.unwrap()
+    );
+    println!("lang = {:?}", store.get(get("lang")).await.unwrap());
+    println!("missing = {:?}", store.get(get("missing")).await.unwrap());
+    let deleted = store
+        .delete(KvMsg::Delete {
+            key: "lang".into(),
+            resp: None,
+        })
+        .await
+        .unwrap();
+    println!("deleted: {:?}", deleted);
+    println!(
+        "len = {}",
+        store.len(KvMsg::Len { resp: None }).await.unwrap()
+    );
+    let dropped = store.stop_within(Duration::from_secs(1)).await.unwrap();
+    println!("stopped, {} msgs dropped", dropped);
+    println!("after stop: {:?}", store.get(get("lang")).await);
+}
@@ -0,0 +1,164 @@
+// a supervisor handing jobs to a few workers round robin. workers are
+// `supervised`, so a panicking job only fails its own caller, and they stop on
+// errors (`on_error = "stop"`), in which case the supervisor replaces them with
+// a fresh one before handing out the next job.
+//
+// cargo run --example supervisor
+use std::time::Duration;
+
+use tokio_actor::actors;
+
+#[actors]
+pub mod supervise {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Job {
+        Square(u64),
+        // panics in `process`
+        Panic,
+        // `process` returns an error, stopping the worker
+        Invalid,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct InvalidJob;
+
+    pub enum WorkerMsg {
+        Run { job: Job, resp: u64 },
+    }
+
+    #[actor(supervised, on_error = "stop")]
+    #[derive(Default)]
+    pub struct Worker {
+        pub done: u64,
+    }
+
+    impl Worker {
+        async fn process(&mut self, msg: WorkerMsg) -> Result<(), InvalidJob> {
+            match msg {
+                WorkerMsg::Run { job, resp } => {
+                    let n = match job {
+                        Job::Square(n) => n,
+                        Job::Panic => panic!("job panicked"),
+                        Job::Invalid => return Err(InvalidJob),
+                    };
+                    self.done += 1;
+                    if let Some(r) = resp {
+                        let _ = r.send(n * n);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        async fn on_restart(&mut self) {
+            self.done = 0;
+        }
+    }
+
+    pub enum SupervisorMsg {
+        Run {
+            job: Job,
+            resp: Result<u64, ActorError>,
+        },
+        // panics survived plus workers replaced
+        Restarts {
+            resp: usize,
+        },
+        // stops every worker, answers with the number of jobs they dropped
+        Shutdown {
+            resp: usize,
+        },
+    }
+
+    pub struct Supervisor {
+        pub workers: Vec<ActorWorker>,
+        pub next: usize,
+        pub replaced: usize,
+    }
+
+    impl Supervisor {
+        pub fn new(size: usize) -> Self {
+            Supervisor {
+                workers: (0..size)
+                    .map(|_i| ActorWorker::spawn(Worker::default()))
+                    .collect(),
+                next: 0,
+                replaced: 0,
+            }
+        }
+
+        async fn process(&mut self, msg: SupervisorMsg) {
+            match msg {
+                SupervisorMsg::Run { job, resp } => {
+                    let i = self.next % self.workers.len();
+                    self.next += 1;
+                    if self.workers[i].is_closed() {
+                        self.workers[i] = ActorWorker::spawn(Worker::default());
+                        self.replaced += 1;
+                    }
+                    // the worker answers the caller directly, the supervisor moves on
+                    let ask = self.workers[i].run(WorkerMsg::Run { job, resp: None });
+                    tokio::spawn(async move {
+                        let result = ask.await;
+                        if let Some(r) = resp {
+                            let _ = r.send(result);
+                        }
+                    });
+                }
+                SupervisorMsg::Restarts { resp } => {
+                    let panics: usize = self.workers.iter().map(|w| w.restart_count()).sum();
+                    if let Some(r) = resp {
+                        let _ = r.send(panics + self.replaced);
+                    }
+                }
+                SupervisorMsg::Shutdown { resp } => {
+                    let mut dropped = 0;
+                    for worker in &self.workers {
+                        dropped += worker
+                            .stop_within(std::time::Duration::from_secs(1))
+                            .await
+                            .unwrap_or(0);
+                    }
+                    if let Some(r) = resp {
+                        let _ = r.send(dropped);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use supervise::{ActorSupervisor, Job, Supervisor, SupervisorMsg};
+
+pub fn run(job: Job) -> SupervisorMsg {
+    SupervisorMsg::Run { job, resp: None }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut supervisor = ActorSupervisor::spawn(Supervisor::new(2));
+    for job in [
+        Job::Square(3),
+        Job::Panic,
+        Job::Invalid,
+        Job::Square(4),
+        Job::Square(5),
+    ] {
+        let result = supervisor.run(run(job)).await.unwrap();
+        println!("{:?} -> {:?}", job, result);
+    }
+    let restarts = supervisor
+        .restarts(SupervisorMsg::Restarts { resp: None })
+        .await
+        .unwrap();
+    println!("restarts: {}", restarts);
+    let dropped = supervisor
+        .shutdown(SupervisorMsg::Shutdown { resp: None })
+        .await
+        .unwrap();
+    println!("shut down, {} jobs dropped", dropped);
+    supervisor
+        .stop_within(Duration::from_secs(1))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,470 @@
+use inflector::Inflector;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Attribute, Ident, LitInt, LitStr, Token, Type};
+
+use crate::runtime::{Paths, Runtime};
+use crate::snapshot;
+
+// arguments of `#[actors(..)]`, shared by every actor in the module.
+#[derive(Default)]
+pub(crate) struct ActorsArgs {
+    pub(crate) bounded: Option<LitInt>,
+    pub(crate) error: Option<Type>,
+    // child module receiving every generated item
+    pub(crate) output_mod: Option<Ident>,
+    // max msgs processed per wakeup of the run loop
+    pub(crate) batch: Option<usize>,
+    // `#[allow(dead_code)]` on every generated item
+    pub(crate) allow_unused: bool,
+    // upper bound on the variants of a msg enum, against runaway expansions
+    pub(crate) max_variants: Option<usize>,
+    // the executor generated code targets
+    pub(crate) runtime: Runtime,
+    // an `Actors` struct spawning every actor of the module at once
+    pub(crate) registry: bool,
+    // only the msg enums and their request structs, no actors
+    pub(crate) minimal: bool,
+    // case of the generated per-variant method names
+    pub(crate) rename_all: RenameAll,
+    // debug builds fail asks an actor sends itself instead of hanging
+    pub(crate) deadlock_check: bool,
+    // debug builds fail asks to an actor dropped with another runtime
+    pub(crate) runtime_check: bool,
+    // no up-front `Send + 'static` assertions on msgs, actor states and handles
+    pub(crate) no_assert_send: bool,
+    // how long the registry waits for every actor to be ready, in ms
+    pub(crate) ready_timeout: Option<u64>,
+    // `cfg(..)`: the predicate everything tokio, msg enums included, is compiled
+    // under. `#[wire]` copies are plain data and stay.
+    pub(crate) cfg: Option<TokenStream>,
+}
+
+// `#[actors(rename_all = "..")]`
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum RenameAll {
+    #[default]
+    SnakeCase,
+    CamelCase,
+    PascalCase,
+    // `Add_no_wait`, the variant as written plus the usual suffixes
+    Verbatim,
+}
+
+impl RenameAll {
+    // `name` is built from the variant ident, e.g. `pipe_Add_to`
+    pub(crate) fn method(self, name: &str) -> Ident {
+        let name = match self {
+            RenameAll::SnakeCase => name.to_snake_case(),
+            RenameAll::CamelCase => name.to_camel_case(),
+            RenameAll::PascalCase => name.to_pascal_case(),
+            RenameAll::Verbatim => name.to_string(),
+        };
+        Ident::new(&name, Span::call_site())
+    }
+
+    // anything but snake_case trips the lint at every generated method
+    pub(crate) fn allow(self) -> TokenStream {
+        match self {
+            RenameAll::SnakeCase => quote! {},
+            _ => quote! {#[allow(non_snake_case)]},
+        }
+    }
+}
+
+impl Parse for ActorsArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut result = Self::default();
+        let mut ready_timeout = None;
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            match key.to_string().as_str() {
+                "bounded" => {
+                    input.parse::<Token![=]>()?;
+                    result.bounded = Some(input.parse().map_err(|e| {
+                        syn::Error::new(e.span(), "expected a mailbox capacity like `bounded = 64`")
+                    })?);
+                }
+                "error" => {
+                    input.parse::<Token![=]>()?;
+                    result.error = Some(input.parse()?);
+                }
+                "output_mod" => {
+                    input.parse::<Token![=]>()?;
+                    result.output_mod = Some(input.parse()?);
+                }
+                "allow_unused" => result.allow_unused = true,
+                "registry" => result.registry = true,
+                "minimal" => result.minimal = true,
+                "deadlock_check" => result.deadlock_check = true,
+                "runtime_check" => result.runtime_check = true,
+                "no_assert_send" => result.no_assert_send = true,
+                "ready_timeout" => {
+                    input.parse::<Token![=]>()?;
+                    result.ready_timeout = Some(parse_duration(input)?);
+                    ready_timeout = Some(key);
+                }
+                "cfg" => {
+                    let predicate;
+                    syn::parenthesized!(predicate in input);
+                    result.cfg = Some(predicate.parse()?);
+                }
+                "rename_all" => {
+                    input.parse::<Token![=]>()?;
+                    let lit: LitStr = input.parse()?;
+                    result.rename_all = match lit.value().as_str() {
+                        "snake_case" => RenameAll::SnakeCase,
+                        "camelCase" => RenameAll::CamelCase,
+                        "PascalCase" => RenameAll::PascalCase,
+                        "verbatim" => RenameAll::Verbatim,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                &lit,
+                                "expected \"snake_case\", \"camelCase\", \"PascalCase\" or \"verbatim\"",
+                            ))
+                        }
+                    };
+                }
+                "max_variants" => {
+                    input.parse::<Token![=]>()?;
+                    result.max_variants = Some(input.parse::<LitInt>()?.base10_parse()?);
+                }
+                "batch" => {
+                    input.parse::<Token![=]>()?;
+                    let lit: LitInt = input.parse()?;
+                    match lit.base10_parse::<usize>()? {
+                        0 => {
+                            return Err(syn::Error::new_spanned(
+                                &lit,
+                                "batch needs to be at least 1",
+                            ))
+                        }
+                        v => result.batch = Some(v),
+                    }
+                }
+                _ => return Err(syn::Error::new_spanned(&key, "unknown actors argument")),
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        if let (Some(key), false) = (ready_timeout, result.registry) {
+            return Err(syn::Error::new_spanned(
+                key,
+                "`ready_timeout` bounds `Actors::spawn_all`, add `registry`",
+            ));
+        }
+        Ok(result)
+    }
+}
+
+// arguments of `#[actor(..)]` on an actor struct
+#[derive(Default)]
+pub(crate) struct ActorArgs {
+    pub(crate) idle_timeout: Option<u64>,
+    // `on_idle` only runs once the mailbox stayed empty this long
+    pub(crate) idle_debounce: Option<u64>,
+    // field carrying the msg id handed to `ack`/`nack`
+    pub(crate) ack: Option<Ident>,
+    // survive panics in `process`
+    pub(crate) supervised: bool,
+    // `#[must_use]` on the handle, warning about handles dropped right away
+    pub(crate) must_use: bool,
+    // the run loop also waits on `next_external` and hands its events to `process_external`
+    pub(crate) with_external: bool,
+    // what `on_stop` returns, handed to whoever awaits the actor
+    pub(crate) exit: Option<syn::Type>,
+    // `AsRef` to the raw mailbox sender, for callers accepting the coupling
+    pub(crate) raw_sender: bool,
+    // `spawn_pool`: several instances behind one handle, `shared_queue` the only mode
+    pub(crate) pool: bool,
+    pub(crate) shared_queue: bool,
+    // what the run loop does when a `Result`-returning `process` fails
+    pub(crate) on_error: Option<OnError>,
+    // tasks from `ctx.spawn` are awaited when the actor stops instead of aborted
+    pub(crate) await_tasks: bool,
+    // actors of the registry whose `on_start` has to succeed before this one starts
+    pub(crate) await_ready: Vec<Ident>,
+}
+
+// `#[actor(on_error = "..")]`
+#[derive(Clone, Copy, Default, PartialEq)]
+pub(crate) enum OnError {
+    // the error goes to `on_error` and the next msg is processed
+    #[default]
+    Continue,
+    // the mailbox is closed with `CloseReason::Failed`, what's queued is dropped
+    Stop,
+    // like a panic under `supervised`: counted, `on_restart` runs, then the next msg
+    Restart,
+}
+
+impl Parse for ActorArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut result = Self::default();
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            match key.to_string().as_str() {
+                "idle_timeout" => {
+                    input.parse::<Token![=]>()?;
+                    result.idle_timeout = Some(parse_duration(input)?);
+                }
+                "idle_debounce" => {
+                    input.parse::<Token![=]>()?;
+                    result.idle_debounce = Some(parse_duration(input)?);
+                }
+                "supervised" => result.supervised = true,
+                "must_use" => result.must_use = true,
+                "with_external" => result.with_external = true,
+                "raw_sender" => result.raw_sender = true,
+                "pool" => result.pool = true,
+                "shared_queue" => result.shared_queue = true,
+                "ack" => {
+                    input.parse::<Token![=]>()?;
+                    result.ack = Some(input.parse()?);
+                }
+                "exit" => {
+                    input.parse::<Token![=]>()?;
+                    result.exit = Some(input.parse()?);
+                }
+                "on_error" => {
+                    input.parse::<Token![=]>()?;
+                    let lit: LitStr = input.parse()?;
+                    result.on_error = Some(match lit.value().as_str() {
+                        "continue" => OnError::Continue,
+                        "stop" => OnError::Stop,
+                        "restart" => OnError::Restart,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                &lit,
+                                "expected \"continue\", \"stop\" or \"restart\"",
+                            ))
+                        }
+                    });
+                }
+                "await_ready" => {
+                    let actors;
+                    syn::parenthesized!(actors in input);
+                    let actors = actors.parse_terminated::<Ident, Token![,]>(Ident::parse)?;
+                    result.await_ready.extend(actors);
+                }
+                "ctx_tasks" => {
+                    input.parse::<Token![=]>()?;
+                    let lit: LitStr = input.parse()?;
+                    result.await_tasks = match lit.value().as_str() {
+                        "abort" => false,
+                        "await" => true,
+                        _ => {
+                            return Err(syn::Error::new_spanned(
+                                &lit,
+                                "expected \"abort\" or \"await\"",
+                            ))
+                        }
+                    };
+                }
+                _ => return Err(syn::Error::new_spanned(&key, "unknown actor argument")),
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(result)
+    }
+}
+
+impl ActorArgs {
+    // reads and strips `#[actor(..)]` from the actor struct
+    pub(crate) fn take(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+        let mut result = Self::default();
+        let mut kept = vec![];
+        for attr in attrs.drain(..) {
+            if attr.path.is_ident("actor") {
+                result = attr.parse_args()?;
+            } else {
+                kept.push(attr);
+            }
+        }
+        *attrs = kept;
+        Ok(result)
+    }
+}
+
+// every attribute taking a duration goes through here: a string like "500ms",
+// "5s", "10m", "1h" or a sum of those like "1h30m", or an integer of milliseconds.
+// gives milliseconds.
+pub(crate) fn parse_duration(input: ParseStream) -> syn::Result<u64> {
+    let lookahead = input.lookahead1();
+    if lookahead.peek(LitInt) {
+        return input.parse::<LitInt>()?.base10_parse();
+    }
+    if lookahead.peek(LitStr) {
+        return duration_str(&input.parse()?);
+    }
+    Err(lookahead.error())
+}
+
+fn duration_str(lit: &LitStr) -> syn::Result<u64> {
+    let text = lit.value();
+    let error = || {
+        syn::Error::new_spanned(
+            lit,
+            format!("expected a duration like \"500ms\", got {:?}", text),
+        )
+    };
+    let mut rest = text.as_str();
+    let mut total: u64 = 0;
+    if rest.is_empty() {
+        return Err(error());
+    }
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value = rest[..split].parse::<u64>().map_err(|_e| error())?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "ms" => 1,
+            "s" => 1000,
+            "m" => 60 * 1000,
+            "h" => 60 * 60 * 1000,
+            _ => return Err(error()),
+        };
+        rest = &rest[unit_len..];
+        total = value
+            .checked_mul(scale)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(error)?;
+    }
+    Ok(total)
+}
+
+impl ActorsArgs {
+    // `#[cfg(..)]` of `cfg(..)`, on every item needing tokio
+    pub(crate) fn gate(&self) -> TokenStream {
+        match &self.cfg {
+            Some(predicate) => quote! {#[cfg(#predicate)]},
+            None => quote! {},
+        }
+    }
+
+    pub(crate) fn receiver_type(&self, msg_type: &TokenStream) -> TokenStream {
+        let Paths { mpsc, .. } = self.runtime.paths();
+        match self.bounded {
+            Some(_) => quote! {#mpsc::Receiver<Envelope<#msg_type>>},
+            None => quote! {#mpsc::UnboundedReceiver<Envelope<#msg_type>>},
+        }
+    }
+
+    // `tokio_stream` wrapper matching the receiver, for the `stream` feature
+    pub(crate) fn receiver_stream(&self) -> TokenStream {
+        match self.bounded {
+            Some(_) => quote! {::tokio_stream::wrappers::ReceiverStream},
+            None => quote! {::tokio_stream::wrappers::UnboundedReceiverStream},
+        }
+    }
+
+    pub(crate) fn sender_type(&self, msg_type: &TokenStream) -> TokenStream {
+        let Paths { mpsc, .. } = self.runtime.paths();
+        match self.bounded {
+            Some(_) => quote! {#mpsc::Sender<Envelope<#msg_type>>},
+            None => quote! {#mpsc::UnboundedSender<Envelope<#msg_type>>},
+        }
+    }
+
+    // what `ctx.handle()` holds instead, not keeping the mailbox open
+    pub(crate) fn weak_sender_type(&self, msg_type: &TokenStream) -> TokenStream {
+        let Paths { mpsc, .. } = self.runtime.paths();
+        match self.bounded {
+            Some(_) => quote! {#mpsc::WeakSender<Envelope<#msg_type>>},
+            None => quote! {#mpsc::WeakUnboundedSender<Envelope<#msg_type>>},
+        }
+    }
+
+    // neither channel can be pre-sized: both queue msgs in blocks of 32 allocated
+    // as they fill up, `bounded` only adds a semaphore on top.
+    pub(crate) fn channel(&self) -> TokenStream {
+        let Paths { mpsc, .. } = self.runtime.paths();
+        match &self.bounded {
+            Some(capacity) => quote! {#mpsc::channel(#capacity)},
+            None => quote! {#mpsc::unbounded_channel()},
+        }
+    }
+
+    // hands `envelope` over and turns `r` into an `AskFuture`. bounded sends are
+    // deferred into the future, so the request methods never need to be async.
+    pub(crate) fn ask_send(&self, envelope: TokenStream) -> TokenStream {
+        match self.bounded {
+            Some(_) => {
+                let envelope = snapshot::queued(envelope, quote! {shared});
+                quote! {{
+                let sender = self.sender.clone();
+                let shared = self.shared.clone();
+                AskFuture::sending(Box::pin(async move {
+                    sender.send(#envelope).await.map_err(|_e|{return ActorError::MailboxClosed{reason: shared.close_reason()};})?;
+                    return Ok(r);
+                }))
+                }}
+            }
+            None => {
+                let envelope = snapshot::queued(envelope, quote! {self.shared});
+                quote! {
+                match self.sender.send(#envelope){
+                    Ok(_)=>AskFuture::waiting(r),
+                    Err(_e)=>AskFuture::failed(ActorError::MailboxClosed{reason: self.shared.close_reason()}),
+                }
+                }
+            }
+        }
+    }
+
+    // a generated runtime type as named from the user's items, which stay
+    // outside of `output_mod`
+    pub(crate) fn runtime_type(&self, ident: &str) -> TokenStream {
+        let ident = Ident::new(ident, proc_macro2::Span::call_site());
+        match &self.output_mod {
+            Some(m) => quote! {#m::#ident},
+            None => quote! {#ident},
+        }
+    }
+
+    // bounded senders wait for capacity before handing the msg over.
+    pub(crate) fn send(&self) -> TokenStream {
+        self.send_envelope(snapshot::queued(
+            quote! {Envelope::new(msg)},
+            quote! {self.shared},
+        ))
+    }
+
+    // hands the msg over without waiting, a full bounded mailbox counts as an
+    // enqueue timed out right away
+    pub(crate) fn try_send(&self) -> TokenStream {
+        let Paths { mpsc, .. } = self.runtime.paths();
+        let envelope = snapshot::queued(quote! {Envelope::new(msg)}, quote! {self.shared});
+        match self.bounded {
+            Some(_) => quote! {
+                self.sender.try_send(#envelope).map_err(|e|{
+                    return match e{
+                        #mpsc::error::TrySendError::Full(_v)=>ActorError::EnqueueTimeout,
+                        #mpsc::error::TrySendError::Closed(_v)=>ActorError::MailboxClosed{reason: self.shared.close_reason()},
+                    };
+                })
+            },
+            None => quote! {
+                self.sender.send(#envelope).map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})
+            },
+        }
+    }
+
+    pub(crate) fn send_envelope(&self, envelope: TokenStream) -> TokenStream {
+        match self.bounded {
+            Some(_) => quote! {self.sender.send(#envelope).await},
+            None => quote! {self.sender.send(#envelope)},
+        }
+    }
+}
@@ -0,0 +1,1524 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::args::ActorsArgs;
+use crate::runtime::Paths;
+use crate::snapshot;
+
+// `xxx_traced` asks telling where their time went, with `metrics` or `tracing`
+pub(crate) fn timings() -> bool {
+    cfg!(feature = "metrics") || cfg!(feature = "tracing")
+}
+
+// what a run loop needs to answer `xxx_traced`: the binding out of
+// `Letter::User`, the dequeue time, and the report once the msg was processed
+pub(crate) fn report(args: &ActorsArgs) -> (TokenStream, TokenStream, TokenStream) {
+    let Paths { time, .. } = args.runtime.paths();
+    match timings() {
+        true => (
+            quote! {timed,},
+            quote! {let dequeued = #time::Instant::now();},
+            quote! {
+                if let Some(timed) = timed {
+                    timed.report(dequeued);
+                }
+            },
+        ),
+        false => (quote! {}, quote! {}, quote! {}),
+    }
+}
+
+// `ActorError`, `AskFuture` and its combinators, plus the state shared by
+// handles and run loops, emitted once into every module that ends up with at
+// least one actor. every future here is `Unpin`, so they go straight into
+// `tokio::select!` or `FuturesUnordered`. timers, here and in the run loops, only
+// ever come from `tokio::time`, so they follow a paused test clock. `lanes` when
+// an actor of the module has `#[bounded(..)]` variants.
+pub(crate) fn items(args: &ActorsArgs, lanes: bool) -> TokenStream {
+    let Paths {
+        mpsc,
+        oneshot,
+        broadcast,
+        spawn,
+        time,
+        task_local,
+        runtime,
+        ..
+    } = args.runtime.paths();
+    // `#[actors(deadlock_check)]`: run loops know which actor they run, so debug
+    // builds can tell an ask an actor sends itself
+    let (running_actor, asks_itself) = match args.deadlock_check {
+        true => (
+            quote! {
+                #[cfg(debug_assertions)]
+                #task_local! {
+                    static RUNNING_ACTOR: ActorId;
+                }
+            },
+            quote! {
+                // an ask from the actor's own task would wait on the msg it's processing
+                #[allow(dead_code)]
+                fn asks_itself(&self) -> bool {
+                    #[cfg(debug_assertions)]
+                    return RUNNING_ACTOR.try_with(|id| *id == self.id).unwrap_or(false);
+                    #[cfg(not(debug_assertions))]
+                    return false;
+                }
+            },
+        ),
+        false => (quote! {}, quote! {}),
+    };
+    // `#[actors(runtime_check)]`: the run loop notes the runtime it runs on, so a
+    // debug build tells an ask to an actor that was dropped along with it from one
+    // to an actor that merely stopped. `ActorError::RuntimeGone` is all the caller
+    // gets, the `tracing` feature adds a warning naming the runtime
+    let warn_runtime_gone = match cfg!(feature = "tracing") {
+        true => quote! {
+            ::tracing::warn!(actor = %self.id, spawned_on = %spawned_on, "asked from another runtime, the actor was dropped with its own");
+        },
+        false => quote! {},
+    };
+    let (runtime_fields, runs_on) = match args.runtime_check {
+        true => (
+            quote! {
+                // the runtime the run loop started on
+                #[cfg(debug_assertions)]
+                runtime: std::sync::OnceLock<#runtime::Id>,
+                // the run loop returned, rather than being dropped with its runtime
+                #[cfg(debug_assertions)]
+                returned: std::sync::atomic::AtomicBool,
+            },
+            quote! {
+                fn running(&self) {
+                    #[cfg(debug_assertions)]
+                    let _ = self.runtime.set(#runtime::Handle::current().id());
+                }
+
+                fn returned(&self) {
+                    #[cfg(debug_assertions)]
+                    self.returned.store(true, std::sync::atomic::Ordering::SeqCst);
+                }
+
+                // checked once the mailbox is found closed: the actor is gone because its
+                // runtime shut down, and the ask comes from another one
+                #[allow(dead_code)]
+                fn runtime_gone(&self) -> bool {
+                    #[cfg(debug_assertions)]
+                    {
+                        let spawned_on = match self.runtime.get() {
+                            Some(id) => *id,
+                            None => return false,
+                        };
+                        if self.returned.load(std::sync::atomic::Ordering::SeqCst) {
+                            return false;
+                        }
+                        let here = #runtime::Handle::try_current().ok().map(|handle| handle.id());
+                        if here != Some(spawned_on) {
+                            #warn_runtime_gone
+                            return true;
+                        }
+                    }
+                    return false;
+                }
+            },
+        ),
+        false => (quote! {}, quote! {}),
+    };
+    let receiver_type = args.receiver_type(&quote! {M});
+    // `#[bounded(..)]` variants travel through a bounded channel of their own, the
+    // run loop takes from both. the unbounded one goes first, so control msgs and
+    // the other variants never wait behind a full lane.
+    let lane_len = lanes.then(|| quote! {+ self.lane.as_ref().map_or(0, |lane| lane.len())});
+    let close_lane = lanes.then(|| {
+        quote! {
+            if let Some(lane) = &mut self.lane {
+                lane.close();
+            }
+        }
+    });
+    let (lane_field, lane_init, with_lane, poll_envelope, try_lane, drain_lane) = match lanes {
+        true => (
+            quote! {lane: Option<#mpsc::Receiver<Envelope<M>>>,},
+            quote! {lane: None,},
+            quote! {
+                #[allow(dead_code)]
+                fn with_lane(mut self, lane: #mpsc::Receiver<Envelope<M>>) -> Self {
+                    self.lane = Some(lane);
+                    return self;
+                }
+            },
+            quote! {
+                let polled = self.receiver.poll_recv(cx);
+                let lane = match (&mut self.lane, &polled) {
+                    (Some(lane), std::task::Poll::Ready(None) | std::task::Poll::Pending) => lane,
+                    _ => return polled,
+                };
+                // ends once both channels did
+                return match (polled, lane.poll_recv(cx)) {
+                    (_, std::task::Poll::Ready(Some(envelope))) => std::task::Poll::Ready(Some(envelope)),
+                    (std::task::Poll::Ready(None), std::task::Poll::Ready(None)) => std::task::Poll::Ready(None),
+                    _ => std::task::Poll::Pending,
+                };
+            },
+            quote! {
+                .or_else(|| self.lane.as_mut()?.try_recv().ok())
+            },
+            quote! {
+                if let Some(lane) = &mut self.lane {
+                    while let Ok(envelope) = lane.try_recv() {
+                        if let Letter::User { msg, .. } = envelope.0 {
+                            msgs.push(msg);
+                        }
+                    }
+                }
+            },
+        ),
+        false => (
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {
+                return self.receiver.poll_recv(cx);
+            },
+            quote! {},
+            quote! {},
+        ),
+    };
+    let test_util = match cfg!(feature = "test-util") {
+        true => quote! {
+            impl<M> Mailbox<M> {
+                // takes what is queued right now without processing any of it
+                pub fn drain_to_vec(&mut self) -> Vec<M> {
+                    let mut msgs = vec![];
+                    while let Ok(envelope) = self.receiver.try_recv() {
+                        if let Letter::User { msg, .. } = envelope.0 {
+                            msgs.push(msg);
+                        }
+                    }
+                    #drain_lane
+                    return msgs;
+                }
+            }
+        },
+        false => quote! {},
+    };
+    // `wait_until_idle` callers park in the mailbox, which answers them as soon as
+    // a receive finds nothing queued. receives only happen between msgs.
+    let (idle_field, idle_init, idle_control, notify_idle) = match cfg!(feature = "test-util") {
+        true => (
+            quote! {idle_waiters: Vec<#oneshot::Sender<()>>,},
+            quote! {idle_waiters: vec![],},
+            quote! {Idle(#oneshot::Sender<()>),},
+            quote! {
+                if polled.is_pending() {
+                    for waiter in self.idle_waiters.drain(..) {
+                        let _ = waiter.send(());
+                    }
+                }
+            },
+        ),
+        false => (quote! {}, quote! {}, quote! {}, quote! {}),
+    };
+    let receiver_stream = args.receiver_stream();
+    let stream = match (cfg!(feature = "stream"), lanes) {
+        (true, true) => quote! {
+            impl<M> Mailbox<M> {
+                // the msgs as a `Stream`, ending once every handle is gone
+                pub fn into_stream(self) -> impl ::tokio_stream::Stream<Item = M> {
+                    let lane = self.lane.unwrap_or_else(|| #mpsc::channel(1).1);
+                    let envelopes = ::tokio_stream::StreamExt::merge(#receiver_stream::new(self.receiver), ::tokio_stream::wrappers::ReceiverStream::new(lane));
+                    return ::tokio_stream::StreamExt::filter_map(envelopes, Envelope::into_msg);
+                }
+            }
+        },
+        (true, false) => quote! {
+            impl<M> Mailbox<M> {
+                // the msgs as a `Stream`, ending once every handle is gone
+                pub fn into_stream(self) -> impl ::tokio_stream::Stream<Item = M> {
+                    return ::tokio_stream::StreamExt::filter_map(#receiver_stream::new(self.receiver), Envelope::into_msg);
+                }
+            }
+        },
+        (false, _) => quote! {},
+    };
+    // the mailbox slot a bounded `tower::Service` or throttled sink reserved in `poll_ready`
+    let reserved_slot = match (
+        cfg!(feature = "tower") || cfg!(feature = "sink"),
+        &args.bounded,
+    ) {
+        (true, Some(_)) => quote! {
+            enum ReservedSlot<M> {
+                Idle,
+                Reserving(std::pin::Pin<Box<dyn std::future::Future<Output = Result<#mpsc::OwnedPermit<Envelope<M>>, #mpsc::error::SendError<()>>> + Send>>),
+                Ready(#mpsc::OwnedPermit<Envelope<M>>),
+            }
+
+            impl<M: Send + 'static> ReservedSlot<M> {
+                // moves the slot on to `Ready`, reserving one of `sender` if it holds none
+                fn poll_reserve(&mut self, sender: &#mpsc::Sender<Envelope<M>>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Result<(), #mpsc::error::SendError<()>>> {
+                    loop {
+                        match self {
+                            ReservedSlot::Ready(_permit) => return std::task::Poll::Ready(Ok(())),
+                            ReservedSlot::Reserving(reserve) => match reserve.as_mut().poll(cx) {
+                                std::task::Poll::Ready(Ok(permit)) => *self = ReservedSlot::Ready(permit),
+                                std::task::Poll::Ready(Err(e)) => {
+                                    *self = ReservedSlot::Idle;
+                                    return std::task::Poll::Ready(Err(e));
+                                }
+                                std::task::Poll::Pending => return std::task::Poll::Pending,
+                            },
+                            ReservedSlot::Idle => *self = ReservedSlot::Reserving(Box::pin(sender.clone().reserve_owned())),
+                        }
+                    }
+                }
+            }
+        },
+        _ => quote! {},
+    };
+    // `#[instrument]` variants hand their request span to the ask, entered on every poll
+    // and the request methods note where they were called from, for the warning
+    // logged when `process` drops `resp` without answering
+    let (span_field, span_init, span_enter, in_span, dropped) = match cfg!(feature = "tracing") {
+        true => (
+            quote! {
+                span: ::tracing::Span,
+                caller: Option<&'static std::panic::Location<'static>>,
+            },
+            quote! {span: ::tracing::Span::none(), caller: None,},
+            quote! {
+                let span = this.span.clone();
+                let _enter = span.enter();
+            },
+            quote! {
+                #[allow(dead_code)]
+                fn in_span(mut self, span: ::tracing::Span) -> Self {
+                    self.span = span;
+                    return self;
+                }
+
+                #[allow(dead_code)]
+                fn called_from(mut self, caller: &'static std::panic::Location<'static>) -> Self {
+                    self.caller = Some(caller);
+                    return self;
+                }
+
+                // `resp` went away unanswered, which is a bug in `process`
+                fn dropped(&self) -> ActorError {
+                    match self.caller {
+                        Some(caller) => ::tracing::warn!(variant = self.variant, caller = %caller, "response dropped without an answer"),
+                        None => ::tracing::warn!(variant = self.variant, "response dropped without an answer"),
+                    }
+                    return ActorError::ResponseDropped;
+                }
+            },
+            quote! {this.dropped()},
+        ),
+        false => (
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {ActorError::ResponseDropped},
+        ),
+    };
+    // `debug`: user msgs note themselves in a snapshot of what's queued
+    let (queued_field, queued_init, queued_ring, queued_items) = snapshot::items(args);
+    // `xxx_traced`: the envelope carries when it entered the mailbox and where to
+    // report how long it waited there and how long `process` took
+    let (timed_field, timed_init, timed_bind, timed_items) = match timings() {
+        true => (
+            quote! {timed: Option<Timed>,},
+            quote! {timed: None,},
+            quote! {timed,},
+            quote! {
+                // how long one ask spent in each stage, `send` measured by the caller,
+                // `queued` and `processing` by the run loop
+                #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+                pub struct AskTimings {
+                    // waiting for room in a bounded mailbox
+                    pub send: std::time::Duration,
+                    // in the mailbox, until the run loop took it out
+                    pub queued: std::time::Duration,
+                    // `process` running, from dequeue until it returned
+                    pub processing: std::time::Duration,
+                }
+
+                impl AskTimings {
+                    pub fn total(&self) -> std::time::Duration {
+                        return self.send + self.queued + self.processing;
+                    }
+                }
+
+                struct Timed {
+                    sent: #time::Instant,
+                    report: #oneshot::Sender<(std::time::Duration, std::time::Duration)>,
+                }
+
+                impl Timed {
+                    // called once the msg was processed
+                    fn report(self, dequeued: #time::Instant) {
+                        let queued = dequeued.saturating_duration_since(self.sent);
+                        let _ = self.report.send((queued, dequeued.elapsed()));
+                    }
+                }
+
+                impl<M> Envelope<M> {
+                    fn timed(msg: M, panicked: Option<#oneshot::Sender<ActorPanic>>, timed: Timed) -> Self {
+                        return Self(Letter::User { msg, dequeued: None, panicked, timed: Some(timed) } #queued_init);
+                    }
+                }
+            },
+        ),
+        false => (quote! {}, quote! {}, quote! {}, quote! {}),
+    };
+    // `metrics`: per-variant counts of asks that timed out, and of responses that
+    // only arrived after that. a timed out ask hands its receiver to a task waiting
+    // for the late response, which is why the variant's response needs `Send`.
+    // `test-util`: the counters are process wide, tests looking at them start over
+    let reset_counters = match cfg!(feature = "test-util") {
+        true => quote! {
+            pub fn reset_ask_counters() {
+                ASK_COUNTERS.lock().unwrap().clear();
+            }
+        },
+        false => quote! {},
+    };
+    let (late_field, late_init, variant_fn, expire_count, metrics) = match cfg!(feature = "metrics")
+    {
+        true => (
+            quote! {actor: &'static str, late: Option<fn(#oneshot::Receiver<T>, &'static str, &'static str)>,},
+            quote! {actor: "", late: None,},
+            quote! {
+                impl<T: Send + 'static> AskFuture<T> {
+                    #[allow(dead_code)]
+                    fn variant(mut self, actor: &'static str, variant: &'static str) -> Self {
+                        self.actor = actor;
+                        self.variant = variant;
+                        self.late = Some(|r, actor, variant| {
+                            #spawn(async move {
+                                if r.await.is_ok() {
+                                    count_ask(actor, variant, |c| c.late_replies += 1);
+                                }
+                            });
+                        });
+                        return self;
+                    }
+                }
+            },
+            quote! {
+                count_ask(self.actor, self.variant, |c| c.timeouts += 1);
+                if let (AskState::Waiting(r), Some(late)) = (state, self.late) {
+                    late(r, self.actor, self.variant);
+                }
+            },
+            quote! {
+                #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+                pub struct AskCounters {
+                    pub timeouts: u64,
+                    pub late_replies: u64,
+                }
+
+                // by actor and variant, in the order they first timed out
+                static ASK_COUNTERS: std::sync::Mutex<Vec<(&'static str, &'static str, AskCounters)>> = std::sync::Mutex::new(Vec::new());
+
+                fn count_ask(actor: &'static str, variant: &'static str, f: impl FnOnce(&mut AskCounters)) {
+                    let mut counters = ASK_COUNTERS.lock().unwrap();
+                    match counters.iter_mut().find(|(a, v, _)| (*a, *v) == (actor, variant)) {
+                        Some((_, _, c)) => f(c),
+                        None => {
+                            let mut c = AskCounters::default();
+                            f(&mut c);
+                            counters.push((actor, variant, c));
+                        }
+                    }
+                }
+
+                // the counters of every actor and variant of this module that ever timed out
+                pub fn ask_counters() -> Vec<(&'static str, &'static str, AskCounters)> {
+                    return ASK_COUNTERS.lock().unwrap().clone();
+                }
+
+                #reset_counters
+            },
+        ),
+        false => (
+            quote! {},
+            quote! {},
+            quote! {
+                impl<T> AskFuture<T> {
+                    #[allow(dead_code)]
+                    fn variant(mut self, _actor: &'static str, variant: &'static str) -> Self {
+                        self.variant = variant;
+                        return self;
+                    }
+                }
+            },
+            quote! {drop(state);},
+            quote! {},
+        ),
+    };
+    quote! {
+        // the receiving end of an actor, owned by its run loop
+        pub struct Mailbox<M> {
+            receiver: #receiver_type,
+            #lane_field
+            #idle_field
+            // the pipeline of `new_from_receiver`, taken from once the mailbox is empty
+            source: Option<Box<dyn MailboxFeed<M>>>,
+        }
+
+        impl<M> Mailbox<M> {
+            fn new(receiver: #receiver_type) -> Self {
+                return Self { receiver, #lane_init #idle_init source: None };
+            }
+
+            #with_lane
+
+            #[allow(dead_code)]
+            fn fed_by<S: MsgSource<M>>(mut self, source: S) -> Self
+            where
+                M: Send + 'static,
+            {
+                self.source = Some(Box::new(SourceFeed::new(source)));
+                return self;
+            }
+
+            #[allow(dead_code)]
+            async fn recv(&mut self) -> Option<Letter<M>> {
+                return std::future::poll_fn(|cx| self.poll_recv(cx)).await;
+            }
+
+            #[allow(dead_code)]
+            fn try_recv(&mut self) -> Option<Letter<M>> {
+                return self.try_envelope().map(Envelope::open);
+            }
+
+            fn try_envelope(&mut self) -> Option<Envelope<M>> {
+                return self.receiver.try_recv().ok() #try_lane;
+            }
+
+            #[allow(dead_code)]
+            fn poll_recv(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Letter<M>>> {
+                let polled = self.poll_envelope(cx);
+                #notify_idle
+                return polled.map(|v| v.map(Envelope::open));
+            }
+
+            // the next envelope as sent, `xxx_acked` callers aren't told yet. ends
+            // once every handle is gone and the source, if any, ended too
+            fn poll_envelope(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Envelope<M>>> {
+                let polled = self.poll_channels(cx);
+                let source = match (&polled, &mut self.source) {
+                    (std::task::Poll::Ready(Some(_)), _) | (_, None) => return polled,
+                    (_, Some(source)) => source,
+                };
+                return match source.poll_feed(cx) {
+                    std::task::Poll::Ready(Some(msg)) => std::task::Poll::Ready(Some(Envelope::new(msg))),
+                    std::task::Poll::Ready(None) => {
+                        self.source = None;
+                        polled
+                    }
+                    std::task::Poll::Pending => std::task::Poll::Pending,
+                };
+            }
+
+            fn poll_channels(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<Envelope<M>>> {
+                #poll_envelope
+            }
+
+            async fn recv_envelope(&mut self) -> Option<Envelope<M>> {
+                return std::future::poll_fn(|cx| self.poll_envelope(cx)).await;
+            }
+
+            // `recv_envelope` on a closed mailbox being stopped. past `deadline` only
+            // what's queued already: a slot reserved but not spent yet would otherwise
+            // keep the stop waiting for as long as its permit is held
+            async fn recv_until(&mut self, deadline: #time::Instant) -> Option<Envelope<M>> {
+                return match #time::timeout_at(deadline, self.recv_envelope()).await {
+                    Ok(v) => v,
+                    Err(_e) => self.try_envelope(),
+                };
+            }
+
+            // msgs waiting, not counting the one being processed
+            #[allow(dead_code)]
+            fn len(&self) -> usize {
+                return self.receiver.len() #lane_len;
+            }
+
+            // no new msgs, what's queued can still be received
+            fn close(&mut self) {
+                self.receiver.close();
+                #close_lane
+                self.source = None;
+            }
+        }
+
+        // what travels through a mailbox, opaque outside of this module. users
+        // holding a raw sender can only wrap their own msgs into one, with `From`.
+        pub struct Envelope<M>(Letter<M> #queued_field);
+
+        // the user's msg enum stays as declared, signals meant for the run loop
+        // itself go next to it as `Control`.
+        enum Letter<M> {
+            // a msg for `process`, plus who to tell once it is dequeued for the
+            // `xxx_acked` methods
+            User {
+                msg: M,
+                dequeued: Option<#oneshot::Sender<()>>,
+                // an ask to an actor watching for panics: told what `process`
+                // panicked with on the msg, dropped once it is done with it otherwise
+                panicked: Option<#oneshot::Sender<ActorPanic>>,
+                #timed_field
+            },
+            Control(ControlMsg),
+        }
+
+        // handled by the run loop, never reaching `process`
+        enum ControlMsg {
+            // wakes a run loop waiting on an empty mailbox to look at `stop_within`
+            Stop,
+            // answered once every msg queued before it was processed
+            Flush(#oneshot::Sender<()>),
+            #idle_control
+        }
+
+        impl<M> Envelope<M> {
+            fn new(msg: M) -> Self {
+                return Self(Letter::User { msg, dequeued: None, panicked: None, #timed_init } #queued_init);
+            }
+
+            fn acked(msg: M, dequeued: #oneshot::Sender<()>) -> Self {
+                return Self(Letter::User { msg, dequeued: Some(dequeued), panicked: None, #timed_init } #queued_init);
+            }
+
+            #[allow(dead_code)]
+            fn watched(msg: M, panicked: Option<#oneshot::Sender<ActorPanic>>) -> Self {
+                return Self(Letter::User { msg, dequeued: None, panicked, #timed_init } #queued_init);
+            }
+
+            fn control(control: ControlMsg) -> Self {
+                return Self(Letter::Control(control) #queued_init);
+            }
+
+            // tells an `xxx_acked` caller its msg left the mailbox
+            fn open(self) -> Letter<M> {
+                return match self.0 {
+                    Letter::User { msg, dequeued, panicked, #timed_bind } => {
+                        if let Some(dequeued) = dequeued {
+                            let _ = dequeued.send(());
+                        }
+                        Letter::User { msg, dequeued: None, panicked, #timed_bind }
+                    }
+                    control => control,
+                };
+            }
+
+            #[allow(dead_code)]
+            fn into_msg(self) -> Option<M> {
+                return match self.open() {
+                    Letter::User { msg, .. } => Some(msg),
+                    Letter::Control(_control) => None,
+                };
+            }
+        }
+
+        #timed_items
+        #queued_items
+
+        impl<M> From<M> for Envelope<M> {
+            fn from(msg: M) -> Self {
+                return Self::new(msg);
+            }
+        }
+
+        #test_util
+
+        #stream
+        #reserved_slot
+
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum CloseReason {
+            // the run loop ended, e.g. every handle was dropped or `process` panicked
+            // in an actor not watching for panics
+            Exited,
+            // no msg arrived within the actor's `idle_timeout`
+            Idle,
+            // `stop_within` was called
+            Stopped,
+            // `stop_within` was called and the actor is still working through what it
+            // accepted before, only ever reported to senders
+            Stopping,
+            // `on_start` returned an error, no msg was processed
+            StartFailed,
+            // `process` returned an error under `#[actor(on_error = "stop")]`
+            Failed,
+            // `process` panicked in an actor watching for panics, see `last_panic`
+            Panicked,
+        }
+
+        // what `process` panicked with, when the payload was text
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct ActorPanic {
+            pub message: String,
+        }
+
+        impl ActorPanic {
+            fn from_payload(payload: &(dyn std::any::Any + Send)) -> Self {
+                let message = match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+                    (Some(text), _) => text.to_string(),
+                    (None, Some(text)) => text.clone(),
+                    (None, None) => "Box<dyn Any>".to_string(),
+                };
+                return Self { message };
+            }
+        }
+
+        impl std::fmt::Display for ActorPanic {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                return write!(f, "handler panicked: {}", self.message);
+            }
+        }
+
+        impl std::error::Error for ActorPanic {}
+
+        // no variant names the actor, the caller knows which handle it asked
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ActorError {
+            MailboxClosed { reason: CloseReason },
+            ResponseDropped,
+            InvalidMsgType,
+            EnqueueTimeout,
+            // `variant` as in `FooMsg::Get`
+            ResponseTimeout { waited: std::time::Duration, limit: std::time::Duration, variant: &'static str },
+            // turned down by the variant's `#[validate(..)]`, never sent
+            Rejected(ValidationError),
+            // `process` panicked on the msg before answering, in an actor watching for
+            // panics. the text is in the handle's `last_panic`
+            Panicked,
+            // the actor asked itself, which would never be answered. only reported in
+            // debug builds with `#[actors(deadlock_check)]`
+            WouldDeadlock,
+            // the actor was dropped when the runtime it ran on shut down, and is asked
+            // from another one. only reported in debug builds with
+            // `#[actors(runtime_check)]`
+            RuntimeGone,
+            // a `#[resp(try_from = ..)]` variant's answer didn't convert
+            RespConversion { variant: &'static str },
+        }
+
+        impl std::fmt::Display for ActorError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let text = match self {
+                    ActorError::MailboxClosed { reason: CloseReason::Exited } => "mailbox closed",
+                    ActorError::MailboxClosed { reason: CloseReason::Idle } => "mailbox closed after idling",
+                    ActorError::MailboxClosed { reason: CloseReason::Stopped } => "mailbox closed by stop",
+                    ActorError::MailboxClosed { reason: CloseReason::Stopping } => "mailbox closed, actor stopping",
+                    ActorError::MailboxClosed { reason: CloseReason::StartFailed } => "mailbox closed, on_start failed",
+                    ActorError::MailboxClosed { reason: CloseReason::Failed } => "mailbox closed, process failed",
+                    ActorError::MailboxClosed { reason: CloseReason::Panicked } => "mailbox closed, process panicked",
+                    ActorError::ResponseDropped => "response dropped",
+                    ActorError::Panicked => "handler panicked",
+                    ActorError::WouldDeadlock => "actor asked itself, that would deadlock",
+                    ActorError::RuntimeGone => "actor dropped with its runtime, asked from another one",
+                    ActorError::InvalidMsgType => "invalid msg type",
+                    ActorError::EnqueueTimeout => "enqueue timed out",
+                    ActorError::Rejected(e) => {
+                        return write!(f, "rejected: {}", e);
+                    }
+                    ActorError::RespConversion { variant } => {
+                        return write!(f, "response to {} failed to convert", variant);
+                    }
+                    ActorError::ResponseTimeout { waited, limit, variant } => {
+                        return write!(f, "response to {} timed out after {:?} (limit {:?})", variant, waited, limit);
+                    }
+                };
+                return f.write_str(text);
+            }
+        }
+
+        impl std::error::Error for ActorError {}
+
+        // why a `#[validate(..)]` function turned a msg down
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct ValidationError(pub &'static str);
+
+        impl std::fmt::Display for ValidationError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                return f.write_str(self.0);
+            }
+        }
+
+        impl std::error::Error for ValidationError {}
+
+        // identity of one actor instance, the same for all of its handles. numbered
+        // per actors module in the order actors are created, starting at 1: two
+        // modules both hand out a 1, so only the (module, value) pair is unique, and
+        // that pair is what ids compare, hash and order by. every actors module has
+        // its own `ActorId`, so anything keeping ids of several modules together has
+        // to keep the module next to `value()`. actors of `spawn_many` and the
+        // workers of a pool also carry their index.
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct ActorId {
+            module: &'static str,
+            actor: &'static str,
+            id: u64,
+            index: Option<usize>,
+        }
+
+        impl ActorId {
+            fn next(actor: &'static str, index: Option<usize>) -> Self {
+                static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(1);
+                return Self { module: module_path!(), actor, id: NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed), index };
+            }
+
+            // unique within `module()` only, see `ActorId`
+            pub fn value(&self) -> u64 {
+                return self.id;
+            }
+
+            // the name of the actor struct
+            pub fn actor(&self) -> &'static str {
+                return self.actor;
+            }
+
+            // the path of the actors module numbering this id, `my_crate::actors`
+            pub fn module(&self) -> &'static str {
+                return self.module;
+            }
+
+            // the index `spawn_many` handed this actor's state, or its place in a pool
+            pub fn index(&self) -> Option<usize> {
+                return self.index;
+            }
+        }
+
+        impl PartialEq for ActorId {
+            fn eq(&self, other: &Self) -> bool {
+                return (self.module, self.id) == (other.module, other.id);
+            }
+        }
+
+        impl Eq for ActorId {}
+
+        impl std::hash::Hash for ActorId {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                (self.module, self.id).hash(state);
+            }
+        }
+
+        impl PartialOrd for ActorId {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                return Some(self.cmp(other));
+            }
+        }
+
+        impl Ord for ActorId {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                return (self.module, self.id).cmp(&(other.module, other.id));
+            }
+        }
+
+        impl std::fmt::Display for ActorId {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                return match self.index {
+                    Some(index) => write!(f, "{}::{}[{}]#{}", self.module, self.actor, index, self.id),
+                    None => write!(f, "{}::{}#{}", self.module, self.actor, self.id),
+                };
+            }
+        }
+
+        // an actor's own load, in a field marked `#[actor_stats]`. the run loop
+        // updates it before every msg it hands to `process`.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub struct ActorStats {
+            // msgs handed to `process`, the current one included
+            pub processed: u64,
+            // msgs waiting in the mailbox behind the current one
+            pub queued: usize,
+            // when the current msg was taken out of the mailbox
+            pub last_msg: Option<#time::Instant>,
+            // when the run loop started
+            pub started: Option<#time::Instant>,
+        }
+
+        impl ActorStats {
+            pub fn uptime(&self) -> std::time::Duration {
+                return self.started.map_or(std::time::Duration::ZERO, |started| started.elapsed());
+            }
+        }
+
+        // one variant of a msg enum, as listed in its `FooMsg::ROUTES`. types are
+        // spelled as written in the enum, `#[shared]` fields as the `Arc` they became
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct RouteInfo {
+            // `Add`, without the enum's name
+            pub variant: &'static str,
+            // `(name, type)` of the payload fields, `resp` left out
+            pub fields: &'static [(&'static str, &'static str)],
+            // the `resp` type as declared, `None` for variants that are only told
+            pub resp: Option<&'static str>,
+            pub stream: bool,
+            pub sink: bool,
+            // `#[timeout(ms = ..)]`
+            pub timeout_ms: Option<u64>,
+            // capacity of the bounded channel the variant's msgs go through
+            pub bounded: Option<usize>,
+            // the key field of a `#[latest_only]` variant
+            pub latest_key: Option<&'static str>,
+            pub deprecated: bool,
+            // `#[internal]`: its methods aren't reachable outside the crate
+            pub internal: bool,
+        }
+
+        // state shared between an actor's run loop and all of its handles
+        #[derive(Default)]
+        struct ActorShared {
+            id: ActorId,
+            close_reason: std::sync::atomic::AtomicU8,
+            // panics survived by a supervised actor
+            restarts: std::sync::atomic::AtomicUsize,
+            // live handles, the actor stops once the last one is dropped
+            handles: std::sync::atomic::AtomicUsize,
+            // set by `stop_within`, checked by the run loop between msgs
+            stopping: std::sync::atomic::AtomicBool,
+            stop: std::sync::Mutex<StopState>,
+            // msgs dropped because the stop deadline passed
+            abandoned: std::sync::atomic::AtomicUsize,
+            // the last panic caught in `process`
+            panic: std::sync::Mutex<Option<ActorPanic>>,
+            #runtime_fields
+            #queued_ring
+            // told how `on_start` went, by the run loop
+            ready: std::sync::Mutex<Option<#oneshot::Sender<Result<(), Box<dyn std::error::Error + Send + Sync>>>>>,
+        }
+
+        // what `on_start` may return: nothing, or a `Result` with any error
+        trait IntoStarted {
+            fn into_started(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+        }
+
+        impl IntoStarted for () {
+            fn into_started(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                return Ok(());
+            }
+        }
+
+        impl<E: Into<Box<dyn std::error::Error + Send + Sync>>> IntoStarted for Result<(), E> {
+            fn into_started(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+                return self.map_err(Into::into);
+            }
+        }
+
+        #[derive(Default)]
+        enum StopState {
+            #[default]
+            Running,
+            Requested {
+                deadline: #time::Instant,
+                done: Vec<#oneshot::Sender<usize>>,
+            },
+            Exited,
+        }
+
+        // answers `stop_within` callers however the run loop ends, panics included
+        struct StopGuard(std::sync::Arc<ActorShared>);
+
+        impl Drop for StopGuard {
+            fn drop(&mut self) {
+                let state = match self.0.stop.lock() {
+                    Ok(mut stop) => std::mem::replace(&mut *stop, StopState::Exited),
+                    Err(_e) => return,
+                };
+                if let StopState::Requested { done, .. } = state {
+                    let abandoned = self.0.abandoned.load(std::sync::atomic::Ordering::SeqCst);
+                    for done in done {
+                        let _ = done.send(abandoned);
+                    }
+                }
+            }
+        }
+
+        #running_actor
+
+        impl ActorShared {
+            #asks_itself
+
+            #runs_on
+
+            fn close(&self, reason: CloseReason) {
+                self.close_reason.store(reason as u8, std::sync::atomic::Ordering::SeqCst);
+            }
+
+            // resolves once the run loop got past `on_start`. call before the actor runs
+            #[allow(dead_code)]
+            fn ready(&self) -> #oneshot::Receiver<Result<(), Box<dyn std::error::Error + Send + Sync>>> {
+                let (s, r) = #oneshot::channel();
+                *self.ready.lock().unwrap() = Some(s);
+                return r;
+            }
+
+            fn started(&self, result: Result<(), Box<dyn std::error::Error + Send + Sync>>) {
+                if let Some(ready) = self.ready.lock().unwrap().take() {
+                    let _ = ready.send(result);
+                }
+            }
+
+            fn close_reason(&self) -> CloseReason {
+                match self.close_reason.load(std::sync::atomic::Ordering::SeqCst) {
+                    v if v == CloseReason::Idle as u8 => CloseReason::Idle,
+                    v if v == CloseReason::Stopped as u8 => CloseReason::Stopped,
+                    v if v == CloseReason::StartFailed as u8 => CloseReason::StartFailed,
+                    v if v == CloseReason::Failed as u8 => CloseReason::Failed,
+                    v if v == CloseReason::Panicked as u8 => CloseReason::Panicked,
+                    _ => CloseReason::Exited,
+                }
+            }
+
+            // checked by every send before it hands a msg over: once a stop was requested
+            // no msg is accepted anymore, instead of being queued only to be dropped
+            fn accepting(&self) -> Result<(), ActorError> {
+                if !self.stopping.load(std::sync::atomic::Ordering::SeqCst) {
+                    return Ok(());
+                }
+                let reason = match (self.close_reason(), &*self.stop.lock().unwrap()) {
+                    (CloseReason::Failed, _) => CloseReason::Failed,
+                    (_, StopState::Exited) => CloseReason::Stopped,
+                    _ => CloseReason::Stopping,
+                };
+                return Err(ActorError::MailboxClosed { reason });
+            }
+
+            // `Err` with the abandoned count if the actor already exited. the
+            // earliest deadline asked for wins.
+            fn request_stop(&self, deadline: #time::Instant) -> Result<#oneshot::Receiver<usize>, usize> {
+                let (s, r) = #oneshot::channel();
+                let mut stop = self.stop.lock().unwrap();
+                match &mut *stop {
+                    StopState::Running => *stop = StopState::Requested { deadline, done: vec![s] },
+                    StopState::Requested { deadline: earliest, done } => {
+                        *earliest = deadline.min(*earliest);
+                        done.push(s);
+                    }
+                    StopState::Exited => return Err(self.abandoned.load(std::sync::atomic::Ordering::SeqCst)),
+                }
+                self.stopping.store(true, std::sync::atomic::Ordering::SeqCst);
+                return Ok(r);
+            }
+
+            #[allow(dead_code)]
+            fn panicked(&self, payload: &(dyn std::any::Any + Send)) -> ActorPanic {
+                let panic = ActorPanic::from_payload(payload);
+                if let Ok(mut last) = self.panic.lock() {
+                    *last = Some(panic.clone());
+                }
+                return panic;
+            }
+
+            // `on_error = "stop"`: stops right away, dropping what's queued
+            #[allow(dead_code)]
+            fn failed(&self) {
+                self.close(CloseReason::Failed);
+                let _ = self.request_stop(#time::Instant::now());
+            }
+
+            fn stop_deadline(&self) -> Option<#time::Instant> {
+                if !self.stopping.load(std::sync::atomic::Ordering::Relaxed) {
+                    return None;
+                }
+                return match &*self.stop.lock().unwrap() {
+                    StopState::Requested { deadline, .. } => Some(*deadline),
+                    _ => None,
+                };
+            }
+        }
+
+        pub struct AskFuture<T> {
+            state: AskState<T>,
+            // a variant's `#[timeout(ms = ..)]`, dropped by `with_timeout`
+            timeout: Option<AskDeadline>,
+            // the request's variant, named in timeout errors
+            variant: &'static str,
+            // tells a panic in `process` apart from a dropped `resp`
+            panicked: Option<#oneshot::Receiver<ActorPanic>>,
+            // `#[propagate_panic]`: turns the panic into the response
+            propagate: Option<fn(ActorPanic) -> T>,
+            #late_field
+            #span_field
+        }
+
+        struct AskDeadline {
+            sleep: std::pin::Pin<Box<#time::Sleep>>,
+            start: #time::Instant,
+            limit: std::time::Duration,
+        }
+
+        impl AskDeadline {
+            fn new(limit: std::time::Duration) -> Self {
+                return Self { sleep: Box::pin(#time::sleep(limit)), start: #time::Instant::now(), limit };
+            }
+        }
+
+        #metrics
+
+        #variant_fn
+
+        enum AskState<T> {
+            Sending(std::pin::Pin<Box<dyn std::future::Future<Output = Result<#oneshot::Receiver<T>, ActorError>> + Send>>),
+            Waiting(#oneshot::Receiver<T>),
+            // `resp` was dropped, waiting to hear whether `process` panicked
+            Dropped(#oneshot::Receiver<ActorPanic>),
+            Failed(Option<ActorError>),
+        }
+
+        impl<T> AskFuture<T> {
+            fn sending(f: std::pin::Pin<Box<dyn std::future::Future<Output = Result<#oneshot::Receiver<T>, ActorError>> + Send>>) -> Self {
+                return Self { state: AskState::Sending(f), timeout: None, variant: "", panicked: None, propagate: None, #late_init #span_init };
+            }
+
+            fn waiting(r: #oneshot::Receiver<T>) -> Self {
+                return Self { state: AskState::Waiting(r), timeout: None, variant: "", panicked: None, propagate: None, #late_init #span_init };
+            }
+
+            fn failed(e: ActorError) -> Self {
+                return Self { state: AskState::Failed(Some(e)), timeout: None, variant: "", panicked: None, propagate: None, #late_init #span_init };
+            }
+
+            #in_span
+
+            #[allow(dead_code)]
+            fn default_timeout(mut self, dur: std::time::Duration) -> Self {
+                self.timeout = Some(AskDeadline::new(dur));
+                return self;
+            }
+
+            #[allow(dead_code)]
+            fn watching(mut self, panicked: #oneshot::Receiver<ActorPanic>) -> Self {
+                self.panicked = Some(panicked);
+                return self;
+            }
+
+            #[allow(dead_code)]
+            fn propagating(mut self, propagate: fn(ActorPanic) -> T) -> Self {
+                self.propagate = Some(propagate);
+                return self;
+            }
+
+            // waits until the msg is in the mailbox, only ever pending on a full
+            // bounded one. the response is still to be awaited
+            #[allow(dead_code)]
+            async fn enqueued(mut self) -> Self {
+                if let AskState::Sending(f) = &mut self.state {
+                    self.state = match f.await {
+                        Ok(r) => AskState::Waiting(r),
+                        Err(e) => AskState::Failed(Some(e)),
+                    };
+                }
+                return self;
+            }
+
+            pub fn with_timeout(mut self, dur: std::time::Duration) -> AskTimeout<T> {
+                self.timeout = None;
+                return AskTimeout { inner: self, deadline: AskDeadline::new(dur) };
+            }
+
+            // gives up waiting for the response
+            fn expire(&mut self, deadline: &AskDeadline) -> ActorError {
+                let state = std::mem::replace(&mut self.state, AskState::Failed(None));
+                #expire_count
+                return ActorError::ResponseTimeout { waited: deadline.start.elapsed(), limit: deadline.limit, variant: self.variant };
+            }
+
+            pub fn map_err_into<E: From<ActorError>>(self) -> AskMapErr<T, E> {
+                return AskMapErr { inner: self, _marker: std::marker::PhantomData };
+            }
+        }
+
+        impl<T> AskFuture<Box<T>> {
+            // the value of a `#[resp(boxed)]` reply, boxed on its way through the mailbox
+            pub fn unboxed(self) -> AskUnboxed<T> {
+                return AskUnboxed { inner: self };
+            }
+        }
+
+        impl<T, E: From<ActorError>> AskFuture<Result<T, E>> {
+            // folds mailbox errors into the handler's own error type
+            pub fn flatten(self) -> AskFlatten<T, E> {
+                return AskFlatten { inner: self };
+            }
+        }
+
+        impl<T> std::future::Future for AskFuture<T> {
+            type Output = Result<T, ActorError>;
+
+            fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+                let this = self.get_mut();
+                #span_enter
+                loop {
+                    match &mut this.state {
+                        AskState::Sending(f) => match f.as_mut().poll(cx) {
+                            std::task::Poll::Ready(Ok(r)) => this.state = AskState::Waiting(r),
+                            std::task::Poll::Ready(Err(e)) => this.state = AskState::Failed(Some(e)),
+                            std::task::Poll::Pending => break,
+                        },
+                        AskState::Waiting(r) => match std::pin::Pin::new(r).poll(cx) {
+                            std::task::Poll::Ready(Ok(v)) => return std::task::Poll::Ready(Ok(v)),
+                            std::task::Poll::Ready(Err(_e)) => match this.panicked.take() {
+                                Some(panicked) => this.state = AskState::Dropped(panicked),
+                                None => return std::task::Poll::Ready(Err(#dropped)),
+                            },
+                            std::task::Poll::Pending => break,
+                        },
+                        AskState::Dropped(panicked) => match std::pin::Pin::new(panicked).poll(cx) {
+                            std::task::Poll::Ready(Ok(panic)) => {
+                                return std::task::Poll::Ready(match this.propagate {
+                                    Some(propagate) => Ok(propagate(panic)),
+                                    None => Err(ActorError::Panicked),
+                                });
+                            }
+                            std::task::Poll::Ready(Err(_e)) => return std::task::Poll::Ready(Err(#dropped)),
+                            std::task::Poll::Pending => break,
+                        },
+                        AskState::Failed(e) => {
+                            return std::task::Poll::Ready(Err(e.take().expect("AskFuture polled after completion")));
+                        }
+                    }
+                }
+                if let Some(deadline) = &mut this.timeout {
+                    if deadline.sleep.as_mut().poll(cx).is_ready() {
+                        let deadline = this.timeout.take().unwrap();
+                        return std::task::Poll::Ready(Err(this.expire(&deadline)));
+                    }
+                }
+                return std::task::Poll::Pending;
+            }
+        }
+
+        pub struct AskTimeout<T> {
+            inner: AskFuture<T>,
+            deadline: AskDeadline,
+        }
+
+        impl<T> std::future::Future for AskTimeout<T> {
+            type Output = Result<T, ActorError>;
+
+            fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+                let this = self.get_mut();
+                if let std::task::Poll::Ready(v) = std::pin::Pin::new(&mut this.inner).poll(cx) {
+                    return std::task::Poll::Ready(v);
+                }
+                return this.deadline.sleep.as_mut().poll(cx).map(|_| Err(this.inner.expire(&this.deadline)));
+            }
+        }
+
+        pub struct AskMapErr<T, E> {
+            inner: AskFuture<T>,
+            _marker: std::marker::PhantomData<fn() -> E>,
+        }
+
+        impl<T, E: From<ActorError>> std::future::Future for AskMapErr<T, E> {
+            type Output = Result<T, E>;
+
+            fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+                return std::pin::Pin::new(&mut self.get_mut().inner).poll(cx).map(|v| v.map_err(E::from));
+            }
+        }
+
+        pub struct AskFlatten<T, E> {
+            inner: AskFuture<Result<T, E>>,
+        }
+
+        impl<T, E: From<ActorError>> std::future::Future for AskFlatten<T, E> {
+            type Output = Result<T, E>;
+
+            fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+                return std::pin::Pin::new(&mut self.get_mut().inner).poll(cx).map(|v| match v {
+                    Ok(v) => v,
+                    Err(e) => Err(E::from(e)),
+                });
+            }
+        }
+
+        pub struct AskUnboxed<T> {
+            inner: AskFuture<Box<T>>,
+        }
+
+        impl<T> std::future::Future for AskUnboxed<T> {
+            type Output = Result<T, ActorError>;
+
+            fn poll(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<Self::Output> {
+                return std::pin::Pin::new(&mut self.get_mut().inner).poll(cx).map(|v| v.map(|boxed| *boxed));
+            }
+        }
+
+        // `resp` of `resp: ()` variants: call `ack()` once the msg is handled. dropping
+        // it unanswered fails the caller with `ActorError::ResponseDropped`.
+        #[derive(Debug)]
+        #[must_use = "the caller waits until `ack()` is called"]
+        pub struct Ack {
+            sender: #oneshot::Sender<()>,
+        }
+
+        // `resp` of a `#[resp(..)]` variant: answered with the handler's own type,
+        // converted into the one the caller asked for
+        #[must_use = "the caller waits until `respond_with(..)` is called"]
+        pub struct Responder<I, T> {
+            sender: #oneshot::Sender<T>,
+            convert: fn(I) -> T,
+        }
+
+        impl<I, T> Responder<I, T> {
+            pub fn respond_with(self, internal: I) -> Result<(), T> {
+                return self.sender.send((self.convert)(internal));
+            }
+
+            // answers with the caller's type as is
+            pub fn send(self, v: T) -> Result<(), T> {
+                return self.sender.send(v);
+            }
+        }
+
+        impl Ack {
+            pub fn ack(self) {
+                let _ = self.sender.send(());
+            }
+
+            // same as `ack()`, so handlers written against the plain oneshot keep compiling
+            pub fn send(self, v: ()) -> Result<(), ()> {
+                return self.sender.send(v);
+            }
+        }
+
+        // events an actor publishes from a `#[events]` field. every subscriber gets
+        // each event, one lagging more than `capacity` behind misses the oldest ones.
+        // the run loop closes it once the actor stops, subscribers then see the end
+        // after everything emitted before.
+        pub struct Emitter<E> {
+            sender: std::sync::Arc<std::sync::Mutex<Option<#broadcast::Sender<E>>>>,
+        }
+
+        impl<E> Clone for Emitter<E> {
+            fn clone(&self) -> Self {
+                return Self { sender: self.sender.clone() };
+            }
+        }
+
+        impl<E: Clone> Emitter<E> {
+            pub fn new(capacity: usize) -> Self {
+                let (sender, _receiver) = #broadcast::channel(capacity);
+                return Self { sender: std::sync::Arc::new(std::sync::Mutex::new(Some(sender))) };
+            }
+
+            // the number of subscribers reached, none once closed
+            pub fn emit(&self, event: E) -> usize {
+                return match &*self.sender.lock().unwrap() {
+                    Some(sender) => sender.send(event).unwrap_or(0),
+                    None => 0,
+                };
+            }
+
+            pub fn subscribe(&self) -> #broadcast::Receiver<E> {
+                return match &*self.sender.lock().unwrap() {
+                    Some(sender) => sender.subscribe(),
+                    None => #broadcast::channel(1).1,
+                };
+            }
+
+            fn close(&self) {
+                self.sender.lock().unwrap().take();
+            }
+        }
+
+        // publishes events of an `#[event]` enum to other actors without knowing their
+        // types: each subscriber registers a handle plus a closure turning an event into
+        // one of its own msgs, told to it right away so it sees events in emit order.
+        pub struct EventBus<E> {
+            subscribers: std::sync::Arc<std::sync::Mutex<EventSubscribers<E>>>,
+        }
+
+        // told whether the subscriber is still alive
+        type EventDelivery<E> = Box<dyn Fn(&E) -> bool + Send + Sync>;
+
+        struct EventSubscribers<E> {
+            next: u64,
+            list: Vec<(SubscriptionId, EventDelivery<E>)>,
+        }
+
+        // what `EventBus::unsubscribe` takes to remove a subscription again
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct SubscriptionId(u64);
+
+        impl<E> Clone for EventBus<E> {
+            fn clone(&self) -> Self {
+                return Self { subscribers: self.subscribers.clone() };
+            }
+        }
+
+        impl<E> Default for EventBus<E> {
+            fn default() -> Self {
+                return Self::new();
+            }
+        }
+
+        impl<E> EventBus<E> {
+            pub fn new() -> Self {
+                return Self { subscribers: std::sync::Arc::new(std::sync::Mutex::new(EventSubscribers { next: 0, list: vec![] })) };
+            }
+
+            // `map` picks the events `sink` gets and turns them into its msgs. the bus holds
+            // a handle of the subscriber, dropped as soon as it's the last one left.
+            pub fn subscribe<M, S: EventSink<M>>(&self, sink: &S, map: impl Fn(&E) -> Option<M> + Send + Sync + 'static) -> SubscriptionId {
+                let sink = sink.clone();
+                let delivery: EventDelivery<E> = Box::new(move |event| {
+                    if sink.handle_count() <= 1 {
+                        return false;
+                    }
+                    return match map(event) {
+                        Some(msg) => !matches!(sink.try_tell(msg), Err(ActorError::MailboxClosed { .. })),
+                        None => true,
+                    };
+                });
+                let mut subscribers = self.subscribers.lock().unwrap();
+                let id = SubscriptionId(subscribers.next);
+                subscribers.next += 1;
+                subscribers.list.push((id, delivery));
+                return id;
+            }
+
+            // whether the subscription was still there
+            pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+                let mut subscribers = self.subscribers.lock().unwrap();
+                let before = subscribers.list.len();
+                subscribers.list.retain(|(v, _delivery)| *v != id);
+                return subscribers.list.len() < before;
+            }
+
+            // tells every subscriber, pruning the ones whose actor is gone. returns the
+            // number of subscribers left.
+            pub fn emit(&self, event: E) -> usize {
+                let mut subscribers = self.subscribers.lock().unwrap();
+                subscribers.list.retain(|(_id, delivery)| delivery(&event));
+                return subscribers.list.len();
+            }
+
+            pub fn subscriber_count(&self) -> usize {
+                return self.subscribers.lock().unwrap().list.len();
+            }
+        }
+
+        // a handle an `EventBus` can deliver to, implemented by every handle of the module
+        pub trait EventSink<M>: Clone + Send + Sync + 'static {
+            // hands `msg` over without waiting, like `Extend`
+            fn try_tell(&self, msg: M) -> Result<(), ActorError>;
+
+            fn handle_count(&self) -> usize;
+        }
+
+        // a request type an actor answers, for code generic over what it accepts.
+        // implemented for every plain ask variant, with `CounterAddRequest` for `Counter`'s `Add`.
+        pub trait Handles<R> {
+            type Response;
+
+            fn call(&self, req: R) -> AskFuture<Self::Response>;
+        }
+
+        // what every handle of the module can do whatever its actor, so handles of
+        // different actors fit in one `Vec<Box<dyn AnyActor>>`
+        pub trait AnyActor: Send + Sync {
+            fn id(&self) -> ActorId;
+
+            // the mailbox takes no more msgs: the actor stopped or is stopping
+            fn is_closed(&self) -> bool;
+
+            // `stop_within` with no time left, without waiting for `on_stop`
+            fn stop(&self);
+
+            // how long a msg sent now takes to reach the run loop
+            fn ping(&self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<std::time::Duration, ActorError>> + Send + '_>>;
+        }
+
+        // a pipeline `new_from_receiver` takes its msgs from, `None` once it ended.
+        // implement it to feed an actor from anything else.
+        pub trait MsgSource<M>: Send + 'static {
+            fn next_msg(&mut self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<M>> + Send + '_>>;
+        }
+
+        impl<M: Send + 'static> MsgSource<M> for #mpsc::Receiver<M> {
+            fn next_msg(&mut self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<M>> + Send + '_>> {
+                return Box::pin(self.recv());
+            }
+        }
+
+        impl<M: Send + 'static> MsgSource<M> for #mpsc::UnboundedReceiver<M> {
+            fn next_msg(&mut self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<M>> + Send + '_>> {
+                return Box::pin(self.recv());
+            }
+        }
+
+        // a `MsgSource` as polled by the mailbox, which can't name its type
+        trait MailboxFeed<M>: Send {
+            fn poll_feed(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<M>>;
+        }
+
+        // the pending `next_msg` owns the source and hands it back along with the msg
+        struct SourceFeed<M, S> {
+            next: std::pin::Pin<Box<dyn std::future::Future<Output = (Option<M>, S)> + Send>>,
+        }
+
+        impl<M: Send + 'static, S: MsgSource<M>> SourceFeed<M, S> {
+            fn new(source: S) -> Self {
+                return Self { next: Self::next(source) };
+            }
+
+            fn next(mut source: S) -> std::pin::Pin<Box<dyn std::future::Future<Output = (Option<M>, S)> + Send>> {
+                return Box::pin(async move {
+                    let msg = source.next_msg().await;
+                    return (msg, source);
+                });
+            }
+        }
+
+        impl<M: Send + 'static, S: MsgSource<M>> MailboxFeed<M> for SourceFeed<M, S> {
+            fn poll_feed(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<Option<M>> {
+                let (msg, source) = match self.next.as_mut().poll(cx) {
+                    std::task::Poll::Ready(v) => v,
+                    std::task::Poll::Pending => return std::task::Poll::Pending,
+                };
+                if msg.is_some() {
+                    self.next = Self::next(source);
+                }
+                return std::task::Poll::Ready(msg);
+            }
+        }
+
+        // msgs the receiver lagged behind on are skipped
+        impl<M: Clone + Send + 'static> MsgSource<M> for #broadcast::Receiver<M> {
+            fn next_msg(&mut self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<M>> + Send + '_>> {
+                return Box::pin(async move {
+                    loop {
+                        match self.recv().await {
+                            Ok(v) => return Some(v),
+                            Err(#broadcast::error::RecvError::Lagged(_n)) => continue,
+                            Err(#broadcast::error::RecvError::Closed) => return None,
+                        }
+                    }
+                });
+            }
+        }
+
+        // a handle `pipe_xxx_to` can forward items into, implemented by every handle of the module
+        pub trait PipeSink<M>: Clone + Send + Sync + 'static {
+            fn pipe_send(&self, msg: M) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), ActorError>> + Send + '_>>;
+        }
+
+        // what a `pipe_xxx_to` task forwarded before it ended
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+        pub struct PipeStats {
+            pub items: u64,
+            // failed requests to the source and sends the sink refused
+            pub errors: u64,
+            // the pipe stopped because the sink went away, not because the source ended
+            pub sink_closed: bool,
+        }
+    }
+}
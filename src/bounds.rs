@@ -0,0 +1,60 @@
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+
+// what a feature needs of the user's types, e.g. `#[events]` an event type that
+// is `Clone`. each feature registers its bounds while reading its attribute, and
+// they are all asserted up front next to the handle, the type respanned so an
+// unmet bound is reported at the attribute or field asking for it rather than
+// somewhere inside a generated method.
+pub(crate) struct Bound {
+    ty: TokenStream,
+    bound: TokenStream,
+    // `#[cfg(..)]` of the variant it comes from
+    cfgs: Vec<syn::Attribute>,
+}
+
+impl Bound {
+    // `ty: bound`, reported at `span`
+    pub(crate) fn new(ty: impl ToTokens, bound: TokenStream, span: Span) -> Self {
+        Self {
+            ty: respan(ty.into_token_stream(), span),
+            bound: respan(bound, span),
+            cfgs: vec![],
+        }
+    }
+
+    pub(crate) fn with_cfgs(mut self, cfgs: &[syn::Attribute]) -> Self {
+        self.cfgs = cfgs.to_vec();
+        self
+    }
+}
+
+// one assertion per bound. const items can't be generic, so generic actors and
+// msg enums are left to the errors of the generated code
+pub(crate) fn generate(bounds: &[Bound]) -> TokenStream {
+    let checks = bounds.iter().map(|Bound { ty, bound, cfgs }| {
+        quote! {
+            #(#cfgs)*
+            const _: () = {
+                #[allow(dead_code)]
+                struct Required where #ty: #bound;
+            };
+        }
+    });
+    quote! {#(#checks)*}
+}
+
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|mut tt| {
+            if let TokenTree::Group(g) = &tt {
+                let mut group = Group::new(g.delimiter(), respan(g.stream(), span));
+                group.set_span(span);
+                tt = TokenTree::Group(group);
+            }
+            tt.set_span(span);
+            tt
+        })
+        .collect()
+}
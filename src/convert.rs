@@ -0,0 +1,82 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{Ident, Path, Token, Type, Variant};
+
+// `#[resp(from = RowSet, map = to_records)]` on a variant: `process` answers with
+// its own `RowSet` through `resp.respond_with(..)`, and the caller gets the
+// declared `resp` type, converted on the actor's side before it's sent.
+// `#[resp(try_from = RowSet)]` goes through `TryFrom` instead, a failed
+// conversion reaches the caller as `ActorError::RespConversion`.
+// `#[resp(boxed)]` sends a large reply through the mailbox as a `Box` of the
+// declared type, unboxed again for the caller; `boxed_public` leaves it boxed.
+// `process` answers with the value (`respond_with`) or a box of it (`send`).
+pub(crate) enum Convert {
+    Map { from: Type, map: Path },
+    TryFrom(Type),
+    Boxed { public: bool },
+}
+
+const USAGE: &str =
+    "expected `#[resp(from = Internal, map = path::to::convert)]`, `#[resp(try_from = Internal)]`, `#[resp(boxed)]` or `#[resp(boxed_public)]`";
+
+// reads and strips `#[resp(..)]` from a variant
+pub(crate) fn take(variant: &mut Variant) -> syn::Result<Option<Convert>> {
+    let pos = match variant.attrs.iter().position(|a| a.path.is_ident("resp")) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let attr = variant.attrs.remove(pos);
+    if let Ok(flag) = attr.parse_args::<Ident>() {
+        return match flag.to_string().as_str() {
+            "boxed" => Ok(Some(Convert::Boxed { public: false })),
+            "boxed_public" => Ok(Some(Convert::Boxed { public: true })),
+            _ => Err(syn::Error::new_spanned(&flag, USAGE)),
+        };
+    }
+    let (mut from, mut map, mut try_from) = (None, None, None);
+    attr.parse_args_with(|input: ParseStream| {
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "from" => from = Some(input.parse::<Type>()?),
+                "map" => map = Some(input.parse::<Path>()?),
+                "try_from" => try_from = Some(input.parse::<Type>()?),
+                _ => return Err(syn::Error::new_spanned(&key, USAGE)),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(())
+    })?;
+    match (from, map, try_from) {
+        (Some(from), Some(map), None) => Ok(Some(Convert::Map { from, map })),
+        (None, None, Some(from)) => Ok(Some(Convert::TryFrom(from))),
+        _ => Err(syn::Error::new_spanned(&attr, USAGE)),
+    }
+}
+
+impl Convert {
+    // what `process` answers with, `declared` being the `resp` type as written
+    pub(crate) fn internal<'a>(&'a self, declared: &'a Type) -> &'a Type {
+        match self {
+            Convert::Map { from, .. } => from,
+            Convert::TryFrom(from) => from,
+            Convert::Boxed { .. } => declared,
+        }
+    }
+
+    // the `convert` fn of the variant's `Responder`, `public` being the declared
+    // `resp` type
+    pub(crate) fn function(&self, public: &TokenStream, variant_name: &str) -> TokenStream {
+        match self {
+            Convert::Map { map, .. } => quote! {#map},
+            Convert::Boxed { .. } => quote! {Box::new},
+            Convert::TryFrom(from) => quote! {
+                |internal: #from| <#public as std::convert::TryFrom<#from>>::try_from(internal).map_err(|_e|{return ActorError::RespConversion{variant: #variant_name};})
+            },
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use inflector::Inflector;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{FnArg, Ident, ImplItem, Item, Pat, ReturnType, Type, Visibility};
+
+fn type_ident(ty: &Type) -> Option<&Ident> {
+    match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| &s.ident),
+        _ => None,
+    }
+}
+
+// `#[delegate(Db)]` on an actor struct holding a `Db` field: every public
+// async method of `Db` found in the module becomes a variant of a generated
+// `XXXMsg`, and the generated `process` calls through to the field. it runs
+// before anything else, the rest of the macro sees a regular actor.
+pub(crate) fn expand(items: &mut Vec<Item>) -> syn::Result<()> {
+    let mut generated = vec![];
+    for item in items.iter_mut() {
+        let actor = match item {
+            Item::Struct(v) => v,
+            _ => continue,
+        };
+        let pos = match actor.attrs.iter().position(|a| a.path.is_ident("delegate")) {
+            Some(v) => v,
+            None => continue,
+        };
+        let attr = actor.attrs.remove(pos);
+        let target: Ident = attr.parse_args()?;
+        let field = actor
+            .fields
+            .iter()
+            .enumerate()
+            .find(|(_, f)| type_ident(&f.ty) == Some(&target))
+            .map(|(i, f)| match &f.ident {
+                Some(v) => quote! {#v},
+                None => {
+                    let index = syn::Index::from(i);
+                    quote! {#index}
+                }
+            })
+            .ok_or_else(|| {
+                syn::Error::new_spanned(
+                    &target,
+                    format!("the actor needs a field of type `{}`", target),
+                )
+            })?;
+        generated.push((actor.ident.clone(), actor.vis.clone(), target, field));
+    }
+    for (actor_ident, vis, target, field) in generated {
+        let methods: Vec<syn::ImplItemMethod> = items
+            .iter()
+            .filter_map(|i| match i {
+                Item::Impl(v) if v.trait_.is_none() && type_ident(&v.self_ty) == Some(&target) => {
+                    Some(v)
+                }
+                _ => None,
+            })
+            .flat_map(|v| v.items.iter())
+            .filter_map(|i| match i {
+                ImplItem::Method(m)
+                    if m.sig.asyncness.is_some()
+                        && matches!(m.vis, Visibility::Public(_))
+                        && m.sig.receiver().is_some() =>
+                {
+                    Some(m.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        if methods.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &target,
+                format!(
+                    "no `pub async fn` taking `self` found in an `impl {}` of this module",
+                    target
+                ),
+            ));
+        }
+        let msg_ident = Ident::new(&format!("{}Msg", actor_ident), Span::call_site());
+        let mut variants = vec![];
+        let mut arms = vec![];
+        for m in methods {
+            if !m.sig.generics.params.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &m.sig.generics,
+                    "generic methods cannot be delegated",
+                ));
+            }
+            let name = &m.sig.ident;
+            let variant = Ident::new(&name.to_string().to_pascal_case(), name.span());
+            let mut names = vec![];
+            let mut types = vec![];
+            for arg in m.sig.inputs.iter() {
+                if let FnArg::Typed(t) = arg {
+                    match &*t.pat {
+                        Pat::Ident(p) => names.push(p.ident.clone()),
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                other,
+                                "delegated methods need plain argument names",
+                            ))
+                        }
+                    }
+                    types.push((*t.ty).clone());
+                }
+            }
+            let resp = match &m.sig.output {
+                ReturnType::Default => quote! {()},
+                ReturnType::Type(_, ty) => quote! {#ty},
+            };
+            variants.push(quote! {
+                #variant { #(#names: #types,)* resp: #resp }
+            });
+            arms.push(quote! {
+                #msg_ident::#variant { #(#names,)* resp } => {
+                    let v = self.#field.#name(#(#names),*).await;
+                    if let Some(r) = resp {
+                        let _ = r.send(v);
+                    }
+                }
+            });
+        }
+        items.push(syn::parse_quote! {
+            #vis enum #msg_ident {
+                #(#variants,)*
+            }
+        });
+        items.push(syn::parse_quote! {
+            impl #actor_ident {
+                async fn process(&mut self, msg: #msg_ident) {
+                    match msg {
+                        #(#arms)*
+                    }
+                }
+            }
+        });
+    }
+    Ok(())
+}
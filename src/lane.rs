@@ -0,0 +1,93 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Ident, LitInt, Variant};
+
+use crate::args::ActorsArgs;
+use crate::runtime::Paths;
+use crate::ActorInfo;
+
+// `#[bounded(256)]` on a variant: its msgs go through a bounded channel of their
+// own next to the unbounded mailbox, so a firehose variant gets backpressure
+// while the other variants, and the run loop's control msgs, never wait on it.
+// every `#[bounded(..)]` variant of an actor shares that one channel, the lane.
+
+// reads and strips `#[bounded(n)]` from a variant
+pub(crate) fn take(variant: &mut Variant, args: &ActorsArgs) -> syn::Result<Option<LitInt>> {
+    let pos = match variant
+        .attrs
+        .iter()
+        .position(|a| a.path.is_ident("bounded"))
+    {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let attr = variant.attrs.remove(pos);
+    if args.bounded.is_some() {
+        return Err(syn::Error::new_spanned(
+            &attr,
+            "`#[bounded(..)]` is for variants of unbounded modules, `#[actors(bounded = ..)]` already bounds every msg",
+        ));
+    }
+    let capacity: LitInt = attr.parse_args().map_err(|e| {
+        syn::Error::new(
+            e.span(),
+            "expected a channel capacity like `#[bounded(256)]`",
+        )
+    })?;
+    if capacity.base10_parse::<usize>()? == 0 {
+        return Err(syn::Error::new_spanned(
+            &capacity,
+            "a `#[bounded(..)]` channel needs room for at least one msg",
+        ));
+    }
+    Ok(Some(capacity))
+}
+
+// `Self::__in_lane(&msg)`, telling which of the two channels a msg goes through
+pub(crate) fn generate(info: &ActorInfo) -> Option<TokenStream> {
+    info.lane()?;
+    let ident = info.actor_ident.as_ref().unwrap();
+    let actor_ident = Ident::new(&format!("Actor{}", ident), Span::call_site());
+    let (impl_generics, ty_generics, where_clause) = info.actor_generics.split_for_impl();
+    let msg_type = info.msg_type();
+    let msg_ident = &info.msg_ident;
+    let lanes = info.variants.iter().filter(|v| v.bounded.is_some());
+    let arms = lanes.clone().map(|v| {
+        let cfgs = &v.cfgs;
+        let req = &v.ident;
+        let pattern = v.pattern(quote! {});
+        quote! {#(#cfgs)* #msg_ident::#req #pattern=>true,}
+    });
+    let rest = (lanes.count() < info.variants.len()).then(|| quote! {_=>false,});
+    Some(quote! {
+        impl #impl_generics #actor_ident #ty_generics #where_clause{
+            fn __in_lane(msg: &#msg_type)->bool{
+                return match msg{
+                    #(#arms)*
+                    #rest
+                };
+            }
+        }
+    })
+}
+
+// `lane` for msgs of the `#[bounded(..)]` variants, `main` for the rest.
+// `main` without a lane.
+pub(crate) fn route(info: &ActorInfo, main: TokenStream, lane: TokenStream) -> TokenStream {
+    match info.lane() {
+        Some(_) => quote! {
+            match Self::__in_lane(&msg){
+                true=>#lane,
+                false=>#main,
+            }
+        },
+        None => main,
+    }
+}
+
+// the lane's channel, created with the mailbox
+pub(crate) fn channel(info: &ActorInfo, args: &ActorsArgs) -> Option<TokenStream> {
+    let Paths { mpsc, .. } = args.runtime.paths();
+    info.lane()
+        .map(|capacity| quote! {#mpsc::channel(#capacity)})
+}
@@ -0,0 +1,222 @@
+use inflector::Inflector;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Ident, LitStr, Variant};
+
+use crate::ActorInfo;
+
+// `#[latest_only(key = "device_id")]` on a variant without `resp`: while one
+// msg for a key is still queued, later ones for that key replace it instead of
+// queueing up. the queued msg keeps its place, the run loop swaps in the newest
+// one when it gets there. the slots live next to the handle, guarded by a mutex
+// that is never held across an await. a send takes the key's slot before its msg
+// is queued and gives it back if the msg never gets there, so a failed, dropped
+// or cancelled send doesn't leave a slot nothing will ever empty.
+
+// reads and strips `#[latest_only(..)]`, giving the key field
+pub(crate) fn take(variant: &mut Variant) -> syn::Result<Option<(Ident, syn::Type)>> {
+    let pos = match variant
+        .attrs
+        .iter()
+        .position(|a| a.path.is_ident("latest_only"))
+    {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let attr = variant.attrs.remove(pos);
+    let key: LitStr = attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let name: Ident = input.parse()?;
+        if name != "key" {
+            return Err(syn::Error::new_spanned(&name, "expected `key = \"field\"`"));
+        }
+        input.parse::<syn::Token![=]>()?;
+        input.parse()
+    })?;
+    if variant
+        .fields
+        .iter()
+        .any(|f| f.ident.as_ref().is_some_and(|i| i == "resp"))
+    {
+        return Err(syn::Error::new_spanned(
+            &attr,
+            "`#[latest_only]` msgs may be dropped, they cannot have a `resp`",
+        ));
+    }
+    match variant
+        .fields
+        .iter()
+        .find(|f| f.ident.as_ref().is_some_and(|i| *i == key.value()))
+    {
+        Some(f) => Ok(Some((f.ident.clone().unwrap(), f.ty.clone()))),
+        None => Err(syn::Error::new_spanned(
+            &key,
+            "no such field on this variant",
+        )),
+    }
+}
+
+fn slot(variant: &Ident) -> Ident {
+    Ident::new(
+        &format!("slots_{}", variant.to_string().to_snake_case()),
+        Span::call_site(),
+    )
+}
+
+pub(crate) fn latest_ident(info: &ActorInfo) -> Ident {
+    let ident = info.actor_ident.as_ref().unwrap();
+    Ident::new(&format!("Actor{}Latest", ident), Span::call_site())
+}
+
+fn taken_ident(info: &ActorInfo) -> Ident {
+    let ident = info.actor_ident.as_ref().unwrap();
+    Ident::new(&format!("Actor{}LatestTaken", ident), Span::call_site())
+}
+
+// the guard a send holds on the slot it took, one per `#[latest_only]` variant
+fn taken_var(variant: &Ident) -> Ident {
+    Ident::new(&format!("taken_{}", slot(variant)), Span::call_site())
+}
+
+// the slots shared by the handles and the run loop, and how to make them
+pub(crate) fn slots(info: &ActorInfo) -> (TokenStream, TokenStream) {
+    let latest_ident = latest_ident(info);
+    let msg_type = info.msg_type();
+    let generics = &info.actor_generics;
+    let where_clause = &info.actor_generics.where_clause;
+    let mut fields = vec![];
+    let mut inits = vec![];
+    for v in &info.variants {
+        if let Some((_, ty)) = &v.latest {
+            let slot = slot(&v.ident);
+            let cfgs = &v.cfgs;
+            fields.push(quote! {
+                #(#cfgs)* #slot: std::sync::Mutex<std::collections::HashMap<#ty, Option<#msg_type>>>,
+            });
+            inits.push(
+                quote! {#(#cfgs)* #slot: std::sync::Mutex::new(std::collections::HashMap::new()),},
+            );
+        }
+    }
+    let marker_field = info.marker_type().map(|m| quote! {_marker: #m,});
+    let marker = info
+        .marker_type()
+        .map(|_| quote! {_marker: std::marker::PhantomData,});
+    let taken_ident = taken_ident(info);
+    (
+        quote! {
+            struct #latest_ident #generics #where_clause {
+                #(#fields)*
+                replaced: std::sync::atomic::AtomicU64,
+                #marker_field
+            }
+
+            // a slot a send took for a msg not queued yet, given back when dropped
+            // before `sent`. a msg that filled it meanwhile goes with it, like a msg
+            // queued for an actor that stopped before taking it
+            struct #taken_ident<'a, K: std::hash::Hash + Eq, M> {
+                slots: &'a std::sync::Mutex<std::collections::HashMap<K, Option<M>>>,
+                key: Option<K>,
+            }
+
+            impl<'a, K: std::hash::Hash + Eq, M> #taken_ident<'a, K, M> {
+                fn sent(mut self) {
+                    self.key = None;
+                }
+            }
+
+            impl<'a, K: std::hash::Hash + Eq, M> Drop for #taken_ident<'a, K, M> {
+                fn drop(&mut self) {
+                    if let (Some(key), Ok(mut slots)) = (self.key.take(), self.slots.lock()) {
+                        slots.remove(&key);
+                    }
+                }
+            }
+        },
+        quote! {
+            std::sync::Arc::new(#latest_ident{
+                #(#inits)*
+                replaced: std::sync::atomic::AtomicU64::new(0),
+                #marker
+            })
+        },
+    )
+}
+
+// on the send side: fills the slot of a key that is still queued, or takes it.
+// gives the guards to hold until the msg is queued, and how to let go of them
+// once it is
+pub(crate) fn replace(info: &ActorInfo) -> (TokenStream, TokenStream) {
+    let msg_ident = &info.msg_ident;
+    let taken_ident = taken_ident(info);
+    let mut takes = vec![];
+    let mut sents = vec![];
+    for v in &info.variants {
+        let (key, _) = match &v.latest {
+            Some(v) => v,
+            None => continue,
+        };
+        let ident = &v.ident;
+        let slot = slot(ident);
+        let taken = taken_var(ident);
+        let cfgs = &v.cfgs;
+        takes.push(quote! {
+            #(#cfgs)*
+            let #taken = if let #msg_ident::#ident{#key, ..} = &msg {
+                let key = #key.clone();
+                let mut slots = self.latest.#slot.lock().unwrap();
+                match slots.get_mut(&key) {
+                    // an actor gone for good never takes the queued msg
+                    Some(_slot) if self.sender.is_closed() => {
+                        return Err(ActorError::MailboxClosed{reason: self.shared.close_reason()});
+                    }
+                    Some(slot) => {
+                        *slot = Some(msg);
+                        self.latest.replaced.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        return Ok(());
+                    }
+                    None => {
+                        slots.insert(key.clone(), None);
+                        Some(#taken_ident{slots: &self.latest.#slot, key: Some(key)})
+                    }
+                }
+            } else {
+                None
+            };
+        });
+        sents.push(quote! {
+            #(#cfgs)*
+            if let Some(taken) = #taken {
+                taken.sent();
+            }
+        });
+    }
+    (quote! {#(#takes)*}, quote! {#(#sents)*})
+}
+
+// on the receive side: frees the key, taking the newest msg if one came in since
+pub(crate) fn swap(info: &ActorInfo) -> TokenStream {
+    let msg_ident = &info.msg_ident;
+    let arms = info.variants.iter().filter_map(|v| {
+        let (key, _) = v.latest.as_ref()?;
+        let ident = &v.ident;
+        let slot = slot(ident);
+        let cfgs = &v.cfgs;
+        Some(quote! {
+            #(#cfgs)*
+            #msg_ident::#ident{#key, ..} => {
+                let newest = latest.#slot.lock().unwrap().remove(#key);
+                match newest {
+                    Some(Some(newest)) => newest,
+                    _ => msg,
+                }
+            }
+        })
+    });
+    let rest = info.catch_all(quote! {msg,});
+    quote! {
+        let msg = match &msg{
+            #(#arms)*
+            #rest
+        };
+    }
+}
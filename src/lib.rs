@@ -1,27 +1,877 @@
 extern crate proc_macro;
 
-use std::collections::HashMap;
+mod args;
+mod ask;
+mod bounds;
+mod convert;
+mod delegate;
+mod lane;
+mod latest;
+mod minimal;
+mod permit;
+mod profile;
+mod redact;
+mod registry;
+mod routes;
+mod runtime;
+mod sink;
+mod snapshot;
+mod trace;
+mod wire;
 
+use std::collections::{BTreeSet, HashMap};
+
+use args::{ActorArgs, ActorsArgs, OnError};
 use inflector::Inflector;
 use proc_macro::TokenStream;
 use quote::__private::Span;
 use quote::quote;
+use runtime::Paths;
 use syn::parse::{Parse, Parser};
-use syn::{parse_macro_input, Ident, ItemEnum, ItemImpl, ItemMod, ItemStruct, Type};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, GenericParam, Generics, Ident, ItemEnum, ItemImpl, ItemMod, ItemStruct, Type,
+};
+
+enum Shape {
+    Named,
+    Unnamed,
+    Unit,
+}
+
+struct VariantInfo {
+    ident: Ident,
+    shape: Shape,
+    // names of the fields, empty unless `Shape::Named`
+    fields: Vec<Ident>,
+    trace: trace::VariantTrace,
+    // what travels through the oneshot, `None` for variants without `resp`
+    resp: Option<Type>,
+    // `(T, E)` for `#[fallible]` variants answering with `Result<T, E>`
+    fallible: Option<(Type, Type)>,
+    // `#[cfg(..)]` of the variant, repeated on everything generated for it
+    cfgs: Vec<syn::Attribute>,
+    // `#[deprecated]` of the variant, moved onto its request methods
+    deprecated: Vec<syn::Attribute>,
+    // key field and its type for `#[latest_only]` variants
+    latest: Option<(Ident, Type)>,
+    // `#[timeout(ms = ..)]`, applied by the waiting request method
+    timeout: Option<u64>,
+    // `#[bounded(n)]`: sent through the actor's bounded lane
+    bounded: Option<syn::LitInt>,
+    // `#[stream]`: `resp` is a channel `process` may send any number of items into
+    stream: bool,
+    // `#[sink]`: `resp` is a sink the caller builds, e.g. a bounded mpsc sender
+    sink: bool,
+    // `resp: ()`, answered through an `Ack`
+    unit: bool,
+    // `#[service]`: the request `tower::Service` impl of the handle
+    service: bool,
+    // `#[map]`: an `xxx_map` request method applying a closure to the response
+    map: bool,
+    // the named fields besides `resp`, with their `#[default_arg(..)]` if any
+    typed_args: Vec<(Ident, Type, Option<syn::Expr>)>,
+    // `#[validate(path)]`, run on the request struct before the msg is sent
+    validate: Option<syn::Path>,
+    // `#[propagate_panic]`: a panic in `process` answers with `Err(E::from(ActorPanic))`
+    propagate_panic: bool,
+    // `#[resp(..)]`: `process` answers with its own type, converted for the caller
+    convert: Option<convert::Convert>,
+    // `#[resp(boxed)]`: the declared type the caller gets, `resp` being a box of it
+    unboxed: Option<Type>,
+    // `resp` as written, before it became a channel
+    declared: Option<Type>,
+    // name and type of each payload field, `0`, `1`, .. in tuple variants
+    payload: Vec<(String, Type)>,
+    // which request methods to generate, narrowed by `#[only(ask)]`/`#[only(tell)]`
+    ask: bool,
+    tell: bool,
+    // `pub`, or narrower for `#[internal]` variants: their request methods and structs
+    vis: syn::Visibility,
+}
+
+impl VariantInfo {
+    // what the request methods put into `resp`, around the oneshot sender `s`
+    fn responder(&self, msg_ident: &Ident) -> proc_macro2::TokenStream {
+        if let Some(convert) = &self.convert {
+            let public = match (&self.fallible, &self.resp) {
+                (Some((t, _)), _) => quote! {#t},
+                (None, resp) => quote! {#resp},
+            };
+            let variant_name = format!("{}::{}", msg_ident, self.ident);
+            let function = convert.function(&public, &variant_name);
+            return quote! {Responder{sender: s, convert: #function}};
+        }
+        match self.unit && !self.stream {
+            true => quote! {Ack{sender: s}},
+            false => quote! {s},
+        }
+    }
+
+    // what the caller's ask resolves to: the future, the adapter turning the
+    // variant's `AskFuture` into it, and its `Ok` and `Err` types
+    fn ask_output(
+        &self,
+    ) -> (
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+    ) {
+        match (&self.fallible, &self.unboxed) {
+            (Some((t, e)), _) => (
+                quote! {AskFlatten<#t, #e>},
+                quote! {.flatten()},
+                quote! {#t},
+                quote! {#e},
+            ),
+            (None, Some(t)) => (
+                quote! {AskUnboxed<#t>},
+                quote! {.unboxed()},
+                quote! {#t},
+                quote! {ActorError},
+            ),
+            (None, None) => {
+                let resp = &self.resp;
+                (
+                    quote! {AskFuture<#resp>},
+                    quote! {},
+                    quote! {#resp},
+                    quote! {ActorError},
+                )
+            }
+        }
+    }
+
+    // matches the variant, binding only the given named fields
+    fn pattern(&self, bindings: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self.shape {
+            Shape::Named => quote! {{#bindings ..}},
+            Shape::Unnamed => quote! {(..)},
+            Shape::Unit => quote! {},
+        }
+    }
+}
 
 struct ActorInfo {
     actor_ident: Option<Ident>,
+    // `#[msg_for(inner::Xxx)]`: modules leading to the actor struct
+    actor_mods: Vec<Ident>,
+    actor_generics: Generics,
+    actor_args: ActorArgs,
+    // actors without fields can be spawned without passing any state
+    stateless: bool,
+    // methods found in inherent impls of the actor struct, for optional hooks
+    hooks: Vec<String>,
+    // `process` takes an `ActorXXXCtx` as its third argument
+    ctx: bool,
+    // `process` returns a `Result`, its errors handled as `on_error` says
+    fallible: bool,
+    // the `#[events]` field of the actor struct and its event type
+    events: Option<(Ident, Type)>,
+    // variants of the event enum, when it's declared in the module
+    event_variants: Vec<syn::Variant>,
+    // `#[view(Reader, includes(Get, List))]` on the msg enum: handles with only the
+    // request methods of some variants
+    views: Vec<(Ident, Vec<Ident>)>,
+    // the `#[actor_id]` field of the actor struct, set when the run loop starts
+    id_field: Option<Ident>,
+    // the `#[actor_stats]` field of the actor struct, kept up by the run loop
+    stats_field: Option<Ident>,
+    // what the features in use need of the user's types, asserted up front
+    bounds: Vec<bounds::Bound>,
     msg_ident: Ident,
-    msg_mapping: HashMap<Ident, Type>,
+    msg_generics: Generics,
+    variants: Vec<VariantInfo>,
 }
 
 impl ActorInfo {
-    fn new(msg_ident: Ident) -> Self {
+    fn new(msg_ident: Ident, msg_generics: Generics) -> Self {
         Self {
             actor_ident: None,
+            actor_mods: vec![],
+            actor_generics: Generics::default(),
+            actor_args: ActorArgs::default(),
+            stateless: false,
+            hooks: vec![],
+            ctx: false,
+            fallible: false,
+            events: None,
+            event_variants: vec![],
+            views: vec![],
+            id_field: None,
+            stats_field: None,
+            bounds: vec![],
             msg_ident,
-            msg_mapping: HashMap::new(),
+            msg_generics,
+            variants: vec![],
+        }
+    }
+
+    fn has_resp(&self) -> bool {
+        self.variants.iter().any(|v| v.resp.is_some())
+    }
+
+    // `_ => ..` for the variants a match on the msg enum doesn't pick. left out when
+    // the enum has a single variant, the match is exhaustive and it'd be unreachable
+    fn catch_all(&self, arm: proc_macro2::TokenStream) -> Option<proc_macro2::TokenStream> {
+        (self.variants.len() > 1).then(|| quote! {_=>#arm})
+    }
+
+    // `supervised` or a `#[propagate_panic]` variant: panics in `process` are
+    // caught, recorded and told to the ask waiting on the msg
+    fn watches_panics(&self) -> bool {
+        self.actor_args.supervised || self.variants.iter().any(|v| v.propagate_panic)
+    }
+
+    fn has_hook(&self, name: &str) -> bool {
+        self.hooks.iter().any(|h| h == name)
+    }
+
+    // the `#[service]` variant, when the `tower` feature generates its impl
+    fn service(&self) -> Option<&VariantInfo> {
+        match cfg!(feature = "tower") {
+            true => self.variants.iter().find(|v| v.service),
+            false => None,
+        }
+    }
+
+    fn has_latest(&self) -> bool {
+        self.variants.iter().any(|v| v.latest.is_some())
+    }
+
+    // capacity of the lane shared by the `#[bounded(..)]` variants
+    fn lane(&self) -> Option<&syn::LitInt> {
+        self.variants.iter().find_map(|v| v.bounded.as_ref())
+    }
+
+    // the actor struct as seen from the module root
+    fn actor_type(&self) -> proc_macro2::TokenStream {
+        let mods = &self.actor_mods;
+        let ident = self.actor_ident.as_ref().unwrap();
+        quote! {#(#mods::)* #ident}
+    }
+
+    // `CounterAddRequest`, the payload of `Counter`'s `Add` as a struct of its own.
+    // generated for plain asks, for `Handles`, and for `#[validate]` variants
+    fn request_ident(&self, v: &VariantInfo) -> Option<Ident> {
+        let plain_ask = v.resp.is_some() && v.ask && !v.stream && !v.sink;
+        if !self.msg_generics.params.is_empty() || !(plain_ask || v.validate.is_some()) {
+            return None;
+        }
+        let ident = self.actor_ident.as_ref().unwrap();
+        Some(Ident::new(
+            &format!("{}{}Request", ident, v.ident),
+            Span::call_site(),
+        ))
+    }
+
+    // `reserve` and a `FooPermit`, for bounded mailboxes. `#[latest_only]` msgs have
+    // to go through their slots, so those actors don't get one
+    fn has_permit(&self, args: &ActorsArgs) -> bool {
+        args.bounded.is_some()
+            && self.actor_generics.params.is_empty()
+            && self.msg_generics.params.is_empty()
+            && !self.has_latest()
+    }
+
+    // asks to an actor watching for panics hand the run loop a oneshot to complete
+    // if `process` panics on their msg, so the caller gets `ActorError::Panicked`
+    // rather than `ResponseDropped`. gives the channel, the envelope carrying it
+    // and the `AskFuture` adapter
+    fn watch_panics(
+        &self,
+        args: &ActorsArgs,
+    ) -> (
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+        proc_macro2::TokenStream,
+    ) {
+        let Paths { oneshot, .. } = args.runtime.paths();
+        match self.watches_panics() {
+            true => (
+                quote! {let (panicked, verdict) = #oneshot::channel();},
+                quote! {Envelope::watched(msg, Some(panicked))},
+                quote! {.watching(verdict)},
+            ),
+            false => (quote! {}, quote! {Envelope::new(msg)}, quote! {}),
+        }
+    }
+
+    fn ctx_ident(&self) -> Ident {
+        let ident = self.actor_ident.as_ref().unwrap();
+        Ident::new(&format!("Actor{}Ctx", ident), Span::call_site())
+    }
+
+    fn weak_ident(&self) -> Ident {
+        let ident = self.actor_ident.as_ref().unwrap();
+        Ident::new(&format!("Actor{}Weak", ident), Span::call_site())
+    }
+
+    // the run loop: waits for msgs, dispatches them and calls `on_stop` once
+    // the mailbox is gone. with `idle_timeout` it also closes the mailbox when
+    // nothing arrives in time, still handling whatever was already queued.
+    fn run_loop(&self, args: &ActorsArgs) -> proc_macro2::TokenStream {
+        let Paths {
+            mpsc, task, time, ..
+        } = args.runtime.paths();
+        let msg_type = self.msg_type();
+        let dispatch = self.dispatch();
+        // control msgs are taken care of here, only user msgs reach `process`
+        let control = match cfg!(feature = "test-util") {
+            true => quote! {ControlMsg::Idle(waiter)=>mailbox.idle_waiters.push(waiter),},
+            false => quote! {},
+        };
+        let control = quote! {
+            ControlMsg::Stop=>{}
+            ControlMsg::Flush(done)=>{let _ = done.send(());}
+            #control
+        };
+        let panicked = match self.watches_panics() {
+            true => quote! {mut panicked,},
+            false => quote! {},
+        };
+        let (timed, dequeued, report) = ask::report(args);
+        // plain counters, nothing allocated per msg
+        let count = self.stats_field.as_ref().map(|field| {
+            quote! {
+                self.#field.processed += 1;
+                self.#field.queued = mailbox.len();
+                self.#field.last_msg = Some(#time::Instant::now());
+            }
+        });
+        let dispatch = quote! {
+            match envelope{
+                Letter::User{msg, #panicked #timed ..}=>{
+                    #dequeued
+                    #count
+                    #dispatch
+                    #report
+                }
+                Letter::Control(control)=>match control{#control},
+            }
+        };
+        let latest_param = match self.has_latest() {
+            true => {
+                let latest_ident = latest::latest_ident(self);
+                let (_, ty_generics, _) = self.actor_generics.split_for_impl();
+                quote! {, latest: std::sync::Arc<#latest_ident #ty_generics>}
+            }
+            false => quote! {},
+        };
+        let latest_param = match self.ctx {
+            true => {
+                let weak_ident = self.weak_ident();
+                let (_, ty_generics, _) = self.actor_generics.split_for_impl();
+                quote! {#latest_param, handle: #weak_ident #ty_generics}
+            }
+            false => latest_param,
+        };
+        let on_stop = match (self.has_hook("on_stop"), &self.actor_args.exit) {
+            (true, Some(_)) => quote! {let exit = self.on_stop().await;},
+            (true, None) => quote! {self.on_stop().await;},
+            (false, _) => quote! {},
+        };
+        // tasks from `ctx.spawn` are gone by the time `on_stop` runs
+        let on_stop = match (self.ctx, self.actor_args.await_tasks) {
+            (true, false) => quote! {
+                let mut tasks = std::mem::take(&mut *ctx.tasks.lock().unwrap());
+                tasks.abort_all();
+                while tasks.join_next().await.is_some() {}
+                #on_stop
+            },
+            (true, true) => quote! {
+                let mut tasks = std::mem::take(&mut *ctx.tasks.lock().unwrap());
+                while tasks.join_next().await.is_some() {}
+                #on_stop
+            },
+            (false, _) => on_stop,
+        };
+        let (exit_type, exit) = match &self.actor_args.exit {
+            Some(exit) => (quote! {->#exit}, quote! {return exit;}),
+            None => (quote! {}, quote! {}),
+        };
+        // events emitted from `on_stop` still go out, then subscribers see the end
+        let on_stop = match &self.events {
+            Some((field, _)) => quote! {#on_stop self.#field.close();},
+            None => on_stop,
+        };
+        // with a ctx, continuations finished in the meantime run before the next msg
+        let (ctx, recv) = match self.ctx {
+            true => {
+                let ctx_ident = self.ctx_ident();
+                (
+                    quote! {
+                        let (continuation_sender, mut continuations) = #mpsc::unbounded_channel();
+                        let ctx = #ctx_ident{sender: continuation_sender, id: shared.id, shared: shared.clone(), handle, tasks: std::sync::Mutex::new(#task::JoinSet::new())};
+                    },
+                    quote! {
+                        std::future::poll_fn(|cx|{
+                            while let std::task::Poll::Ready(Some(k)) = continuations.poll_recv(cx) {
+                                k(&mut *self);
+                            }
+                            return mailbox.poll_recv(cx);
+                        })
+                    },
+                )
+            }
+            false => (quote! {}, quote! {mailbox.recv()}),
+        };
+        // `with_external`: the mailbox goes first, external events are taken whenever
+        // it is empty. the pending `next_external` is dropped when a msg wins.
+        let (ctx, recv) = match self.actor_args.with_external {
+            true => (
+                quote! {
+                    #ctx
+                    enum Next<M, E> {
+                        Msg(Option<M>),
+                        External(Option<E>),
+                    }
+                    let mut external_done = false;
+                },
+                quote! {
+                    async {
+                        loop {
+                            let next = match external_done {
+                                true => Next::Msg(mailbox.recv().await),
+                                false => {
+                                    let mut external = std::pin::pin!(self.next_external());
+                                    std::future::poll_fn(|cx|{
+                                        if let std::task::Poll::Ready(msg) = mailbox.poll_recv(cx) {
+                                            return std::task::Poll::Ready(Next::Msg(msg));
+                                        }
+                                        return std::future::Future::poll(external.as_mut(), cx).map(Next::External);
+                                    }).await
+                                }
+                            };
+                            match next {
+                                Next::Msg(msg)=>break msg,
+                                Next::External(Some(event))=>self.process_external(event).await,
+                                Next::External(None)=>external_done = true,
+                            }
+                        }
+                    }
+                },
+            ),
+            false => (ctx, recv),
+        };
+        // `on_idle`: runs once the msgs at hand are processed and none are left, so
+        // once per burst. control msgs alone don't make a burst. with `idle_debounce`
+        // it waits for the mailbox to stay empty that long, a msg arriving first is
+        // processed and the wait starts over once the mailbox is empty again.
+        let (busy, idle, ctx, recv) = match (
+            self.has_hook("on_idle"),
+            self.actor_args.idle_debounce,
+        ) {
+            (false, _) => (quote! {}, quote! {}, ctx, recv),
+            (true, None) => (
+                quote! {busy |= matches!(envelope, Letter::User{..});},
+                quote! {
+                    if busy && mailbox.len() == 0 {
+                        busy = false;
+                        self.on_idle().await;
+                    }
+                },
+                quote! {#ctx let mut busy = false;},
+                recv,
+            ),
+            (true, Some(ms)) => (
+                quote! {busy |= matches!(envelope, Letter::User{..});},
+                quote! {
+                    if busy && mailbox.len() == 0 {
+                        busy = false;
+                        idle_due = true;
+                    }
+                },
+                quote! {#ctx let (mut busy, mut idle_due) = (false, false);},
+                quote! {
+                    async {
+                        if std::mem::take(&mut idle_due) {
+                            match #time::timeout(std::time::Duration::from_millis(#ms), #recv).await{
+                                Ok(envelope)=>return envelope,
+                                Err(_e)=>self.on_idle().await,
+                            }
+                        }
+                        return #recv.await;
+                    }
+                },
+            ),
+        };
+        let receive = match self.actor_args.idle_timeout {
+            Some(ms) => quote! {
+                let envelope = match #time::timeout(std::time::Duration::from_millis(#ms), #recv).await{
+                    Ok(Some(envelope))=>envelope,
+                    Ok(None)=>break,
+                    Err(_e)=>{
+                        shared.close(CloseReason::Idle);
+                        mailbox.close();
+                        while let Some(envelope) = mailbox.recv().await {
+                            #dispatch
+                        }
+                        break;
+                    }
+                };
+            },
+            None => quote! {
+                let envelope = match #recv.await{
+                    Some(envelope)=>envelope,
+                    None=>break,
+                };
+            },
+        };
+        // `on_start` runs before the first msg. when it fails the mailbox is closed
+        // unprocessed, `on_stop` still runs to clean up what was half opened
+        let on_start = match self.has_hook("on_start") {
+            true => quote! {IntoStarted::into_started(self.on_start().await)},
+            false => quote! {Ok(())},
+        };
+        let set_id = self
+            .id_field
+            .as_ref()
+            .map(|field| quote! {self.#field = shared.id;});
+        let set_id = match &self.stats_field {
+            Some(field) => quote! {#set_id self.#field.started = Some(#time::Instant::now());},
+            None => quote! {#set_id},
+        };
+        // keep going with whatever is already queued, without waiting. try_recv
+        // doesn't count against tokio's coop budget, so a full batch charges it once
+        // and a busy actor still yields to other tasks.
+        let batch = match args.batch {
+            Some(batch) if batch > 1 => quote! {
+                let mut batched = 1usize;
+                while batched < #batch {
+                    let envelope = match mailbox.try_recv(){
+                        Some(envelope)=>envelope,
+                        None=>break,
+                    };
+                    #busy
+                    #dispatch
+                    batched += 1;
+                }
+                if batched == #batch {
+                    #task::consume_budget().await;
+                }
+            },
+            _ => quote! {},
+        };
+        // `stop_within`: no new msgs, what's queued is processed until the deadline
+        // and dropped after it, so callers waiting on those fail right away
+        let stop = quote! {
+            if let Some(deadline) = shared.stop_deadline() {
+                if shared.close_reason() != CloseReason::Failed {
+                    shared.close(CloseReason::Stopped);
+                }
+                mailbox.close();
+                let mut abandoned = 0usize;
+                while let Some(envelope) = mailbox.recv_until(deadline).await {
+                    if #time::Instant::now() >= deadline {
+                        if let Letter::User{..} = envelope.0 {
+                            abandoned += 1;
+                        }
+                        continue;
+                    }
+                    let envelope = envelope.open();
+                    #dispatch
+                }
+                shared.abandoned.store(abandoned, std::sync::atomic::Ordering::SeqCst);
+                break;
+            }
+        };
+        let (running, returned) = match args.runtime_check {
+            true => (quote! {shared.running();}, quote! {shared.returned();}),
+            false => (quote! {}, quote! {}),
+        };
+        quote! {
+            async fn run(&mut self, mut mailbox: Mailbox<#msg_type>, shared: std::sync::Arc<ActorShared> #latest_param) #exit_type{
+                let _stop = StopGuard(shared.clone());
+                #running
+                #set_id
+                #ctx
+                let started = #on_start;
+                let failed = started.is_err();
+                shared.started(started);
+                if failed {
+                    shared.close(CloseReason::StartFailed);
+                    mailbox.close();
+                }
+                if !failed {
+                    loop {
+                        #stop
+                        #receive
+                        #busy
+                        #dispatch
+                        #batch
+                        #idle
+                    }
+                }
+                #on_stop
+                #returned
+                drop(shared);
+                #exit
+            }
+        }
+    }
+
+    fn dispatch(&self) -> proc_macro2::TokenStream {
+        let ctx = match self.ctx {
+            true => quote! {, &ctx},
+            false => quote! {},
+        };
+        let msg_ident = &self.msg_ident;
+        let mut prelude = match self.has_latest() {
+            true => latest::swap(self),
+            false => quote! {},
+        };
+        let mut call = quote! {self.process(msg #ctx)};
+        if cfg!(feature = "tracing") {
+            let actor_name = self.actor_ident.as_ref().unwrap().to_string();
+            let arms = self.variants.iter().map(|v| {
+                let ident = &v.ident;
+                let (bindings, span) = v.trace.span(&actor_name, ident);
+                let pattern = v.pattern(bindings);
+                let cfgs = &v.cfgs;
+                quote! {#(#cfgs)* #msg_ident::#ident #pattern => #span,}
+            });
+            prelude = quote! {
+                #prelude
+                let span = match &msg{
+                    #(#arms)*
+                };
+            };
+            call = quote! {::tracing::Instrument::instrument(#call, span)};
+        }
+        // `supervised`: a panic in `process` is caught, counted and followed by the
+        // `on_restart` hook, then the actor moves on to the next msg. an actor only
+        // watching for panics records it and goes on panicking.
+        let restart = match (self.actor_args.supervised, self.has_hook("on_restart")) {
+            (true, true) => quote! {
+                drop(panic);
+                shared.restarts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                self.on_restart().await;
+            },
+            (true, false) => quote! {
+                drop(panic);
+                shared.restarts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            },
+            (false, _) => quote! {
+                shared.close(CloseReason::Panicked);
+                std::panic::resume_unwind(panic);
+            },
+        };
+        let caught = quote! {{
+            let mut process = std::pin::pin!(#call);
+            std::future::poll_fn(|cx| {
+                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| std::future::Future::poll(process.as_mut(), cx))) {
+                    Ok(std::task::Poll::Ready(v)) => std::task::Poll::Ready(Ok(v)),
+                    Ok(std::task::Poll::Pending) => std::task::Poll::Pending,
+                    Err(panic) => {
+                        let text = shared.panicked(&*panic);
+                        if let Some(panicked) = panicked.take() {
+                            let _ = panicked.send(text);
+                        }
+                        std::task::Poll::Ready(Err(panic))
+                    }
+                }
+            }).await
+        }};
+        let supervised = self.watches_panics();
+        // a `Result`-returning `process` without `ack`: the error goes to the
+        // `on_error` hook, then `on_error = ".."` decides how the actor carries on.
+        // the msg was moved into `process`, so an unanswered `resp` is dropped by now
+        // and its caller already got `ResponseDropped`
+        if self.fallible && self.actor_args.ack.is_none() {
+            let report = match self.has_hook("on_error") {
+                true => quote! {self.on_error(e).await;},
+                false => quote! {let _ = e;},
+            };
+            let policy = match self.actor_args.on_error.unwrap_or_default() {
+                OnError::Continue => quote! {},
+                OnError::Stop => quote! {shared.failed();},
+                // counted like a panic under `supervised`, with no panic to drop
+                OnError::Restart => {
+                    let on_restart = self
+                        .has_hook("on_restart")
+                        .then(|| quote! {self.on_restart().await;});
+                    quote! {
+                        shared.restarts.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        #on_restart
+                    }
+                }
+            };
+            return match supervised {
+                true => quote! {
+                    #prelude
+                    match #caught{
+                        Ok(Ok(_v))=>{}
+                        Ok(Err(e))=>{
+                            #report
+                            #policy
+                        }
+                        Err(panic)=>{#restart}
+                    }
+                },
+                false => quote! {
+                    #prelude
+                    if let Err(e) = #call.await {
+                        #report
+                        #policy
+                    }
+                },
+            };
+        }
+        // `ack = id`: `process` returns a `Result`, settled through the user's hooks
+        let ack = match (&self.actor_args.ack, supervised) {
+            (Some(field), _) => field,
+            (None, true) => {
+                return quote! {
+                    #prelude
+                    if let Err(panic) = #caught {
+                        #restart
+                    }
+                }
+            }
+            (None, false) => return quote! {#prelude #call.await;},
+        };
+        let arms = self.variants.iter().map(|v| {
+            let ident = &v.ident;
+            let cfgs = &v.cfgs;
+            quote! {#(#cfgs)* #msg_ident::#ident{#ack, ..} => #ack.clone(),}
+        });
+        // a msg that panicked is neither acked nor nacked
+        let settle = match supervised {
+            true => quote! {
+                match #caught{
+                    Ok(Ok(_v))=>self.ack(id).await,
+                    Ok(Err(e))=>self.nack(id, e).await,
+                    Err(panic)=>{#restart}
+                }
+            },
+            false => quote! {
+                match #call.await{
+                    Ok(_v)=>self.ack(id).await,
+                    Err(e)=>self.nack(id, e).await,
+                }
+            },
+        };
+        quote! {
+            #prelude
+            let id = match &msg{
+                #(#arms)*
+            };
+            #settle
+        }
+    }
+
+    // the msg enum as seen from the actor: params shared with the actor struct are
+    // forwarded by name, the rest fall back to their declared defaults.
+    fn msg_type(&self) -> proc_macro2::TokenStream {
+        let msg_ident = &self.msg_ident;
+        if self.msg_generics.params.is_empty() {
+            return quote! {#msg_ident};
+        }
+        let declared: Vec<String> = self
+            .actor_generics
+            .params
+            .iter()
+            .filter_map(generic_param_name)
+            .collect();
+        let mut args = vec![];
+        for param in &self.msg_generics.params {
+            match param {
+                GenericParam::Type(t) => {
+                    let ident = &t.ident;
+                    match &t.default {
+                        Some(default) if !declared.contains(&ident.to_string()) => {
+                            args.push(quote! {#default})
+                        }
+                        _ => args.push(quote! {#ident}),
+                    }
+                }
+                GenericParam::Const(c) => {
+                    let ident = &c.ident;
+                    match &c.default {
+                        Some(default) if !declared.contains(&ident.to_string()) => {
+                            args.push(quote! {{#default}})
+                        }
+                        _ => args.push(quote! {#ident}),
+                    }
+                }
+                GenericParam::Lifetime(l) => {
+                    let lifetime = &l.lifetime;
+                    args.push(quote! {#lifetime});
+                }
+            }
+        }
+        quote! {#msg_ident<#(#args),*>}
+    }
+
+    // types taken from the variants that name a msg enum param the actor struct doesn't
+    // declare get its default instead, the same one `msg_type` picks for the param
+    fn resolve_msg_defaults(&mut self) {
+        struct Defaults(HashMap<String, Type>);
+        impl syn::fold::Fold for Defaults {
+            fn fold_type(&mut self, ty: Type) -> Type {
+                if let Type::Path(p) = &ty {
+                    let default = p.path.get_ident().and_then(|v| self.0.get(&v.to_string()));
+                    if let (None, Some(default)) = (&p.qself, default) {
+                        return default.clone();
+                    }
+                }
+                syn::fold::fold_type(self, ty)
+            }
+        }
+        let declared: Vec<String> = self
+            .actor_generics
+            .params
+            .iter()
+            .filter_map(generic_param_name)
+            .collect();
+        let defaults: HashMap<String, Type> = self
+            .msg_generics
+            .type_params()
+            .filter(|t| !declared.contains(&t.ident.to_string()))
+            .filter_map(|t| Some((t.ident.to_string(), t.default.clone()?)))
+            .collect();
+        if defaults.is_empty() {
+            return;
+        }
+        let mut fold = Defaults(defaults);
+        let mut fold_type = |ty: &mut Type| *ty = syn::fold::Fold::fold_type(&mut fold, ty.clone());
+        for v in &mut self.variants {
+            v.resp.iter_mut().for_each(&mut fold_type);
+            v.unboxed.iter_mut().for_each(&mut fold_type);
+            v.declared.iter_mut().for_each(&mut fold_type);
+            if let Some((t, e)) = &mut v.fallible {
+                fold_type(t);
+                fold_type(e);
+            }
+            if let Some((_, key)) = &mut v.latest {
+                fold_type(key);
+            }
+            v.typed_args.iter_mut().for_each(|(_, ty, _)| fold_type(ty));
+            v.payload.iter_mut().for_each(|(_, ty)| fold_type(ty));
+        }
+    }
+
+    // handles only carry a sender, so actor type params need a marker to stay used.
+    fn marker_type(&self) -> Option<proc_macro2::TokenStream> {
+        let params: Vec<&Ident> = self
+            .actor_generics
+            .type_params()
+            .map(|t| &t.ident)
+            .collect();
+        if params.is_empty() {
+            return None;
         }
+        Some(quote! {std::marker::PhantomData<fn() -> (#(#params,)*)>})
+    }
+}
+
+fn generic_param_name(param: &GenericParam) -> Option<String> {
+    match param {
+        GenericParam::Type(t) => Some(t.ident.to_string()),
+        GenericParam::Const(c) => Some(c.ident.to_string()),
+        GenericParam::Lifetime(_) => None,
     }
 }
 
@@ -46,189 +896,3064 @@ fn get_actor_name(id: ID) -> Option<String> {
     }
 }
 
-fn process_enum(item: &mut ItemEnum, info: &mut ActorInfo) {
-    for v in &mut item.variants {
-        match &mut v.fields {
-            syn::Fields::Named(fields) => {
-                let mut new_list = vec![];
-                for field in &mut fields.named {
-                    if field.ident.is_some() && field.ident.as_ref().unwrap() == "resp" {
-                        let ty = field.ty.clone();
-                        info.msg_mapping.insert(v.ident.clone(), ty.clone());
-                        new_list.push(
-                            syn::Field::parse_named
-                                .parse2(quote! { resp: Option<tokio::sync::oneshot::Sender<#ty>>})
-                                .unwrap(),
-                        );
-                    } else {
-                        new_list.push(field.clone());
-                    }
-                }
-                fields.named.clear();
-                for v in new_list {
-                    fields.named.push(v);
-                }
+// one msg enum per actor: a second one mapping to the same name would merge its
+// variants into the first's handle. names only differing in case, `HttpServer`
+// and `HTTPServer`, are refused too, they'd share the `http_server` field of
+// `Actors` and read as the same actor anyway.
+fn check_actor_name(
+    context: &HashMap<String, ActorInfo>,
+    name: &str,
+    msg: &Ident,
+) -> syn::Result<()> {
+    let snake = name.to_snake_case();
+    let first = context
+        .iter()
+        .find(|(other, info)| other.to_snake_case() == snake && info.msg_ident != *msg);
+    let (other, info) = match first {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let first = &info.msg_ident;
+    let mut e = match other == name {
+        true => syn::Error::new_spanned(
+            msg,
+            format!(
+                "`{}` and `{}` both map to the actor `{}`, which can only have one handle `Actor{}`",
+                first, msg, name, name
+            ),
+        ),
+        false => syn::Error::new_spanned(
+            msg,
+            format!(
+                "`{}` maps to the actor `{}`, too close to `{}` of `{}`: both are `{}` once snake cased",
+                msg, name, other, first, snake
+            ),
+        ),
+    };
+    e.combine(syn::Error::new_spanned(
+        first,
+        format!("`{}` maps to `{}` here", first, other),
+    ));
+    Err(e)
+}
+
+// a variant's `#[deprecated]` attrs, moved to its request methods so only callers are warned
+fn take_deprecated(attrs: &mut Vec<syn::Attribute>) -> Vec<syn::Attribute> {
+    let (deprecated, rest) = std::mem::take(attrs)
+        .into_iter()
+        .partition(|a| a.path.is_ident("deprecated"));
+    *attrs = rest;
+    deprecated
+}
+
+// removes a bare marker attribute like `#[fallible]`, telling whether it was there
+fn take_flag(attrs: &mut Vec<syn::Attribute>, name: &str) -> bool {
+    let before = attrs.len();
+    attrs.retain(|a| !a.path.is_ident(name));
+    attrs.len() != before
+}
+
+// `#[only(ask)]` or `#[only(tell)]`, as `(ask, tell)`
+fn take_only(attrs: &mut Vec<syn::Attribute>) -> syn::Result<(bool, bool)> {
+    let pos = match attrs.iter().position(|a| a.path.is_ident("only")) {
+        Some(v) => v,
+        None => return Ok((true, true)),
+    };
+    let attr = attrs.remove(pos);
+    let which: Ident = attr.parse_args()?;
+    match which.to_string().as_str() {
+        "ask" => Ok((true, false)),
+        "tell" => Ok((false, true)),
+        _ => Err(syn::Error::new_spanned(
+            &which,
+            "expected `#[only(ask)]` or `#[only(tell)]`",
+        )),
+    }
+}
+
+// `#[internal]` or `#[internal(super)]`: the visibility of the variant's request
+// methods and structs, `pub(crate)` or `pub(super)` as seen from the actors module
+fn take_internal(
+    attrs: &mut Vec<syn::Attribute>,
+    args: &ActorsArgs,
+) -> syn::Result<syn::Visibility> {
+    let pos = match attrs.iter().position(|a| a.path.is_ident("internal")) {
+        Some(v) => v,
+        None => return Ok(syn::parse_quote! {pub}),
+    };
+    let attr = attrs.remove(pos);
+    if attr.tokens.is_empty() {
+        return Ok(syn::parse_quote! {pub(crate)});
+    }
+    let which: Ident = attr.parse_args()?;
+    match (which.to_string().as_str(), &args.output_mod) {
+        // generated into `output_mod`, one level further down
+        ("super", Some(_)) => Ok(syn::parse_quote! {pub(in super::super)}),
+        ("super", None) => Ok(syn::parse_quote! {pub(super)}),
+        _ => Err(syn::Error::new_spanned(
+            &which,
+            "expected `#[internal]` or `#[internal(super)]`",
+        )),
+    }
+}
+
+// every `#[view(Name, includes(A, B))]`, stripped from the msg enum
+fn take_views(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Vec<(Ident, Vec<Ident>)>> {
+    let mut views = vec![];
+    let mut kept = vec![];
+    for attr in attrs.drain(..) {
+        if !attr.path.is_ident("view") {
+            kept.push(attr);
+            continue;
+        }
+        views.push(attr.parse_args_with(|input: syn::parse::ParseStream| {
+            let name: Ident = input.parse()?;
+            input.parse::<syn::Token![,]>()?;
+            let key: Ident = input.parse()?;
+            if key != "includes" {
+                return Err(syn::Error::new_spanned(&key, "expected `includes(..)`"));
             }
-            _ => {}
+            let content;
+            syn::parenthesized!(content in input);
+            let variants =
+                syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated(&content)?;
+            Ok((name, variants.into_iter().collect()))
+        })?);
+    }
+    *attrs = kept;
+    Ok(views)
+}
+
+// `#[timeout("500ms")]`, `#[timeout(500)]` or `#[timeout(ms = 500)]` in milliseconds
+fn take_timeout(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<u64>> {
+    let pos = match attrs.iter().position(|a| a.path.is_ident("timeout")) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let attr = attrs.remove(pos);
+    let ms = attr.parse_args_with(|input: syn::parse::ParseStream| {
+        if !input.peek(Ident) {
+            return args::parse_duration(input);
+        }
+        let key: Ident = input.parse()?;
+        if key != "ms" {
+            return Err(syn::Error::new_spanned(
+                &key,
+                "expected `#[timeout(\"500ms\")]` or `#[timeout(ms = 500)]`",
+            ));
+        }
+        input.parse::<syn::Token![=]>()?;
+        input.parse::<syn::LitInt>()?.base10_parse::<u64>()
+    })?;
+    Ok(Some(ms))
+}
+
+// `#[default_arg(expr)]` on a variant field, the value its `xxx_args` request fills in
+fn take_default_arg(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<syn::Expr>> {
+    let mut result = None;
+    let mut kept = vec![];
+    for attr in attrs.drain(..) {
+        if attr.path.is_ident("default_arg") {
+            result = Some(attr.parse_args()?);
+        } else {
+            kept.push(attr);
         }
     }
+    *attrs = kept;
+    Ok(result)
 }
-fn process_struct(item: &mut ItemStruct, info: &mut ActorInfo) {
-    match &mut item.fields {
-        syn::Fields::Named(fields) => {
-            let msg_type = info.msg_ident.clone();
-            fields.named.push(
-                syn::Field::parse_named
-                    .parse2(quote! { receiver: tokio::sync::mpsc::UnboundedReceiver<#msg_type>})
-                    .unwrap(),
-            );
+
+// `#[msg_for(inner::Counter)]` on a msg enum, naming its actor struct explicitly
+fn take_msg_for(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<syn::Path>> {
+    let pos = match attrs.iter().position(|a| a.path.is_ident("msg_for")) {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+    let attr = attrs.remove(pos);
+    let path: syn::Path = attr.parse_args_with(syn::Path::parse_mod_style)?;
+    if path.leading_colon.is_some() {
+        return Err(syn::Error::new_spanned(
+            &path,
+            "expected a path relative to the actors module, like `inner::Counter`",
+        ));
+    }
+    Ok(Some(path))
+}
+
+// `#[validate(path::to::fn)]` on a variant, run on the caller's side before its msg is sent
+fn take_validate(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<syn::Path>> {
+    let mut result = None;
+    let mut kept = vec![];
+    for attr in attrs.drain(..) {
+        if attr.path.is_ident("validate") {
+            result = Some(attr.parse_args()?);
+        } else {
+            kept.push(attr);
         }
-        _ => {}
     }
+    *attrs = kept;
+    Ok(result)
 }
 
-#[proc_macro_attribute]
-pub fn actors(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(item as ItemMod);
-    let mut context = HashMap::<String, ActorInfo>::new();
-    if let Some(content) = &mut ast.content {
-        for item in &mut content.1 {
-            match item {
-                syn::Item::Enum(v) => {
-                    let actor_name = get_actor_name(ID::RemoveMsg(v.ident.clone()));
-                    if let Some(name) = actor_name {
-                        if !context.contains_key(&name) {
-                            context.insert(name.clone(), ActorInfo::new(v.ident.clone()));
-                        }
-                        let info = context.get_mut(&name).unwrap();
-                        process_enum(v, info)
-                    }
-                }
-                _ => {}
+// the struct `ident` inside the inline modules `mods`
+fn find_struct<'a>(
+    items: &'a mut [syn::Item],
+    mods: &[Ident],
+    ident: &Ident,
+) -> Option<&'a mut ItemStruct> {
+    match mods.split_first() {
+        None => items.iter_mut().find_map(|item| match item {
+            syn::Item::Struct(v) if v.ident == *ident => Some(v),
+            _ => None,
+        }),
+        Some((first, rest)) => items.iter_mut().find_map(|item| match item {
+            syn::Item::Mod(m) if m.ident == *first => {
+                find_struct(&mut m.content.as_mut()?.1, rest, ident)
             }
+            _ => None,
+        }),
+    }
+}
+
+// the items of the inline module `mods`
+fn find_items<'a>(items: &'a [syn::Item], mods: &[Ident]) -> Option<&'a [syn::Item]> {
+    match mods.split_first() {
+        None => Some(items),
+        Some((first, rest)) => items.iter().find_map(|item| match item {
+            syn::Item::Mod(m) if m.ident == *first => find_items(&m.content.as_ref()?.1, rest),
+            _ => None,
+        }),
+    }
+}
+
+// takes the actor struct into `info`, reading its `#[actor(..)]`
+fn bind_actor(info: &mut ActorInfo, v: &mut ItemStruct) -> syn::Result<()> {
+    info.actor_ident = Some(v.ident.clone());
+    info.actor_generics = v.generics.clone();
+    info.actor_args = ActorArgs::take(&mut v.attrs)?;
+    info.stateless = v.fields.is_empty();
+    info.events = take_events(v)?;
+    // the event type is named as seen from the actor struct's module
+    if let (Some((_, event)), true) = (&info.events, info.actor_mods.is_empty()) {
+        info.bounds.push(bounds::Bound::new(
+            event,
+            quote! {Clone + Send + 'static},
+            event.span(),
+        ));
+    }
+    info.id_field = None;
+    info.stats_field = None;
+    for field in v.fields.iter_mut() {
+        if take_flag(&mut field.attrs, "actor_stats") {
+            info.stats_field = match &field.ident {
+                Some(ident) => Some(ident.clone()),
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "`#[actor_stats]` needs a named field typed `ActorStats`",
+                    ))
+                }
+            };
         }
-        //println!("finished enum processing");
-        let mut to_add = vec![];
-        for item in &mut content.1 {
-            match item {
-                syn::Item::Struct(v) => {
-                    let actor_name = get_actor_name(ID::Direct(v.ident.clone()));
-                    if let Some(name) = actor_name {
-                        if !context.contains_key(&name) {
-                            continue;
-                        }
-                        let info = context.get_mut(&name).unwrap();
-                        if info.msg_mapping.len() == 0 {
-                            continue;
-                        }
-                        info.actor_ident = Some(v.ident.clone());
-                        process_struct(v, info);
-                        let actor_ident =
-                            Ident::new(&format!("Actor{}", &v.ident), Span::call_site());
-                        let msg_ident = info.msg_ident.clone();
-                        to_add.push(quote! {
-                            pub struct #actor_ident{
-                                sender: tokio::sync::mpsc::UnboundedSender<#msg_ident>,
-                            }
-                        });
-                    }
+        if take_flag(&mut field.attrs, "actor_id") {
+            info.id_field = match &field.ident {
+                Some(ident) => Some(ident.clone()),
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "`#[actor_id]` needs a named field typed `ActorId`",
+                    ))
                 }
-                _ => {}
-            }
+            };
+        }
+    }
+    if let Some(ack) = &info.actor_args.ack {
+        if let Some(v) = info.variants.iter().find(|v| !v.fields.contains(ack)) {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                format!(
+                    "`#[actor(ack = {})]` needs the field `{}` on every variant",
+                    ack, ack
+                ),
+            ));
         }
-        for add in to_add {
-            content
-                .1
-                .push(syn::Item::Struct(ItemStruct::parse.parse2(add).unwrap()));
+    }
+    Ok(())
+}
+
+// reads and strips `#[events]` from a field typed `Emitter<E>`
+fn take_events(v: &mut ItemStruct) -> syn::Result<Option<(Ident, Type)>> {
+    let mut result = None;
+    for field in v.fields.iter_mut() {
+        if !take_flag(&mut field.attrs, "events") {
+            continue;
         }
-        //println!("finished struct processing");
-        for (_name, info) in context.into_iter() {
-            if info.msg_mapping.len() == 0 || info.actor_ident.is_none() {
-                continue;
+        let event = match &field.ty {
+            Type::Path(p) => match &p.path.segments.last().unwrap().arguments {
+                syn::PathArguments::AngleBracketed(args)
+                    if p.path.segments.last().unwrap().ident == "Emitter" =>
+                {
+                    args.args.iter().find_map(|a| match a {
+                        syn::GenericArgument::Type(t) => Some(t.clone()),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match (&field.ident, event, &result) {
+            (Some(ident), Some(event), None) => result = Some((ident.clone(), event)),
+            (_, _, Some(_)) => {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "only one `#[events]` field per actor",
+                ))
             }
-            let ident = info.actor_ident.as_ref().unwrap().clone();
-            let actor_ident = Ident::new(&format!("Actor{}", &ident), Span::call_site());
-            let msg_ident = info.msg_ident.clone();
-            let actor_impl = quote! {
-                impl #actor_ident{
-                    pub async fn new()->Self{
-                        let (s, r) = tokio::sync::mpsc::unbounded_channel();
-                        let mut a = #ident::new(r);
-                        tokio::spawn(async move {
-                            a.run().await;
-                        });
-                        return Self{sender:s};
-                    }
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "`#[events]` needs a named field typed `Emitter<E>`",
+                ))
+            }
+        }
+    }
+    Ok(result)
+}
 
-                }
-            };
-            content
-                .1
-                .push(syn::Item::Impl(ItemImpl::parse.parse2(actor_impl).unwrap()));
-            let o_impl = quote! {
-                impl #ident{
-                    fn new(r: tokio::sync::mpsc::UnboundedReceiver<#msg_ident>)->Self{
-                        return Self{ receiver: r };
-                    }
+// the enum `ty` names, if it's a plain ident declared in `items` without generics
+fn find_enum<'a>(items: &'a [syn::Item], ty: &Type) -> Option<&'a ItemEnum> {
+    let ident = match ty {
+        Type::Path(p) if p.qself.is_none() => p.path.get_ident()?,
+        _ => return None,
+    };
+    items.iter().find_map(|item| match item {
+        syn::Item::Enum(v) if v.ident == *ident && v.generics.params.is_empty() => Some(v),
+        _ => None,
+    })
+}
 
-                    async fn run(&mut self){
-                        while let Some(msg) = self.receiver.recv().await {
-                            self.process(msg).await;
-                        }
-                    }
+// methods of the inherent impls of `name`, for optional hooks
+fn collect_hooks(items: &[syn::Item], name: &str, info: &mut ActorInfo) {
+    for item in items {
+        if let syn::Item::Impl(v) = item {
+            if v.trait_.is_some() {
+                continue;
+            }
+            if let Type::Path(p) = &*v.self_ty {
+                if p.path.segments.last().unwrap().ident != name {
+                    continue;
                 }
-            };
-            content
-                .1
-                .push(syn::Item::Impl(ItemImpl::parse.parse2(o_impl).unwrap()));
-            for (req, resp) in info.msg_mapping.into_iter() {
-                let fname_wait =
-                    Ident::new(&format!("{}", &req).to_snake_case(), Span::call_site());
-                let method = quote! {
-                    impl #actor_ident{
-                        pub async fn #fname_wait(&mut self,mut msg:#msg_ident)->Result<#resp,&'static str>{
-                            match msg{
-                                #msg_ident::#req{ref mut resp,..}=>{
-                                    let (mut s,mut r) = tokio::sync::oneshot::channel();
-                                    *resp = Some(s);
-                                    self.sender.send(msg).map_err(|_e|{return "send failed";})?;
-                                    match r.await{
-                                        Ok(v)=>{return Ok(v);}
-                                        _=>{return Err("mailbox closed");}
-                                    };
-                                }
-                                _=>{return Err("invalid msg type");}
-                            };
+                for i in &v.items {
+                    if let syn::ImplItem::Method(m) = i {
+                        info.hooks.push(m.sig.ident.to_string());
+                        if m.sig.ident == "process" && m.sig.inputs.len() == 3 {
+                            info.ctx = true;
                         }
-                    }
-                };
-                content
-                    .1
-                    .push(syn::Item::Impl(ItemImpl::parse.parse2(method).unwrap()));
-                let fname_nowait = Ident::new(
-                    &format!("{}_no_wait", &req).to_snake_case(),
-                    Span::call_site(),
-                );
-                let method_no_wait = quote! {
-                    impl #actor_ident{
-                        pub async fn #fname_nowait(&mut self,mut msg:#msg_ident)->Result<(),&'static str>{
-                            match msg{
-                                #msg_ident::#req{..}=>{
-                                    self.sender.send(msg).map_err(|_e|{return "send failed";})?;
-                                    return Ok(());
-                                }
-                                _=>{return Err("invalid msg type");}
+                        if m.sig.ident == "process" {
+                            info.fallible = match &m.sig.output {
+                                syn::ReturnType::Type(_, ty) => is_result(ty),
+                                syn::ReturnType::Default => false,
                             };
                         }
                     }
-                };
-                content.1.push(syn::Item::Impl(
-                    ItemImpl::parse.parse2(method_no_wait).unwrap(),
-                ));
+                }
             }
         }
     }
-    let result = quote! {#ast};
-    //println!("{}", &result);
-    return result.into();
+}
+
+// `#[shared]` fields: `T` as `Arc<T>`, so a payload fanned out to many actors
+// is cloned as a pointer. fields already typed as an `Arc` are kept as they are.
+fn shared_type(ty: &Type) -> Type {
+    if let Type::Path(p) = ty {
+        if p.path.segments.last().is_some_and(|s| s.ident == "Arc") {
+            return ty.clone();
+        }
+    }
+    syn::parse_quote! {std::sync::Arc<#ty>}
+}
+
+// `Result<..>` under any path, aliases like `anyhow::Result<()>` included
+fn is_result(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) => p.path.segments.last().is_some_and(|s| s.ident == "Result"),
+        _ => false,
+    }
+}
+
+// `Result<T, E>` split into `(T, E)`
+fn result_args(ty: &Type) -> Option<(Type, Type)> {
+    if let Type::Path(p) = ty {
+        let last = p.path.segments.last()?;
+        if last.ident != "Result" {
+            return None;
+        }
+        if let syn::PathArguments::AngleBracketed(args) = &last.arguments {
+            let types: Vec<&Type> = args
+                .args
+                .iter()
+                .filter_map(|a| match a {
+                    syn::GenericArgument::Type(t) => Some(t),
+                    _ => None,
+                })
+                .collect();
+            if types.len() == 2 {
+                return Some((types[0].clone(), types[1].clone()));
+            }
+        }
+    }
+    None
+}
+
+// the first `impl Trait` anywhere in `ty`
+fn find_impl_trait(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::ImplTrait(_) => Some(ty),
+        Type::Path(p) => p.path.segments.iter().find_map(|s| match &s.arguments {
+            syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|a| match a {
+                syn::GenericArgument::Type(t) => find_impl_trait(t),
+                _ => None,
+            }),
+            _ => None,
+        }),
+        Type::Tuple(t) => t.elems.iter().find_map(find_impl_trait),
+        Type::Reference(r) => find_impl_trait(&r.elem),
+        Type::Array(a) => find_impl_trait(&a.elem),
+        Type::Slice(s) => find_impl_trait(&s.elem),
+        Type::Paren(p) => find_impl_trait(&p.elem),
+        Type::Group(g) => find_impl_trait(&g.elem),
+        _ => None,
+    }
+}
+
+// `Box<dyn Bounds + Send>` as written in a diagnostic
+fn boxed_hint(bounds: &syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>) -> String {
+    let mut bounds: Vec<String> = bounds
+        .iter()
+        .map(|b| {
+            quote! {#b}
+                .to_string()
+                .replace(" :: ", "::")
+                .replace(" < ", "<")
+                .replace(" >", ">")
+        })
+        .collect();
+    if !bounds.iter().any(|b| b == "Send") {
+        bounds.push("Send".to_string());
+    }
+    format!("Box<dyn {}>", bounds.join(" + "))
+}
+
+// `resp` ends up as the type parameter of a oneshot sender, so it has to be a
+// sized, nameable type. caught here, before it turns into errors deep in the expansion.
+fn check_resp_type(ty: &Type) -> syn::Result<()> {
+    if let Some(v @ Type::ImplTrait(t)) = find_impl_trait(ty) {
+        return Err(syn::Error::new_spanned(
+            v,
+            format!(
+                "`impl Trait` can't be used in a `resp` type, box it instead: `{}`",
+                boxed_hint(&t.bounds)
+            ),
+        ));
+    }
+    let unsized_value = match result_args(ty) {
+        Some((t, e)) => vec![t, e],
+        None => vec![ty.clone()],
+    };
+    for v in unsized_value {
+        if let Type::TraitObject(t) = &v {
+            return Err(syn::Error::new_spanned(
+                &v,
+                format!(
+                    "a trait object isn't sized, use `{}` as the `resp` type",
+                    boxed_hint(&t.bounds)
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn process_enum(
+    item: &mut ItemEnum,
+    info: &mut ActorInfo,
+    redacted: &redact::Redacted,
+    args: &ActorsArgs,
+) -> syn::Result<()> {
+    let Paths { mpsc, oneshot, .. } = args.runtime.paths();
+    if item.variants.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.ident,
+            format!("message enum {} has no variants", item.ident),
+        ));
+    }
+    if let Some(max) = args.max_variants {
+        if item.variants.len() > max {
+            return Err(syn::Error::new_spanned(
+                &item.ident,
+                format!(
+                    "`{}` has {} variants, more than `max_variants = {}`; consider splitting it into several msg enums, one per actor",
+                    item.ident,
+                    item.variants.len(),
+                    max
+                ),
+            ));
+        }
+    }
+    for v in &mut item.variants {
+        let shape = match &v.fields {
+            syn::Fields::Named(_) => Shape::Named,
+            syn::Fields::Unnamed(_) => Shape::Unnamed,
+            syn::Fields::Unit => Shape::Unit,
+        };
+        let is_fallible = take_flag(&mut v.attrs, "fallible");
+        let is_stream = take_flag(&mut v.attrs, "stream");
+        let is_sink = take_flag(&mut v.attrs, "sink");
+        let (ask, tell) = take_only(&mut v.attrs)?;
+        let latest = latest::take(v)?;
+        let timeout = take_timeout(&mut v.attrs)?;
+        let bounded = lane::take(v, args)?;
+        let propagate_span = v
+            .attrs
+            .iter()
+            .find(|a| a.path.is_ident("propagate_panic"))
+            .map(|a| a.span());
+        let mut variant = VariantInfo {
+            ident: v.ident.clone(),
+            shape,
+            fields: v.fields.iter().filter_map(|f| f.ident.clone()).collect(),
+            trace: trace::take_variant_attrs(v)?,
+            resp: None,
+            fallible: None,
+            cfgs: v
+                .attrs
+                .iter()
+                .filter(|a| a.path.is_ident("cfg"))
+                .cloned()
+                .collect(),
+            deprecated: take_deprecated(&mut v.attrs),
+            ask,
+            tell,
+            latest,
+            timeout,
+            bounded,
+            stream: is_stream,
+            sink: is_sink,
+            unit: false,
+            service: take_flag(&mut v.attrs, "service"),
+            map: take_flag(&mut v.attrs, "map"),
+            typed_args: vec![],
+            validate: take_validate(&mut v.attrs)?,
+            propagate_panic: take_flag(&mut v.attrs, "propagate_panic"),
+            convert: convert::take(v)?,
+            unboxed: None,
+            declared: None,
+            payload: vec![],
+            vis: take_internal(&mut v.attrs, args)?,
+        };
+        variant.trace.redacted = redacted.names(&v.ident);
+        let mut has_default = false;
+        for (index, field) in v.fields.iter_mut().enumerate() {
+            let default = take_default_arg(&mut field.attrs)?;
+            if default.is_some() {
+                has_default = true;
+                if field.ident.as_ref().is_none_or(|f| f == "resp") {
+                    return Err(syn::Error::new_spanned(
+                        &field.ty,
+                        "`#[default_arg(..)]` goes on named payload fields",
+                    ));
+                }
+            }
+            if let Some(ident) = field.ident.as_ref().filter(|f| *f != "resp") {
+                variant
+                    .typed_args
+                    .push((ident.clone(), field.ty.clone(), default));
+            }
+            let name = field
+                .ident
+                .as_ref()
+                .map_or(index.to_string(), |f| f.to_string());
+            if name != "resp" {
+                variant.payload.push((name, field.ty.clone()));
+            }
+            if !take_flag(&mut field.attrs, "shared") {
+                continue;
+            }
+            if field.ident.as_ref().is_some_and(|f| f == "resp") {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "`#[shared]` goes on payload fields, not on `resp`",
+                ));
+            }
+            field.ty = shared_type(&field.ty);
+            variant.typed_args.last_mut().unwrap().1 = field.ty.clone();
+            variant.payload.last_mut().unwrap().1 = field.ty.clone();
+        }
+        if let syn::Fields::Named(fields) = &mut v.fields {
+            // rewrite `resp` in place so attributes on the field survive
+            for field in &mut fields.named {
+                if field.ident.is_some() && field.ident.as_ref().unwrap() == "resp" {
+                    check_resp_type(&field.ty)?;
+                    let declared = field.ty.clone();
+                    variant.declared = Some(declared.clone());
+                    let mut ty = field.ty.clone();
+                    if is_fallible {
+                        match (result_args(&ty), &args.error) {
+                            (Some(split), _) => variant.fallible = Some(split),
+                            (None, Some(error)) => {
+                                variant.fallible = Some((ty.clone(), error.clone()));
+                                ty = syn::parse_quote! {Result<#ty, #error>};
+                            }
+                            (None, None) => {
+                                return Err(syn::Error::new_spanned(
+                                    &field.ty,
+                                    "`#[fallible]` needs `resp: Result<T, E>` or a module default like `#[actors(error = MyError)]`",
+                                ))
+                            }
+                        }
+                    }
+                    if let Some(convert::Convert::TryFrom(_)) = &variant.convert {
+                        let error = args.runtime_type("ActorError");
+                        let error: Type = syn::parse_quote! {#error};
+                        variant.fallible = Some((ty.clone(), error.clone()));
+                        ty = syn::parse_quote! {Result<#ty, #error>};
+                    }
+                    if let Some(convert::Convert::Boxed { public }) = &variant.convert {
+                        if !public {
+                            variant.unboxed = Some(ty.clone());
+                        }
+                        ty = syn::parse_quote! {Box<#ty>};
+                    }
+                    variant.resp = Some(ty.clone());
+                    variant.unit = !is_sink && matches!(&ty, Type::Tuple(t) if t.elems.is_empty());
+                    field.ty = match (is_stream, variant.unit) {
+                        (true, _) => {
+                            syn::parse_quote! {Option<#mpsc::UnboundedSender<#ty>>}
+                        }
+                        (false, _) if is_sink => syn::parse_quote! {Option<#ty>},
+                        // `minimal` has no `Ack`, the plain oneshot stays
+                        (false, true) if !args.minimal => {
+                            let ack = args.runtime_type("Ack");
+                            syn::parse_quote! {Option<#ack>}
+                        }
+                        (false, _) => {
+                            syn::parse_quote! {Option<#oneshot::Sender<#ty>>}
+                        }
+                    };
+                    if let Some(convert) = &variant.convert {
+                        let internal = convert.internal(&declared);
+                        let responder = args.runtime_type("Responder");
+                        field.ty = syn::parse_quote! {Option<#responder<#internal, #ty>>};
+                    }
+                }
+            }
+        }
+        if is_fallible && variant.resp.is_none() {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[fallible]` needs a `resp` field",
+            ));
+        }
+        if is_stream && variant.resp.is_none() {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[stream]` needs a `resp` field",
+            ));
+        }
+        if is_sink
+            && (variant.resp.is_none()
+                || is_stream
+                || is_fallible
+                || variant.timeout.is_some()
+                || variant.service)
+        {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[sink]` needs a `resp` field and can't be combined with `#[stream]`, `#[fallible]`, `#[timeout]` or `#[service]`",
+            ));
+        }
+        if variant.convert.is_some() && args.minimal {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[resp(..)]` answers through a generated `Responder`, which `#[actors(minimal)]` leaves out",
+            ));
+        }
+        if variant.convert.is_some()
+            && (variant.resp.is_none() || variant.unit || is_stream || is_sink || is_fallible)
+        {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[resp(..)]` converts a plain `resp` field, it can't be combined with `#[stream]`, `#[sink]`, `#[fallible]` or `resp: ()`",
+            ));
+        }
+        if variant.propagate_panic
+            && (is_stream || is_sink || variant.resp.as_ref().and_then(result_args).is_none())
+        {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[propagate_panic]` needs `resp: Result<T, E>` with `E: From<ActorPanic>`, and no `#[stream]` or `#[sink]`",
+            ));
+        }
+        if variant.map && (is_stream || is_sink || variant.resp.is_none()) {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[map]` needs a `resp` field and no `#[stream]` or `#[sink]`",
+            ));
+        }
+        if has_default && (is_stream || is_sink || variant.resp.is_none() || !variant.ask) {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[default_arg(..)]` needs a `resp` field, no `#[stream]` or `#[sink]`, and the ask request",
+            ));
+        }
+        if variant.validate.is_some()
+            && (!matches!(variant.shape, Shape::Named) || !info.msg_generics.params.is_empty())
+        {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[validate(..)]` needs a variant with named fields, in a msg enum without generics",
+            ));
+        }
+        if variant.service && (is_stream || variant.resp.is_none()) {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[service]` needs a `resp` field and no `#[stream]`",
+            ));
+        }
+        if variant.service && !matches!(variant.vis, syn::Visibility::Public(_)) {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[internal]` variants can't be `#[service]`, the `tower::Service` impl is as public as the handle",
+            ));
+        }
+        if variant.service && info.variants.iter().any(|v| v.service) {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "only one `#[service]` variant per actor",
+            ));
+        }
+        if is_stream && (is_fallible || variant.timeout.is_some()) {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[stream]` can't be combined with `#[fallible]` or `#[timeout]`",
+            ));
+        }
+        if variant.timeout.is_some() && variant.resp.is_none() {
+            return Err(syn::Error::new_spanned(
+                &v.ident,
+                "`#[timeout]` needs a `resp` field",
+            ));
+        }
+        if let Some(capacity) = &variant.bounded {
+            if variant.latest.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &v.ident,
+                    "`#[bounded(..)]` can't be combined with `#[latest_only]`",
+                ));
+            }
+            if info
+                .lane()
+                .is_some_and(|lane| lane.base10_digits() != capacity.base10_digits())
+            {
+                return Err(syn::Error::new_spanned(
+                    capacity,
+                    format!(
+                        "every `#[bounded(..)]` variant of `{}` goes through the same channel, give them the same capacity",
+                        item.ident
+                    ),
+                ));
+            }
+        }
+        let cfgs = &variant.cfgs;
+        // payloads and replies cross into and out of the actor's `tokio::spawn`ed task
+        if !args.no_assert_send {
+            for ty in variant
+                .payload
+                .iter()
+                .map(|(_, ty)| ty)
+                .chain(&variant.declared)
+            {
+                info.bounds.push(
+                    bounds::Bound::new(ty, quote! {Send + 'static}, ty.span()).with_cfgs(cfgs),
+                );
+            }
+        }
+        if let Some((_, key)) = &variant.latest {
+            info.bounds.push(
+                bounds::Bound::new(key, quote! {Clone + Eq + std::hash::Hash}, key.span())
+                    .with_cfgs(cfgs),
+            );
+        }
+        if let (Some((_, e)), Some(span)) =
+            (variant.resp.as_ref().and_then(result_args), propagate_span)
+        {
+            info.bounds
+                .push(bounds::Bound::new(e, quote! {From<ActorPanic>}, span).with_cfgs(cfgs));
+        }
+        // `fallible` holds the declared type, `resp` the `Result` it became
+        if let (Some(convert::Convert::TryFrom(from)), Some((public, _))) =
+            (&variant.convert, &variant.fallible)
+        {
+            info.bounds.push(
+                bounds::Bound::new(public, quote! {std::convert::TryFrom<#from>}, from.span())
+                    .with_cfgs(cfgs),
+            );
+        }
+        info.variants.push(variant);
+    }
+    for (view, includes) in take_views(&mut item.attrs)? {
+        if !item.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                &view,
+                "views can't be generated for msg enums with generics",
+            ));
+        }
+        for name in &includes {
+            match info.variants.iter().find(|v| v.ident == *name) {
+                Some(v) if v.resp.is_some() => {}
+                Some(_v) => {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        "only variants with a `resp` field have request methods to put in a view",
+                    ))
+                }
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        name,
+                        format!("no variant `{}` in `{}`", name, item.ident),
+                    ))
+                }
+            }
+        }
+        info.views.push((view, includes));
+    }
+    Ok(())
+}
+
+// everything generated for one actor: the handle, its constructors, the run
+// loop and the per-variant methods
+fn generate_actor(info: &ActorInfo, args: &ActorsArgs) -> Vec<syn::Item> {
+    let Paths {
+        mpsc,
+        oneshot,
+        broadcast,
+        mutex,
+        spawn,
+        task,
+        time,
+        ..
+    } = args.runtime.paths();
+    let mut out = vec![];
+    // first, so they are reported ahead of the errors they cause further down
+    if info.actor_generics.params.is_empty() && info.msg_generics.params.is_empty() {
+        out.push(syn::Item::Verbatim(bounds::generate(&info.bounds)));
+        // the state moves into the spawned task
+        if !args.no_assert_send {
+            let ident = info.actor_ident.as_ref().unwrap();
+            let state =
+                bounds::Bound::new(info.actor_type(), quote! {Send + 'static}, ident.span());
+            out.push(syn::Item::Verbatim(bounds::generate(&[state])));
+        }
+    }
+    let ident = info.actor_ident.as_ref().unwrap().clone();
+    let actor_ident = Ident::new(&format!("Actor{}", &ident), Span::call_site());
+    let actor_type = info.actor_type();
+    let actor_name = ident.to_string();
+    let msg_ident = info.msg_ident.clone();
+    let msg_type = info.msg_type();
+    let channel = args.channel();
+    let closed = quote! {.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?};
+    let send = args.send();
+    let lane_envelope = snapshot::queued(quote! {Envelope::new(msg)}, quote! {self.shared});
+    let send = lane::route(
+        info,
+        quote! {#send #closed},
+        quote! {self.lane.send(#lane_envelope).await #closed},
+    );
+    let (watch, watched, watching) = info.watch_panics(args);
+    let lane_watched = snapshot::queued(watched.clone(), quote! {shared});
+    let queued_watched = snapshot::queued(watched.clone(), quote! {self.shared});
+    let ask_send = lane::route(
+        info,
+        args.ask_send(watched.clone()),
+        quote! {{
+            let lane = self.lane.clone();
+            let shared = self.shared.clone();
+            AskFuture::sending(Box::pin(async move {
+                lane.send(#lane_watched).await.map_err(|_e|{return ActorError::MailboxClosed{reason: shared.close_reason()};})?;
+                return Ok(r);
+            }))
+        }},
+    );
+    let run_loop = info.run_loop(args);
+    let (impl_generics, ty_generics, where_clause) = info.actor_generics.split_for_impl();
+    let generics = &info.actor_generics;
+    let sender_type = args.sender_type(&msg_type);
+    let marker_field = info.marker_type().map(|m| quote! {_marker: #m,});
+    let (lane_field, lane_clone, lane_let, lane_init, with_lane) = match lane::channel(info, args) {
+        Some(lane_channel) => (
+            quote! {lane: #mpsc::Sender<Envelope<#msg_type>>,},
+            quote! {lane: self.lane.clone(),},
+            quote! {let (lane, lane_receiver) = #lane_channel;},
+            quote! {lane,},
+            quote! {.with_lane(lane_receiver)},
+        ),
+        None => (quote! {}, quote! {}, quote! {}, quote! {}, quote! {}),
+    };
+    out.extend(lane::generate(info).map(syn::Item::Verbatim));
+    out.push(syn::Item::Verbatim(routes::generate(info, args)));
+    if snapshot::enabled() {
+        out.push(syn::Item::Verbatim(snapshot::generate(info)));
+    }
+    let (latest_field, latest_init, latest_clone, latest_arg) = match info.has_latest() {
+        true => {
+            let latest_ident = latest::latest_ident(info);
+            let (slots, init) = latest::slots(info);
+            out.push(syn::Item::Verbatim(slots));
+            (
+                quote! {latest: std::sync::Arc<#latest_ident #ty_generics>,},
+                quote! {latest: #init,},
+                quote! {latest: self.latest.clone(),},
+                quote! {, latest},
+            )
+        }
+        false => (quote! {}, quote! {}, quote! {}, quote! {}),
+    };
+    let latest_let = match info.has_latest() {
+        true => quote! {let latest = handle.latest.clone();},
+        false => quote! {},
+    };
+    // handles subscribe through the actor's own emitter, handles not made by
+    // `start` get one nothing is ever emitted into
+    let (events_field, events_init, events_clone, events_let) = match &info.events {
+        Some((field, event)) => (
+            quote! {events: Emitter<#event>,},
+            quote! {events: Emitter::new(1),},
+            quote! {events: self.events.clone(),},
+            quote! {let mut handle = handle; handle.events = a.#field.clone();},
+        ),
+        None => (quote! {}, quote! {}, quote! {}, quote! {}),
+    };
+    // bounded services keep the slot reserved by `poll_ready` for the next `call`
+    let service_slot = info.service().is_some() && args.bounded.is_some();
+    let (service_field, service_init) = match service_slot {
+        true => (
+            quote! {service: std::sync::Mutex<ReservedSlot<#msg_type>>,},
+            quote! {service: std::sync::Mutex::new(ReservedSlot::Idle),},
+        ),
+        false => (quote! {}, quote! {}),
+    };
+    let wake_stop = match args.bounded {
+        Some(_) => quote! {let _ = self.sender.try_send(Envelope::control(ControlMsg::Stop))},
+        None => quote! {let _ = self.sender.send(Envelope::control(ControlMsg::Stop))},
+    };
+    let must_use = info
+        .actor_args
+        .must_use
+        .then(|| quote! {#[must_use = "dropping the handle shuts the actor down"]});
+    let handle = quote! {
+        #must_use
+        pub struct #actor_ident #generics #where_clause {
+            sender: #sender_type,
+            #lane_field
+            shared: std::sync::Arc<ActorShared>,
+            #latest_field
+            #events_field
+            #service_field
+            #marker_field
+        }
+    };
+    out.push(syn::Item::Struct(ItemStruct::parse.parse2(handle).unwrap()));
+    // handles are meant to be shared across threads, fail right here if one isn't.
+    // const items can't be generic, generic handles only add a `fn() -> T` marker.
+    if info.actor_generics.params.is_empty() && !args.no_assert_send {
+        out.push(syn::Item::Verbatim(quote! {
+            const _: fn() = || {
+                fn assert_send_sync<T: Send + Sync>() {}
+                assert_send_sync::<#actor_ident>();
+            };
+        }));
+    }
+    let marker = info
+        .marker_type()
+        .map(|_| quote! {_marker: std::marker::PhantomData,});
+    // by hand, a derive would require the actor's type params to be Clone
+    out.push(syn::Item::Verbatim(quote! {
+        impl #impl_generics Clone for #actor_ident #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                self.shared.handles.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                return Self{sender:self.sender.clone(), #lane_clone shared:self.shared.clone(), #latest_clone #events_clone #service_init #marker};
+            }
+        }
+
+        impl #impl_generics Drop for #actor_ident #ty_generics #where_clause {
+            fn drop(&mut self) {
+                self.shared.handles.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+    }));
+    let mut spawn_generics = info.actor_generics.clone();
+    {
+        let bounds: Vec<Ident> = spawn_generics
+            .type_params()
+            .map(|t| t.ident.clone())
+            .collect();
+        let spawn_where = spawn_generics.make_where_clause();
+        for t in bounds {
+            spawn_where
+                .predicates
+                .push(syn::parse_quote! {#t: Send + 'static});
+        }
+    }
+    let spawn_where_clause = &spawn_generics.where_clause;
+    // the ctx's `handle()`, taken once the handle is complete
+    let (weak_let, weak_arg) = match info.ctx {
+        true => (quote! {let weak = handle.__downgrade();}, quote! {, weak}),
+        false => (quote! {}, quote! {}),
+    };
+    // `exit = Summary`: the future ends with what `on_stop` returned instead of
+    // the actor, a panic reaches the `JoinHandle` of `spawn_with_exit` as a `JoinError`
+    let (start_output, run, spawn_with_exit) = match &info.actor_args.exit {
+        Some(exit) => (
+            quote! {#exit},
+            quote! {return a.run(mailbox, shared #latest_arg #weak_arg).await;},
+            quote! {
+                pub fn spawn_with_exit(state: #actor_type #ty_generics)->(Self, #task::JoinHandle<#exit>){
+                    let (handle, run) = Self::start(state);
+                    return (handle, #spawn(run));
+                }
+            },
+        ),
+        None => (
+            quote! {#actor_type #ty_generics},
+            quote! {
+                a.run(mailbox, shared #latest_arg #weak_arg).await;
+                return a;
+            },
+            quote! {},
+        ),
+    };
+    // `pool`: every worker is an actor of its own, fed by a task that takes the next
+    // msg off the shared queue only once its worker finished the last one, so a slow
+    // msg holds up its worker and nothing else. msgs are handled in no particular order.
+    let (timed, dequeued, report) = ask::report(args);
+    let spawn_pool = match info.actor_args.pool {
+        true => {
+            let send_msg = args.send_envelope(quote! {Envelope::watched(msg, panicked)});
+            let send_flush = args.send_envelope(quote! {Envelope::control(ControlMsg::Flush(s))});
+            quote! {
+                // `spawn_many`, with the `n` actors behind one handle as a pool
+                pub fn spawn_pool_many(n: usize, make_state: impl Fn(usize)->#actor_type #ty_generics)->Self{
+                    return Self::spawn_pool((0..n).map(make_state));
+                }
+
+                pub fn spawn_pool(states: impl IntoIterator<Item = #actor_type #ty_generics>)->Self{
+                    let (handle, mailbox, shared) = Self::__channel(None);
+                    let queue = std::sync::Arc::new(#mutex::new(mailbox));
+                    let stop = std::sync::Arc::new(StopGuard(shared.clone()));
+                    for (index, state) in states.into_iter().enumerate() {
+                        let (worker, run) = Self::__start_nth(Some(index), state);
+                        let (queue, shared, stop) = (queue.clone(), shared.clone(), stop.clone());
+                        #spawn(async move {
+                            let worker_task = #spawn(run);
+                            loop {
+                                let envelope = {
+                                    let mut queue = queue.lock().await;
+                                    match shared.stop_deadline() {
+                                        Some(deadline) => {
+                                            shared.close(CloseReason::Stopped);
+                                            queue.close();
+                                            queue.recv_until(deadline).await
+                                        }
+                                        None => queue.recv_envelope().await,
+                                    }
+                                };
+                                let envelope = match envelope{
+                                    Some(v)=>v,
+                                    None=>break,
+                                };
+                                // past a `stop_within` deadline what's left is dropped
+                                if let Some(deadline) = shared.stop_deadline() {
+                                    if #time::Instant::now() >= deadline {
+                                        if let Letter::User{..} = envelope.0 {
+                                            shared.abandoned.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                                        }
+                                        continue;
+                                    }
+                                }
+                                match envelope.open() {
+                                    Letter::User{msg, panicked, #timed ..}=>{
+                                        #dequeued
+                                        if worker.__process(msg, panicked).await.is_err() {
+                                            break;
+                                        }
+                                        #report
+                                    }
+                                    // a `ping` through the shared queue
+                                    Letter::Control(ControlMsg::Flush(done))=>{let _ = done.send(());}
+                                    Letter::Control(_control)=>{}
+                                }
+                            }
+                            // `stop_within` callers are answered once every worker ran `on_stop`
+                            drop(worker);
+                            let _ = worker_task.await;
+                            drop(stop);
+                        });
+                    }
+                    return handle;
+                }
+
+                // hands `msg` to this worker and waits until it was processed
+                async fn __process(&self, msg: #msg_type, panicked: Option<#oneshot::Sender<ActorPanic>>)->Result<(),ActorError>{
+                    let (s,r) = #oneshot::channel();
+                    #send_msg.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+                    #send_flush.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+                    return r.await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};});
+                }
+            }
+        }
+        false => quote! {},
+    };
+    let start_run = match args.deadlock_check {
+        true => quote! {
+            let id = shared.id;
+            let run = async move {
+                #run
+            };
+            #[cfg(debug_assertions)]
+            let run = RUNNING_ACTOR.scope(id, run);
+            return (handle, run);
+        },
+        false => quote! {
+            return (handle, async move {
+                #run
+            });
+        },
+    };
+    // everything else is built from `start`: a handle plus the future
+    // running the actor, which stops the actor when dropped.
+    let actor_impl = quote! {
+        impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+            fn __channel(index: Option<usize>)->(Self, Mailbox<#msg_type>, std::sync::Arc<ActorShared>){
+                let (s, r) = #channel;
+                #lane_let
+                let shared = std::sync::Arc::new(ActorShared{id: ActorId::next(#actor_name, index), handles: std::sync::atomic::AtomicUsize::new(1), ..Default::default()});
+                return (Self{sender:s, #lane_init shared:shared.clone(), #latest_init #events_init #service_init #marker}, Mailbox::new(r) #with_lane, shared);
+            }
+
+            pub fn start(state: #actor_type #ty_generics)->(Self, impl std::future::Future<Output = #start_output>){
+                return Self::__start_nth(None, state);
+            }
+
+            // `start` for one of several actors, `index` going into its id
+            fn __start_nth(index: Option<usize>, state: #actor_type #ty_generics)->(Self, impl std::future::Future<Output = #start_output>){
+                let (handle, mailbox, shared) = Self::__channel(index);
+                return Self::__start(handle, mailbox, shared, state);
+            }
+
+            fn __start(handle: Self, mailbox: Mailbox<#msg_type>, shared: std::sync::Arc<ActorShared>, state: #actor_type #ty_generics)->(Self, impl std::future::Future<Output = #start_output>){
+                let mut a = state;
+                #events_let
+                #latest_let
+                #weak_let
+                #start_run
+            }
+
+            pub fn spawn(state: #actor_type #ty_generics)->Self{
+                let (handle, run) = Self::start(state);
+                #spawn(run);
+                return handle;
+            }
+
+            // `n` actors, `make_state` handed the index of each, which their ids carry too
+            pub fn spawn_many(n: usize, make_state: impl Fn(usize)->#actor_type #ty_generics)->Vec<Self>{
+                return (0..n).map(|i| {
+                    let (handle, run) = Self::__start_nth(Some(i), make_state(i));
+                    #spawn(run);
+                    return handle;
+                }).collect();
+            }
+
+            // an actor fed by an existing pipeline: the run loop takes whatever `source`
+            // yields next to its mailbox, until it ends or the actor stopped. the handle
+            // returned sends like any other, drop it to have the actor stop with its source.
+            pub fn new_from_receiver(source: impl MsgSource<#msg_type>, state: #actor_type #ty_generics)->Self{
+                let (handle, mailbox, shared) = Self::__channel(None);
+                let (handle, run) = Self::__start(handle, mailbox.fed_by(source), shared, state);
+                #spawn(run);
+                return handle;
+            }
+
+            #spawn_with_exit
+            #spawn_pool
+        }
+    };
+    out.push(syn::Item::Impl(ItemImpl::parse.parse2(actor_impl).unwrap()));
+    if info.stateless {
+        let new_impl = quote! {
+            impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+                pub async fn new()->Self{
+                    return Self::spawn(#actor_type {});
+                }
+            }
+        };
+        out.push(syn::Item::Impl(ItemImpl::parse.parse2(new_impl).unwrap()));
+    }
+    if cfg!(feature = "test-util") {
+        // lets tests look at what was queued, nothing ever processes it
+        let test_impl = quote! {
+            impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+                pub fn new_unspawned()->(Self, Mailbox<#msg_type>){
+                    let (handle, mailbox, _shared) = Self::__channel(None);
+                    return (handle, mailbox);
+                }
+            }
+        };
+        let send_idle = args.send_envelope(quote! {Envelope::control(ControlMsg::Idle(s))});
+        // best effort: a msg sent from elsewhere meanwhile may be queued right after
+        let idle_impl = quote! {
+            impl #impl_generics #actor_ident #ty_generics #where_clause{
+                pub async fn wait_until_idle(&self)->Result<(),ActorError>{
+                    let (s,r) = #oneshot::channel();
+                    #send_idle.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+                    return r.await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};});
+                }
+            }
+        };
+        out.push(syn::Item::Impl(ItemImpl::parse.parse2(test_impl).unwrap()));
+        out.push(syn::Item::Impl(ItemImpl::parse.parse2(idle_impl).unwrap()));
+    }
+    let (latest_replace, latest_sent) = match info.has_latest() {
+        true => latest::replace(info),
+        false => (quote! {}, quote! {}),
+    };
+    let mut checks = vec![];
+    for v in &info.variants {
+        let request_ident = match info.request_ident(v) {
+            Some(v) => v,
+            None => continue,
+        };
+        let req = &v.ident;
+        let cfgs = &v.cfgs;
+        let deprecated = &v.deprecated;
+        let vis = &v.vis;
+        let fields = v
+            .typed_args
+            .iter()
+            .map(|(field, ty, _)| quote! {#vis #field: #ty});
+        out.push(syn::Item::Verbatim(quote! {
+            #(#cfgs)*
+            #(#deprecated)*
+            #vis struct #request_ident {
+                #(#fields,)*
+            }
+        }));
+        if let Some(validate) = &v.validate {
+            let names: Vec<&Ident> = v.typed_args.iter().map(|(field, _, _)| field).collect();
+            let resp = v.resp.as_ref().map(|_| quote! {resp});
+            checks.push(quote! {
+                #(#cfgs)*
+                #msg_ident::#req{#(#names,)* #resp}=>{
+                    let req = #request_ident{#(#names,)*};
+                    if let Err(e) = #validate(&req) {
+                        return Err(ActorError::Rejected(e));
+                    }
+                    return Ok(#msg_ident::#req{#(#names: req.#names,)* #resp});
+                }
+            });
+        }
+    }
+    // `#[validate(..)]`: every way into the mailbox goes through `__validate` first,
+    // a rejected msg is dropped right there
+    let validate = match checks.is_empty() {
+        true => quote! {},
+        false => {
+            out.push(syn::Item::Verbatim(quote! {
+                impl #impl_generics #actor_ident #ty_generics #where_clause{
+                    #[allow(deprecated)]
+                    fn __validate(msg: #msg_type)->Result<#msg_type,ActorError>{
+                        match msg{
+                            #(#checks)*
+                            msg=>{return Ok(msg);}
+                        }
+                    }
+                }
+            }));
+            quote! {let msg = Self::__validate(msg)?;}
+        }
+    };
+    let validate_ask = match checks.is_empty() {
+        true => quote! {},
+        false => quote! {
+            let msg = match Self::__validate(msg){
+                Ok(v)=>v,
+                Err(e)=>{return AskFuture::failed(e);}
+            };
+        },
+    };
+    if info.has_latest() {
+        out.push(syn::Item::Verbatim(quote! {
+            impl #impl_generics #actor_ident #ty_generics #where_clause{
+                // `#[latest_only]` msgs dropped because a newer one took their place
+                pub fn replaced(&self)->u64{
+                    return self.latest.replaced.load(std::sync::atomic::Ordering::Relaxed);
+                }
+            }
+        }));
+    }
+    out.push(syn::Item::Verbatim(quote! {
+        impl #impl_generics #actor_ident #ty_generics #where_clause{
+            pub fn id(&self)->ActorId{
+                return self.shared.id;
+            }
+
+            // clones of this handle alive right now, this one included
+            pub fn handle_count(&self)->usize{
+                return self.shared.handles.load(std::sync::atomic::Ordering::Relaxed);
+            }
+
+            // closes the mailbox, processes what's queued until `within` has passed and
+            // drops the rest, then waits for `on_stop`. resolves to the number of msgs dropped.
+            pub async fn stop_within(&self, within: std::time::Duration)->Result<usize,ActorError>{
+                let done = match self.__request_stop(#time::Instant::now() + within){
+                    Ok(v)=>v,
+                    Err(abandoned)=>{return Ok(abandoned);}
+                };
+                return done.await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};});
+            }
+
+            // `stop_within` for every actor of `handles` at once, all with the same
+            // deadline. resolves to the msgs they dropped, added up.
+            pub async fn stop_all(handles: &[Self], within: std::time::Duration)->Result<usize,ActorError>{
+                let deadline = #time::Instant::now() + within;
+                let mut abandoned = 0;
+                let mut pending = vec![];
+                for handle in handles {
+                    match handle.__request_stop(deadline){
+                        Ok(done)=>pending.push((handle, done)),
+                        Err(n)=>abandoned += n,
+                    }
+                }
+                for (handle, done) in pending {
+                    abandoned += done.await.map_err(|_e|{return ActorError::MailboxClosed{reason: handle.shared.close_reason()};})?;
+                }
+                return Ok(abandoned);
+            }
+
+            // `Err` with the abandoned count if the actor already exited
+            fn __request_stop(&self, deadline: #time::Instant)->Result<#oneshot::Receiver<usize>,usize>{
+                let done = self.shared.request_stop(deadline)?;
+                // a full mailbox means the run loop is busy and sees the request between msgs
+                #wake_stop;
+                return Ok(done);
+            }
+        }
+    }));
+    let send_flush = args.send_envelope(quote! {Envelope::control(ControlMsg::Flush(s))});
+    // the lane is flushed on its own, after the mailbox
+    let flush_lane = info.lane().map(|_| {
+        quote! {
+            let (s,r) = #oneshot::channel();
+            self.lane.send(Envelope::control(ControlMsg::Flush(s))).await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+            r.await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+        }
+    });
+    out.push(syn::Item::Verbatim(quote! {
+        impl #impl_generics AnyActor for #actor_ident #ty_generics #where_clause{
+            fn id(&self)->ActorId{
+                return self.shared.id;
+            }
+
+            fn is_closed(&self)->bool{
+                return self.sender.is_closed() || self.shared.accepting().is_err();
+            }
+
+            fn stop(&self){
+                if self.shared.request_stop(#time::Instant::now()).is_ok() {
+                    #wake_stop;
+                }
+            }
+
+            // queued behind every msg sent before it, answered by the run loop
+            fn ping(&self)->std::pin::Pin<Box<dyn std::future::Future<Output = Result<std::time::Duration, ActorError>> + Send + '_>>{
+                return Box::pin(async move {
+                    let start = #time::Instant::now();
+                    let (s,r) = #oneshot::channel();
+                    #send_flush.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+                    r.await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+                    #flush_lane
+                    return Ok(start.elapsed());
+                });
+            }
+        }
+    }));
+    if info.has_permit(args) {
+        out.push(syn::Item::Verbatim(permit::generate(info, args)));
+        if cfg!(feature = "sink") {
+            out.push(syn::Item::Verbatim(sink::generate(info)));
+        }
+    }
+    if info.actor_args.raw_sender {
+        out.push(syn::Item::Verbatim(quote! {
+            impl #impl_generics AsRef<#sender_type> for #actor_ident #ty_generics #where_clause{
+                fn as_ref(&self)->&#sender_type{
+                    return &self.sender;
+                }
+            }
+        }));
+    }
+    if let Some(v) = info.service() {
+        out.push(syn::Item::Verbatim(service(info, v, args, &spawn_generics)));
+    }
+    if info.actor_args.supervised
+        || (info.fallible && info.actor_args.on_error == Some(OnError::Restart))
+    {
+        out.push(syn::Item::Verbatim(quote! {
+            impl #impl_generics #actor_ident #ty_generics #where_clause{
+                // panics in `process` the actor survived so far, and errors under
+                // `on_error = "restart"`
+                pub fn restart_count(&self)->usize{
+                    return self.shared.restarts.load(std::sync::atomic::Ordering::Relaxed);
+                }
+            }
+        }));
+    }
+    if info.watches_panics() {
+        out.push(syn::Item::Verbatim(quote! {
+            impl #impl_generics #actor_ident #ty_generics #where_clause{
+                // what `process` last panicked with, also once the actor is gone
+                pub fn last_panic(&self)->Option<ActorPanic>{
+                    return self.shared.panic.lock().ok().and_then(|panic| panic.clone());
+                }
+            }
+        }));
+    }
+    if cfg!(feature = "stream") {
+        // the inbound side of an actor without its run loop, for routing msgs elsewhere
+        out.push(syn::Item::Verbatim(quote! {
+            impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+                pub fn new_stream()->(Self, impl ::tokio_stream::Stream<Item = #msg_type>){
+                    let (handle, mailbox, _shared) = Self::__channel(None);
+                    return (handle, mailbox.into_stream());
+                }
+            }
+        }));
+    }
+    if let Some((_, event)) = &info.events {
+        out.push(syn::Item::Verbatim(quote! {
+            impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+                pub fn subscribe(&self)->#broadcast::Receiver<#event>{
+                    return self.events.subscribe();
+                }
+
+                // forwards what `extract` picks from the events onto a std channel for
+                // synchronous consumers: `sync_channel(n)` with a capacity, `channel()`
+                // without. stops once the actor stopped or the receiver is gone.
+                pub fn bridge_events<T: Send + 'static>(&self, extract: impl Fn(&#event) -> Option<T> + Send + 'static, capacity: Option<usize>)->std::sync::mpsc::Receiver<T>{
+                    enum Bridge<T> {
+                        Unbounded(std::sync::mpsc::Sender<T>),
+                        Bounded(std::sync::mpsc::SyncSender<T>),
+                    }
+                    let (bridge, receiver) = match capacity{
+                        Some(n)=>{
+                            let (s, r) = std::sync::mpsc::sync_channel(n);
+                            (Bridge::Bounded(s), r)
+                        }
+                        None=>{
+                            let (s, r) = std::sync::mpsc::channel();
+                            (Bridge::Unbounded(s), r)
+                        }
+                    };
+                    let mut events = self.events.subscribe();
+                    #task::spawn_blocking(move || {
+                        loop {
+                            let event = match events.blocking_recv(){
+                                Ok(v)=>v,
+                                Err(#broadcast::error::RecvError::Lagged(_n))=>continue,
+                                Err(#broadcast::error::RecvError::Closed)=>return,
+                            };
+                            let item = match extract(&event){
+                                Some(v)=>v,
+                                None=>continue,
+                            };
+                            let sent = match &bridge{
+                                Bridge::Unbounded(s)=>s.send(item).is_ok(),
+                                Bridge::Bounded(s)=>s.send(item).is_ok(),
+                            };
+                            if !sent {
+                                return;
+                            }
+                        }
+                    });
+                    return receiver;
+                }
+            }
+        }));
+    }
+    // `subscribe_xxx()` per event variant: the events of that variant only, as its
+    // payload. lagging behind skips the events missed, whatever their variant.
+    if let (Some((_, event)), true) = (&info.events, cfg!(feature = "stream")) {
+        let naming = args.rename_all.allow();
+        for v in &info.event_variants {
+            let variant = &v.ident;
+            let cfgs = v.attrs.iter().filter(|a| a.path.is_ident("cfg"));
+            let fname = args.rename_all.method(&format!("subscribe_{}", variant));
+            let (pattern, payload, item) = match &v.fields {
+                syn::Fields::Unit => (quote! {}, quote! {()}, quote! {()}),
+                fields => {
+                    let names: Vec<Ident> = fields
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| match &f.ident {
+                            Some(v) => v.clone(),
+                            None => Ident::new(&format!("v{}", i), Span::call_site()),
+                        })
+                        .collect();
+                    let types: Vec<&Type> = fields.iter().map(|f| &f.ty).collect();
+                    let pattern = match fields {
+                        syn::Fields::Named(_) => quote! {{#(#names),*}},
+                        _ => quote! {(#(#names),*)},
+                    };
+                    match names.len() {
+                        1 => (pattern, quote! {#(#names)*}, quote! {#(#types)*}),
+                        _ => (pattern, quote! {(#(#names),*)}, quote! {(#(#types),*)}),
+                    }
+                }
+            };
+            out.push(syn::Item::Verbatim(quote! {
+                #(#cfgs)*
+                #naming
+                impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+                    pub fn #fname(&self)->impl ::tokio_stream::Stream<Item = #item>{
+                        let events = ::tokio_stream::wrappers::BroadcastStream::new(self.events.subscribe());
+                        return ::tokio_stream::StreamExt::filter_map(events, |event| match event{
+                            Ok(#event::#variant #pattern)=>Some(#payload),
+                            _=>None,
+                        });
+                    }
+                }
+            }));
+        }
+    }
+    let send_into = quote! {
+        impl #impl_generics #actor_ident #ty_generics #where_clause{
+            pub async fn send_into<IntoMsg: Into<#msg_type>>(&self, t: IntoMsg)->Result<(),ActorError>{
+                let msg: #msg_type = t.into();
+                self.shared.accepting()?;
+                #validate
+                #latest_replace
+                #send;
+                #latest_sent
+                return Ok(());
+            }
+        }
+    };
+    out.push(syn::Item::Impl(ItemImpl::parse.parse2(send_into).unwrap()));
+    // `Extend` can't wait: msgs that don't fit a full bounded mailbox are dropped,
+    // and the rest is left alone once the mailbox is closed. a dropped
+    // `#[latest_only]` msg gives its key's slot back as its guard goes, the same
+    // on `Full` as on `Closed`
+    let try_send = lane::route(
+        info,
+        args.try_send(),
+        quote! {
+            self.lane.try_send(#lane_envelope).map_err(|e|{
+                return match e{
+                    #mpsc::error::TrySendError::Full(_v)=>ActorError::EnqueueTimeout,
+                    #mpsc::error::TrySendError::Closed(_v)=>ActorError::MailboxClosed{reason: self.shared.close_reason()},
+                };
+            })
+        },
+    );
+    out.push(syn::Item::Verbatim(quote! {
+        impl #impl_generics #actor_ident #ty_generics #where_clause{
+            fn __try_tell(&self, msg: #msg_type)->Result<(),ActorError>{
+                self.shared.accepting()?;
+                #validate
+                #latest_replace
+                #try_send?;
+                #latest_sent
+                return Ok(());
+            }
+        }
+
+        impl #impl_generics Extend<#msg_type> for #actor_ident #ty_generics #where_clause{
+            fn extend<I: IntoIterator<Item = #msg_type>>(&mut self, iter: I){
+                for msg in iter {
+                    if let Err(ActorError::MailboxClosed{..}) = self.__try_tell(msg) {
+                        return;
+                    }
+                }
+            }
+        }
+    }));
+    out.push(syn::Item::Verbatim(quote! {
+        impl #impl_generics EventSink<#msg_type> for #actor_ident #ty_generics #spawn_where_clause{
+            fn try_tell(&self, msg: #msg_type)->Result<(),ActorError>{
+                return self.__try_tell(msg);
+            }
+
+            fn handle_count(&self)->usize{
+                return self.shared.handles.load(std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+    }));
+    out.push(syn::Item::Verbatim(quote! {
+        impl #impl_generics PipeSink<#msg_type> for #actor_ident #ty_generics #spawn_where_clause{
+            fn pipe_send(&self, msg: #msg_type)->std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), ActorError>> + Send + '_>>{
+                return Box::pin(self.send_into(msg));
+            }
+        }
+    }));
+    let o_impl = quote! {
+        impl #impl_generics #actor_type #ty_generics #spawn_where_clause{
+            #run_loop
+        }
+    };
+    out.push(syn::Item::Impl(ItemImpl::parse.parse2(o_impl).unwrap()));
+    if info.ctx {
+        let ctx_ident = info.ctx_ident();
+        let weak_ident = info.weak_ident();
+        let weak_sender_type = args.weak_sender_type(&msg_type);
+        let (lane_weak_field, lane_downgrade, lane_upgrade) = match info.lane() {
+            Some(_) => (
+                quote! {lane: #mpsc::WeakSender<Envelope<#msg_type>>,},
+                quote! {lane: self.lane.downgrade(),},
+                quote! {lane: self.lane.upgrade()?,},
+            ),
+            None => (quote! {}, quote! {}, quote! {}),
+        };
+        // `ctx.handle()`: a handle that doesn't keep the actor alive, or it never
+        // stops once it holds one of its own
+        out.push(syn::Item::Verbatim(quote! {
+            pub struct #weak_ident #generics #where_clause {
+                sender: #weak_sender_type,
+                #lane_weak_field
+                shared: std::sync::Arc<ActorShared>,
+                #latest_field
+                #events_field
+                #marker_field
+            }
+
+            impl #impl_generics Clone for #weak_ident #ty_generics #where_clause {
+                fn clone(&self) -> Self {
+                    return Self{sender:self.sender.clone(), #lane_clone shared:self.shared.clone(), #latest_clone #events_clone #marker};
+                }
+            }
+
+            impl #impl_generics #weak_ident #ty_generics #where_clause {
+                // `None` once every handle is dropped
+                pub fn upgrade(&self)->Option<#actor_ident #ty_generics>{
+                    let sender = self.sender.upgrade()?;
+                    self.shared.handles.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                    return Some(#actor_ident{sender, #lane_upgrade shared:self.shared.clone(), #latest_clone #events_clone #service_init #marker});
+                }
+            }
+
+            impl #impl_generics #actor_ident #ty_generics #where_clause {
+                fn __downgrade(&self)->#weak_ident #ty_generics{
+                    return #weak_ident{sender:self.sender.downgrade(), #lane_downgrade shared:self.shared.clone(), #latest_clone #events_clone #marker};
+                }
+            }
+        }));
+        // `then` runs on the actor between msgs, so the mailbox keeps moving while
+        // the ask is pending. continuations still pending when the actor stops are dropped.
+        // `spawn`ed tasks are aborted when the actor stops, or awaited with
+        // `#[actor(ctx_tasks = "await")]`, either way before `on_stop`.
+        out.push(syn::Item::Verbatim(quote! {
+            pub struct #ctx_ident #generics #where_clause {
+                sender: #mpsc::UnboundedSender<Box<dyn FnOnce(&mut #actor_type #ty_generics) + Send>>,
+                id: ActorId,
+                shared: std::sync::Arc<ActorShared>,
+                handle: #weak_ident #ty_generics,
+                tasks: std::sync::Mutex<#task::JoinSet<()>>,
+            }
+
+            impl #impl_generics #ctx_ident #ty_generics #spawn_where_clause {
+                pub fn id(&self)->ActorId{
+                    return self.id;
+                }
+
+                pub fn actor_id(&self)->ActorId{
+                    return self.id;
+                }
+
+                // the actor stops once the current msg is processed, what's still
+                // queued is dropped
+                pub fn stop(&self){
+                    let _ = self.shared.request_stop(#time::Instant::now());
+                }
+
+                pub fn handle(&self)->#weak_ident #ty_generics{
+                    return self.handle.clone();
+                }
+
+                pub fn spawn(&self, fut: impl std::future::Future<Output = ()> + Send + 'static)->#task::AbortHandle{
+                    let mut tasks = self.tasks.lock().unwrap();
+                    // finished tasks are let go of here, not only once the actor stops
+                    while tasks.try_join_next().is_some() {}
+                    return tasks.spawn(fut);
+                }
+
+                pub fn spawn_ask<R: Send + 'static>(&self, ask: impl std::future::Future<Output = R> + Send + 'static, then: impl FnOnce(R, &mut #actor_type #ty_generics) + Send + 'static){
+                    let sender = self.sender.clone();
+                    #spawn(async move {
+                        let result = ask.await;
+                        let _ = sender.send(Box::new(move |this: &mut #actor_type #ty_generics| then(result, this)));
+                    });
+                }
+            }
+        }));
+    }
+    // bounded asks, and those going through a lane, box the pending send, which
+    // must be `Send + 'static`
+    let (ask_where_clause, ask_bound) = match args.bounded.is_some() || info.lane().is_some() {
+        true => (spawn_where_clause, quote! {: Send + 'static}),
+        false => (&info.actor_generics.where_clause, quote! {}),
+    };
+    // so are responses under `metrics`, a timed out ask leaves its receiver to a task
+    let ask_where_clause = match cfg!(feature = "metrics") {
+        true => spawn_where_clause,
+        false => ask_where_clause,
+    };
+    let (check_deadlock, check_deadlock_result) = match args.deadlock_check {
+        true => (
+            quote! {
+                if self.shared.asks_itself() {
+                    return AskFuture::failed(ActorError::WouldDeadlock);
+                }
+            },
+            quote! {
+                if self.shared.asks_itself() {
+                    return Err(ActorError::WouldDeadlock);
+                }
+            },
+        ),
+        false => (quote! {}, quote! {}),
+    };
+    let check_runtime = args.runtime_check.then(|| {
+        quote! {
+            if self.sender.is_closed() && self.shared.runtime_gone() {
+                return AskFuture::failed(ActorError::RuntimeGone);
+            }
+        }
+    });
+    // the request wiring, written once per actor. the per-variant methods only
+    // check the variant and plug a channel into its `resp`.
+    let acked = snapshot::queued(quote! {Envelope::acked(msg, s)}, quote! {self.shared});
+    let send_acked = args.send_envelope(acked.clone());
+    let send_acked = lane::route(
+        info,
+        quote! {#send_acked #closed},
+        quote! {self.lane.send(#acked).await #closed},
+    );
+    out.push(syn::Item::Verbatim(quote! {
+        #[allow(dead_code)]
+        impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+            fn __ask<R #ask_bound>(&self,msg:#msg_type,r:#oneshot::Receiver<R>)->AskFuture<R>{
+                if let Err(e) = self.shared.accepting() {
+                    return AskFuture::failed(e);
+                }
+                #check_deadlock
+                #check_runtime
+                #validate_ask
+                #watch
+                let ask: AskFuture<R> = #ask_send;
+                return ask #watching;
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #impl_generics #actor_ident #ty_generics #where_clause{
+            async fn __acked(&self,msg:#msg_type)->Result<(),ActorError>{
+                self.shared.accepting()?;
+                #check_deadlock_result
+                #validate
+                let (s,r) = #oneshot::channel();
+                #send_acked;
+                return r.await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};});
+            }
+        }
+    }));
+    // `xxx_traced`: the envelope carries a second oneshot the run loop reports
+    // queue wait and processing time on, once the msg was processed
+    if ask::timings() {
+        let panicked = match info.watches_panics() {
+            true => quote! {Some(panicked)},
+            false => quote! {None},
+        };
+        let timed = snapshot::queued(
+            quote! {Envelope::timed(msg, #panicked, Timed{sent: #time::Instant::now(), report})},
+            quote! {self.shared},
+        );
+        // bounded mailboxes count the wait for a slot as send time
+        let send_timed = match args.bounded {
+            Some(_) => quote! {{
+                let permit = self.sender.reserve().await #closed;
+                permit.send(#timed);
+            }},
+            None => lane::route(
+                info,
+                quote! {{self.sender.send(#timed) #closed;}},
+                quote! {{
+                    let permit = self.lane.reserve().await #closed;
+                    permit.send(#timed);
+                }},
+            ),
+        };
+        out.push(syn::Item::Verbatim(quote! {
+            #[allow(dead_code)]
+            impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+                async fn __timed<R #ask_bound>(&self,msg:#msg_type,r:#oneshot::Receiver<R>)->Result<(R,AskTimings),ActorError>{
+                    self.shared.accepting()?;
+                    #check_deadlock_result
+                    #validate
+                    #watch
+                    let (report, timings) = #oneshot::channel();
+                    let start = #time::Instant::now();
+                    #send_timed
+                    let send = start.elapsed();
+                    let value = AskFuture::waiting(r) #watching.await?;
+                    let (queued, processing) = timings.await.map_err(|_e|{return ActorError::ResponseDropped;})?;
+                    return Ok((value, AskTimings{send, queued, processing}));
+                }
+            }
+        }));
+    }
+    // reserving first keeps "mailbox full" apart from "actor too slow to answer".
+    if args.bounded.is_some() {
+        out.push(syn::Item::Verbatim(quote! {
+            #[allow(dead_code)]
+            impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+                async fn __try_ask<R #ask_bound>(&self,msg:#msg_type,r:#oneshot::Receiver<R>,variant:&'static str,enqueue_timeout:std::time::Duration,response_timeout:std::time::Duration)->Result<R,ActorError>{
+                    #check_deadlock_result
+                    #validate
+                    let permit = match #time::timeout(enqueue_timeout,self.sender.reserve()).await{
+                        Ok(Ok(v))=>v,
+                        Ok(Err(_e))=>{return Err(ActorError::MailboxClosed{reason: self.shared.close_reason()});}
+                        Err(_e)=>{return Err(ActorError::EnqueueTimeout);}
+                    };
+                    self.shared.accepting()?;
+                    #watch
+                    permit.send(#queued_watched);
+                    return AskFuture::waiting(r) #watching.variant(#actor_name, variant).with_timeout(response_timeout).await;
+                }
+            }
+        }));
+    }
+    // `#[view(..)]`: a handle of its own around a clone of the actor's, forwarding
+    // the helpers the per-variant methods are built on and nothing else
+    for (view, _) in &info.views {
+        let try_ask = args.bounded.as_ref().map(|_| {
+            quote! {
+                async fn __try_ask<R #ask_bound>(&self,msg:#msg_type,r:#oneshot::Receiver<R>,variant:&'static str,enqueue_timeout:std::time::Duration,response_timeout:std::time::Duration)->Result<R,ActorError>{
+                    return self.inner.__try_ask(msg,r,variant,enqueue_timeout,response_timeout).await;
+                }
+            }
+        });
+        let timed = ask::timings().then(|| {
+            quote! {
+                async fn __timed<R #ask_bound>(&self,msg:#msg_type,r:#oneshot::Receiver<R>)->Result<(R,AskTimings),ActorError>{
+                    return self.inner.__timed(msg,r).await;
+                }
+            }
+        });
+        out.push(syn::Item::Verbatim(quote! {
+            #[derive(Clone)]
+            pub struct #view {
+                inner: #actor_ident,
+            }
+
+            impl From<#actor_ident> for #view {
+                fn from(inner: #actor_ident)->Self{
+                    return Self{inner};
+                }
+            }
+
+            impl #view {
+                pub fn id(&self)->ActorId{
+                    return self.inner.id();
+                }
+            }
+
+            #[allow(dead_code)]
+            impl #view {
+                fn __ask<R #ask_bound>(&self,msg:#msg_type,r:#oneshot::Receiver<R>)->AskFuture<R>{
+                    return self.inner.__ask(msg,r);
+                }
+
+                async fn __acked(&self,msg:#msg_type)->Result<(),ActorError>{
+                    return self.inner.__acked(msg).await;
+                }
+
+                async fn send_into(&self,msg:#msg_type)->Result<(),ActorError>{
+                    return self.inner.send_into(msg).await;
+                }
+
+                #try_ask
+                #timed
+            }
+        }));
+    }
+    // the handle gets every variant's methods, each view those of its variants,
+    // generated the same way on top of the view's forwarding helpers
+    let targets = std::iter::once((actor_ident.clone(), None)).chain(
+        info.views
+            .iter()
+            .map(|(view, includes)| (view.clone(), Some(includes))),
+    );
+    let invalid_ask = info.catch_all(quote! {AskFuture::failed(ActorError::InvalidMsgType),});
+    let invalid = info.catch_all(quote! {{return Err(ActorError::InvalidMsgType);}});
+    for (target, includes) in targets {
+        let actor_ident = &target;
+        for v in info.variants.iter().filter(|v| {
+            v.resp.is_some() && includes.is_none_or(|includes| includes.contains(&v.ident))
+        }) {
+            let req = &v.ident;
+            let vis = &v.vis;
+            let naming = args.rename_all.allow();
+            let resp = v.resp.as_ref().unwrap();
+            let cfgs = &v.cfgs;
+            let deprecated = &v.deprecated;
+            let fname_wait = args.rename_all.method(&format!("{}", &req));
+            let variant_name = format!("{}::{}", msg_ident, req);
+            let responder = v.responder(&msg_ident);
+            // `#[fallible]` variants fold `ActorError` into the handler's own error,
+            // `#[resp(boxed)]` ones unbox the reply
+            let (ask_type, flatten, value, error) = v.ask_output();
+            let try_ask_type = quote! {Result<#value, #error>};
+            let (ok, err) = match (&v.fallible, &v.unboxed) {
+                (Some(_), _) => (quote! {v}, quote! {.into()}),
+                (None, Some(_)) => (quote! {Ok(*v)}, quote! {}),
+                (None, None) => (quote! {Ok(v)}, quote! {}),
+            };
+            let invalid_try =
+                info.catch_all(quote! {{return Err(ActorError::InvalidMsgType #err);}});
+            // `#[instrument]`: the request runs in a span built from the msg fields. the
+            // ask enters it right away for the send and keeps it for every later poll.
+            let request_span = match cfg!(feature = "tracing") {
+                true => v.trace.request_span(&ident.to_string(), req),
+                false => None,
+            };
+            // single-variant enums match exhaustively without it
+            let no_span = info.catch_all(quote! {::tracing::Span::none(),});
+            let (enter, in_span) = match &request_span {
+                Some((bindings, span)) => {
+                    let pattern = v.pattern(bindings.clone());
+                    (
+                        quote! {
+                            let span = match &msg{
+                                #msg_ident::#req #pattern => #span,
+                                #no_span
+                            };
+                            let _enter = span.enter();
+                        },
+                        quote! {.in_span(span.clone())},
+                    )
+                }
+                None => (quote! {}, quote! {}),
+            };
+            let instrument = |body: proc_macro2::TokenStream| match &request_span {
+                Some((bindings, span)) => {
+                    let pattern = v.pattern(bindings.clone());
+                    quote! {
+                        let span = match &msg{
+                            #msg_ident::#req #pattern => #span,
+                            #no_span
+                        };
+                        return ::tracing::Instrument::instrument(async move {#body}, span).await;
+                    }
+                }
+                None => body,
+            };
+            let default_timeout = v
+                .timeout
+                .map(|ms| quote! {.default_timeout(std::time::Duration::from_millis(#ms))});
+            let propagating = v
+                .propagate_panic
+                .then(|| quote! {.propagating(|panic| Err(From::from(panic)))});
+            // `tracing`: the caller's location, named when `resp` is dropped unanswered
+            let (track_caller, called_from) = match cfg!(feature = "tracing") {
+                true => (
+                    quote! {#[track_caller]},
+                    quote! {.called_from(std::panic::Location::caller())},
+                ),
+                false => (quote! {}, quote! {}),
+            };
+            let method = quote! {
+                #(#cfgs)*
+                #naming
+                impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+                    #(#deprecated)*
+                    #track_caller
+                    #vis fn #fname_wait(&mut self,mut msg:#msg_type)->#ask_type{
+                        #enter
+                        let ask: AskFuture<#resp> = match msg{
+                            #msg_ident::#req{ref mut resp,..}=>{
+                                let (s,r) = #oneshot::channel();
+                                *resp = Some(#responder);
+                                self.__ask(msg, r)
+                            }
+                            #invalid_ask
+                        };
+                        return ask.variant(#actor_name, #variant_name) #propagating #called_from #in_span #default_timeout #flatten;
+                    }
+                }
+            };
+            // `#[stream]`: the request hands back the receiving end of `resp` instead
+            let stream_body = instrument(quote! {
+                match msg{
+                    #msg_ident::#req{ref mut resp,..}=>{
+                        let (s,r) = #mpsc::unbounded_channel();
+                        *resp = Some(s);
+                        self.send_into(msg).await?;
+                        return Ok(r);
+                    }
+                    #invalid
+                };
+            });
+            let fname_pipe = args.rename_all.method(&format!("pipe_{}_to", &req));
+            // forwards every item into `sink` until the stream ends or the sink goes away
+            let method_stream = quote! {
+                #(#cfgs)*
+                #naming
+                impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+                    #(#deprecated)*
+                    #vis async fn #fname_wait(&mut self,mut msg:#msg_type)->Result<#mpsc::UnboundedReceiver<#resp>,ActorError>{
+                        #stream_body
+                    }
+
+                    #(#deprecated)*
+                    #[allow(deprecated)]
+                    #vis fn #fname_pipe<M: Send + 'static, S: PipeSink<M>>(&self, msg:#msg_type, sink: &S, map: impl Fn(#resp) -> M + Send + 'static)->#task::JoinHandle<PipeStats>{
+                        let mut source = self.clone();
+                        let sink = sink.clone();
+                        return #spawn(async move {
+                            let mut stats = PipeStats::default();
+                            let mut items = match source.#fname_wait(msg).await{
+                                Ok(v)=>v,
+                                Err(_e)=>{
+                                    stats.errors += 1;
+                                    return stats;
+                                }
+                            };
+                            while let Some(item) = items.recv().await{
+                                match sink.pipe_send(map(item)).await{
+                                    Ok(_v)=>stats.items += 1,
+                                    Err(_e)=>{
+                                        stats.errors += 1;
+                                        stats.sink_closed = true;
+                                        return stats;
+                                    }
+                                }
+                            }
+                            return stats;
+                        });
+                    }
+                }
+            };
+            // `#[sink]`: the caller's `make` builds the sink and what it hands back
+            let fname_with = args.rename_all.method(&format!("{}_with", &req));
+            let sink_body = instrument(quote! {
+                match msg{
+                    #msg_ident::#req{ref mut resp,..}=>{
+                        let (s,r) = make();
+                        *resp = Some(s);
+                        self.send_into(msg).await?;
+                        return Ok(r);
+                    }
+                    #invalid
+                };
+            });
+            let method_sink = quote! {
+                #(#cfgs)*
+                #naming
+                impl #impl_generics #actor_ident #ty_generics #where_clause{
+                    #(#deprecated)*
+                    #vis async fn #fname_with<R>(&mut self,mut msg:#msg_type,make:impl FnOnce()->(#resp, R))->Result<R,ActorError>{
+                        #sink_body
+                    }
+                }
+            };
+            match (v.ask, v.stream, v.sink) {
+                (true, true, _) => out.push(syn::Item::Impl(
+                    ItemImpl::parse.parse2(method_stream).unwrap(),
+                )),
+                (true, false, true) => out.push(syn::Item::Impl(
+                    ItemImpl::parse.parse2(method_sink).unwrap(),
+                )),
+                (true, false, false) => {
+                    out.push(syn::Item::Impl(ItemImpl::parse.parse2(method).unwrap()))
+                }
+                (false, _, _) => {}
+            }
+            // `Handles<CounterAddRequest>`: the plain ask again, with the payload in its
+            // request struct so code can be generic over the requests an actor answers
+            if let (Some(request_ident), true) =
+                (info.request_ident(v), v.ask && !v.stream && !v.sink)
+            {
+                let moves = v
+                    .typed_args
+                    .iter()
+                    .map(|(field, _, _)| quote! {#field: req.#field});
+                out.push(syn::Item::Verbatim(quote! {
+                    #(#cfgs)*
+                    #naming
+                    #[allow(deprecated)]
+                    impl #impl_generics Handles<#request_ident> for #actor_ident #ty_generics #ask_where_clause{
+                        type Response = #resp;
+
+                        fn call(&self, req: #request_ident)->AskFuture<#resp>{
+                            let (s,r) = #oneshot::channel();
+                            let msg = #msg_ident::#req{#(#moves,)* resp: Some(#responder)};
+                            #enter
+                            let ask: AskFuture<#resp> = self.__ask(msg, r);
+                            return ask.variant(#actor_name, #variant_name) #propagating #in_span #default_timeout;
+                        }
+                    }
+                }));
+                // `ask_stream_xxx`: requests are sent as `reqs` yields them, in order, and
+                // answered in that same order. a task keeps up to `window` asks in
+                // flight, a full window leaves `reqs` alone until the oldest answer is taken
+                if cfg!(feature = "stream") && includes.is_none() {
+                    let fname_stream = args.rename_all.method(&format!("ask_stream_{}", &req));
+                    out.push(syn::Item::Verbatim(quote! {
+                        #(#cfgs)*
+                        #naming
+                        impl #impl_generics #actor_ident #ty_generics #spawn_where_clause{
+                            #(#deprecated)*
+                            #vis fn #fname_stream(&self, reqs: impl ::tokio_stream::Stream<Item = #request_ident> + Send + 'static, window: usize)->impl ::tokio_stream::Stream<Item = Result<#resp, ActorError>>{
+                                let handle = self.clone();
+                                let (asks, answers) = #mpsc::channel(window.max(1));
+                                #spawn(async move {
+                                    let mut reqs = std::pin::pin!(reqs);
+                                    loop {
+                                        let slot = match asks.reserve().await{
+                                            Ok(v)=>v,
+                                            Err(_e)=>return,
+                                        };
+                                        let req = match ::tokio_stream::StreamExt::next(&mut reqs).await{
+                                            Some(v)=>v,
+                                            None=>return,
+                                        };
+                                        slot.send(Handles::<#request_ident>::call(&handle, req).enqueued().await);
+                                    }
+                                });
+                                return ::tokio_stream::StreamExt::then(::tokio_stream::wrappers::ReceiverStream::new(answers), |ask: AskFuture<#resp>| ask);
+                            }
+                        }
+                    }));
+                }
+            }
+            // `#[map]`: the same ask, with the caller's projection applied on success
+            if v.map && v.ask {
+                let fname_map = args.rename_all.method(&format!("{}_map", &req));
+                out.push(syn::Item::Verbatim(quote! {
+                    #(#cfgs)*
+                    #naming
+                    impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+                        #(#deprecated)*
+                        #[allow(deprecated)]
+                        #vis async fn #fname_map<R>(&mut self,msg:#msg_type,f:impl FnOnce(#value)->R)->Result<R,#error>{
+                            return self.#fname_wait(msg).await.map(f);
+                        }
+                    }
+                }));
+            }
+            // `xxx_traced`: the plain ask, answered along with where the time went
+            if ask::timings() && v.ask && !v.stream && !v.sink {
+                let fname_traced = args.rename_all.method(&format!("{}_traced", &req));
+                out.push(syn::Item::Verbatim(quote! {
+                    #(#cfgs)*
+                    #naming
+                    impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+                        #(#deprecated)*
+                        #vis async fn #fname_traced(&mut self,mut msg:#msg_type)->Result<(#resp,AskTimings),ActorError>{
+                            match msg{
+                                #msg_ident::#req{ref mut resp,..}=>{
+                                    let (s,r) = #oneshot::channel();
+                                    *resp = Some(#responder);
+                                    return self.__timed(msg, r).await;
+                                }
+                                #invalid
+                            };
+                        }
+                    }
+                }));
+            }
+            // `#[default_arg(..)]`: the ask taking the payload fields as arguments, minus
+            // the defaulted ones
+            if v.typed_args.iter().any(|(_, _, default)| default.is_some()) {
+                let fname_args = args.rename_all.method(&format!("{}_args", &req));
+                let params = v
+                    .typed_args
+                    .iter()
+                    .filter(|(_, _, default)| default.is_none())
+                    .map(|(field, ty, _)| quote! {#field: #ty});
+                let inits = v
+                    .typed_args
+                    .iter()
+                    .map(|(field, _, default)| match default {
+                        Some(default) => quote! {#field: #default},
+                        None => quote! {#field},
+                    });
+                out.push(syn::Item::Verbatim(quote! {
+                    #(#cfgs)*
+                    #naming
+                    impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+                        #(#deprecated)*
+                        #[allow(deprecated)]
+                        #track_caller
+                        #vis fn #fname_args(&mut self, #(#params),*)->#ask_type{
+                            return self.#fname_wait(#msg_ident::#req{#(#inits,)* resp: None});
+                        }
+                    }
+                }));
+            }
+            let fname_nowait = args.rename_all.method(&format!("{}_no_wait", &req));
+            let no_wait_body = instrument(quote! {
+                match msg{
+                    #msg_ident::#req{..}=>{return self.send_into(msg).await;}
+                    #invalid
+                };
+            });
+            let method_no_wait = quote! {
+                #(#cfgs)*
+                #naming
+                impl #impl_generics #actor_ident #ty_generics #where_clause{
+                    #(#deprecated)*
+                    #vis async fn #fname_nowait(&mut self,mut msg:#msg_type)->Result<(),ActorError>{
+                        #no_wait_body
+                    }
+                }
+            };
+            // resolves once the run loop took the msg out of the mailbox, before `process`
+            let fname_acked = args.rename_all.method(&format!("{}_acked", &req));
+            let acked_body = instrument(quote! {
+                match msg{
+                    #msg_ident::#req{..}=>{return self.__acked(msg).await;}
+                    #invalid
+                };
+            });
+            let method_acked = quote! {
+                #(#cfgs)*
+                #naming
+                impl #impl_generics #actor_ident #ty_generics #where_clause{
+                    #(#deprecated)*
+                    #vis async fn #fname_acked(&self,msg:#msg_type)->Result<(),ActorError>{
+                        #acked_body
+                    }
+                }
+            };
+            if v.tell {
+                out.push(syn::Item::Impl(
+                    ItemImpl::parse.parse2(method_no_wait).unwrap(),
+                ));
+                out.push(syn::Item::Impl(
+                    ItemImpl::parse.parse2(method_acked).unwrap(),
+                ));
+            }
+            if args.bounded.is_none() || !v.ask || v.stream || v.sink {
+                continue;
+            }
+            let fname_try_ask = args.rename_all.method(&format!("{}_try_ask_timeout", &req));
+            let try_ask_body = instrument(quote! {
+                match msg{
+                    #msg_ident::#req{ref mut resp,..}=>{
+                        let (s,r) = #oneshot::channel();
+                        *resp = Some(#responder);
+                        return match self.__try_ask(msg,r,#variant_name,enqueue_timeout,response_timeout).await{
+                            Ok(v)=>#ok,
+                            Err(e)=>Err(e #err),
+                        };
+                    }
+                    #invalid_try
+                };
+            });
+            let method_try_ask = quote! {
+                #(#cfgs)*
+                #naming
+                impl #impl_generics #actor_ident #ty_generics #ask_where_clause{
+                    #(#deprecated)*
+                    #vis async fn #fname_try_ask(&mut self,mut msg:#msg_type,enqueue_timeout:std::time::Duration,response_timeout:std::time::Duration)->#try_ask_type{
+                        #try_ask_body
+                    }
+                }
+            };
+            out.push(syn::Item::Impl(
+                ItemImpl::parse.parse2(method_try_ask).unwrap(),
+            ));
+        }
+    }
+    out
+}
+
+// `tower::Service<XXXMsg>` answering with the `#[service]` variant's response.
+// bounded handles reserve a mailbox slot in `poll_ready`, so tower's backpressure
+// sees a full mailbox; `call` without a reservation waits for one in its future.
+fn service(
+    info: &ActorInfo,
+    v: &VariantInfo,
+    args: &ActorsArgs,
+    spawn_generics: &Generics,
+) -> proc_macro2::TokenStream {
+    let Paths { oneshot, .. } = args.runtime.paths();
+    let actor_ident = Ident::new(
+        &format!("Actor{}", info.actor_ident.as_ref().unwrap()),
+        Span::call_site(),
+    );
+    let msg_ident = &info.msg_ident;
+    let msg_type = info.msg_type();
+    let req = &v.ident;
+    let actor_name = info.actor_ident.as_ref().unwrap().to_string();
+    let variant_name = format!("{}::{}", msg_ident, req);
+    let resp = v.resp.as_ref().unwrap();
+    let invalid_ask = info.catch_all(quote! {AskFuture::failed(ActorError::InvalidMsgType),});
+    let (impl_generics, ty_generics, where_clause) = spawn_generics.split_for_impl();
+    let (future, flatten, response, error) = v.ask_output();
+    let responder = v.responder(msg_ident);
+    let default_timeout = v
+        .timeout
+        .map(|ms| quote! {.default_timeout(std::time::Duration::from_millis(#ms))});
+    let (watch, watched, watching) = info.watch_panics(args);
+    let queued_watched = snapshot::queued(watched.clone(), quote! {self.shared});
+    let check_deadlock = args.deadlock_check.then(|| {
+        quote! {
+            if self.shared.asks_itself() {
+                return AskFuture::failed(ActorError::WouldDeadlock).variant(#actor_name, #variant_name) #flatten;
+            }
+        }
+    });
+    // the reserved slot is given back when `#[validate]` turns the msg down
+    let validate = match info.variants.iter().any(|v| v.validate.is_some()) {
+        true => quote! {
+            let msg = match Self::__validate(msg){
+                Ok(v)=>v,
+                Err(e)=>{return AskFuture::failed(e).variant(#actor_name, #variant_name) #flatten;}
+            };
+        },
+        false => quote! {},
+    };
+    let (poll_ready, send) = match args.bounded {
+        Some(_) => (
+            quote! {
+                return match self.service.get_mut().unwrap().poll_reserve(&self.sender, cx){
+                    std::task::Poll::Ready(Ok(()))=>std::task::Poll::Ready(Ok(())),
+                    std::task::Poll::Ready(Err(_e))=>std::task::Poll::Ready(Err(ActorError::MailboxClosed{reason: self.shared.close_reason()}.into())),
+                    std::task::Poll::Pending=>std::task::Poll::Pending,
+                };
+            },
+            quote! {
+                match std::mem::replace(self.service.get_mut().unwrap(), ReservedSlot::Idle){
+                    ReservedSlot::Ready(permit)=>{
+                        if let Err(e) = self.shared.accepting() {
+                            return AskFuture::failed(e).variant(#actor_name, #variant_name) #flatten;
+                        }
+                        #check_deadlock
+                        #validate
+                        #watch
+                        permit.send(#queued_watched);
+                        AskFuture::waiting(r) #watching
+                    }
+                    _=>self.__ask(msg, r),
+                }
+            },
+        ),
+        None => (
+            quote! {
+                return match self.sender.is_closed(){
+                    true=>std::task::Poll::Ready(Err(ActorError::MailboxClosed{reason: self.shared.close_reason()}.into())),
+                    false=>std::task::Poll::Ready(Ok(())),
+                };
+            },
+            quote! {self.__ask(msg, r)},
+        ),
+    };
+    quote! {
+        impl #impl_generics ::tower::Service<#msg_type> for #actor_ident #ty_generics #where_clause{
+            type Response = #response;
+            type Error = #error;
+            type Future = #future;
+
+            fn poll_ready(&mut self, cx: &mut std::task::Context<'_>)->std::task::Poll<Result<(), Self::Error>>{
+                #poll_ready
+            }
+
+            fn call(&mut self, mut msg: #msg_type)->Self::Future{
+                let ask: AskFuture<#resp> = match msg{
+                    #msg_ident::#req{ref mut resp,..}=>{
+                        let (s,r) = #oneshot::channel();
+                        *resp = Some(#responder);
+                        #send
+                    }
+                    #invalid_ask
+                };
+                return ask.variant(#actor_name, #variant_name) #default_timeout #flatten;
+            }
+        }
+    }
+}
+
+// `pub mod prelude` re-exporting every public type the module ends up with,
+// generated or not, sorted by name
+fn prelude(
+    items: &[syn::Item],
+    context: &HashMap<String, ActorInfo>,
+    wires: &[(wire::Wire, ItemEnum)],
+    events: &[Ident],
+    args: &ActorsArgs,
+) -> syn::Result<proc_macro2::TokenStream> {
+    for item in items {
+        if let syn::Item::Mod(m) = item {
+            if m.ident == "prelude" {
+                return Err(syn::Error::new_spanned(
+                    &m.ident,
+                    "`prelude` is generated by `#[actors]`, rename this module",
+                ));
+            }
+        }
+    }
+    let is_pub = |ident: &Ident| {
+        items.iter().any(|item| match item {
+            syn::Item::Enum(v) => v.ident == *ident && matches!(v.vis, syn::Visibility::Public(_)),
+            _ => false,
+        })
+    };
+    let mut generated = BTreeSet::new();
+    let mut own = BTreeSet::new();
+    // plain data, outside of `#[actors(cfg(..))]`
+    let mut ungated = BTreeSet::new();
+    for info in context.values() {
+        if !info.has_resp() || info.actor_ident.is_none() {
+            continue;
+        }
+        // shared by every actor of the module
+        generated.extend(
+            [
+                "ActorError",
+                "AskFlatten",
+                "AskFuture",
+                "AskUnboxed",
+                "AskMapErr",
+                "AskTimeout",
+                "Ack",
+                "Handles",
+                "AnyActor",
+                "EventBus",
+                "EventSink",
+                "SubscriptionId",
+                "MsgSource",
+                "ValidationError",
+                "ActorId",
+                "ActorPanic",
+                "ActorStats",
+                "CloseReason",
+                "Emitter",
+                "Mailbox",
+                "PipeSink",
+                "PipeStats",
+                "Responder",
+                "RouteInfo",
+            ]
+            .iter()
+            .map(|v| v.to_string()),
+        );
+        if ask::timings() {
+            generated.insert("AskTimings".to_string());
+        }
+        if snapshot::enabled() {
+            generated.insert("QueuedInfo".to_string());
+        }
+        generated.insert(format!("Actor{}", info.actor_ident.as_ref().unwrap()));
+        if info.ctx {
+            generated.insert(info.ctx_ident().to_string());
+            generated.insert(info.weak_ident().to_string());
+        }
+        if info.has_permit(args) {
+            generated.insert(format!("{}Permit", info.actor_ident.as_ref().unwrap()));
+            if cfg!(feature = "sink") {
+                generated.insert(format!(
+                    "{}ThrottledSink",
+                    info.actor_ident.as_ref().unwrap()
+                ));
+            }
+        }
+        generated.extend(info.views.iter().map(|(view, _)| view.to_string()));
+        if is_pub(&info.msg_ident) {
+            own.insert(info.msg_ident.to_string());
+        }
+    }
+    if args.registry && !generated.is_empty() {
+        generated.insert("Actors".to_string());
+        generated.insert("StartError".to_string());
+        if args.ready_timeout.is_some() {
+            generated.insert("NotReady".to_string());
+        }
+    }
+    for event in events {
+        generated.insert(format!("{}Bus", event));
+        own.insert(event.to_string());
+        ungated.insert(event.to_string());
+    }
+    for (_, item) in wires {
+        if matches!(item.vis, syn::Visibility::Public(_)) {
+            generated.insert(format!("{}Wire", item.ident));
+            ungated.insert(format!("{}Wire", item.ident));
+        }
+    }
+    for ident in type_names(items) {
+        if generated.contains(&ident.to_string()) {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!(
+                    "`{}` is also generated by `#[actors]` and re-exported from its prelude, rename this item",
+                    ident
+                ),
+            ));
+        }
+    }
+    let gate = |v: &String| match ungated.contains(v) {
+        true => quote! {},
+        false => args.gate(),
+    };
+    let path = match &args.output_mod {
+        Some(name) => quote! {super::#name},
+        None => quote! {super},
+    };
+    let generated = generated.iter().map(|v| {
+        let (gate, ident) = (gate(v), Ident::new(v, Span::call_site()));
+        quote! {#gate pub use #path::#ident;}
+    });
+    let own = own.iter().map(|v| {
+        let (gate, ident) = (gate(v), Ident::new(v, Span::call_site()));
+        quote! {#gate pub use super::#ident;}
+    });
+    Ok(quote! {
+        pub mod prelude {
+            #(#generated)*
+            #(#own)*
+        }
+    })
+}
+
+// the names `items` take in the type namespace, imports included
+fn type_names(items: &[syn::Item]) -> Vec<&Ident> {
+    fn walk<'a>(tree: &'a syn::UseTree, names: &mut Vec<&'a Ident>) {
+        match tree {
+            syn::UseTree::Path(v) => walk(&v.tree, names),
+            syn::UseTree::Name(v) => names.push(&v.ident),
+            syn::UseTree::Rename(v) => names.push(&v.rename),
+            syn::UseTree::Group(v) => v.items.iter().for_each(|tree| walk(tree, names)),
+            syn::UseTree::Glob(_) => {}
+        }
+    }
+    let mut names = vec![];
+    for item in items {
+        match item {
+            syn::Item::Struct(v) => names.push(&v.ident),
+            syn::Item::Enum(v) => names.push(&v.ident),
+            syn::Item::Union(v) => names.push(&v.ident),
+            syn::Item::Type(v) => names.push(&v.ident),
+            syn::Item::Trait(v) => names.push(&v.ident),
+            syn::Item::Mod(v) => names.push(&v.ident),
+            syn::Item::Use(v) => walk(&v.tree, &mut names),
+            _ => {}
+        }
+    }
+    names
+}
+
+// `#[actors(cfg(..))]`: puts the predicate on every generated item
+fn gate(out: Vec<syn::Item>, gate: proc_macro2::TokenStream) -> syn::Result<Vec<syn::Item>> {
+    let mut result = vec![];
+    for item in out {
+        let items = match item {
+            syn::Item::Verbatim(tokens) => syn::parse2::<syn::File>(tokens)?.items,
+            item => vec![item],
+        };
+        result.extend(
+            items
+                .into_iter()
+                .map(|item| syn::Item::Verbatim(quote! {#gate #item})),
+        );
+    }
+    Ok(result)
+}
+
+// splits verbatim chunks into single items so each one gets the allow
+fn allow_unused(out: Vec<syn::Item>) -> syn::Result<Vec<syn::Item>> {
+    let mut result = vec![];
+    for item in out {
+        match item {
+            syn::Item::Verbatim(tokens) => result.extend(syn::parse2::<syn::File>(tokens)?.items),
+            item => result.push(item),
+        }
+    }
+    for item in &mut result {
+        let attrs = match item {
+            syn::Item::Struct(v) => &mut v.attrs,
+            syn::Item::Enum(v) => &mut v.attrs,
+            syn::Item::Impl(v) => &mut v.attrs,
+            syn::Item::Fn(v) => &mut v.attrs,
+            syn::Item::Trait(v) => &mut v.attrs,
+            syn::Item::Type(v) => &mut v.attrs,
+            _ => continue,
+        };
+        attrs.push(syn::parse_quote! {#[allow(dead_code)]});
+    }
+    Ok(result)
+}
+
+/// Turns every actor of the annotated module into an `ActorXxx` handle.
+///
+/// Every path the generated code names is absolute, so the module can sit
+/// anywhere, `#[cfg(test)]` modules and doctests like this one included.
+///
+/// ```
+/// use tokio_actor::actors;
+///
+/// #[actors]
+/// mod counter {
+///     pub enum CounterMsg {
+///         Add { n: u64, resp: u64 },
+///     }
+///
+///     pub struct Counter {
+///         pub total: u64,
+///     }
+///
+///     impl Counter {
+///         async fn process(&mut self, msg: CounterMsg) {
+///             match msg {
+///                 CounterMsg::Add { n, resp } => {
+///                     self.total += n;
+///                     if let Some(r) = resp {
+///                         let _ = r.send(self.total);
+///                     }
+///                 }
+///             }
+///         }
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut counter = counter::ActorCounter::spawn(counter::Counter { total: 0 });
+///     let total = counter.add(counter::CounterMsg::Add { n: 2, resp: None }).await;
+///     assert_eq!(total.unwrap(), 2);
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn actors(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = match profile::take(attr.into()) {
+        Ok((Some(name), rest)) => return profile::apply(&name, rest, item.into()).into(),
+        Ok((None, rest)) => rest,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let args = match syn::parse2::<ActorsArgs>(attr) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let mut ast = parse_macro_input!(item as ItemMod);
+    let mut context = HashMap::<String, ActorInfo>::new();
+    // actor names in declaration order, so the output does not depend on hashing
+    let mut order = vec![];
+    let mut out = vec![];
+    let mut wires = vec![];
+    let mut debugs = vec![];
+    // `(actor name, path)` of msg enums with `#[msg_for(..)]`
+    let mut mapped = vec![];
+    // `pub` enums marked `#[event]`
+    let mut events = vec![];
+    if let Some(content) = &mut ast.content {
+        if let Err(e) = delegate::expand(&mut content.1) {
+            return e.to_compile_error().into();
+        }
+        for item in &mut content.1 {
+            if let syn::Item::Enum(v) = item {
+                // `#[event]`: published through an `EventBus`, never a msg enum
+                if take_flag(&mut v.attrs, "event") {
+                    if args.minimal {
+                        return syn::Error::new_spanned(
+                            &v.ident,
+                            "`#[event]` enums are published through a generated `EventBus`, which `#[actors(minimal)]` leaves out",
+                        )
+                        .to_compile_error()
+                        .into();
+                    }
+                    if !v.generics.params.is_empty() {
+                        return syn::Error::new_spanned(
+                            &v.generics,
+                            "`#[event]` enums can't have generics",
+                        )
+                        .to_compile_error()
+                        .into();
+                    }
+                    let (vis, ident) = (&v.vis, &v.ident);
+                    let bus = Ident::new(&format!("{}Bus", ident), Span::call_site());
+                    out.push(syn::Item::Verbatim(
+                        quote! {#vis type #bus = EventBus<#ident>;},
+                    ));
+                    if matches!(vis, syn::Visibility::Public(_)) {
+                        events.push(ident.clone());
+                    }
+                    continue;
+                }
+                let msg_for = match take_msg_for(&mut v.attrs) {
+                    Ok(p) => p,
+                    Err(e) => return e.to_compile_error().into(),
+                };
+                let actor_name = match &msg_for {
+                    Some(p) => Some(p.segments.last().unwrap().ident.to_string()),
+                    None => get_actor_name(ID::RemoveMsg(v.ident.clone())),
+                };
+                if let Some(name) = actor_name {
+                    if let Err(e) = check_actor_name(&context, &name, &v.ident) {
+                        return e.to_compile_error().into();
+                    }
+                    if !context.contains_key(&name) {
+                        order.push(name.clone());
+                        context.insert(
+                            name.clone(),
+                            ActorInfo::new(v.ident.clone(), v.generics.clone()),
+                        );
+                    }
+                    let info = context.get_mut(&name).unwrap();
+                    if let Some(p) = msg_for {
+                        info.actor_mods = p.segments.iter().map(|s| s.ident.clone()).collect();
+                        info.actor_mods.pop();
+                        mapped.push((name.clone(), p));
+                    }
+                    let redacted = match redact::Redacted::take(v) {
+                        Ok(r) => r,
+                        Err(e) => return e.to_compile_error().into(),
+                    };
+                    let wire = match wire::Wire::take(v, &redacted) {
+                        Ok(w) => w,
+                        Err(e) => return e.to_compile_error().into(),
+                    };
+                    if let Err(e) = process_enum(v, info, &redacted, &args) {
+                        return e.to_compile_error().into();
+                    }
+                    match redacted.debug(v) {
+                        Ok(Some(debug)) => debugs.push(syn::Item::Verbatim(debug)),
+                        Ok(None) => {}
+                        Err(e) => return e.to_compile_error().into(),
+                    }
+                    if let Some(wire) = wire {
+                        wires.push((wire, v.clone()));
+                    }
+                    // `resp` fields are tokio channels now
+                    if let (Some(predicate), true) = (&args.cfg, info.has_resp()) {
+                        v.attrs.push(syn::parse_quote! {#[cfg(#predicate)]});
+                    }
+                }
+            }
+        }
+        //println!("finished enum processing");
+        if args.minimal {
+            let mut out = vec![syn::Item::Verbatim(minimal::generate(
+                &order, &context, &args,
+            ))];
+            out.extend(debugs);
+            if args.cfg.is_some() {
+                out = match gate(out, args.gate()) {
+                    Ok(v) => v,
+                    Err(e) => return e.to_compile_error().into(),
+                };
+            }
+            content.1.extend(out);
+            for (wire, item) in &wires {
+                content
+                    .1
+                    .push(syn::Item::Verbatim(wire.generate(item, &[], &args.gate())));
+            }
+            return quote! {#ast}.into();
+        }
+        for item in &mut content.1 {
+            if let syn::Item::Struct(v) = item {
+                let actor_name = get_actor_name(ID::Direct(v.ident.clone()));
+                if let Some(name) = actor_name {
+                    if !context.contains_key(&name) {
+                        continue;
+                    }
+                    let info = context.get_mut(&name).unwrap();
+                    if !info.has_resp() || !info.actor_mods.is_empty() {
+                        continue;
+                    }
+                    if let Err(e) = bind_actor(info, v) {
+                        return e.to_compile_error().into();
+                    }
+                }
+            }
+        }
+        // actors named by `#[msg_for(..)]` may live in a nested inline module
+        for (name, path) in &mapped {
+            let info = context.get_mut(name).unwrap();
+            if !info.has_resp() || info.actor_mods.is_empty() {
+                continue;
+            }
+            let ident = &path.segments.last().unwrap().ident;
+            let v = match find_struct(&mut content.1, &info.actor_mods, ident) {
+                Some(v) => v,
+                None => {
+                    return syn::Error::new_spanned(
+                        path,
+                        format!("no struct `{}` in an inline module of this path", ident),
+                    )
+                    .to_compile_error()
+                    .into()
+                }
+            };
+            if let Err(e) = bind_actor(info, v) {
+                return e.to_compile_error().into();
+            }
+        }
+        //println!("finished struct processing");
+        for name in &order {
+            let info = context.get_mut(name).unwrap();
+            info.resolve_msg_defaults();
+            collect_hooks(&content.1, name, info);
+            if !info.actor_mods.is_empty() {
+                if let Some(items) = find_items(&content.1, &info.actor_mods) {
+                    collect_hooks(items, name, info);
+                }
+            }
+            if let Some(event) = info
+                .events
+                .as_ref()
+                .and_then(|(_, ty)| find_enum(&content.1, ty))
+            {
+                info.event_variants = event.variants.iter().cloned().collect();
+            }
+            if info.actor_args.exit.is_some() && !info.has_hook("on_stop") {
+                return syn::Error::new_spanned(
+                    info.actor_ident.as_ref().unwrap(),
+                    "`#[actor(exit = ..)]` needs an `async fn on_stop(&mut self)` returning it",
+                )
+                .to_compile_error()
+                .into();
+            }
+            if info.actor_args.idle_debounce.is_some() && !info.has_hook("on_idle") {
+                return syn::Error::new_spanned(
+                    info.actor_ident.as_ref().unwrap(),
+                    "`#[actor(idle_debounce = ..)]` needs an `async fn on_idle(&mut self)` to debounce",
+                )
+                .to_compile_error()
+                .into();
+            }
+            if let (Some(first), false) = (info.actor_args.await_ready.first(), args.registry) {
+                return syn::Error::new_spanned(
+                    first,
+                    "`await_ready` is kept by `Actors::spawn_all`, add `#[actors(registry)]`",
+                )
+                .to_compile_error()
+                .into();
+            }
+            // a pool worker is handed one msg at a time, its mailbox is empty after each
+            if info.actor_args.pool && info.has_hook("on_idle") {
+                return syn::Error::new_spanned(
+                    info.actor_ident.as_ref().unwrap(),
+                    "`#[actor(pool)]` workers are idle after every msg, `on_idle` would run for each",
+                )
+                .to_compile_error()
+                .into();
+            }
+            let actor_args = &info.actor_args;
+            if actor_args.pool != actor_args.shared_queue {
+                return syn::Error::new_spanned(
+                    info.actor_ident.as_ref().unwrap(),
+                    "pools consume one shared queue, write `#[actor(pool, shared_queue)]`",
+                )
+                .to_compile_error()
+                .into();
+            }
+            if actor_args.pool
+                && (info.has_latest() || info.events.is_some() || actor_args.idle_timeout.is_some())
+            {
+                return syn::Error::new_spanned(
+                    info.actor_ident.as_ref().unwrap(),
+                    "`#[actor(pool)]` can't be combined with `#[latest_only]`, `#[events]` or `idle_timeout`",
+                )
+                .to_compile_error()
+                .into();
+            }
+            if let (Some((view, _)), false) =
+                (info.views.first(), info.actor_generics.params.is_empty())
+            {
+                return syn::Error::new_spanned(
+                    view,
+                    "views can't be generated for actors with generics",
+                )
+                .to_compile_error()
+                .into();
+            }
+            if info.actor_args.on_error.is_some()
+                && (!info.fallible || info.actor_args.ack.is_some())
+            {
+                return syn::Error::new_spanned(
+                    info.actor_ident.as_ref().unwrap(),
+                    "`#[actor(on_error = ..)]` needs a `process` returning a `Result` and can't be combined with `ack`",
+                )
+                .to_compile_error()
+                .into();
+            }
+            if info.ctx && info.actor_args.with_external {
+                return syn::Error::new_spanned(
+                    info.actor_ident.as_ref().unwrap(),
+                    "`#[actor(with_external)]` can't be combined with a ctx argument on `process`",
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
+        for name in &order {
+            let info = &context[name];
+            if !info.has_resp() || info.actor_ident.is_none() {
+                continue;
+            }
+            out.extend(generate_actor(info, &args));
+        }
+        if args.registry {
+            let infos: Vec<&ActorInfo> = order
+                .iter()
+                .map(|name| &context[name])
+                .filter(|info| info.has_resp() && info.actor_ident.is_some())
+                .collect();
+            match registry::generate(&infos, &args) {
+                Ok(v) => out.push(syn::Item::Verbatim(v)),
+                Err(e) => return e.to_compile_error().into(),
+            }
+        }
+    }
+    if let Some(content) = &ast.content {
+        if let Err(e) = check_user_methods(&out, &content.1) {
+            return e.to_compile_error().into();
+        }
+    }
+    if !out.is_empty() {
+        let lanes = context.values().any(|info| info.lane().is_some());
+        out.push(syn::Item::Verbatim(ask::items(&args, lanes)));
+    }
+    // generated last, so every handle in the module is known by then
+    let handles: Vec<Ident> = context
+        .values()
+        .filter(|info| info.has_resp() && info.actor_ident.is_some())
+        .map(|info| {
+            let ident = info.actor_ident.as_ref().unwrap();
+            Ident::new(&format!("Actor{}", ident), Span::call_site())
+        })
+        .collect();
+    out.extend(debugs);
+    if args.cfg.is_some() {
+        out = match gate(out, args.gate()) {
+            Ok(v) => v,
+            Err(e) => return e.to_compile_error().into(),
+        };
+    }
+    for (wire, item) in &wires {
+        out.push(syn::Item::Verbatim(wire.generate(
+            item,
+            &handles,
+            &args.gate(),
+        )));
+    }
+    if args.allow_unused {
+        out = match allow_unused(out) {
+            Ok(v) => v,
+            Err(e) => return e.to_compile_error().into(),
+        };
+    }
+    if !out.is_empty() {
+        if let Some(content) = &mut ast.content {
+            let prelude = match prelude(&content.1, &context, &wires, &events, &args) {
+                Ok(v) => v,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            content.1.push(syn::Item::Verbatim(prelude));
+            match &args.output_mod {
+                Some(name) => content.1.push(syn::Item::Verbatim(quote! {
+                    pub mod #name {
+                        #[allow(unused_imports)]
+                        use super::*;
+                        #(#out)*
+                    }
+                })),
+                None => content.1.extend(out),
+            }
+        }
+    }
+    let result = quote! {#ast};
+    //println!("{}", &result);
+    result.into()
+}
+
+// the handle and method names of inherent impls among `items`
+fn inherent_methods(items: &[syn::Item], found: &mut Vec<(Ident, Ident)>) {
+    for item in items {
+        let v = match item {
+            syn::Item::Impl(v) => v,
+            syn::Item::Verbatim(tokens) => {
+                if let Ok(file) = syn::parse2::<syn::File>(tokens.clone()) {
+                    inherent_methods(&file.items, found);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let handle = match (&v.trait_, &*v.self_ty) {
+            (None, Type::Path(p)) => p.path.segments.last().unwrap().ident.clone(),
+            _ => continue,
+        };
+        for item in &v.items {
+            if let syn::ImplItem::Method(m) = item {
+                found.push((handle.clone(), m.sig.ident.clone()));
+            }
+        }
+    }
+}
+
+// user impls on a handle may add methods, as long as they don't take a name the
+// macro generates. `__` names are kept for generated internals, so they can
+// grow without breaking anyone.
+fn check_user_methods(generated: &[syn::Item], user: &[syn::Item]) -> syn::Result<()> {
+    let mut taken = vec![];
+    inherent_methods(generated, &mut taken);
+    let mut added = vec![];
+    inherent_methods(user, &mut added);
+    for (handle, method) in added {
+        if !taken.iter().any(|(h, _)| h == &handle) {
+            continue;
+        }
+        if method.to_string().starts_with("__") {
+            return Err(syn::Error::new_spanned(
+                &method,
+                format!(
+                    "methods starting with `__` are reserved for what `#[actors]` generates on `{}`",
+                    handle
+                ),
+            ));
+        }
+        if taken.contains(&(handle.clone(), method.clone())) {
+            return Err(syn::Error::new_spanned(
+                &method,
+                format!(
+                    "`{}` is already generated for `{}`, pick another name",
+                    method, handle
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// `actors_profile!{ production = { bounded = 1024, batch = 32 } }` names a set
+// of `#[actors(..)]` arguments for `#[actors(profile = "production")]`.
+#[proc_macro]
+pub fn actors_profile(input: TokenStream) -> TokenStream {
+    match profile::expand(input.into()) {
+        Ok(v) => v.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
 }
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::args::ActorsArgs;
+use crate::runtime::Paths;
+use crate::ActorInfo;
+
+// `#[actors(minimal)]`: msg enums get their `resp` fields rewritten and nothing
+// else is generated besides a request struct per plain ask variant, tied to its
+// msg and response by `ActorRequest`. actor structs, handles, run loops and the
+// runtime items are left to the module, for code bringing its own.
+pub(crate) fn generate(
+    order: &[String],
+    context: &HashMap<String, ActorInfo>,
+    args: &ActorsArgs,
+) -> TokenStream {
+    let Paths { oneshot, .. } = args.runtime.paths();
+    let mut out = vec![];
+    for name in order {
+        let info = &context[name];
+        if !info.msg_generics.params.is_empty() {
+            continue;
+        }
+        let msg_ident = &info.msg_ident;
+        for v in &info.variants {
+            let resp = match (&v.resp, v.stream || v.sink) {
+                (Some(resp), false) => resp,
+                _ => continue,
+            };
+            let req = &v.ident;
+            let request_ident = Ident::new(&format!("{}{}Request", name, req), Span::call_site());
+            let variant_name = format!("{}::{}", msg_ident, req);
+            let cfgs = &v.cfgs;
+            let deprecated = &v.deprecated;
+            let vis = &v.vis;
+            let fields = v
+                .typed_args
+                .iter()
+                .map(|(field, ty, _)| quote! {#vis #field: #ty});
+            let names: Vec<&Ident> = v.typed_args.iter().map(|(field, _, _)| field).collect();
+            out.push(quote! {
+                #(#cfgs)*
+                #(#deprecated)*
+                #vis struct #request_ident {
+                    #(#fields,)*
+                }
+
+                #(#cfgs)*
+                #[allow(deprecated)]
+                impl ActorRequest for #request_ident {
+                    type Msg = #msg_ident;
+                    type Response = #resp;
+                    const VARIANT: &'static str = #variant_name;
+
+                    fn into_msg(self, resp: #oneshot::Sender<#resp>)->#msg_ident{
+                        return #msg_ident::#req{#(#names: self.#names,)* resp: Some(resp)};
+                    }
+                }
+            });
+        }
+    }
+    quote! {
+        // a request struct, the msg enum carrying it and what it's answered with
+        pub trait ActorRequest {
+            type Msg;
+            type Response;
+            // `FooMsg::Bar`
+            const VARIANT: &'static str;
+
+            // the msg, with `resp` the sender half of the oneshot the answer comes through
+            fn into_msg(self, resp: #oneshot::Sender<Self::Response>) -> Self::Msg;
+        }
+
+        #(#out)*
+    }
+}
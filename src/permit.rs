@@ -0,0 +1,148 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::args::ActorsArgs;
+use crate::runtime::Paths;
+use crate::snapshot;
+use crate::ActorInfo;
+
+// bounded mailboxes: `reserve` waits for a free slot and hands out a `FooPermit`
+// holding it, so an expensive msg is only built once it's sure to fit. the permit
+// sends one msg through the same typed methods as the handle, consuming itself,
+// and gives the slot back when dropped unused.
+pub(crate) fn generate(info: &ActorInfo, args: &ActorsArgs) -> TokenStream {
+    let Paths {
+        mpsc,
+        oneshot,
+        time,
+        ..
+    } = args.runtime.paths();
+    let ident = info.actor_ident.as_ref().unwrap();
+    let actor_ident = Ident::new(&format!("Actor{}", ident), Span::call_site());
+    let permit_ident = Ident::new(&format!("{}Permit", ident), Span::call_site());
+    let msg_ident = &info.msg_ident;
+    let (watch, watched, watching) = info.watch_panics(args);
+    let watched = snapshot::queued(watched, quote! {self.shared});
+    let envelope = snapshot::queued(quote! {Envelope::new(msg)}, quote! {self.shared});
+    let validate = match info.variants.iter().any(|v| v.validate.is_some()) {
+        true => quote! {let msg = #actor_ident::__validate(msg)?;},
+        false => quote! {},
+    };
+    let validate_ask = match info.variants.iter().any(|v| v.validate.is_some()) {
+        true => quote! {
+            let msg = match #actor_ident::__validate(msg){
+                Ok(v)=>v,
+                Err(e)=>{return AskFuture::failed(e);}
+            };
+        },
+        false => quote! {},
+    };
+    let check_deadlock = args.deadlock_check.then(|| {
+        quote! {
+            if self.shared.asks_itself() {
+                return AskFuture::failed(ActorError::WouldDeadlock);
+            }
+        }
+    });
+    let naming = args.rename_all.allow();
+    let invalid_ask = info.catch_all(quote! {AskFuture::failed(ActorError::InvalidMsgType),});
+    let invalid = info.catch_all(quote! {{return Err(ActorError::InvalidMsgType);}});
+    let mut methods = vec![];
+    for v in info.variants.iter().filter(|v| v.resp.is_some()) {
+        let req = &v.ident;
+        let resp = v.resp.as_ref().unwrap();
+        let (cfgs, vis) = (&v.cfgs, &v.vis);
+        let deprecated = &v.deprecated;
+        let actor_name = ident.to_string();
+        let variant_name = format!("{}::{}", msg_ident, req);
+        if v.ask && !v.stream && !v.sink {
+            let fname = args.rename_all.method(&format!("{}", req));
+            let responder = v.responder(msg_ident);
+            let (ask_type, flatten) = match &v.fallible {
+                Some((t, e)) => (quote! {AskFlatten<#t, #e>}, quote! {.flatten()}),
+                None => (quote! {AskFuture<#resp>}, quote! {}),
+            };
+            let default_timeout = v
+                .timeout
+                .map(|ms| quote! {.default_timeout(std::time::Duration::from_millis(#ms))});
+            methods.push(quote! {
+                #(#cfgs)*
+                #(#deprecated)*
+                #vis fn #fname(self, mut msg: #msg_ident)->#ask_type{
+                    let ask: AskFuture<#resp> = match msg{
+                        #msg_ident::#req{ref mut resp,..}=>{
+                            let (s,r) = #oneshot::channel();
+                            *resp = Some(#responder);
+                            self.__ask(msg, r)
+                        }
+                        #invalid_ask
+                    };
+                    return ask.variant(#actor_name, #variant_name) #default_timeout #flatten;
+                }
+            });
+        }
+        if v.tell {
+            let fname = args.rename_all.method(&format!("{}_no_wait", req));
+            methods.push(quote! {
+                #(#cfgs)*
+                #(#deprecated)*
+                #vis fn #fname(self, msg: #msg_ident)->Result<(),ActorError>{
+                    match msg{
+                        #msg_ident::#req{..}=>{return self.send(msg);}
+                        #invalid
+                    };
+                }
+            });
+        }
+    }
+    quote! {
+        // a slot reserved in a bounded mailbox, for exactly one msg
+        pub struct #permit_ident<'a> {
+            permit: #mpsc::Permit<'a, Envelope<#msg_ident>>,
+            shared: &'a std::sync::Arc<ActorShared>,
+        }
+
+        #naming
+        impl<'a> #permit_ident<'a> {
+            // hands `msg` over without waiting, the slot is already there
+            pub fn send(self, msg: #msg_ident)->Result<(),ActorError>{
+                self.shared.accepting()?;
+                #validate
+                self.permit.send(#envelope);
+                return Ok(());
+            }
+
+            #[allow(dead_code)]
+            fn __ask<R: Send + 'static>(self, msg: #msg_ident, r: #oneshot::Receiver<R>)->AskFuture<R>{
+                if let Err(e) = self.shared.accepting() {
+                    return AskFuture::failed(e);
+                }
+                #check_deadlock
+                #validate_ask
+                #watch
+                self.permit.send(#watched);
+                return AskFuture::waiting(r) #watching;
+            }
+
+            #(#methods)*
+        }
+
+        impl #actor_ident {
+            // waits for a free slot in the mailbox
+            pub async fn reserve(&self)->Result<#permit_ident<'_>,ActorError>{
+                self.shared.accepting()?;
+                let permit = self.sender.reserve().await.map_err(|_e|{return ActorError::MailboxClosed{reason: self.shared.close_reason()};})?;
+                return Ok(#permit_ident{permit, shared: &self.shared});
+            }
+
+            // `reserve`, giving up with `EnqueueTimeout` after `timeout`
+            pub async fn reserve_timeout(&self, timeout: std::time::Duration)->Result<#permit_ident<'_>,ActorError>{
+                return match #time::timeout(timeout, self.reserve()).await{
+                    Ok(v)=>v,
+                    Err(_e)=>Err(ActorError::EnqueueTimeout),
+                };
+            }
+        }
+    }
+}
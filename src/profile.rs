@@ -0,0 +1,123 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Ident, LitStr, Token};
+
+use crate::args::ActorsArgs;
+
+// top level `key = value` segments of an argument list, trailing comma dropped
+fn segments(tokens: TokenStream) -> Vec<TokenStream> {
+    let mut result = vec![];
+    let mut current = vec![];
+    for tt in tokens {
+        match &tt {
+            TokenTree::Punct(p) if p.as_char() == ',' => {
+                result.push(current.drain(..).collect());
+            }
+            _ => current.push(tt),
+        }
+    }
+    if !current.is_empty() {
+        result.push(current.into_iter().collect());
+    }
+    result
+}
+
+// splits `profile = "name"` off the arguments of `#[actors(..)]`
+pub(crate) fn take(attr: TokenStream) -> syn::Result<(Option<Ident>, TokenStream)> {
+    let mut profile = None;
+    let mut rest = vec![];
+    for segment in segments(attr) {
+        match segment.clone().into_iter().next() {
+            Some(TokenTree::Ident(key)) if key == "profile" => {
+                let name = syn::parse2::<ProfileArg>(segment)?.0;
+                if profile.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        &name,
+                        "only one profile per module",
+                    ));
+                }
+                profile = Some(name);
+            }
+            _ => rest.push(segment),
+        }
+    }
+    Ok((profile, quote! {#(#rest),*}))
+}
+
+struct ProfileArg(Ident);
+
+impl Parse for ProfileArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Ident>()?;
+        input.parse::<Token![=]>()?;
+        let lit: LitStr = input.parse().map_err(|e| {
+            syn::Error::new(
+                e.span(),
+                "expected a profile name like `profile = \"production\"`",
+            )
+        })?;
+        Ok(Self(lit.parse()?))
+    }
+}
+
+// `name = { args }` entries of `actors_profile!`
+struct Profile {
+    name: Ident,
+    args: TokenStream,
+}
+
+struct Profiles(Vec<Profile>);
+
+impl Parse for Profiles {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut result = vec![];
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let body;
+            syn::braced!(body in input);
+            let args: TokenStream = body.parse()?;
+            // same rules as `#[actors(..)]`, so typos surface where the profile is defined
+            syn::parse2::<ActorsArgs>(args.clone())?;
+            result.push(Profile { name, args });
+            if input.is_empty() {
+                break;
+            }
+            if input.peek(Token![;]) {
+                input.parse::<Token![;]>()?;
+            } else {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self(result))
+    }
+}
+
+// one `macro_rules!` per profile. `#[actors(profile = "name", ..)]` hands the
+// module to it, and it puts the module back under `#[actors(..)]` with the
+// profile arguments first, so anything the module sets itself wins.
+pub(crate) fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let profiles = syn::parse2::<Profiles>(input)?.0;
+    let mut out = vec![];
+    for Profile { name, args } in profiles {
+        let args = segments(args);
+        out.push(quote! {
+            #[allow(unused_macros)]
+            macro_rules! #name {
+                ([$($args:tt)*] $($item:tt)*) => {
+                    #[::tokio_actor::actors(#(#args,)* $($args)*)]
+                    $($item)*
+                };
+            }
+        });
+    }
+    Ok(quote! {#(#out)*})
+}
+
+// the module re-emitted through the profile macro
+pub(crate) fn apply(profile: &Ident, rest: TokenStream, item: TokenStream) -> TokenStream {
+    quote! {
+        #profile! { [#rest] #item }
+    }
+}
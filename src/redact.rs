@@ -0,0 +1,180 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Attribute, Fields, Ident, ItemEnum, Path, Token};
+
+// `#[redact]` on msg fields holding secrets, so they don't end up in logs: a
+// `#[derive(Debug)]` on the msg enum is swapped for an impl printing `***` in
+// their place, spans never record them, and `#[wire(..)]` mirrors leave them out
+// unless the field says `#[redact(keep)]`.
+#[derive(Default)]
+pub(crate) struct Redacted {
+    fields: Vec<RedactedField>,
+}
+
+struct RedactedField {
+    variant: Ident,
+    index: usize,
+    name: Option<Ident>,
+    // `#[redact(keep)]`: still carried by wire mirrors
+    keep: bool,
+}
+
+impl Redacted {
+    // reads and strips `#[redact]` from the fields of the msg enum
+    pub(crate) fn take(item: &mut ItemEnum) -> syn::Result<Self> {
+        let mut fields = vec![];
+        for v in &mut item.variants {
+            for (index, field) in v.fields.iter_mut().enumerate() {
+                let attr = match field.attrs.iter().position(|a| a.path.is_ident("redact")) {
+                    Some(pos) => field.attrs.remove(pos),
+                    None => continue,
+                };
+                if field.ident.as_ref().is_some_and(|f| f == "resp") {
+                    return Err(syn::Error::new_spanned(
+                        &attr,
+                        "`#[redact]` goes on payload fields, not on `resp`",
+                    ));
+                }
+                let keep = match attr.tokens.is_empty() {
+                    true => false,
+                    false => {
+                        let arg: Ident = attr.parse_args()?;
+                        if arg != "keep" {
+                            return Err(syn::Error::new_spanned(
+                                &arg,
+                                "expected `#[redact]` or `#[redact(keep)]`",
+                            ));
+                        }
+                        true
+                    }
+                };
+                fields.push(RedactedField {
+                    variant: v.ident.clone(),
+                    index,
+                    name: field.ident.clone(),
+                    keep,
+                });
+            }
+        }
+        Ok(Self { fields })
+    }
+
+    fn contains(&self, variant: &Ident, index: usize) -> bool {
+        self.fields
+            .iter()
+            .any(|f| f.variant == *variant && f.index == index)
+    }
+
+    // named fields of `variant` spans must not record
+    pub(crate) fn names(&self, variant: &Ident) -> Vec<Ident> {
+        self.fields
+            .iter()
+            .filter(|f| f.variant == *variant)
+            .filter_map(|f| f.name.clone())
+            .collect()
+    }
+
+    // `(variant, field index)` wire mirrors leave out
+    pub(crate) fn wire_skipped(&self) -> impl Iterator<Item = (Ident, usize)> + '_ {
+        self.fields
+            .iter()
+            .filter(|f| !f.keep)
+            .map(|f| (f.variant.clone(), f.index))
+    }
+
+    // takes `Debug` out of the msg enum's derives and hands back an impl masking
+    // the redacted fields. `None` when nothing is redacted or `Debug` isn't derived.
+    pub(crate) fn debug(&self, item: &mut ItemEnum) -> syn::Result<Option<TokenStream>> {
+        if self.fields.is_empty() || !take_derive(&mut item.attrs, "Debug")? {
+            return Ok(None);
+        }
+        let ident = &item.ident;
+        let mut generics = item.generics.clone();
+        for param in generics.type_params_mut() {
+            param.bounds.push(syn::parse_quote! {::std::fmt::Debug});
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let (_, ty_generics, _) = item.generics.split_for_impl();
+        let arms = item.variants.iter().map(|v| {
+            let variant = &v.ident;
+            let name = variant.to_string();
+            let cfgs = v.attrs.iter().filter(|a| a.path.is_ident("cfg"));
+            let mut bindings = vec![];
+            let mut values = vec![];
+            for (index, field) in v.fields.iter().enumerate() {
+                let redacted = self.contains(variant, index);
+                let binding = match &field.ident {
+                    Some(f) => quote! {#f},
+                    None => {
+                        let f = Ident::new(&format!("__{}", index), Span::call_site());
+                        quote! {#f}
+                    }
+                };
+                let value = match redacted {
+                    true => quote! {&format_args!("***")},
+                    false => binding.clone(),
+                };
+                values.push(match &field.ident {
+                    Some(f) => {
+                        let name = f.to_string();
+                        quote! {.field(#name, #value)}
+                    }
+                    None => quote! {.field(#value)},
+                });
+                // the secret itself is never bound
+                bindings.push(match (redacted, &field.ident) {
+                    (false, _) => binding,
+                    (true, Some(f)) => quote! {#f: _},
+                    (true, None) => quote! {_},
+                });
+            }
+            match &v.fields {
+                Fields::Named(_) => quote! {
+                    #(#cfgs)* #ident::#variant { #(#bindings),* } => __f.debug_struct(#name) #(#values)* .finish(),
+                },
+                Fields::Unnamed(_) => quote! {
+                    #(#cfgs)* #ident::#variant ( #(#bindings),* ) => __f.debug_tuple(#name) #(#values)* .finish(),
+                },
+                Fields::Unit => quote! {
+                    #(#cfgs)* #ident::#variant => __f.write_str(#name),
+                },
+            }
+        });
+        Ok(Some(quote! {
+            impl #impl_generics ::std::fmt::Debug for #ident #ty_generics #where_clause {
+                fn fmt(&self, __f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    return match self {
+                        #(#arms)*
+                    };
+                }
+            }
+        }))
+    }
+}
+
+// removes `name` from the `#[derive(..)]` attributes, dropping those left empty
+fn take_derive(attrs: &mut Vec<Attribute>, name: &str) -> syn::Result<bool> {
+    let mut found = false;
+    let mut kept = vec![];
+    for mut attr in attrs.drain(..) {
+        if !attr.path.is_ident("derive") {
+            kept.push(attr);
+            continue;
+        }
+        let paths =
+            attr.parse_args_with(syn::punctuated::Punctuated::<Path, Token![,]>::parse_terminated)?;
+        let before = paths.len();
+        let paths: Vec<Path> = paths
+            .into_iter()
+            .filter(|p| p.segments.last().is_none_or(|s| s.ident != name))
+            .collect();
+        found |= paths.len() != before;
+        if paths.is_empty() {
+            continue;
+        }
+        attr.tokens = quote! {(#(#paths),*)};
+        kept.push(attr);
+    }
+    *attrs = kept;
+    Ok(found)
+}
@@ -0,0 +1,281 @@
+use std::collections::HashMap;
+
+use inflector::Inflector;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::args::ActorsArgs;
+use crate::runtime::Paths;
+use crate::ActorInfo;
+
+// `#[actors(registry)]`: an `Actors` struct with a handle for every actor of the
+// module, built by `spawn_all` from one state per actor. every actor is spawned
+// right away, so their `on_start`s run side by side; `spawn_all` resolves once
+// all of them are done. if any failed, the ones that did start are stopped again,
+// last declared first, and the first failure in declaration order is returned.
+// an actor with `#[actor(await_ready(Storage))]` is spawned too, its run loop held
+// back until `Storage`'s `on_start` succeeded, so msgs sent to it meanwhile queue.
+pub(crate) fn generate(infos: &[&ActorInfo], args: &ActorsArgs) -> syn::Result<TokenStream> {
+    let Paths {
+        oneshot,
+        spawn,
+        task,
+        time,
+        ..
+    } = args.runtime.paths();
+    let fields: Vec<Ident> = infos
+        .iter()
+        .map(|info| {
+            let ident = info.actor_ident.as_ref().unwrap();
+            Ident::new(&ident.to_string().to_snake_case(), Span::call_site())
+        })
+        .collect();
+    let order = start_order(infos)?;
+    let mut handles = vec![];
+    let mut params = vec![];
+    let mut spawns = vec![];
+    let mut waits = vec![];
+    let mut stops = vec![];
+    for (index, (info, field)) in infos.iter().zip(&fields).enumerate() {
+        let ident = info.actor_ident.as_ref().unwrap();
+        if !info.actor_generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "`#[actors(registry)]` can't spawn actors with generics",
+            ));
+        }
+        let actor_ident = Ident::new(&format!("Actor{}", ident), Span::call_site());
+        let actor_type = info.actor_type();
+        let ok = Ident::new(&format!("{}_ok", field), Span::call_site());
+        let go = Ident::new(&format!("{}_go", field), Span::call_site());
+        handles.push(quote! {pub #field: #actor_ident});
+        params.push(quote! {#field: #actor_type});
+        let ready = Ident::new(&format!("{}_ready", field), Span::call_site());
+        spawns.push(match info.actor_args.await_ready.is_empty() {
+            true => quote! {
+                let (#field, run) = #actor_ident::start(#field);
+                let #ready = #field.shared.ready();
+                #spawn(run);
+            },
+            false => quote! {
+                let (#field, run) = #actor_ident::start(#field);
+                let #ready = #field.shared.ready();
+                let (#go, go) = #oneshot::channel::<()>();
+                #spawn(async move {
+                    if go.await.is_ok() {
+                        let _ = run.await;
+                    }
+                });
+            },
+        });
+        stops.push(quote! {
+            if #ok {
+                let _ = #field.stop_within(std::time::Duration::ZERO).await;
+            }
+        });
+        let actor_name = ident.to_string();
+        let started = quote! {
+            Ok(Ok(()))=>true,
+            Ok(Err(error))=>{
+                failed.push((#index, StartError{actor: #actor_name, error}));
+                false
+            }
+            Err(_e)=>{
+                failed.push((#index, StartError{actor: #actor_name, error: "panicked in on_start".into()}));
+                false
+            }
+        };
+        let started = match args.ready_timeout {
+            Some(_) => quote! {
+                match #time::timeout_at(deadline, #ready).await{
+                    Ok(started)=>match started{
+                        #started
+                    },
+                    Err(_e)=>{
+                        AnyActor::stop(&#field);
+                        failed.push((#index, StartError{actor: #actor_name, error: Box::new(NotReady{limit})}));
+                        false
+                    }
+                }
+            },
+            None => quote! {
+                match #ready.await{
+                    #started
+                }
+            },
+        };
+        // dependencies come first in `order`, their outcome is known by now
+        let deps: Vec<Ident> = info
+            .actor_args
+            .await_ready
+            .iter()
+            .map(|dep| {
+                Ident::new(
+                    &format!("{}_ok", dep.to_string().to_snake_case()),
+                    Span::call_site(),
+                )
+            })
+            .collect();
+        let wait = match deps.is_empty() {
+            true => quote! {let #ok = #started;},
+            false => quote! {
+                let #ok = match #(#deps &&)* true{
+                    true=>{
+                        let _ = #go.send(());
+                        #started
+                    }
+                    false=>false,
+                };
+            },
+        };
+        waits.push(wait);
+    }
+    stops.reverse();
+    let waits = order.iter().map(|&i| &waits[i]);
+    let (deadline, not_ready) = match args.ready_timeout {
+        Some(ms) => (
+            quote! {
+                let limit = std::time::Duration::from_millis(#ms);
+                let deadline = #time::Instant::now() + limit;
+            },
+            quote! {
+                // the actor's `on_start` was still running when `ready_timeout` ran out,
+                // for actors held back by `await_ready` that's the wait for their dependencies too
+                #[derive(Debug)]
+                pub struct NotReady {
+                    pub limit: std::time::Duration,
+                }
+
+                impl std::fmt::Display for NotReady {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        return write!(f, "not ready after {:?}", self.limit);
+                    }
+                }
+
+                impl std::error::Error for NotReady {}
+            },
+        ),
+        None => (quote! {}, quote! {}),
+    };
+    Ok(quote! {
+        #[derive(Clone)]
+        pub struct Actors {
+            #(#handles,)*
+        }
+
+        impl Actors {
+            pub async fn spawn_all(#(#params),*)->Result<Self, StartError>{
+                let (actors, ready) = Self::__start_all(#(#fields),*);
+                ready.await?;
+                return Ok(actors);
+            }
+
+            // `spawn_all` handing out the handles right away: msgs sent to an actor
+            // that isn't ready yet queue up. the task tells how starting went
+            pub fn start_all(#(#params),*)->(Self, #task::JoinHandle<Result<(), StartError>>){
+                let (actors, ready) = Self::__start_all(#(#fields),*);
+                return (actors, #spawn(ready));
+            }
+
+            fn __start_all(#(#params),*)->(Self, impl std::future::Future<Output = Result<(), StartError>> + Send + 'static){
+                #(#spawns)*
+                let actors = Self{#(#fields,)*};
+                let Self{#(#fields,)*} = actors.clone();
+                let ready = async move {
+                    #deadline
+                    let mut failed: Vec<(usize, StartError)> = vec![];
+                    #(#waits)*
+                    if let Some((_, e)) = failed.into_iter().min_by_key(|(index, _)| *index) {
+                        #(#stops)*
+                        return Err(e);
+                    }
+                    return Ok(());
+                };
+                return (actors, ready);
+            }
+        }
+
+        // the actor whose `on_start` failed, with what it failed with
+        #[derive(Debug)]
+        pub struct StartError {
+            pub actor: &'static str,
+            pub error: Box<dyn std::error::Error + Send + Sync>,
+        }
+
+        impl std::fmt::Display for StartError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                return write!(f, "{} failed to start: {}", self.actor, self.error);
+            }
+        }
+
+        impl std::error::Error for StartError {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                return Some(&*self.error);
+            }
+        }
+
+        #not_ready
+    })
+}
+
+// indices of `infos` with every actor after those it awaits, otherwise in
+// declaration order
+fn start_order(infos: &[&ActorInfo]) -> syn::Result<Vec<usize>> {
+    let index: HashMap<String, usize> = infos
+        .iter()
+        .enumerate()
+        .map(|(i, info)| (info.actor_ident.as_ref().unwrap().to_string(), i))
+        .collect();
+    let mut deps = vec![];
+    for info in infos {
+        let mut of_actor = vec![];
+        for dep in &info.actor_args.await_ready {
+            match index.get(&dep.to_string()) {
+                Some(&i) => of_actor.push((i, dep)),
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        dep,
+                        format!("`{}` isn't an actor of this registry", dep),
+                    ))
+                }
+            }
+        }
+        deps.push(of_actor);
+    }
+    // 0: not visited, 1: its dependencies being visited, 2: placed
+    let mut state = vec![0u8; infos.len()];
+    let mut order = vec![];
+    fn visit(
+        i: usize,
+        deps: &[Vec<(usize, &Ident)>],
+        state: &mut [u8],
+        order: &mut Vec<usize>,
+    ) -> syn::Result<()> {
+        state[i] = 1;
+        for &(dep, ident) in &deps[i] {
+            match state[dep] {
+                0 => visit(dep, deps, state, order)?,
+                1 => {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        format!(
+                            "`await_ready({})` closes a cycle, neither would ever start",
+                            ident
+                        ),
+                    ))
+                }
+                _ => {}
+            }
+        }
+        state[i] = 2;
+        order.push(i);
+        Ok(())
+    }
+    for i in 0..infos.len() {
+        if state[i] == 0 {
+            visit(i, &deps, &mut state, &mut order)?;
+        }
+    }
+    Ok(order)
+}
@@ -0,0 +1,86 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+use crate::args::ActorsArgs;
+use crate::ActorInfo;
+
+// `FooMsg::ROUTES`: one `RouteInfo` per variant of the msg enum, in declaration
+// order, for admin endpoints listing what an actor takes and for generating
+// clients. everything in it is known at expansion time, so it's a plain const.
+pub(crate) fn generate(info: &ActorInfo, args: &ActorsArgs) -> TokenStream {
+    let msg_ident = &info.msg_ident;
+    let (impl_generics, ty_generics, where_clause) = info.msg_generics.split_for_impl();
+    let routes = info.variants.iter().map(|v| {
+        let cfgs = &v.cfgs;
+        let variant = v.ident.to_string();
+        let fields = v.payload.iter().map(|(name, ty)| {
+            let ty = type_name(ty);
+            quote! {(#name, #ty)}
+        });
+        let resp = option(v.declared.as_ref().map(type_name));
+        let latest_key = option(v.latest.as_ref().map(|(key, _)| key.to_string()));
+        let timeout_ms = match v.timeout {
+            Some(ms) => quote! {Some(#ms)},
+            None => quote! {None},
+        };
+        // a `#[bounded(..)]` lane or a module bounded as a whole
+        let bounded = match v.bounded.as_ref().or(args.bounded.as_ref()) {
+            Some(capacity) => {
+                let capacity = capacity.base10_parse::<usize>().unwrap();
+                quote! {Some(#capacity)}
+            }
+            None => quote! {None},
+        };
+        let (stream, sink, deprecated) = (v.stream, v.sink, !v.deprecated.is_empty());
+        let internal = !matches!(v.vis, syn::Visibility::Public(_));
+        quote! {
+            #(#cfgs)*
+            RouteInfo {
+                variant: #variant,
+                fields: &[#(#fields),*],
+                resp: #resp,
+                stream: #stream,
+                sink: #sink,
+                timeout_ms: #timeout_ms,
+                bounded: #bounded,
+                latest_key: #latest_key,
+                deprecated: #deprecated,
+                internal: #internal,
+            }
+        }
+    });
+    quote! {
+        impl #impl_generics #msg_ident #ty_generics #where_clause {
+            pub const ROUTES: &'static [RouteInfo] = &[#(#routes),*];
+        }
+    }
+}
+
+fn option(value: Option<String>) -> TokenStream {
+    match value {
+        Some(v) => quote! {Some(#v)},
+        None => quote! {None},
+    }
+}
+
+// a type as written, without the spaces `to_string` puts between its tokens
+fn type_name(ty: &syn::Type) -> String {
+    let mut name = ty.to_token_stream().to_string();
+    for (spaced, tight) in [
+        (" <", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ::", "::"),
+        (":: ", "::"),
+        (" ,", ","),
+        (" ;", ";"),
+        ("& ", "&"),
+        ("( ", "("),
+        (" )", ")"),
+        ("[ ", "["),
+        (" ]", "]"),
+    ] {
+        name = name.replace(spaced, tight);
+    }
+    name
+}
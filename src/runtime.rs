@@ -0,0 +1,45 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+// the executor generated code is written against. tokio is the only one so far;
+// codegen takes every executor path from here, so another backend only has to
+// provide its own set with the same api.
+#[derive(Clone, Copy, Default)]
+pub(crate) enum Runtime {
+    #[default]
+    Tokio,
+}
+
+// the paths generated code uses, named after tokio's modules
+pub(crate) struct Paths {
+    pub(crate) mpsc: TokenStream,
+    pub(crate) oneshot: TokenStream,
+    pub(crate) broadcast: TokenStream,
+    // an async mutex, its guard held across awaits
+    pub(crate) mutex: TokenStream,
+    pub(crate) spawn: TokenStream,
+    pub(crate) task: TokenStream,
+    pub(crate) time: TokenStream,
+    // declares a value living as long as the task running a future
+    pub(crate) task_local: TokenStream,
+    // the executor itself, its handle and id
+    pub(crate) runtime: TokenStream,
+}
+
+impl Runtime {
+    pub(crate) fn paths(self) -> Paths {
+        match self {
+            Runtime::Tokio => Paths {
+                mpsc: quote! {::tokio::sync::mpsc},
+                oneshot: quote! {::tokio::sync::oneshot},
+                broadcast: quote! {::tokio::sync::broadcast},
+                mutex: quote! {::tokio::sync::Mutex},
+                spawn: quote! {::tokio::spawn},
+                task: quote! {::tokio::task},
+                time: quote! {::tokio::time},
+                task_local: quote! {::tokio::task_local},
+                runtime: quote! {::tokio::runtime},
+            },
+        }
+    }
+}
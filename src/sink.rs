@@ -0,0 +1,101 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::snapshot;
+use crate::ActorInfo;
+
+// the `sink` feature, bounded mailboxes: `throttled_sink()` is a
+// `futures_sink::Sink` of msgs whose `poll_ready` holds a reserved mailbox slot,
+// so `stream.forward(handle.throttled_sink())` stops pulling from the stream
+// while the actor is behind. `throttled_shard_sink` does the same over the
+// handles of `spawn_many`, keeping a slot in each.
+pub(crate) fn generate(info: &ActorInfo) -> TokenStream {
+    let ident = info.actor_ident.as_ref().unwrap();
+    let actor_ident = Ident::new(&format!("Actor{}", ident), Span::call_site());
+    let sink_ident = Ident::new(&format!("{}ThrottledSink", ident), Span::call_site());
+    let msg_ident = &info.msg_ident;
+    let validate = match info.variants.iter().any(|v| v.validate.is_some()) {
+        true => quote! {let msg = #actor_ident::__validate(msg)?;},
+        false => quote! {},
+    };
+    let envelope = snapshot::queued(
+        quote! {Envelope::new(msg)},
+        quote! {this.shards[index].shared},
+    );
+    quote! {
+        // tells msgs to one actor, or to the shard `shard_of` picks, once
+        // `poll_ready` got a mailbox slot for it
+        pub struct #sink_ident {
+            shards: Vec<#actor_ident>,
+            shard_of: Box<dyn Fn(&#msg_ident)->usize + Send>,
+            slots: Vec<ReservedSlot<#msg_ident>>,
+        }
+
+        impl ::futures_sink::Sink<#msg_ident> for #sink_ident {
+            type Error = ActorError;
+
+            // ready once every shard has a slot for the next msg, whichever it goes to
+            fn poll_ready(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>)->std::task::Poll<Result<(), ActorError>>{
+                let this = self.get_mut();
+                let mut ready = true;
+                for (shard, slot) in this.shards.iter().zip(this.slots.iter_mut()) {
+                    match slot.poll_reserve(&shard.sender, cx){
+                        std::task::Poll::Ready(Ok(()))=>{}
+                        std::task::Poll::Ready(Err(_e))=>{
+                            return std::task::Poll::Ready(Err(ActorError::MailboxClosed{reason: shard.shared.close_reason()}));
+                        }
+                        std::task::Poll::Pending=>ready = false,
+                    }
+                }
+                return match ready{
+                    true=>std::task::Poll::Ready(Ok(())),
+                    false=>std::task::Poll::Pending,
+                };
+            }
+
+            // the slot is given back when `#[validate]` turns the msg down
+            fn start_send(self: std::pin::Pin<&mut Self>, msg: #msg_ident)->Result<(), ActorError>{
+                let this = self.get_mut();
+                let index = (this.shard_of)(&msg) % this.shards.len();
+                this.shards[index].shared.accepting()?;
+                #validate
+                match std::mem::replace(&mut this.slots[index], ReservedSlot::Idle){
+                    ReservedSlot::Ready(permit)=>permit.send(#envelope),
+                    _=>panic!("`start_send` called before `poll_ready` was ready"),
+                };
+                return Ok(());
+            }
+
+            // msgs are in the mailbox as soon as `start_send` returned
+            fn poll_flush(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>)->std::task::Poll<Result<(), ActorError>>{
+                return std::task::Poll::Ready(Ok(()));
+            }
+
+            // gives back the reserved slots, the actors keep running
+            fn poll_close(self: std::pin::Pin<&mut Self>, _cx: &mut std::task::Context<'_>)->std::task::Poll<Result<(), ActorError>>{
+                let this = self.get_mut();
+                for slot in this.slots.iter_mut() {
+                    *slot = ReservedSlot::Idle;
+                }
+                return std::task::Poll::Ready(Ok(()));
+            }
+        }
+
+        impl #actor_ident {
+            // a `Sink` of msgs told to this actor, ready only while its mailbox has room
+            pub fn throttled_sink(&self)->#sink_ident{
+                return Self::throttled_shard_sink(vec![self.clone()], |_msg| 0);
+            }
+
+            // a `Sink` spreading msgs over `shards`, each going to the one at
+            // `shard_of(&msg) % shards.len()`. it waits for room in every shard, so one
+            // that is behind holds up the others
+            pub fn throttled_shard_sink(shards: Vec<Self>, shard_of: impl Fn(&#msg_ident)->usize + Send + 'static)->#sink_ident{
+                assert!(!shards.is_empty(), "a throttled sink needs at least one shard");
+                let slots = shards.iter().map(|_shard| ReservedSlot::Idle).collect();
+                return #sink_ident{shards, shard_of: Box::new(shard_of), slots};
+            }
+        }
+    }
+}
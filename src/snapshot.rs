@@ -0,0 +1,156 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::Ident;
+
+use crate::args::ActorsArgs;
+use crate::runtime::Paths;
+use crate::ActorInfo;
+
+// the `debug` feature: every msg sent through a handle notes its variant and
+// when it was sent in a ring of the last `QUEUED_KEPT` msgs on `ActorShared`,
+// taken out again once the msg leaves the mailbox. `queue_snapshot` copies the
+// ring, for `/debug/actors` style endpoints looking at a wedged actor. payloads
+// are never looked at, and without the feature none of this is generated.
+pub(crate) fn enabled() -> bool {
+    cfg!(feature = "debug")
+}
+
+// `envelope` noting itself in the queue snapshot of `shared`, an
+// `Arc<ActorShared>` or a reference to one
+pub(crate) fn queued(envelope: TokenStream, shared: TokenStream) -> TokenStream {
+    match enabled() {
+        true => quote! {#envelope.queued(&#shared)},
+        false => envelope,
+    }
+}
+
+// the runtime items: the envelope's extra field and how it starts out, the ring
+// on `ActorShared`, and the types behind them
+pub(crate) fn items(args: &ActorsArgs) -> (TokenStream, TokenStream, TokenStream, TokenStream) {
+    let Paths { time, .. } = args.runtime.paths();
+    if !enabled() {
+        return (quote! {}, quote! {}, quote! {}, quote! {});
+    }
+    (
+        quote! {, Option<QueuedMark>},
+        quote! {, None},
+        quote! {
+            // msgs sent but not taken out of the mailbox yet, as far as handles can tell
+            queued: std::sync::Mutex<QueuedRing>,
+        },
+        quote! {
+            // msgs noted in `queue_snapshot`, newer ones push out the oldest
+            const QUEUED_KEPT: usize = 64;
+
+            // a msg believed to still wait in a mailbox, see `queue_snapshot`
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub struct QueuedInfo {
+                // `Add`, without the enum's name
+                pub variant: &'static str,
+                // when a handle sent it
+                pub enqueued: #time::Instant,
+            }
+
+            impl QueuedInfo {
+                pub fn age(&self) -> std::time::Duration {
+                    return self.enqueued.elapsed();
+                }
+            }
+
+            #[derive(Default)]
+            struct QueuedRing {
+                next: u64,
+                // oldest first, by the sequence number of their mark
+                entries: std::collections::VecDeque<(u64, QueuedInfo)>,
+            }
+
+            // held by an envelope while it's in the mailbox, dropping it takes the
+            // msg out of the ring again, whether it was received, or the send failed
+            struct QueuedMark {
+                shared: std::sync::Arc<ActorShared>,
+                seq: u64,
+            }
+
+            impl QueuedMark {
+                fn new(shared: &std::sync::Arc<ActorShared>, variant: &'static str) -> Self {
+                    let mut queued = shared.queued.lock().unwrap();
+                    let seq = queued.next;
+                    queued.next += 1;
+                    if queued.entries.len() == QUEUED_KEPT {
+                        queued.entries.pop_front();
+                    }
+                    queued.entries.push_back((seq, QueuedInfo { variant, enqueued: #time::Instant::now() }));
+                    return Self { shared: shared.clone(), seq };
+                }
+            }
+
+            impl Drop for QueuedMark {
+                fn drop(&mut self) {
+                    if let Ok(mut queued) = self.shared.queued.lock() {
+                        if let Ok(index) = queued.entries.binary_search_by_key(&self.seq, |(seq, _)| *seq) {
+                            queued.entries.remove(index);
+                        }
+                    }
+                }
+            }
+
+            // the name of a msg's variant, implemented for every msg enum
+            trait VariantName {
+                fn variant_name(&self) -> &'static str;
+            }
+
+            impl<M: VariantName> Envelope<M> {
+                #[allow(dead_code)]
+                fn queued(mut self, shared: &std::sync::Arc<ActorShared>) -> Self {
+                    if let Letter::User { msg, .. } = &self.0 {
+                        self.1 = Some(QueuedMark::new(shared, VariantName::variant_name(msg)));
+                    }
+                    return self;
+                }
+            }
+
+            impl ActorShared {
+                fn queue_snapshot(&self) -> Vec<QueuedInfo> {
+                    return self.queued.lock().unwrap().entries.iter().map(|(_, info)| *info).collect();
+                }
+            }
+        },
+    )
+}
+
+// `VariantName` for the actor's msg enum and `queue_snapshot` on its handle
+pub(crate) fn generate(info: &ActorInfo) -> TokenStream {
+    let ident = info.actor_ident.as_ref().unwrap();
+    let actor_ident = Ident::new(&format!("Actor{}", ident), Span::call_site());
+    let msg_ident = &info.msg_ident;
+    let (impl_generics, ty_generics, where_clause) = info.msg_generics.split_for_impl();
+    let (actor_impl_generics, actor_ty_generics, actor_where_clause) =
+        info.actor_generics.split_for_impl();
+    let arms = info.variants.iter().map(|v| {
+        let cfgs = &v.cfgs;
+        let variant = &v.ident;
+        let name = variant.to_string();
+        quote! {
+            #(#cfgs)*
+            #msg_ident::#variant{..}=>#name,
+        }
+    });
+    quote! {
+        impl #impl_generics VariantName for #msg_ident #ty_generics #where_clause {
+            fn variant_name(&self)->&'static str{
+                return match self{
+                    #(#arms)*
+                };
+            }
+        }
+
+        impl #actor_impl_generics #actor_ident #actor_ty_generics #actor_where_clause{
+            // up to the last 64 msgs sent to the actor that are still in its mailbox,
+            // oldest first. best-effort: msgs sent at the same time may show up in
+            // another order than the actor takes them in
+            pub fn queue_snapshot(&self)->Vec<QueuedInfo>{
+                return self.shared.queue_snapshot();
+            }
+        }
+    }
+}
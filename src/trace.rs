@@ -0,0 +1,200 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Ident, Meta, NestedMeta, Variant};
+
+// what the `tracing` feature may record for one variant. nothing but the
+// variant name ends up in spans unless a field is opted in via `fields(..)`.
+#[derive(Default)]
+pub(crate) struct VariantTrace {
+    skip_all: bool,
+    fields: Vec<Ident>,
+    skipped: Vec<Ident>,
+    display: Vec<Ident>,
+    // `#[instrument(..)]`: fields recorded on the caller side request span
+    request: Option<Vec<Ident>>,
+    // `#[redact]` fields, recorded as `***` when opted in
+    pub(crate) redacted: Vec<Ident>,
+}
+
+impl VariantTrace {
+    fn recorded(&self) -> Vec<&Ident> {
+        if self.skip_all {
+            return vec![];
+        }
+        self.fields
+            .iter()
+            .filter(|f| !self.skipped.contains(f))
+            .collect()
+    }
+
+    // the fields to bind out of the msg, and the span recording them
+    fn build(
+        &self,
+        name: &str,
+        actor_name: &str,
+        id: TokenStream,
+        variant: &Ident,
+        recorded: &[&Ident],
+    ) -> (TokenStream, TokenStream) {
+        let variant_name = variant.to_string();
+        let values: Vec<TokenStream> = recorded
+            .iter()
+            .map(
+                |f| match (self.redacted.contains(f), self.display.contains(f)) {
+                    (true, _) => quote! {#f = "***"},
+                    (false, true) => quote! {#f = %#f},
+                    (false, false) => quote! {#f = ?#f},
+                },
+            )
+            .collect();
+        let bound = recorded.iter().filter(|f| !self.redacted.contains(f));
+        let bindings = quote! {#(#bound,)*};
+        let span = quote! {
+            ::tracing::info_span!(#name, actor = #actor_name, actor_id = %#id, actor_index = #id.index(), variant = #variant_name, #(#values),*)
+        };
+        (bindings, span)
+    }
+
+    pub(crate) fn span(&self, actor_name: &str, variant: &Ident) -> (TokenStream, TokenStream) {
+        self.build(
+            "process",
+            actor_name,
+            quote! {shared.id},
+            variant,
+            &self.recorded(),
+        )
+    }
+
+    // the span request methods run in, `None` unless the variant has `#[instrument]`
+    pub(crate) fn request_span(
+        &self,
+        actor_name: &str,
+        variant: &Ident,
+    ) -> Option<(TokenStream, TokenStream)> {
+        let recorded: Vec<&Ident> = self
+            .request
+            .as_ref()?
+            .iter()
+            .filter(|f| !self.skipped.contains(f))
+            .collect();
+        Some(self.build(
+            "request",
+            actor_name,
+            quote! {self.shared.id},
+            variant,
+            &recorded,
+        ))
+    }
+}
+
+fn field_names(list: &syn::MetaList) -> syn::Result<Vec<Ident>> {
+    let mut result = vec![];
+    for field in &list.nested {
+        match field {
+            NestedMeta::Meta(Meta::Path(p)) if p.get_ident().is_some() => {
+                result.push(p.get_ident().unwrap().clone())
+            }
+            _ => return Err(syn::Error::new_spanned(field, "expected a field name")),
+        }
+    }
+    Ok(result)
+}
+
+fn trace_args(attr: &Attribute) -> syn::Result<Vec<NestedMeta>> {
+    match attr.parse_meta()? {
+        Meta::List(list) => Ok(list.nested.into_iter().collect()),
+        Meta::Path(p) if p.is_ident("instrument") => Ok(vec![]),
+        other => Err(syn::Error::new_spanned(
+            other,
+            "expected `#[trace(..)]` with arguments",
+        )),
+    }
+}
+
+// reads and strips `#[trace(..)]` and `#[instrument(..)]` from a variant and
+// its fields, so the attributes never reach the compiler whether or not
+// `tracing` is enabled.
+pub(crate) fn take_variant_attrs(variant: &mut Variant) -> syn::Result<VariantTrace> {
+    let mut result = VariantTrace::default();
+    let mut attrs = vec![];
+    for attr in variant.attrs.drain(..) {
+        if attr.path.is_ident("instrument") {
+            let mut request = vec![];
+            for arg in trace_args(&attr)? {
+                match &arg {
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("fields") => {
+                        request.extend(field_names(list)?)
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &arg,
+                            "expected `fields(..)` in `#[instrument]`",
+                        ))
+                    }
+                }
+            }
+            result.request = Some(request);
+            continue;
+        }
+        if !attr.path.is_ident("trace") {
+            attrs.push(attr);
+            continue;
+        }
+        for arg in trace_args(&attr)? {
+            match &arg {
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("skip_all") => result.skip_all = true,
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("fields") => {
+                    result.fields.extend(field_names(list)?)
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &arg,
+                        "expected `skip_all` or `fields(..)` on a variant",
+                    ))
+                }
+            }
+        }
+    }
+    variant.attrs = attrs;
+    for field in variant.fields.iter_mut() {
+        let mut attrs = vec![];
+        for attr in field.attrs.drain(..) {
+            if !attr.path.is_ident("trace") {
+                attrs.push(attr);
+                continue;
+            }
+            let ident = match &field.ident {
+                Some(v) => v.clone(),
+                None => {
+                    return Err(syn::Error::new_spanned(
+                        &attr,
+                        "`#[trace]` needs a named field",
+                    ))
+                }
+            };
+            for arg in trace_args(&attr)? {
+                match &arg {
+                    NestedMeta::Meta(Meta::Path(p)) if p.is_ident("skip") => {
+                        result.skipped.push(ident.clone())
+                    }
+                    NestedMeta::Meta(Meta::Path(p)) if p.is_ident("display") => {
+                        result.display.push(ident.clone())
+                    }
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            &arg,
+                            "expected `skip` or `display` on a field",
+                        ))
+                    }
+                }
+            }
+        }
+        field.attrs = attrs;
+    }
+    for f in result.fields.iter().chain(result.request.iter().flatten()) {
+        if !variant.fields.iter().any(|v| v.ident.as_ref() == Some(f)) {
+            return Err(syn::Error::new_spanned(f, "no such field on this variant"));
+        }
+    }
+    Ok(result)
+}
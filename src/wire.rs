@@ -0,0 +1,181 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Field, Fields, Ident, ItemEnum, Path, Token, Type};
+
+use crate::redact::Redacted;
+
+// `#[wire(derive(..))]` on a msg enum: a plain copy of the enum without the
+// `resp` fields, for comparing and collecting msgs. no derive is implied, pick
+// the ones your fields allow, e.g. `derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)`.
+// handles of actors in the same module are left out as well, they carry no
+// payload and derive nothing; `#[wire(skip)]` does the same for any other field,
+// and `#[redact]` fields are left out unless marked `#[redact(keep)]`.
+pub(crate) struct Wire {
+    derives: Vec<Path>,
+    // `(variant, field index)` marked `#[wire(skip)]`
+    skipped: Vec<(Ident, usize)>,
+}
+
+impl Wire {
+    // reads and strips `#[wire(..)]` from the msg enum and its fields
+    pub(crate) fn take(item: &mut ItemEnum, redacted: &Redacted) -> syn::Result<Option<Self>> {
+        let mut skipped: Vec<(Ident, usize)> = redacted.wire_skipped().collect();
+        for v in &mut item.variants {
+            for (i, field) in v.fields.iter_mut().enumerate() {
+                let before = field.attrs.len();
+                for attr in field.attrs.iter().filter(|a| a.path.is_ident("wire")) {
+                    let arg: Ident = attr.parse_args()?;
+                    if arg != "skip" {
+                        return Err(syn::Error::new_spanned(&arg, "expected `#[wire(skip)]`"));
+                    }
+                }
+                field.attrs.retain(|a| !a.path.is_ident("wire"));
+                if field.attrs.len() != before {
+                    skipped.push((v.ident.clone(), i));
+                }
+            }
+        }
+        let pos = match item.attrs.iter().position(|a| a.path.is_ident("wire")) {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let attr = item.attrs.remove(pos);
+        let mut derives = vec![];
+        if !attr.tokens.is_empty() {
+            attr.parse_args_with(|input: syn::parse::ParseStream| {
+                let key: Ident = input.parse()?;
+                if key != "derive" {
+                    return Err(syn::Error::new_spanned(&key, "expected `derive(..)`"));
+                }
+                let list;
+                syn::parenthesized!(list in input);
+                derives.extend(list.parse_terminated::<Path, Token![,]>(Path::parse_mod_style)?);
+                Ok(())
+            })?;
+        }
+        Ok(Some(Self { derives, skipped }))
+    }
+
+    fn kept(&self, variant: &Ident, i: usize, field: &Field, handles: &[Ident]) -> bool {
+        if field.ident.as_ref().is_some_and(|f| f == "resp") {
+            return false;
+        }
+        if self.skipped.iter().any(|(v, f)| v == variant && *f == i) {
+            return false;
+        }
+        match &field.ty {
+            Type::Path(p) => !handles.contains(&p.path.segments.last().unwrap().ident),
+            _ => true,
+        }
+    }
+
+    // `XXXWire` plus a `From<XXXMsg>` dropping what is left out. `item` is the
+    // msg enum after processing, so only user attributes are left on it. the
+    // conversions need the msg enum and go under `gate`, the copy itself doesn't.
+    pub(crate) fn generate(
+        &self,
+        item: &ItemEnum,
+        handles: &[Ident],
+        gate: &TokenStream,
+    ) -> TokenStream {
+        let msg_ident = &item.ident;
+        let wire_ident = Ident::new(&format!("{}Wire", msg_ident), Span::call_site());
+        let vis = &item.vis;
+        let derives = &self.derives;
+        let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+        let generics = &item.generics;
+        let mut variants = vec![];
+        let mut arms = vec![];
+        // back to a live msg with `resp: None`, only possible when nothing else was left out
+        let mut back = vec![];
+        let mut lossless = true;
+        for v in &item.variants {
+            let ident = &v.ident;
+            let attrs = &v.attrs;
+            let cfgs: Vec<_> = v.attrs.iter().filter(|a| a.path.is_ident("cfg")).collect();
+            match &v.fields {
+                Fields::Named(fields) => {
+                    let kept: Vec<_> = fields
+                        .named
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, f)| self.kept(ident, *i, f, handles))
+                        .map(|(_, f)| f)
+                        .collect();
+                    let names: Vec<_> = kept.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+                    let resp = fields
+                        .named
+                        .iter()
+                        .any(|f| f.ident.as_ref().is_some_and(|f| f == "resp"))
+                        .then(|| quote! {resp: None,});
+                    lossless &= names.len() + resp.iter().count() == fields.named.len();
+                    variants.push(quote! {#(#attrs)* #ident { #(#kept,)* }});
+                    arms.push(quote! {
+                        #(#cfgs)* #msg_ident::#ident { #(#names,)* .. } => #wire_ident::#ident { #(#names,)* },
+                    });
+                    back.push(quote! {
+                        #(#cfgs)* #wire_ident::#ident { #(#names,)* } => #msg_ident::#ident { #(#names,)* #resp },
+                    });
+                }
+                Fields::Unnamed(fields) => {
+                    let mut kept = vec![];
+                    let mut bindings = vec![];
+                    let mut names = vec![];
+                    for (i, f) in fields.unnamed.iter().enumerate() {
+                        if self.kept(ident, i, f, handles) {
+                            let name = Ident::new(&format!("f{}", i), Span::call_site());
+                            kept.push(f);
+                            bindings.push(quote! {#name});
+                            names.push(name);
+                        } else {
+                            bindings.push(quote! {_});
+                        }
+                    }
+                    lossless &= names.len() == fields.unnamed.len();
+                    variants.push(quote! {#(#attrs)* #ident ( #(#kept,)* )});
+                    arms.push(quote! {
+                        #(#cfgs)* #msg_ident::#ident ( #(#bindings,)* ) => #wire_ident::#ident ( #(#names,)* ),
+                    });
+                    back.push(quote! {
+                        #(#cfgs)* #wire_ident::#ident ( #(#names,)* ) => #msg_ident::#ident ( #(#names,)* ),
+                    });
+                }
+                Fields::Unit => {
+                    variants.push(quote! {#(#attrs)* #ident});
+                    arms.push(quote! {#(#cfgs)* #msg_ident::#ident => #wire_ident::#ident,});
+                    back.push(quote! {#(#cfgs)* #wire_ident::#ident => #msg_ident::#ident,});
+                }
+            }
+        }
+        // replayed msgs have no caller waiting, so requests come back with `resp: None`
+        let replay = lossless.then(|| {
+            quote! {
+                #gate
+                impl #impl_generics From<#wire_ident #ty_generics> for #msg_ident #ty_generics #where_clause {
+                    fn from(wire: #wire_ident #ty_generics) -> Self {
+                        return match wire {
+                            #(#back)*
+                        };
+                    }
+                }
+            }
+        });
+        quote! {
+            #[derive(#(#derives),*)]
+            #vis enum #wire_ident #generics #where_clause {
+                #(#variants,)*
+            }
+
+            #gate
+            impl #impl_generics From<#msg_ident #ty_generics> for #wire_ident #ty_generics #where_clause {
+                fn from(msg: #msg_ident #ty_generics) -> Self {
+                    return match msg {
+                        #(#arms)*
+                    };
+                }
+            }
+
+            #replay
+        }
+    }
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use tokio_actor::actors;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[actors]
+mod directory {
+    pub enum DirectoryMsg {
+        Lookup { resp: u64 },
+        Forget { resp: bool },
+    }
+
+    #[derive(Default)]
+    pub struct Directory {}
+
+    impl Directory {
+        async fn process(&mut self, msg: DirectoryMsg) {
+            match msg {
+                // forgets to answer
+                DirectoryMsg::Lookup { .. } => {}
+                DirectoryMsg::Forget { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(true);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use directory::{ActorDirectory, ActorError, Directory, DirectoryMsg};
+
+// the fields of every event, one `name=value` line each
+#[derive(Clone, Default)]
+struct Events(Arc<Mutex<Vec<String>>>);
+
+struct Line(String);
+
+impl Visit for Line {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.push_str(&format!("{}={:?} ", field.name(), value));
+    }
+}
+
+impl Subscriber for Events {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    fn event(&self, event: &Event<'_>) {
+        let mut line = Line(String::new());
+        event.record(&mut line);
+        self.0.lock().unwrap().push(line.0);
+    }
+    fn enter(&self, _span: &Id) {}
+    fn exit(&self, _span: &Id) {}
+}
+
+fn lookup() -> DirectoryMsg {
+    DirectoryMsg::Lookup { resp: None }
+}
+
+#[tokio::test]
+async fn an_unanswered_ask_warns_with_the_callers_location() {
+    let events = Events::default();
+    let _guard = tracing::subscriber::set_default(events.clone());
+    let (mut handle, run) = ActorDirectory::start(Directory::default());
+    let (ask, line) = (handle.lookup(lookup()), line!());
+    let (answer, _state) = tokio::join!(ask, async move {
+        drop(handle);
+        run.await
+    });
+    assert_eq!(answer, Err(ActorError::ResponseDropped));
+    let events = events.0.lock().unwrap();
+    let caller = format!("caller={}:{}:", file!(), line);
+    let warning = events
+        .iter()
+        .find(|e| e.contains("response dropped without an answer"));
+    assert!(
+        warning
+            .is_some_and(|w| w.contains(&caller) && w.contains("variant=\"DirectoryMsg::Lookup\"")),
+        "{:?}",
+        events
+    );
+}
+
+#[tokio::test]
+async fn answered_asks_stay_quiet() {
+    let events = Events::default();
+    let _guard = tracing::subscriber::set_default(events.clone());
+    let (mut handle, run) = ActorDirectory::start(Directory::default());
+    let ask = handle.forget(DirectoryMsg::Forget { resp: None });
+    let (answer, _state) = tokio::join!(ask, async move {
+        drop(handle);
+        run.await
+    });
+    assert_eq!(answer, Ok(true));
+    assert!(!events
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|e| e.contains("response dropped")),);
+}
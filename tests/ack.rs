@@ -0,0 +1,75 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod store {
+    pub enum StoreMsg {
+        Put { key: String, resp: () },
+        // answers without ever acking
+        Forget { resp: () },
+        // acks through the oneshot style `send(())`
+        Flush { resp: () },
+        Keys { resp: Vec<String> },
+    }
+
+    pub struct Store {
+        pub keys: Vec<String>,
+    }
+
+    impl Store {
+        async fn process(&mut self, msg: StoreMsg) {
+            match msg {
+                StoreMsg::Put { key, resp } => {
+                    self.keys.push(key);
+                    if let Some(ack) = resp {
+                        ack.ack();
+                    }
+                }
+                StoreMsg::Forget { resp } => drop(resp),
+                StoreMsg::Flush { resp } => {
+                    if let Some(ack) = resp {
+                        let _ = ack.send(());
+                    }
+                }
+                StoreMsg::Keys { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.keys.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use store::prelude::*;
+use store::Store;
+
+#[tokio::test(start_paused = true)]
+async fn the_caller_waits_for_the_ack() {
+    let mut store = ActorStore::spawn(Store { keys: vec![] });
+    let put = StoreMsg::Put {
+        key: "a".into(),
+        resp: None,
+    };
+    assert_eq!(within(store.put(put)).await, Ok(()));
+    assert_eq!(
+        within(store.flush(StoreMsg::Flush { resp: None })).await,
+        Ok(())
+    );
+    let keys = within(store.keys(StoreMsg::Keys { resp: None })).await;
+    assert_eq!(keys, Ok(vec!["a".to_string()]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_forgotten_ack_fails_the_caller() {
+    let mut store = ActorStore::spawn(Store { keys: vec![] });
+    let forgot = within(store.forget(StoreMsg::Forget { resp: None })).await;
+    assert!(matches!(forgot, Err(ActorError::ResponseDropped)));
+    // the actor itself carries on
+    assert_eq!(
+        within(store.flush(StoreMsg::Flush { resp: None })).await,
+        Ok(())
+    );
+}
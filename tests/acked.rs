@@ -0,0 +1,89 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::sync::watch;
+use tokio_actor::actors;
+
+#[actors]
+mod handoff {
+    use tokio::sync::watch;
+
+    pub enum JobMsg {
+        // waits for the gate, then stops the actor
+        Last { resp: () },
+        Job { n: u32, resp: usize },
+        Done { resp: Vec<u32> },
+    }
+
+    pub struct Job {
+        pub gate: watch::Receiver<bool>,
+        pub done: Vec<u32>,
+    }
+
+    impl Job {
+        async fn process(&mut self, msg: JobMsg, ctx: &ActorJobCtx) {
+            match msg {
+                JobMsg::Last { .. } => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                    ctx.stop();
+                }
+                JobMsg::Job { n, resp } => {
+                    self.done.push(n);
+                    if let Some(r) = resp {
+                        let _ = r.send(self.done.len());
+                    }
+                }
+                JobMsg::Done { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.done.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use handoff::prelude::*;
+use handoff::Job;
+
+fn spawn() -> (ActorJob, watch::Sender<bool>) {
+    let (open, gate) = watch::channel(false);
+    let job = ActorJob::spawn(Job { gate, done: vec![] });
+    (job, open)
+}
+
+#[tokio::test(start_paused = true)]
+async fn acked_once_the_actor_takes_the_msg() {
+    let (mut job, _open) = spawn();
+    assert!(within(job.job_acked(JobMsg::Job { n: 1, resp: None }))
+        .await
+        .is_ok());
+    let done = within(job.done(JobMsg::Done { resp: None })).await;
+    assert_eq!(done, Ok(vec![1]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn msgs_dropped_in_the_mailbox_are_never_acked() {
+    let (job, open) = spawn();
+    // taken out of the mailbox, so acked while `process` still waits on it
+    assert!(within(job.last_acked(JobMsg::Last { resp: None }))
+        .await
+        .is_ok());
+
+    // both queued behind it, only the acked one tells that it never started
+    job.send_into(JobMsg::Job { n: 1, resp: None })
+        .await
+        .unwrap();
+    let acked = tokio::spawn({
+        let job = job.clone();
+        async move { job.job_acked(JobMsg::Job { n: 2, resp: None }).await }
+    });
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!acked.is_finished());
+
+    open.send(true).unwrap();
+    let acked = within(acked).await.unwrap();
+    assert!(matches!(acked, Err(ActorError::MailboxClosed { .. })));
+}
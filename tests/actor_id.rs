@@ -0,0 +1,86 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::within;
+use tokio_actor::actors;
+
+// the same actor in two modules, each numbering its own ids
+#[actors]
+mod left {
+    pub enum ProbeMsg {
+        Whoami { resp: ActorId },
+    }
+
+    pub struct Probe {}
+
+    impl Probe {
+        async fn process(&mut self, msg: ProbeMsg, ctx: &ActorProbeCtx) {
+            match msg {
+                ProbeMsg::Whoami { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(ctx.actor_id());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[actors]
+mod right {
+    pub enum ProbeMsg {
+        Whoami { resp: ActorId },
+    }
+
+    pub struct Probe {}
+
+    impl Probe {
+        async fn process(&mut self, msg: ProbeMsg, ctx: &ActorProbeCtx) {
+            match msg {
+                ProbeMsg::Whoami { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(ctx.actor_id());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn ids_of_two_modules_never_collide() {
+    // the only actors of either module in this binary, both numbered 1
+    let l = left::ActorProbe::spawn(left::Probe {});
+    let mut r = right::ActorProbe::spawn(right::Probe {});
+    assert_eq!((l.id().value(), r.id().value()), (1, 1));
+    assert_ne!(l.id().to_string(), r.id().to_string());
+    assert_eq!(l.id().module(), "actor_id::left");
+    assert_eq!(l.id().to_string(), "actor_id::left::Probe#1");
+    assert_eq!(r.id().to_string(), "actor_id::right::Probe#1");
+
+    // clones and the actor's own ctx agree on it
+    let mut clone = l.clone();
+    assert_eq!(clone.id(), l.id());
+    assert_eq!(
+        within(clone.whoami(left::ProbeMsg::Whoami { resp: None })).await,
+        Ok(l.id())
+    );
+    assert_eq!(
+        within(r.whoami(right::ProbeMsg::Whoami { resp: None })).await,
+        Ok(r.id())
+    );
+
+    // the value alone repeats, the module next to it tells them apart
+    let pairs: HashSet<_> = [
+        (l.id().module(), l.id().value()),
+        (r.id().module(), r.id().value()),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(pairs.len(), 2);
+    let ids: HashSet<_> = [l.id(), clone.id()].into_iter().collect();
+    assert_eq!(ids.len(), 1);
+    let other = left::ActorProbe::spawn(left::Probe {});
+    assert!(l.id() < other.id());
+}
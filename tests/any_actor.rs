@@ -0,0 +1,112 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod zoo {
+    pub enum CounterMsg {
+        Add(u64),
+        Get { resp: u64 },
+    }
+
+    pub struct Counter {
+        pub count: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add(n) => self.count += n,
+                CounterMsg::Get { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.count);
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum NamesMsg {
+        Add(String),
+        Len { resp: usize },
+    }
+
+    pub struct Names {
+        pub names: Vec<String>,
+    }
+
+    impl Names {
+        async fn process(&mut self, msg: NamesMsg) {
+            match msg {
+                NamesMsg::Add(name) => self.names.push(name),
+                NamesMsg::Len { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.names.len());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use zoo::prelude::*;
+use zoo::{Counter, Names};
+
+#[tokio::test(start_paused = true)]
+async fn stops_a_mixed_collection_through_the_trait() {
+    let mut loops = vec![];
+    let mut all: Vec<Box<dyn AnyActor>> = vec![];
+    for _ in 0..3 {
+        let (counter, run) = ActorCounter::start(Counter { count: 0 });
+        loops.push(tokio::spawn(async move {
+            let _state = run.await;
+        }));
+        counter.send_into(CounterMsg::Add(1)).await.unwrap();
+        all.push(Box::new(counter));
+        let (names, run) = ActorNames::start(Names { names: vec![] });
+        loops.push(tokio::spawn(async move {
+            let _state = run.await;
+        }));
+        names.send_into(NamesMsg::Add("ann".into())).await.unwrap();
+        all.push(Box::new(names));
+    }
+    let mut ids: Vec<_> = all.iter().map(|actor| actor.id().to_string()).collect();
+    ids.dedup();
+    assert_eq!(ids.len(), 6);
+    for actor in &all {
+        let ping = within(actor.ping()).await.unwrap();
+        assert!(ping < Duration::from_secs(1));
+        assert!(!actor.is_closed());
+    }
+
+    for actor in &all {
+        actor.stop();
+    }
+    assert!(all.iter().all(|actor| actor.is_closed()));
+    // every run loop exits although the handles are still around
+    for run in loops {
+        within(run).await.unwrap();
+    }
+    let ping = within(all[0].ping()).await;
+    assert!(matches!(ping, Err(ActorError::MailboxClosed { .. })));
+}
+
+#[tokio::test(start_paused = true)]
+async fn typed_asks_stay_on_the_concrete_handles() {
+    let mut counter = ActorCounter::spawn(Counter { count: 0 });
+    let mut names = ActorNames::spawn(Names { names: vec![] });
+    counter.send_into(CounterMsg::Add(2)).await.unwrap();
+    names.send_into(NamesMsg::Add("bob".into())).await.unwrap();
+    let any: [&dyn AnyActor; 2] = [&counter, &names];
+    for actor in any {
+        within(actor.ping()).await.unwrap();
+    }
+    assert_eq!(
+        within(counter.get(CounterMsg::Get { resp: None })).await,
+        Ok(2)
+    );
+    assert_eq!(within(names.len(NamesMsg::Len { resp: None })).await, Ok(1));
+}
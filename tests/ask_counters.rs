@@ -0,0 +1,88 @@
+#![cfg(all(feature = "metrics", feature = "test-util"))]
+
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod slow {
+    pub enum ReaderMsg {
+        #[timeout(ms = 50)]
+        Read { ms: u64, resp: u64 },
+    }
+
+    pub struct Reader {}
+
+    impl Reader {
+        async fn process(&mut self, msg: ReaderMsg) {
+            let ReaderMsg::Read { ms, resp } = msg;
+            tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+            if let Some(r) = resp {
+                let _ = r.send(ms);
+            }
+        }
+    }
+
+    pub enum WriterMsg {
+        #[timeout(ms = 50)]
+        Write { ms: u64, resp: u64 },
+    }
+
+    pub struct Writer {}
+
+    impl Writer {
+        async fn process(&mut self, msg: WriterMsg) {
+            let WriterMsg::Write { ms, resp } = msg;
+            tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+            if let Some(r) = resp {
+                let _ = r.send(ms);
+            }
+        }
+    }
+}
+
+use slow::{
+    ActorError, ActorReader, ActorWriter, AskCounters, Reader, ReaderMsg, Writer, WriterMsg,
+};
+
+#[tokio::test(start_paused = true)]
+async fn counts_by_actor_and_variant_until_reset() {
+    let mut reader = ActorReader::spawn(Reader {});
+    let mut writer = ActorWriter::spawn(Writer {});
+    for ms in [100, 100, 10] {
+        let read = within(reader.read(ReaderMsg::Read { ms, resp: None })).await;
+        assert_eq!(read.is_err(), ms > 50);
+        // done with the one that timed out before the next ask
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let write = within(writer.write(WriterMsg::Write {
+        ms: 100,
+        resp: None,
+    }))
+    .await;
+    assert!(matches!(write, Err(ActorError::ResponseTimeout { .. })));
+    // the late reply lands on the task left waiting for it
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let counted = AskCounters {
+        timeouts: 2,
+        late_replies: 2,
+    };
+    let once = AskCounters {
+        timeouts: 1,
+        late_replies: 1,
+    };
+    assert_eq!(
+        slow::ask_counters(),
+        [
+            ("Reader", "ReaderMsg::Read", counted),
+            ("Writer", "WriterMsg::Write", once),
+        ]
+    );
+
+    slow::reset_ask_counters();
+    assert!(slow::ask_counters().is_empty());
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+use tokio_stream::StreamExt;
+
+#[actors]
+mod parsing {
+    use std::time::Duration;
+
+    pub enum ParserMsg {
+        Parse { line: String, resp: usize },
+    }
+
+    #[actor(pool, shared_queue)]
+    pub struct Parser {
+        pub seed: u64,
+    }
+
+    impl Parser {
+        async fn process(&mut self, msg: ParserMsg) {
+            match msg {
+                ParserMsg::Parse { line, resp } => {
+                    // a different delay for every msg, so the workers answer out of order
+                    self.seed = self
+                        .seed
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    tokio::time::sleep(Duration::from_millis(self.seed >> 60)).await;
+                    if let Some(r) = resp {
+                        let _ = r.send(line.parse().unwrap_or(usize::MAX));
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum CounterMsg {
+        Next { resp: usize },
+    }
+
+    pub struct Counter {
+        pub n: usize,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Next { resp } => {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    self.n += 1;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.n);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use parsing::{ActorCounter, ActorParser, Counter, CounterNextRequest, Parser, ParserParseRequest};
+
+fn lines(n: usize) -> impl tokio_stream::Stream<Item = ParserParseRequest> {
+    tokio_stream::iter((0..n).map(|i| ParserParseRequest {
+        line: i.to_string(),
+    }))
+}
+
+#[tokio::test(start_paused = true)]
+async fn replies_come_in_request_order() {
+    let parser = ActorParser::spawn_pool_many(4, |i| Parser { seed: i as u64 });
+    let replies: Vec<_> = within(parser.ask_stream_parse(lines(100), 8).collect()).await;
+    let expected: Vec<_> = (0..100).map(Ok).collect();
+    assert_eq!(replies, expected);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_full_window_pauses_the_input() {
+    let counter = ActorCounter::spawn(Counter { n: 0 });
+    let pulled = Arc::new(AtomicUsize::new(0));
+    let reqs = {
+        let pulled = pulled.clone();
+        tokio_stream::iter(0..100).map(move |_i| {
+            pulled.fetch_add(1, Ordering::SeqCst);
+            CounterNextRequest {}
+        })
+    };
+    let mut replies = std::pin::pin!(counter.ask_stream_next(reqs, 3));
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(pulled.load(Ordering::SeqCst), 3);
+    assert_eq!(within(replies.next()).await, Some(Ok(1)));
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(pulled.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test(start_paused = true)]
+async fn requests_are_pipelined() {
+    let counter = ActorCounter::spawn(Counter { n: 0 });
+    let start = tokio::time::Instant::now();
+    let reqs = tokio_stream::iter((0..10).map(|_i| CounterNextRequest {}));
+    let replies: Vec<_> = within(counter.ask_stream_next(reqs, 10).collect()).await;
+    assert_eq!(replies, (1..=10).map(Ok).collect::<Vec<_>>());
+    // back to back on the actor, no round trip in between
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use std::thread;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod feed {
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum FeedEvent {
+        Price(u64),
+        Heartbeat,
+        Closed,
+    }
+
+    pub enum FeedMsg {
+        Price(u64),
+        Beat,
+        Seen { resp: usize },
+    }
+
+    pub struct Feed {
+        #[events]
+        pub events: Emitter<FeedEvent>,
+        pub seen: usize,
+    }
+
+    impl Feed {
+        async fn process(&mut self, msg: FeedMsg) {
+            self.seen += 1;
+            match msg {
+                FeedMsg::Price(p) => {
+                    self.events.emit(FeedEvent::Price(p));
+                }
+                FeedMsg::Beat => {
+                    self.events.emit(FeedEvent::Heartbeat);
+                }
+                FeedMsg::Seen { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.seen);
+                    }
+                }
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            self.events.emit(FeedEvent::Closed);
+        }
+    }
+}
+
+use feed::prelude::*;
+use feed::{Feed, FeedEvent};
+
+fn spawn() -> ActorFeed {
+    ActorFeed::spawn(Feed {
+        events: Emitter::new(1024),
+        seen: 0,
+    })
+}
+
+// everything but heartbeats, the way a GUI loop would want them
+fn prices(e: &FeedEvent) -> Option<FeedEvent> {
+    match e {
+        FeedEvent::Heartbeat => None,
+        other => Some(other.clone()),
+    }
+}
+
+async fn feed_and_stop(mut feed: ActorFeed) {
+    for p in 0..500 {
+        feed.send_into(FeedMsg::Price(p)).await.unwrap();
+        feed.send_into(FeedMsg::Beat).await.unwrap();
+    }
+    let seen = within(feed.seen(FeedMsg::Seen { resp: None })).await;
+    assert_eq!(seen, Ok(1001));
+    drop(feed);
+}
+
+fn expected() -> Vec<FeedEvent> {
+    let mut events: Vec<_> = (0..500).map(FeedEvent::Price).collect();
+    events.push(FeedEvent::Closed);
+    events
+}
+
+#[tokio::test]
+async fn a_plain_thread_gets_every_event_up_to_the_stop() {
+    let feed = spawn();
+    let bridged = feed.bridge_events(prices, None);
+    let gui = thread::spawn(move || bridged.iter().collect::<Vec<_>>());
+    feed_and_stop(feed).await;
+    // ends once the actor is gone
+    let got = within(tokio::task::spawn_blocking(|| gui.join().unwrap())).await;
+    assert_eq!(got.unwrap(), expected());
+}
+
+#[tokio::test]
+async fn a_bounded_bridge_waits_for_a_slow_thread() {
+    let feed = spawn();
+    let bridged = feed.bridge_events(prices, Some(1));
+    let gui = thread::spawn(move || {
+        let mut got = vec![];
+        for event in bridged {
+            thread::yield_now();
+            got.push(event);
+        }
+        got
+    });
+    feed_and_stop(feed).await;
+    let got = within(tokio::task::spawn_blocking(|| gui.join().unwrap())).await;
+    assert_eq!(got.unwrap(), expected());
+}
+
+#[tokio::test]
+async fn stops_when_the_receiver_is_dropped() {
+    let mut feed = spawn();
+    drop(feed.bridge_events(prices, Some(1)));
+    feed.send_into(FeedMsg::Price(1)).await.unwrap();
+    let seen = within(feed.seen(FeedMsg::Seen { resp: None })).await;
+    assert_eq!(seen, Ok(2));
+}
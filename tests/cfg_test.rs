@@ -0,0 +1,67 @@
+// throwaway actors declared inside `#[cfg(test)]` modules, the way unit tests
+// next to the code would. everything generated falls under the same cfg
+
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use super::common::within;
+    use tokio_actor::actors;
+
+    #[actors]
+    mod fixture {
+        pub enum EchoMsg {
+            Echo { text: String, resp: String },
+        }
+
+        pub struct Echo {}
+
+        impl Echo {
+            async fn process(&mut self, msg: EchoMsg) {
+                let EchoMsg::Echo { text, resp } = msg;
+                if let Some(r) = resp {
+                    let _ = r.send(text);
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    #[actors(output_mod = handles)]
+    mod placed {
+        pub enum DoubleMsg {
+            Double { n: u64, resp: u64 },
+        }
+
+        pub struct Double {}
+
+        impl Double {
+            async fn process(&mut self, msg: DoubleMsg) {
+                let DoubleMsg::Double { n, resp } = msg;
+                if let Some(r) = resp {
+                    let _ = r.send(n * 2);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn a_fixture_in_a_test_module() {
+        use fixture::prelude::*;
+        let mut echo = ActorEcho::spawn(fixture::Echo {});
+        let msg = EchoMsg::Echo {
+            text: "hi".into(),
+            resp: None,
+        };
+        assert_eq!(within(echo.echo(msg)).await, Ok("hi".to_string()));
+    }
+
+    #[tokio::test]
+    async fn placed_items_and_the_prelude_follow_the_cfg() {
+        use placed::prelude::*;
+        let mut double = ActorDouble::spawn(placed::Double {});
+        let msg = DoubleMsg::Double { n: 21, resp: None };
+        assert_eq!(within(double.double(msg)).await, Ok(42));
+        let _same: placed::handles::ActorDouble = double;
+    }
+}
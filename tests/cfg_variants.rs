@@ -0,0 +1,81 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+// built with and without `test-util`, the gated variant and its methods come and
+// go together. `cfg(any())` is never set, that variant never exists
+#[actors]
+mod gated {
+    pub enum CounterMsg {
+        Add {
+            n: u64,
+            resp: u64,
+        },
+        #[cfg(feature = "test-util")]
+        Reset {
+            resp: u64,
+        },
+        #[cfg(any())]
+        Never {
+            resp: u64,
+        },
+        #[cfg(not(feature = "test-util"))]
+        Double {
+            resp: u64,
+        },
+    }
+
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            let (total, resp) = match msg {
+                CounterMsg::Add { n, resp } => (self.total + n, resp),
+                #[cfg(feature = "test-util")]
+                CounterMsg::Reset { resp } => (0, resp),
+                #[cfg(not(feature = "test-util"))]
+                CounterMsg::Double { resp } => (self.total * 2, resp),
+            };
+            self.total = total;
+            if let Some(r) = resp {
+                let _ = r.send(total);
+            }
+        }
+    }
+}
+
+use gated::{ActorCounter, Counter, CounterMsg};
+
+#[tokio::test]
+async fn ungated_variants_work_either_way() {
+    let mut counter = ActorCounter::spawn(Counter { total: 0 });
+    let total = within(counter.add(CounterMsg::Add { n: 2, resp: None })).await;
+    assert_eq!(total, Ok(2));
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn gated_variant_is_there_with_the_feature() {
+    let mut counter = ActorCounter::spawn(Counter { total: 5 });
+    assert_eq!(
+        within(counter.reset(CounterMsg::Reset { resp: None })).await,
+        Ok(0)
+    );
+    counter
+        .reset_no_wait(CounterMsg::Reset { resp: None })
+        .await
+        .unwrap();
+}
+
+#[cfg(not(feature = "test-util"))]
+#[tokio::test]
+async fn other_variant_is_there_without_the_feature() {
+    let mut counter = ActorCounter::spawn(Counter { total: 5 });
+    assert_eq!(
+        within(counter.double(CounterMsg::Double { resp: None })).await,
+        Ok(10)
+    );
+}
@@ -0,0 +1,94 @@
+// shared by every integration test through `mod common;`. the actors under test
+// live in `examples/` and are pulled in with `#[path]`, so each example stays a
+// runnable program and is exercised by the tests at the same time.
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// long enough for anything these tests wait on, short enough that a hang fails fast
+pub const PATIENCE: Duration = Duration::from_secs(5);
+
+// awaits `fut`, failing the test instead of hanging it
+pub async fn within<F: Future>(fut: F) -> F::Output {
+    match tokio::time::timeout(PATIENCE, fut).await {
+        Ok(v) => v,
+        Err(_e) => panic!("still waiting after {:?}", PATIENCE),
+    }
+}
+
+// stops an actor through its handle's `stop_within`, what's queued is still
+// processed. returns the number of msgs it had to drop.
+pub async fn drain<E: std::fmt::Debug>(stop: impl Future<Output = Result<usize, E>>) -> usize {
+    within(stop).await.expect("stop_within failed")
+}
+
+// `n` actors from one spawning closure, handed the index of each
+pub fn spawn_many<H>(n: usize, spawn: impl FnMut(usize) -> H) -> Vec<H> {
+    (0..n).map(spawn).collect()
+}
+
+// the ask failed because the actor's mailbox is closed, `ActorError` and
+// `CloseReason` being the ones of the actors module in scope
+#[macro_export]
+macro_rules! assert_closed {
+    ($result:expr, $reason:ident) => {
+        match $result {
+            Err(ActorError::MailboxClosed {
+                reason: CloseReason::$reason,
+            }) => {}
+            other => panic!(
+                "expected MailboxClosed {{ reason: {} }}, got {:?}",
+                stringify!($reason),
+                other
+            ),
+        }
+    };
+}
+
+// a subscriber keeping the names of the spans opened under it, and their fields
+// as `name=value`. set it with `tracing::subscriber::set_default`, which only
+// covers the current thread: run the actor there too
+#[derive(Clone, Default)]
+pub struct SpanFields {
+    spans: Arc<Mutex<Vec<&'static str>>>,
+    fields: Arc<Mutex<Vec<String>>>,
+}
+
+impl SpanFields {
+    pub fn spans(&self) -> Vec<&'static str> {
+        self.spans.lock().unwrap().clone()
+    }
+
+    pub fn fields(&self) -> Vec<String> {
+        self.fields.lock().unwrap().clone()
+    }
+}
+
+impl Visit for SpanFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let mut fields = self.fields.lock().unwrap();
+        fields.push(format!("{}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for SpanFields {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        self.spans.lock().unwrap().push(span.metadata().name());
+        span.record(&mut self.clone());
+        Id::from_u64(1)
+    }
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+    fn event(&self, _event: &Event<'_>) {}
+    fn enter(&self, _span: &Id) {}
+    fn exit(&self, _span: &Id) {}
+}
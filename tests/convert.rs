@@ -0,0 +1,198 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod store {
+    // what the storage layer hands back
+    pub struct RowSet(pub Vec<(u32, String)>);
+
+    #[derive(Debug, PartialEq)]
+    pub struct Record {
+        pub id: u32,
+        pub name: String,
+    }
+
+    // at most one record, more is a conversion failure
+    #[derive(Debug, PartialEq)]
+    pub struct Single(pub Option<Record>);
+
+    impl TryFrom<RowSet> for Single {
+        type Error = usize;
+
+        fn try_from(rows: RowSet) -> Result<Self, usize> {
+            match rows.0.len() {
+                0 | 1 => Ok(Single(to_records(rows).pop())),
+                n => Err(n),
+            }
+        }
+    }
+
+    pub fn to_records(rows: RowSet) -> Vec<Record> {
+        rows.0
+            .into_iter()
+            .map(|(id, name)| Record { id, name })
+            .collect()
+    }
+
+    pub enum TableMsg {
+        #[resp(from = RowSet, map = to_records)]
+        Scan { resp: Vec<Record> },
+        #[resp(try_from = RowSet)]
+        Find { name: String, resp: Single },
+    }
+
+    pub struct Table {
+        pub rows: Vec<(u32, String)>,
+    }
+
+    impl Table {
+        fn select(&self, name: Option<&str>) -> RowSet {
+            let rows = self
+                .rows
+                .iter()
+                .filter(|(_, n)| name.is_none_or(|name| n == name));
+            RowSet(rows.cloned().collect())
+        }
+
+        async fn process(&mut self, msg: TableMsg) {
+            match msg {
+                TableMsg::Scan { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.respond_with(self.select(None));
+                    }
+                }
+                TableMsg::Find { name, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.respond_with(self.select(Some(&name)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[actors]
+mod reports {
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Totals {
+        pub values: Vec<u64>,
+    }
+
+    pub enum ReportMsg {
+        #[resp(boxed)]
+        Latest { resp: Totals },
+        #[resp(boxed_public)]
+        Archived { resp: Totals },
+    }
+
+    pub struct Report {
+        pub latest: Totals,
+        pub archived: Box<Totals>,
+    }
+
+    impl Report {
+        async fn process(&mut self, msg: ReportMsg) {
+            match msg {
+                ReportMsg::Latest { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.respond_with(self.latest.clone());
+                    }
+                }
+                ReportMsg::Archived { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.archived.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use reports::{ActorReport, Report, ReportMsg, Totals};
+use store::{ActorError, ActorTable, Record, Single, Table, TableMsg};
+
+fn table() -> ActorTable {
+    ActorTable::spawn(Table {
+        rows: vec![(1, "ada".into()), (2, "bob".into()), (3, "bob".into())],
+    })
+}
+
+fn find(name: &str) -> TableMsg {
+    TableMsg::Find {
+        name: name.to_string(),
+        resp: None,
+    }
+}
+
+fn record(id: u32, name: &str) -> Record {
+    Record {
+        id,
+        name: name.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn the_caller_sees_the_public_type() {
+    let mut table = table();
+    assert_eq!(
+        within(table.scan(TableMsg::Scan { resp: None })).await,
+        Ok(vec![record(1, "ada"), record(2, "bob"), record(3, "bob")])
+    );
+}
+
+#[tokio::test]
+async fn try_from_converts_on_the_actor_side() {
+    let mut table = table();
+    assert_eq!(
+        within(table.find(find("ada"))).await,
+        Ok(Single(Some(record(1, "ada"))))
+    );
+    assert_eq!(within(table.find(find("eve"))).await, Ok(Single(None)));
+}
+
+#[tokio::test]
+async fn a_failed_conversion_is_its_own_error() {
+    let mut table = table();
+    let failed = within(table.find(find("bob"))).await;
+    assert_eq!(
+        failed,
+        Err(ActorError::RespConversion {
+            variant: "TableMsg::Find"
+        })
+    );
+    assert_eq!(
+        failed.unwrap_err().to_string(),
+        "response to TableMsg::Find failed to convert"
+    );
+}
+
+fn report() -> ActorReport {
+    ActorReport::spawn(Report {
+        latest: Totals {
+            values: vec![1, 2, 3],
+        },
+        archived: Box::new(Totals { values: vec![0] }),
+    })
+}
+
+#[tokio::test]
+async fn boxed_replies_are_unboxed_for_the_caller() {
+    let mut report = report();
+    let latest: Result<Totals, _> = within(report.latest(ReportMsg::Latest { resp: None })).await;
+    assert_eq!(
+        latest.unwrap(),
+        Totals {
+            values: vec![1, 2, 3]
+        }
+    );
+}
+
+#[tokio::test]
+async fn boxed_public_replies_stay_boxed() {
+    let mut report = report();
+    let archived: Result<Box<Totals>, _> =
+        within(report.archived(ReportMsg::Archived { resp: None })).await;
+    assert_eq!(archived.unwrap(), Box::new(Totals { values: vec![0] }));
+}
@@ -0,0 +1,213 @@
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::{drain, within};
+use tokio::sync::oneshot;
+use tokio_actor::actors;
+
+#[actors]
+mod background {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use tokio::sync::oneshot;
+
+    pub enum WatcherMsg {
+        // a task running until the actor stops, holding a clone of `guard`
+        Watch { resp: () },
+        // a task done after `after`
+        Tick { after: Duration, resp: () },
+        Me { resp: ActorId },
+        Upgrade { resp: bool },
+        Quit { resp: () },
+    }
+
+    pub struct Watcher {
+        pub guard: Arc<()>,
+        // clones of `guard` held by tasks once `on_stop` runs, besides the
+        // actor's own and the test's
+        pub at_stop: Option<oneshot::Sender<usize>>,
+    }
+
+    impl Watcher {
+        async fn process(&mut self, msg: WatcherMsg, ctx: &ActorWatcherCtx) {
+            match msg {
+                WatcherMsg::Watch { resp } => {
+                    let guard = self.guard.clone();
+                    ctx.spawn(async move {
+                        let _guard = guard;
+                        std::future::pending::<()>().await;
+                    });
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+                WatcherMsg::Tick { after, resp } => {
+                    let guard = self.guard.clone();
+                    ctx.spawn(async move {
+                        tokio::time::sleep(after).await;
+                        drop(guard);
+                    });
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+                WatcherMsg::Me { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(ctx.actor_id());
+                    }
+                }
+                WatcherMsg::Upgrade { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(ctx.handle().upgrade().is_some());
+                    }
+                }
+                WatcherMsg::Quit { resp } => {
+                    ctx.stop();
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            if let Some(at_stop) = self.at_stop.take() {
+                let _ = at_stop.send(Arc::strong_count(&self.guard) - 2);
+            }
+        }
+    }
+
+    pub enum PatientMsg {
+        Tick { after: Duration, resp: () },
+    }
+
+    #[actor(ctx_tasks = "await")]
+    pub struct Patient {
+        pub guard: Arc<()>,
+        pub at_stop: Option<oneshot::Sender<usize>>,
+    }
+
+    impl Patient {
+        async fn process(&mut self, msg: PatientMsg, ctx: &ActorPatientCtx) {
+            match msg {
+                PatientMsg::Tick { after, resp } => {
+                    let guard = self.guard.clone();
+                    ctx.spawn(async move {
+                        tokio::time::sleep(after).await;
+                        drop(guard);
+                    });
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            if let Some(at_stop) = self.at_stop.take() {
+                let _ = at_stop.send(Arc::strong_count(&self.guard) - 2);
+            }
+        }
+    }
+}
+
+use background::{
+    ActorError, ActorPatient, ActorWatcher, CloseReason, Patient, PatientMsg, Watcher, WatcherMsg,
+};
+
+fn watcher() -> (ActorWatcher, Arc<()>, oneshot::Receiver<usize>) {
+    let guard = Arc::new(());
+    let (at_stop, stopped) = oneshot::channel();
+    let handle = ActorWatcher::spawn(Watcher {
+        guard: guard.clone(),
+        at_stop: Some(at_stop),
+    });
+    (handle, guard, stopped)
+}
+
+#[tokio::test]
+async fn background_tasks_are_aborted_before_on_stop() {
+    let (mut watcher, guard, stopped) = watcher();
+    for _ in 0..3 {
+        within(watcher.watch(WatcherMsg::Watch { resp: None }))
+            .await
+            .unwrap();
+    }
+    assert_eq!(Arc::strong_count(&guard), 5);
+    assert_eq!(drain(watcher.stop_within(Duration::from_secs(1))).await, 0);
+    assert_eq!(within(stopped).await, Ok(0));
+}
+
+#[tokio::test]
+async fn dropping_the_handle_aborts_them_too() {
+    let (mut watcher, _guard, stopped) = watcher();
+    within(watcher.watch(WatcherMsg::Watch { resp: None }))
+        .await
+        .unwrap();
+    // the ctx's own weak handle doesn't keep the actor alive
+    drop(watcher);
+    assert_eq!(within(stopped).await, Ok(0));
+}
+
+#[tokio::test(start_paused = true)]
+async fn finished_tasks_dont_wait_for_the_stop() {
+    let (mut watcher, guard, _stopped) = watcher();
+    within(watcher.tick(WatcherMsg::Tick {
+        after: Duration::from_millis(10),
+        resp: None,
+    }))
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    // gone while the actor keeps running
+    assert_eq!(Arc::strong_count(&guard), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn awaited_tasks_finish_before_on_stop() {
+    let guard = Arc::new(());
+    let (at_stop, stopped) = oneshot::channel();
+    let mut patient = ActorPatient::spawn(Patient {
+        guard: guard.clone(),
+        at_stop: Some(at_stop),
+    });
+    within(patient.tick(PatientMsg::Tick {
+        after: Duration::from_millis(50),
+        resp: None,
+    }))
+    .await
+    .unwrap();
+    let start = tokio::time::Instant::now();
+    assert_eq!(drain(patient.stop_within(Duration::ZERO)).await, 0);
+    assert_eq!(within(stopped).await, Ok(0));
+    assert_eq!(start.elapsed(), Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn the_ctx_knows_its_actor() {
+    let (mut watcher, _guard, _stopped) = watcher();
+    assert_eq!(
+        within(watcher.me(WatcherMsg::Me { resp: None })).await,
+        Ok(watcher.id())
+    );
+    assert_eq!(
+        within(watcher.upgrade(WatcherMsg::Upgrade { resp: None })).await,
+        Ok(true)
+    );
+}
+
+#[tokio::test]
+async fn the_ctx_stops_its_actor() {
+    let (mut watcher, _guard, stopped) = watcher();
+    within(watcher.watch(WatcherMsg::Watch { resp: None }))
+        .await
+        .unwrap();
+    within(watcher.quit(WatcherMsg::Quit { resp: None }))
+        .await
+        .unwrap();
+    assert_eq!(within(stopped).await, Ok(0));
+    assert_closed!(watcher.me(WatcherMsg::Me { resp: None }).await, Stopped);
+}
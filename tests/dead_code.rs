@@ -0,0 +1,69 @@
+// a build denying dead code, using a fraction of what gets generated
+#![deny(dead_code)]
+
+use tokio_actor::actors;
+
+#[actors(allow_unused)]
+mod cache {
+    pub enum CacheMsg {
+        #[only(ask)]
+        Get {
+            key: u64,
+            resp: Option<u64>,
+        },
+        #[only(tell)]
+        Put {
+            key: u64,
+            value: u64,
+            resp: (),
+        },
+        Clear {
+            resp: (),
+        },
+    }
+
+    pub struct Cache {
+        pub entries: std::collections::HashMap<u64, u64>,
+    }
+
+    impl Cache {
+        async fn process(&mut self, msg: CacheMsg) {
+            match msg {
+                CacheMsg::Get { key, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.entries.get(&key).copied());
+                    }
+                }
+                CacheMsg::Put { key, value, resp } => {
+                    self.entries.insert(key, value);
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+                CacheMsg::Clear { resp } => {
+                    self.entries.clear();
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use cache::{ActorCache, Cache, CacheMsg};
+
+#[tokio::test]
+async fn compiles_with_most_generated_methods_unused() {
+    let mut cache = ActorCache::spawn(Cache {
+        entries: Default::default(),
+    });
+    let put = CacheMsg::Put {
+        key: 1,
+        value: 10,
+        resp: None,
+    };
+    cache.put_no_wait(put).await.unwrap();
+    let get = CacheMsg::Get { key: 1, resp: None };
+    assert_eq!(cache.get(get).await, Ok(Some(10)));
+}
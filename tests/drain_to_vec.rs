@@ -0,0 +1,83 @@
+#![cfg(feature = "test-util")]
+
+use tokio_actor::actors;
+
+#[actors]
+mod orders {
+    #[wire(derive(Debug, PartialEq))]
+    pub enum OrderMsg {
+        Place { sku: String, qty: u32 },
+        Cancel { sku: String },
+        Total { resp: u32 },
+    }
+
+    pub struct Order {
+        pub placed: u32,
+    }
+
+    impl Order {
+        async fn process(&mut self, msg: OrderMsg) {
+            match msg {
+                OrderMsg::Place { qty, .. } => self.placed += qty,
+                OrderMsg::Cancel { .. } => {}
+                OrderMsg::Total { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.placed);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use orders::{ActorOrder, OrderMsg, OrderMsgWire};
+
+#[tokio::test]
+async fn lists_what_was_queued_in_order_without_processing_it() {
+    let (mut order, mut mailbox) = ActorOrder::new_unspawned();
+    order
+        .send_into(OrderMsg::Place {
+            sku: "a".into(),
+            qty: 2,
+        })
+        .await
+        .unwrap();
+    order
+        .send_into(OrderMsg::Cancel { sku: "b".into() })
+        .await
+        .unwrap();
+    // sent, but nothing answers it while the mailbox is only drained
+    let total = order.total(OrderMsg::Total { resp: None });
+
+    let queued: Vec<OrderMsgWire> = mailbox.drain_to_vec().into_iter().map(Into::into).collect();
+    assert_eq!(
+        queued,
+        [
+            OrderMsgWire::Place {
+                sku: "a".into(),
+                qty: 2
+            },
+            OrderMsgWire::Cancel { sku: "b".into() },
+            OrderMsgWire::Total {},
+        ]
+    );
+    assert!(mailbox.drain_to_vec().is_empty());
+    drop(mailbox);
+    assert!(total.await.is_err());
+}
+
+#[tokio::test]
+async fn drained_msgs_replay_into_a_running_actor() {
+    let (order, mut mailbox) = ActorOrder::new_unspawned();
+    for qty in [1, 2, 3] {
+        let sku = format!("sku-{}", qty);
+        order.send_into(OrderMsg::Place { sku, qty }).await.unwrap();
+    }
+    let queued: Vec<OrderMsgWire> = mailbox.drain_to_vec().into_iter().map(Into::into).collect();
+
+    let mut running = ActorOrder::spawn(orders::Order { placed: 0 });
+    for msg in queued {
+        running.send_into(msg).await.unwrap();
+    }
+    assert_eq!(running.total(OrderMsg::Total { resp: None }).await, Ok(6));
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::time::Instant;
+use tokio_actor::actors;
+
+// every way of writing a duration, all read on tokio's clock
+#[actors]
+mod clock {
+    use std::time::Duration;
+
+    pub enum ClockMsg {
+        #[timeout("1m30s")]
+        Compound { takes: Duration, resp: () },
+        #[timeout(250)]
+        Bare { takes: Duration, resp: () },
+        #[timeout(ms = 500)]
+        Named { takes: Duration, resp: () },
+    }
+
+    #[actor(idle_timeout = "1h30m")]
+    pub struct Clock {}
+
+    impl Clock {
+        async fn process(&mut self, msg: ClockMsg) {
+            let (ClockMsg::Compound { takes, resp }
+            | ClockMsg::Bare { takes, resp }
+            | ClockMsg::Named { takes, resp }) = msg;
+            tokio::time::sleep(takes).await;
+            if let Some(r) = resp {
+                let _ = r.send(());
+            }
+        }
+    }
+}
+
+use clock::prelude::*;
+use clock::Clock;
+
+const LONG: Duration = Duration::from_secs(3 * 3600);
+
+// how long an ask that would take `LONG` waited before timing out, which is
+// also the limit it reports
+async fn timed_out_after(ask: AskFuture<()>) -> Duration {
+    let started = Instant::now();
+    match ask.await {
+        Err(ActorError::ResponseTimeout { limit, .. }) => assert_eq!(limit, started.elapsed()),
+        other => panic!("expected ResponseTimeout, got {:?}", other),
+    }
+    started.elapsed()
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_compound_duration_adds_up_its_parts() {
+    let mut clock = ActorClock::spawn(Clock {});
+    let ask = clock.compound(ClockMsg::Compound {
+        takes: LONG,
+        resp: None,
+    });
+    assert_eq!(timed_out_after(ask).await, Duration::from_secs(90));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_bare_integer_is_milliseconds() {
+    let mut clock = ActorClock::spawn(Clock {});
+    let ask = clock.bare(ClockMsg::Bare {
+        takes: LONG,
+        resp: None,
+    });
+    assert_eq!(timed_out_after(ask).await, Duration::from_millis(250));
+    let ask = ActorClock::spawn(Clock {}).named(ClockMsg::Named {
+        takes: LONG,
+        resp: None,
+    });
+    assert_eq!(timed_out_after(ask).await, Duration::from_millis(500));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_idle_timeout_reads_the_same_way() {
+    let mut clock = ActorClock::spawn(Clock {});
+    let answered = clock.bare(ClockMsg::Bare {
+        takes: Duration::ZERO,
+        resp: None,
+    });
+    assert_eq!(within(answered).await, Ok(()));
+
+    tokio::time::sleep(Duration::from_secs(90 * 60 - 1)).await;
+    assert!(!clock.is_closed());
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert!(clock.is_closed());
+}
@@ -0,0 +1,206 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod market {
+    #[event]
+    #[derive(Debug)]
+    pub enum PriceEvent {
+        Quote { symbol: &'static str, price: u64 },
+        Halt,
+    }
+
+    pub enum TickerMsg {
+        Tick(&'static str, u64),
+        Halt,
+        Published { resp: usize },
+    }
+
+    // publishes every tick it's told about
+    pub struct Ticker {
+        pub bus: PriceEventBus,
+        pub published: usize,
+    }
+
+    impl Ticker {
+        async fn process(&mut self, msg: TickerMsg) {
+            match msg {
+                TickerMsg::Tick(symbol, price) => {
+                    self.bus.emit(PriceEvent::Quote { symbol, price });
+                    self.published += 1;
+                }
+                TickerMsg::Halt => {
+                    self.bus.emit(PriceEvent::Halt);
+                    self.published += 1;
+                }
+                TickerMsg::Published { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.published);
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum BookMsg {
+        Price { symbol: &'static str, price: u64 },
+        Prices { resp: Vec<(&'static str, u64)> },
+    }
+
+    // every price it was told about, in order
+    pub struct Book {
+        pub prices: Vec<(&'static str, u64)>,
+    }
+
+    impl Book {
+        async fn process(&mut self, msg: BookMsg) {
+            match msg {
+                BookMsg::Price { symbol, price } => self.prices.push((symbol, price)),
+                BookMsg::Prices { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.prices.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use market::prelude::*;
+use market::{Book, Ticker};
+
+// quotes as a `Book` msg, halts skipped
+fn quotes(event: &PriceEvent) -> Option<BookMsg> {
+    match event {
+        PriceEvent::Quote { symbol, price } => Some(BookMsg::Price {
+            symbol,
+            price: *price,
+        }),
+        PriceEvent::Halt => None,
+    }
+}
+
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(10)).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn delivers_into_the_subscribers_mailbox_in_emit_order() {
+    let bus = PriceEventBus::new();
+    let mut book = ActorBook::spawn(Book { prices: vec![] });
+    bus.subscribe(&book, quotes);
+    let mut ticker = ActorTicker::spawn(Ticker {
+        bus: bus.clone(),
+        published: 0,
+    });
+
+    ticker.send_into(TickerMsg::Tick("ACME", 10)).await.unwrap();
+    ticker.send_into(TickerMsg::Halt).await.unwrap();
+    ticker.send_into(TickerMsg::Tick("INIT", 7)).await.unwrap();
+    ticker.send_into(TickerMsg::Tick("ACME", 11)).await.unwrap();
+    assert_eq!(
+        within(ticker.published(TickerMsg::Published { resp: None })).await,
+        Ok(4)
+    );
+    settle().await;
+
+    assert_eq!(
+        within(book.prices(BookMsg::Prices { resp: None })).await,
+        Ok(vec![("ACME", 10), ("INIT", 7), ("ACME", 11)])
+    );
+    assert_eq!(bus.subscriber_count(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_subscriber_maps_its_own_share() {
+    let bus = PriceEventBus::new();
+    let mut all = ActorBook::spawn(Book { prices: vec![] });
+    let mut acme = ActorBook::spawn(Book { prices: vec![] });
+    bus.subscribe(&all, quotes);
+    bus.subscribe(&acme, |e: &PriceEvent| match e {
+        PriceEvent::Quote { symbol, price } if *symbol == "ACME" => Some(BookMsg::Price {
+            symbol,
+            price: price * 100,
+        }),
+        _ => None,
+    });
+
+    assert_eq!(
+        bus.emit(PriceEvent::Quote {
+            symbol: "ACME",
+            price: 1
+        }),
+        2
+    );
+    assert_eq!(
+        bus.emit(PriceEvent::Quote {
+            symbol: "INIT",
+            price: 2
+        }),
+        2
+    );
+    settle().await;
+
+    assert_eq!(
+        within(all.prices(BookMsg::Prices { resp: None })).await,
+        Ok(vec![("ACME", 1), ("INIT", 2)])
+    );
+    assert_eq!(
+        within(acme.prices(BookMsg::Prices { resp: None })).await,
+        Ok(vec![("ACME", 100)])
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn unsubscribed_actors_hear_nothing_more() {
+    let bus = PriceEventBus::new();
+    let mut book = ActorBook::spawn(Book { prices: vec![] });
+    let id = bus.subscribe(&book, quotes);
+    bus.emit(PriceEvent::Quote {
+        symbol: "ACME",
+        price: 1,
+    });
+
+    assert!(bus.unsubscribe(id));
+    assert!(!bus.unsubscribe(id));
+    assert_eq!(
+        bus.emit(PriceEvent::Quote {
+            symbol: "ACME",
+            price: 2
+        }),
+        0
+    );
+    settle().await;
+
+    assert_eq!(
+        within(book.prices(BookMsg::Prices { resp: None })).await,
+        Ok(vec![("ACME", 1)])
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn prunes_subscribers_nobody_else_holds() {
+    let bus = PriceEventBus::new();
+    let book = ActorBook::spawn(Book { prices: vec![] });
+    let stopped = ActorBook::spawn(Book { prices: vec![] });
+    bus.subscribe(&book, quotes);
+    bus.subscribe(&stopped, quotes);
+    assert_eq!(bus.subscriber_count(), 2);
+
+    // the bus's handle alone doesn't keep an actor subscribed
+    drop(book);
+    within(stopped.stop_within(Duration::ZERO)).await.unwrap();
+    // one the stopped actor would have been told about
+    assert_eq!(bus.subscriber_count(), 2);
+    assert_eq!(
+        bus.emit(PriceEvent::Quote {
+            symbol: "ACME",
+            price: 1
+        }),
+        0
+    );
+}
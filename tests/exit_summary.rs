@@ -0,0 +1,93 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod batch {
+    #[derive(Debug, PartialEq, Default)]
+    pub struct Summary {
+        pub processed: u64,
+        pub errors: u64,
+    }
+
+    pub enum RecordsMsg {
+        Record(Result<u64, String>),
+        Boom,
+        Seen { resp: u64 },
+    }
+
+    #[actor(exit = Summary)]
+    pub struct Records {
+        pub summary: Summary,
+    }
+
+    impl Records {
+        async fn process(&mut self, msg: RecordsMsg) {
+            match msg {
+                RecordsMsg::Record(Ok(_v)) => self.summary.processed += 1,
+                RecordsMsg::Record(Err(_e)) => self.summary.errors += 1,
+                RecordsMsg::Boom => panic!("boom"),
+                RecordsMsg::Seen { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.summary.processed + self.summary.errors);
+                    }
+                }
+            }
+        }
+
+        async fn on_stop(&mut self) -> Summary {
+            std::mem::take(&mut self.summary)
+        }
+    }
+}
+
+use batch::{ActorRecords, Records, RecordsMsg, Summary};
+
+fn records() -> Records {
+    Records {
+        summary: Summary::default(),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_summary_counts_every_processed_msg() {
+    let (records, exit) = ActorRecords::spawn_with_exit(records());
+    for n in 0..10 {
+        let record = match n % 4 {
+            0 => Err(format!("bad {}", n)),
+            _ => Ok(n),
+        };
+        records.send_into(RecordsMsg::Record(record)).await.unwrap();
+    }
+    // still queued when the last handle goes, drained before the summary
+    drop(records);
+    let summary = within(exit).await.unwrap();
+    assert_eq!(
+        summary,
+        Summary {
+            processed: 7,
+            errors: 3
+        }
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn start_resolves_to_the_summary() {
+    let (mut records, run) = ActorRecords::start(records());
+    let run = tokio::spawn(run);
+    records.send_into(RecordsMsg::Record(Ok(1))).await.unwrap();
+    let seen = within(records.seen(RecordsMsg::Seen { resp: None })).await;
+    assert_eq!(seen, Ok(1));
+    drop(records);
+    let summary = within(run).await.unwrap();
+    assert_eq!(summary.processed, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_panic_reaches_the_awaiter_instead() {
+    let (records, exit) = ActorRecords::spawn_with_exit(records());
+    records.send_into(RecordsMsg::Boom).await.unwrap();
+    let exit = within(exit).await;
+    assert!(exit.unwrap_err().is_panic());
+}
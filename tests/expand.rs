@@ -0,0 +1,7 @@
+// what `#[actors]` generates, each case in `tests/expand` pinned by the
+// `.expanded.rs` next to it. needs `cargo install cargo-expand`. after an
+// intended change: MACROTEST=overwrite cargo test --test expand
+#[test]
+fn expand() {
+    macrotest::expand("tests/expand/*.rs");
+}
@@ -0,0 +1,2168 @@
+use tokio_actor::actors;
+mod counter {
+    pub enum CounterMsg {
+        Add { n: u64, resp: Option<::tokio::sync::oneshot::Sender<u64>> },
+        Reset,
+    }
+    pub struct Counter {
+        pub total: u64,
+    }
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { n, resp } => {
+                    self.total += n;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+                CounterMsg::Reset => self.total = 0,
+            }
+        }
+    }
+    pub mod prelude {
+        pub use super::Ack;
+        pub use super::ActorCounter;
+        pub use super::ActorError;
+        pub use super::ActorId;
+        pub use super::ActorPanic;
+        pub use super::ActorStats;
+        pub use super::AnyActor;
+        pub use super::AskFlatten;
+        pub use super::AskFuture;
+        pub use super::AskMapErr;
+        pub use super::AskTimeout;
+        pub use super::AskUnboxed;
+        pub use super::CloseReason;
+        pub use super::Emitter;
+        pub use super::EventBus;
+        pub use super::EventSink;
+        pub use super::Handles;
+        pub use super::Mailbox;
+        pub use super::MsgSource;
+        pub use super::PipeSink;
+        pub use super::PipeStats;
+        pub use super::Responder;
+        pub use super::RouteInfo;
+        pub use super::SubscriptionId;
+        pub use super::ValidationError;
+        pub use super::CounterMsg;
+    }
+    const _: () = {
+        #[allow(dead_code)]
+        struct Required
+        where
+            u64: Send + 'static;
+    };
+    const _: () = {
+        #[allow(dead_code)]
+        struct Required
+        where
+            u64: Send + 'static;
+    };
+    const _: () = {
+        #[allow(dead_code)]
+        struct Required
+        where
+            Counter: Send + 'static;
+    };
+    impl CounterMsg {
+        pub const ROUTES: &'static [RouteInfo] = &[
+            RouteInfo {
+                variant: "Add",
+                fields: &[("n", "u64")],
+                resp: Some("u64"),
+                stream: false,
+                sink: false,
+                timeout_ms: None,
+                bounded: None,
+                latest_key: None,
+                deprecated: false,
+                internal: false,
+            },
+            RouteInfo {
+                variant: "Reset",
+                fields: &[],
+                resp: None,
+                stream: false,
+                sink: false,
+                timeout_ms: None,
+                bounded: None,
+                latest_key: None,
+                deprecated: false,
+                internal: false,
+            },
+        ];
+    }
+    pub struct ActorCounter {
+        sender: ::tokio::sync::mpsc::UnboundedSender<Envelope<CounterMsg>>,
+        shared: std::sync::Arc<ActorShared>,
+    }
+    const _: fn() = || {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ActorCounter>();
+    };
+    impl Clone for ActorCounter {
+        fn clone(&self) -> Self {
+            self.shared.handles.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            return Self {
+                sender: self.sender.clone(),
+                shared: self.shared.clone(),
+            };
+        }
+    }
+    impl Drop for ActorCounter {
+        fn drop(&mut self) {
+            self.shared.handles.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+    impl ActorCounter {
+        fn __channel(
+            index: Option<usize>,
+        ) -> (Self, Mailbox<CounterMsg>, std::sync::Arc<ActorShared>) {
+            let (s, r) = ::tokio::sync::mpsc::unbounded_channel();
+            let shared = std::sync::Arc::new(ActorShared {
+                id: ActorId::next("Counter", index),
+                handles: std::sync::atomic::AtomicUsize::new(1),
+                ..Default::default()
+            });
+            return (
+                Self {
+                    sender: s,
+                    shared: shared.clone(),
+                },
+                Mailbox::new(r),
+                shared,
+            );
+        }
+        pub fn start(
+            state: Counter,
+        ) -> (Self, impl std::future::Future<Output = Counter>) {
+            return Self::__start_nth(None, state);
+        }
+        fn __start_nth(
+            index: Option<usize>,
+            state: Counter,
+        ) -> (Self, impl std::future::Future<Output = Counter>) {
+            let (handle, mailbox, shared) = Self::__channel(index);
+            return Self::__start(handle, mailbox, shared, state);
+        }
+        fn __start(
+            handle: Self,
+            mailbox: Mailbox<CounterMsg>,
+            shared: std::sync::Arc<ActorShared>,
+            state: Counter,
+        ) -> (Self, impl std::future::Future<Output = Counter>) {
+            let mut a = state;
+            return (
+                handle,
+                async move {
+                    a.run(mailbox, shared).await;
+                    return a;
+                },
+            );
+        }
+        pub fn spawn(state: Counter) -> Self {
+            let (handle, run) = Self::start(state);
+            ::tokio::spawn(run);
+            return handle;
+        }
+        pub fn spawn_many(n: usize, make_state: impl Fn(usize) -> Counter) -> Vec<Self> {
+            return (0..n)
+                .map(|i| {
+                    let (handle, run) = Self::__start_nth(Some(i), make_state(i));
+                    ::tokio::spawn(run);
+                    return handle;
+                })
+                .collect();
+        }
+        pub fn new_from_receiver(
+            source: impl MsgSource<CounterMsg>,
+            state: Counter,
+        ) -> Self {
+            let (handle, mailbox, shared) = Self::__channel(None);
+            let (handle, run) = Self::__start(
+                handle,
+                mailbox.fed_by(source),
+                shared,
+                state,
+            );
+            ::tokio::spawn(run);
+            return handle;
+        }
+    }
+    pub struct CounterAddRequest {
+        pub n: u64,
+    }
+    impl ActorCounter {
+        pub fn id(&self) -> ActorId {
+            return self.shared.id;
+        }
+        pub fn handle_count(&self) -> usize {
+            return self.shared.handles.load(std::sync::atomic::Ordering::Relaxed);
+        }
+        pub async fn stop_within(
+            &self,
+            within: std::time::Duration,
+        ) -> Result<usize, ActorError> {
+            let done = match self.__request_stop(::tokio::time::Instant::now() + within)
+            {
+                Ok(v) => v,
+                Err(abandoned) => {
+                    return Ok(abandoned);
+                }
+            };
+            return done
+                .await
+                .map_err(|_e| {
+                    return ActorError::MailboxClosed {
+                        reason: self.shared.close_reason(),
+                    };
+                });
+        }
+        pub async fn stop_all(
+            handles: &[Self],
+            within: std::time::Duration,
+        ) -> Result<usize, ActorError> {
+            let deadline = ::tokio::time::Instant::now() + within;
+            let mut abandoned = 0;
+            let mut pending = ::alloc::vec::Vec::new();
+            for handle in handles {
+                match handle.__request_stop(deadline) {
+                    Ok(done) => pending.push((handle, done)),
+                    Err(n) => abandoned += n,
+                }
+            }
+            for (handle, done) in pending {
+                abandoned
+                    += done
+                        .await
+                        .map_err(|_e| {
+                            return ActorError::MailboxClosed {
+                                reason: handle.shared.close_reason(),
+                            };
+                        })?;
+            }
+            return Ok(abandoned);
+        }
+        fn __request_stop(
+            &self,
+            deadline: ::tokio::time::Instant,
+        ) -> Result<::tokio::sync::oneshot::Receiver<usize>, usize> {
+            let done = self.shared.request_stop(deadline)?;
+            let _ = self.sender.send(Envelope::control(ControlMsg::Stop));
+            return Ok(done);
+        }
+    }
+    impl AnyActor for ActorCounter {
+        fn id(&self) -> ActorId {
+            return self.shared.id;
+        }
+        fn is_closed(&self) -> bool {
+            return self.sender.is_closed() || self.shared.accepting().is_err();
+        }
+        fn stop(&self) {
+            if self.shared.request_stop(::tokio::time::Instant::now()).is_ok() {
+                let _ = self.sender.send(Envelope::control(ControlMsg::Stop));
+            }
+        }
+        fn ping(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<
+                    Output = Result<std::time::Duration, ActorError>,
+                > + Send + '_,
+            >,
+        > {
+            return Box::pin(async move {
+                let start = ::tokio::time::Instant::now();
+                let (s, r) = ::tokio::sync::oneshot::channel();
+                self.sender
+                    .send(Envelope::control(ControlMsg::Flush(s)))
+                    .map_err(|_e| {
+                        return ActorError::MailboxClosed {
+                            reason: self.shared.close_reason(),
+                        };
+                    })?;
+                r.await
+                    .map_err(|_e| {
+                        return ActorError::MailboxClosed {
+                            reason: self.shared.close_reason(),
+                        };
+                    })?;
+                return Ok(start.elapsed());
+            });
+        }
+    }
+    impl ActorCounter {
+        pub async fn send_into<IntoMsg: Into<CounterMsg>>(
+            &self,
+            t: IntoMsg,
+        ) -> Result<(), ActorError> {
+            let msg: CounterMsg = t.into();
+            self.shared.accepting()?;
+            self.sender
+                .send(Envelope::new(msg))
+                .map_err(|_e| {
+                    return ActorError::MailboxClosed {
+                        reason: self.shared.close_reason(),
+                    };
+                })?;
+            return Ok(());
+        }
+    }
+    impl ActorCounter {
+        fn __try_tell(&self, msg: CounterMsg) -> Result<(), ActorError> {
+            self.shared.accepting()?;
+            self.sender
+                .send(Envelope::new(msg))
+                .map_err(|_e| {
+                    return ActorError::MailboxClosed {
+                        reason: self.shared.close_reason(),
+                    };
+                })?;
+            return Ok(());
+        }
+    }
+    impl Extend<CounterMsg> for ActorCounter {
+        fn extend<I: IntoIterator<Item = CounterMsg>>(&mut self, iter: I) {
+            for msg in iter {
+                if let Err(ActorError::MailboxClosed { .. }) = self.__try_tell(msg) {
+                    return;
+                }
+            }
+        }
+    }
+    impl EventSink<CounterMsg> for ActorCounter {
+        fn try_tell(&self, msg: CounterMsg) -> Result<(), ActorError> {
+            return self.__try_tell(msg);
+        }
+        fn handle_count(&self) -> usize {
+            return self.shared.handles.load(std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+    impl PipeSink<CounterMsg> for ActorCounter {
+        fn pipe_send(
+            &self,
+            msg: CounterMsg,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), ActorError>> + Send + '_>,
+        > {
+            return Box::pin(self.send_into(msg));
+        }
+    }
+    impl Counter {
+        async fn run(
+            &mut self,
+            mut mailbox: Mailbox<CounterMsg>,
+            shared: std::sync::Arc<ActorShared>,
+        ) {
+            let _stop = StopGuard(shared.clone());
+            let started = Ok(());
+            let failed = started.is_err();
+            shared.started(started);
+            if failed {
+                shared.close(CloseReason::StartFailed);
+                mailbox.close();
+            }
+            if !failed {
+                loop {
+                    if let Some(deadline) = shared.stop_deadline() {
+                        if shared.close_reason() != CloseReason::Failed {
+                            shared.close(CloseReason::Stopped);
+                        }
+                        mailbox.close();
+                        let mut abandoned = 0usize;
+                        while let Some(envelope) = mailbox.recv_until(deadline).await {
+                            if ::tokio::time::Instant::now() >= deadline {
+                                if let Letter::User { .. } = envelope.0 {
+                                    abandoned += 1;
+                                }
+                                continue;
+                            }
+                            let envelope = envelope.open();
+                            match envelope {
+                                Letter::User { msg, .. } => {
+                                    self.process(msg).await;
+                                }
+                                Letter::Control(control) => {
+                                    match control {
+                                        ControlMsg::Stop => {}
+                                        ControlMsg::Flush(done) => {
+                                            let _ = done.send(());
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        shared
+                            .abandoned
+                            .store(abandoned, std::sync::atomic::Ordering::SeqCst);
+                        break;
+                    }
+                    let envelope = match mailbox.recv().await {
+                        Some(envelope) => envelope,
+                        None => break,
+                    };
+                    match envelope {
+                        Letter::User { msg, .. } => {
+                            self.process(msg).await;
+                        }
+                        Letter::Control(control) => {
+                            match control {
+                                ControlMsg::Stop => {}
+                                ControlMsg::Flush(done) => {
+                                    let _ = done.send(());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            drop(shared);
+        }
+    }
+    #[allow(dead_code)]
+    impl ActorCounter {
+        fn __ask<R>(
+            &self,
+            msg: CounterMsg,
+            r: ::tokio::sync::oneshot::Receiver<R>,
+        ) -> AskFuture<R> {
+            if let Err(e) = self.shared.accepting() {
+                return AskFuture::failed(e);
+            }
+            let ask: AskFuture<R> = match self.sender.send(Envelope::new(msg)) {
+                Ok(_) => AskFuture::waiting(r),
+                Err(_e) => {
+                    AskFuture::failed(ActorError::MailboxClosed {
+                        reason: self.shared.close_reason(),
+                    })
+                }
+            };
+            return ask;
+        }
+    }
+    #[allow(dead_code)]
+    impl ActorCounter {
+        async fn __acked(&self, msg: CounterMsg) -> Result<(), ActorError> {
+            self.shared.accepting()?;
+            let (s, r) = ::tokio::sync::oneshot::channel();
+            self.sender
+                .send(Envelope::acked(msg, s))
+                .map_err(|_e| {
+                    return ActorError::MailboxClosed {
+                        reason: self.shared.close_reason(),
+                    };
+                })?;
+            return r
+                .await
+                .map_err(|_e| {
+                    return ActorError::MailboxClosed {
+                        reason: self.shared.close_reason(),
+                    };
+                });
+        }
+    }
+    impl ActorCounter {
+        pub fn add(&mut self, mut msg: CounterMsg) -> AskFuture<u64> {
+            let ask: AskFuture<u64> = match msg {
+                CounterMsg::Add { ref mut resp, .. } => {
+                    let (s, r) = ::tokio::sync::oneshot::channel();
+                    *resp = Some(s);
+                    self.__ask(msg, r)
+                }
+                _ => AskFuture::failed(ActorError::InvalidMsgType),
+            };
+            return ask.variant("Counter", "CounterMsg::Add");
+        }
+    }
+    #[allow(deprecated)]
+    impl Handles<CounterAddRequest> for ActorCounter {
+        type Response = u64;
+        fn call(&self, req: CounterAddRequest) -> AskFuture<u64> {
+            let (s, r) = ::tokio::sync::oneshot::channel();
+            let msg = CounterMsg::Add {
+                n: req.n,
+                resp: Some(s),
+            };
+            let ask: AskFuture<u64> = self.__ask(msg, r);
+            return ask.variant("Counter", "CounterMsg::Add");
+        }
+    }
+    impl ActorCounter {
+        pub async fn add_no_wait(
+            &mut self,
+            mut msg: CounterMsg,
+        ) -> Result<(), ActorError> {
+            match msg {
+                CounterMsg::Add { .. } => {
+                    return self.send_into(msg).await;
+                }
+                _ => {
+                    return Err(ActorError::InvalidMsgType);
+                }
+            };
+        }
+    }
+    impl ActorCounter {
+        pub async fn add_acked(&self, msg: CounterMsg) -> Result<(), ActorError> {
+            match msg {
+                CounterMsg::Add { .. } => {
+                    return self.__acked(msg).await;
+                }
+                _ => {
+                    return Err(ActorError::InvalidMsgType);
+                }
+            };
+        }
+    }
+    pub struct Mailbox<M> {
+        receiver: ::tokio::sync::mpsc::UnboundedReceiver<Envelope<M>>,
+        source: Option<Box<dyn MailboxFeed<M>>>,
+    }
+    impl<M> Mailbox<M> {
+        fn new(receiver: ::tokio::sync::mpsc::UnboundedReceiver<Envelope<M>>) -> Self {
+            return Self { receiver, source: None };
+        }
+        #[allow(dead_code)]
+        fn fed_by<S: MsgSource<M>>(mut self, source: S) -> Self
+        where
+            M: Send + 'static,
+        {
+            self.source = Some(Box::new(SourceFeed::new(source)));
+            return self;
+        }
+        #[allow(dead_code)]
+        async fn recv(&mut self) -> Option<Letter<M>> {
+            return std::future::poll_fn(|cx| self.poll_recv(cx)).await;
+        }
+        #[allow(dead_code)]
+        fn try_recv(&mut self) -> Option<Letter<M>> {
+            return self.try_envelope().map(Envelope::open);
+        }
+        fn try_envelope(&mut self) -> Option<Envelope<M>> {
+            return self.receiver.try_recv().ok();
+        }
+        #[allow(dead_code)]
+        fn poll_recv(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Letter<M>>> {
+            let polled = self.poll_envelope(cx);
+            return polled.map(|v| v.map(Envelope::open));
+        }
+        fn poll_envelope(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Envelope<M>>> {
+            let polled = self.poll_channels(cx);
+            let source = match (&polled, &mut self.source) {
+                (std::task::Poll::Ready(Some(_)), _) | (_, None) => return polled,
+                (_, Some(source)) => source,
+            };
+            return match source.poll_feed(cx) {
+                std::task::Poll::Ready(Some(msg)) => {
+                    std::task::Poll::Ready(Some(Envelope::new(msg)))
+                }
+                std::task::Poll::Ready(None) => {
+                    self.source = None;
+                    polled
+                }
+                std::task::Poll::Pending => std::task::Poll::Pending,
+            };
+        }
+        fn poll_channels(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<Envelope<M>>> {
+            return self.receiver.poll_recv(cx);
+        }
+        async fn recv_envelope(&mut self) -> Option<Envelope<M>> {
+            return std::future::poll_fn(|cx| self.poll_envelope(cx)).await;
+        }
+        async fn recv_until(
+            &mut self,
+            deadline: ::tokio::time::Instant,
+        ) -> Option<Envelope<M>> {
+            return match ::tokio::time::timeout_at(deadline, self.recv_envelope()).await
+            {
+                Ok(v) => v,
+                Err(_e) => self.try_envelope(),
+            };
+        }
+        #[allow(dead_code)]
+        fn len(&self) -> usize {
+            return self.receiver.len();
+        }
+        fn close(&mut self) {
+            self.receiver.close();
+            self.source = None;
+        }
+    }
+    pub struct Envelope<M>(Letter<M>);
+    enum Letter<M> {
+        User {
+            msg: M,
+            dequeued: Option<::tokio::sync::oneshot::Sender<()>>,
+            panicked: Option<::tokio::sync::oneshot::Sender<ActorPanic>>,
+        },
+        Control(ControlMsg),
+    }
+    enum ControlMsg {
+        Stop,
+        Flush(::tokio::sync::oneshot::Sender<()>),
+    }
+    impl<M> Envelope<M> {
+        fn new(msg: M) -> Self {
+            return Self(Letter::User {
+                msg,
+                dequeued: None,
+                panicked: None,
+            });
+        }
+        fn acked(msg: M, dequeued: ::tokio::sync::oneshot::Sender<()>) -> Self {
+            return Self(Letter::User {
+                msg,
+                dequeued: Some(dequeued),
+                panicked: None,
+            });
+        }
+        #[allow(dead_code)]
+        fn watched(
+            msg: M,
+            panicked: Option<::tokio::sync::oneshot::Sender<ActorPanic>>,
+        ) -> Self {
+            return Self(Letter::User {
+                msg,
+                dequeued: None,
+                panicked,
+            });
+        }
+        fn control(control: ControlMsg) -> Self {
+            return Self(Letter::Control(control));
+        }
+        fn open(self) -> Letter<M> {
+            return match self.0 {
+                Letter::User { msg, dequeued, panicked } => {
+                    if let Some(dequeued) = dequeued {
+                        let _ = dequeued.send(());
+                    }
+                    Letter::User {
+                        msg,
+                        dequeued: None,
+                        panicked,
+                    }
+                }
+                control => control,
+            };
+        }
+        #[allow(dead_code)]
+        fn into_msg(self) -> Option<M> {
+            return match self.open() {
+                Letter::User { msg, .. } => Some(msg),
+                Letter::Control(_control) => None,
+            };
+        }
+    }
+    impl<M> From<M> for Envelope<M> {
+        fn from(msg: M) -> Self {
+            return Self::new(msg);
+        }
+    }
+    pub enum CloseReason {
+        Exited,
+        Idle,
+        Stopped,
+        Stopping,
+        StartFailed,
+        Failed,
+        Panicked,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for CloseReason {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::write_str(
+                f,
+                match self {
+                    CloseReason::Exited => "Exited",
+                    CloseReason::Idle => "Idle",
+                    CloseReason::Stopped => "Stopped",
+                    CloseReason::Stopping => "Stopping",
+                    CloseReason::StartFailed => "StartFailed",
+                    CloseReason::Failed => "Failed",
+                    CloseReason::Panicked => "Panicked",
+                },
+            )
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for CloseReason {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for CloseReason {
+        #[inline]
+        fn clone(&self) -> CloseReason {
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for CloseReason {}
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for CloseReason {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for CloseReason {
+        #[inline]
+        fn eq(&self, other: &CloseReason) -> bool {
+            let __self_discr = ::core::intrinsics::discriminant_value(self);
+            let __arg1_discr = ::core::intrinsics::discriminant_value(other);
+            __self_discr == __arg1_discr
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for CloseReason {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {}
+    }
+    pub struct ActorPanic {
+        pub message: String,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for ActorPanic {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_struct_field1_finish(
+                f,
+                "ActorPanic",
+                "message",
+                &&self.message,
+            )
+        }
+    }
+    #[automatically_derived]
+    impl ::core::clone::Clone for ActorPanic {
+        #[inline]
+        fn clone(&self) -> ActorPanic {
+            ActorPanic {
+                message: ::core::clone::Clone::clone(&self.message),
+            }
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for ActorPanic {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for ActorPanic {
+        #[inline]
+        fn eq(&self, other: &ActorPanic) -> bool {
+            self.message == other.message
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for ActorPanic {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {
+            let _: ::core::cmp::AssertParamIsEq<String>;
+        }
+    }
+    impl ActorPanic {
+        fn from_payload(payload: &(dyn std::any::Any + Send)) -> Self {
+            let message = match (
+                payload.downcast_ref::<&str>(),
+                payload.downcast_ref::<String>(),
+            ) {
+                (Some(text), _) => text.to_string(),
+                (None, Some(text)) => text.clone(),
+                (None, None) => "Box<dyn Any>".to_string(),
+            };
+            return Self { message };
+        }
+    }
+    impl std::fmt::Display for ActorPanic {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            return f.write_fmt(format_args!("handler panicked: {0}", self.message));
+        }
+    }
+    impl std::error::Error for ActorPanic {}
+    pub enum ActorError {
+        MailboxClosed { reason: CloseReason },
+        ResponseDropped,
+        InvalidMsgType,
+        EnqueueTimeout,
+        ResponseTimeout {
+            waited: std::time::Duration,
+            limit: std::time::Duration,
+            variant: &'static str,
+        },
+        Rejected(ValidationError),
+        Panicked,
+        WouldDeadlock,
+        RuntimeGone,
+        RespConversion { variant: &'static str },
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for ActorError {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            match self {
+                ActorError::MailboxClosed { reason: __self_0 } => {
+                    ::core::fmt::Formatter::debug_struct_field1_finish(
+                        f,
+                        "MailboxClosed",
+                        "reason",
+                        &__self_0,
+                    )
+                }
+                ActorError::ResponseDropped => {
+                    ::core::fmt::Formatter::write_str(f, "ResponseDropped")
+                }
+                ActorError::InvalidMsgType => {
+                    ::core::fmt::Formatter::write_str(f, "InvalidMsgType")
+                }
+                ActorError::EnqueueTimeout => {
+                    ::core::fmt::Formatter::write_str(f, "EnqueueTimeout")
+                }
+                ActorError::ResponseTimeout {
+                    waited: __self_0,
+                    limit: __self_1,
+                    variant: __self_2,
+                } => {
+                    ::core::fmt::Formatter::debug_struct_field3_finish(
+                        f,
+                        "ResponseTimeout",
+                        "waited",
+                        __self_0,
+                        "limit",
+                        __self_1,
+                        "variant",
+                        &__self_2,
+                    )
+                }
+                ActorError::Rejected(__self_0) => {
+                    ::core::fmt::Formatter::debug_tuple_field1_finish(
+                        f,
+                        "Rejected",
+                        &__self_0,
+                    )
+                }
+                ActorError::Panicked => ::core::fmt::Formatter::write_str(f, "Panicked"),
+                ActorError::WouldDeadlock => {
+                    ::core::fmt::Formatter::write_str(f, "WouldDeadlock")
+                }
+                ActorError::RuntimeGone => {
+                    ::core::fmt::Formatter::write_str(f, "RuntimeGone")
+                }
+                ActorError::RespConversion { variant: __self_0 } => {
+                    ::core::fmt::Formatter::debug_struct_field1_finish(
+                        f,
+                        "RespConversion",
+                        "variant",
+                        &__self_0,
+                    )
+                }
+            }
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for ActorError {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for ActorError {
+        #[inline]
+        fn clone(&self) -> ActorError {
+            let _: ::core::clone::AssertParamIsClone<CloseReason>;
+            let _: ::core::clone::AssertParamIsClone<std::time::Duration>;
+            let _: ::core::clone::AssertParamIsClone<std::time::Duration>;
+            let _: ::core::clone::AssertParamIsClone<&'static str>;
+            let _: ::core::clone::AssertParamIsClone<ValidationError>;
+            let _: ::core::clone::AssertParamIsClone<&'static str>;
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for ActorError {}
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for ActorError {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for ActorError {
+        #[inline]
+        fn eq(&self, other: &ActorError) -> bool {
+            let __self_discr = ::core::intrinsics::discriminant_value(self);
+            let __arg1_discr = ::core::intrinsics::discriminant_value(other);
+            __self_discr == __arg1_discr
+                && match (self, other) {
+                    (
+                        ActorError::MailboxClosed { reason: __self_0 },
+                        ActorError::MailboxClosed { reason: __arg1_0 },
+                    ) => __self_0 == __arg1_0,
+                    (
+                        ActorError::ResponseTimeout {
+                            waited: __self_0,
+                            limit: __self_1,
+                            variant: __self_2,
+                        },
+                        ActorError::ResponseTimeout {
+                            waited: __arg1_0,
+                            limit: __arg1_1,
+                            variant: __arg1_2,
+                        },
+                    ) => {
+                        __self_0 == __arg1_0 && __self_1 == __arg1_1
+                            && __self_2 == __arg1_2
+                    }
+                    (ActorError::Rejected(__self_0), ActorError::Rejected(__arg1_0)) => {
+                        __self_0 == __arg1_0
+                    }
+                    (
+                        ActorError::RespConversion { variant: __self_0 },
+                        ActorError::RespConversion { variant: __arg1_0 },
+                    ) => __self_0 == __arg1_0,
+                    _ => true,
+                }
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for ActorError {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {
+            let _: ::core::cmp::AssertParamIsEq<CloseReason>;
+            let _: ::core::cmp::AssertParamIsEq<std::time::Duration>;
+            let _: ::core::cmp::AssertParamIsEq<std::time::Duration>;
+            let _: ::core::cmp::AssertParamIsEq<&'static str>;
+            let _: ::core::cmp::AssertParamIsEq<ValidationError>;
+            let _: ::core::cmp::AssertParamIsEq<&'static str>;
+        }
+    }
+    impl std::fmt::Display for ActorError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let text = match self {
+                ActorError::MailboxClosed { reason: CloseReason::Exited } => {
+                    "mailbox closed"
+                }
+                ActorError::MailboxClosed { reason: CloseReason::Idle } => {
+                    "mailbox closed after idling"
+                }
+                ActorError::MailboxClosed { reason: CloseReason::Stopped } => {
+                    "mailbox closed by stop"
+                }
+                ActorError::MailboxClosed { reason: CloseReason::Stopping } => {
+                    "mailbox closed, actor stopping"
+                }
+                ActorError::MailboxClosed { reason: CloseReason::StartFailed } => {
+                    "mailbox closed, on_start failed"
+                }
+                ActorError::MailboxClosed { reason: CloseReason::Failed } => {
+                    "mailbox closed, process failed"
+                }
+                ActorError::MailboxClosed { reason: CloseReason::Panicked } => {
+                    "mailbox closed, process panicked"
+                }
+                ActorError::ResponseDropped => "response dropped",
+                ActorError::Panicked => "handler panicked",
+                ActorError::WouldDeadlock => "actor asked itself, that would deadlock",
+                ActorError::RuntimeGone => {
+                    "actor dropped with its runtime, asked from another one"
+                }
+                ActorError::InvalidMsgType => "invalid msg type",
+                ActorError::EnqueueTimeout => "enqueue timed out",
+                ActorError::Rejected(e) => {
+                    return f.write_fmt(format_args!("rejected: {0}", e));
+                }
+                ActorError::RespConversion { variant } => {
+                    return f
+                        .write_fmt(
+                            format_args!("response to {0} failed to convert", variant),
+                        );
+                }
+                ActorError::ResponseTimeout { waited, limit, variant } => {
+                    return f
+                        .write_fmt(
+                            format_args!(
+                                "response to {0} timed out after {1:?} (limit {2:?})",
+                                variant, waited, limit,
+                            ),
+                        );
+                }
+            };
+            return f.write_str(text);
+        }
+    }
+    impl std::error::Error for ActorError {}
+    pub struct ValidationError(pub &'static str);
+    #[automatically_derived]
+    impl ::core::fmt::Debug for ValidationError {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_tuple_field1_finish(
+                f,
+                "ValidationError",
+                &&self.0,
+            )
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for ValidationError {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for ValidationError {
+        #[inline]
+        fn clone(&self) -> ValidationError {
+            let _: ::core::clone::AssertParamIsClone<&'static str>;
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for ValidationError {}
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for ValidationError {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for ValidationError {
+        #[inline]
+        fn eq(&self, other: &ValidationError) -> bool {
+            self.0 == other.0
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for ValidationError {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {
+            let _: ::core::cmp::AssertParamIsEq<&'static str>;
+        }
+    }
+    impl std::fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            return f.write_str(self.0);
+        }
+    }
+    impl std::error::Error for ValidationError {}
+    pub struct ActorId {
+        module: &'static str,
+        actor: &'static str,
+        id: u64,
+        index: Option<usize>,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for ActorId {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_struct_field4_finish(
+                f,
+                "ActorId",
+                "module",
+                &self.module,
+                "actor",
+                &self.actor,
+                "id",
+                &self.id,
+                "index",
+                &&self.index,
+            )
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for ActorId {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for ActorId {
+        #[inline]
+        fn clone(&self) -> ActorId {
+            let _: ::core::clone::AssertParamIsClone<&'static str>;
+            let _: ::core::clone::AssertParamIsClone<&'static str>;
+            let _: ::core::clone::AssertParamIsClone<u64>;
+            let _: ::core::clone::AssertParamIsClone<Option<usize>>;
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for ActorId {}
+    #[automatically_derived]
+    impl ::core::default::Default for ActorId {
+        #[inline]
+        fn default() -> ActorId {
+            ActorId {
+                module: ::core::default::Default::default(),
+                actor: ::core::default::Default::default(),
+                id: ::core::default::Default::default(),
+                index: ::core::default::Default::default(),
+            }
+        }
+    }
+    impl ActorId {
+        fn next(actor: &'static str, index: Option<usize>) -> Self {
+            static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(
+                1,
+            );
+            return Self {
+                module: "exp::counter",
+                actor,
+                id: NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+                index,
+            };
+        }
+        pub fn value(&self) -> u64 {
+            return self.id;
+        }
+        pub fn actor(&self) -> &'static str {
+            return self.actor;
+        }
+        pub fn module(&self) -> &'static str {
+            return self.module;
+        }
+        pub fn index(&self) -> Option<usize> {
+            return self.index;
+        }
+    }
+    impl PartialEq for ActorId {
+        fn eq(&self, other: &Self) -> bool {
+            return (self.module, self.id) == (other.module, other.id);
+        }
+    }
+    impl Eq for ActorId {}
+    impl std::hash::Hash for ActorId {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            (self.module, self.id).hash(state);
+        }
+    }
+    impl PartialOrd for ActorId {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            return Some(self.cmp(other));
+        }
+    }
+    impl Ord for ActorId {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            return (self.module, self.id).cmp(&(other.module, other.id));
+        }
+    }
+    impl std::fmt::Display for ActorId {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            return match self.index {
+                Some(index) => {
+                    f.write_fmt(
+                        format_args!(
+                            "{0}::{1}[{2}]#{3}", self.module, self.actor, index, self.id
+                        ),
+                    )
+                }
+                None => {
+                    f.write_fmt(
+                        format_args!("{0}::{1}#{2}", self.module, self.actor, self.id),
+                    )
+                }
+            };
+        }
+    }
+    pub struct ActorStats {
+        pub processed: u64,
+        pub queued: usize,
+        pub last_msg: Option<::tokio::time::Instant>,
+        pub started: Option<::tokio::time::Instant>,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for ActorStats {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_struct_field4_finish(
+                f,
+                "ActorStats",
+                "processed",
+                &self.processed,
+                "queued",
+                &self.queued,
+                "last_msg",
+                &self.last_msg,
+                "started",
+                &&self.started,
+            )
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for ActorStats {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for ActorStats {
+        #[inline]
+        fn clone(&self) -> ActorStats {
+            let _: ::core::clone::AssertParamIsClone<u64>;
+            let _: ::core::clone::AssertParamIsClone<usize>;
+            let _: ::core::clone::AssertParamIsClone<Option<::tokio::time::Instant>>;
+            let _: ::core::clone::AssertParamIsClone<Option<::tokio::time::Instant>>;
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for ActorStats {}
+    #[automatically_derived]
+    impl ::core::default::Default for ActorStats {
+        #[inline]
+        fn default() -> ActorStats {
+            ActorStats {
+                processed: ::core::default::Default::default(),
+                queued: ::core::default::Default::default(),
+                last_msg: ::core::default::Default::default(),
+                started: ::core::default::Default::default(),
+            }
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for ActorStats {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for ActorStats {
+        #[inline]
+        fn eq(&self, other: &ActorStats) -> bool {
+            self.processed == other.processed && self.queued == other.queued
+                && self.last_msg == other.last_msg && self.started == other.started
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for ActorStats {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {
+            let _: ::core::cmp::AssertParamIsEq<u64>;
+            let _: ::core::cmp::AssertParamIsEq<usize>;
+            let _: ::core::cmp::AssertParamIsEq<Option<::tokio::time::Instant>>;
+            let _: ::core::cmp::AssertParamIsEq<Option<::tokio::time::Instant>>;
+        }
+    }
+    impl ActorStats {
+        pub fn uptime(&self) -> std::time::Duration {
+            return self
+                .started
+                .map_or(std::time::Duration::ZERO, |started| started.elapsed());
+        }
+    }
+    pub struct RouteInfo {
+        pub variant: &'static str,
+        pub fields: &'static [(&'static str, &'static str)],
+        pub resp: Option<&'static str>,
+        pub stream: bool,
+        pub sink: bool,
+        pub timeout_ms: Option<u64>,
+        pub bounded: Option<usize>,
+        pub latest_key: Option<&'static str>,
+        pub deprecated: bool,
+        pub internal: bool,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for RouteInfo {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            let names: &'static _ = &[
+                "variant",
+                "fields",
+                "resp",
+                "stream",
+                "sink",
+                "timeout_ms",
+                "bounded",
+                "latest_key",
+                "deprecated",
+                "internal",
+            ];
+            let values: &[&dyn ::core::fmt::Debug] = &[
+                &self.variant,
+                &self.fields,
+                &self.resp,
+                &self.stream,
+                &self.sink,
+                &self.timeout_ms,
+                &self.bounded,
+                &self.latest_key,
+                &self.deprecated,
+                &&self.internal,
+            ];
+            ::core::fmt::Formatter::debug_struct_fields_finish(
+                f,
+                "RouteInfo",
+                names,
+                values,
+            )
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for RouteInfo {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for RouteInfo {
+        #[inline]
+        fn clone(&self) -> RouteInfo {
+            let _: ::core::clone::AssertParamIsClone<&'static str>;
+            let _: ::core::clone::AssertParamIsClone<
+                &'static [(&'static str, &'static str)],
+            >;
+            let _: ::core::clone::AssertParamIsClone<Option<&'static str>>;
+            let _: ::core::clone::AssertParamIsClone<bool>;
+            let _: ::core::clone::AssertParamIsClone<Option<u64>>;
+            let _: ::core::clone::AssertParamIsClone<Option<usize>>;
+            let _: ::core::clone::AssertParamIsClone<Option<&'static str>>;
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for RouteInfo {}
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for RouteInfo {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for RouteInfo {
+        #[inline]
+        fn eq(&self, other: &RouteInfo) -> bool {
+            self.stream == other.stream && self.sink == other.sink
+                && self.deprecated == other.deprecated && self.internal == other.internal
+                && self.variant == other.variant && self.fields == other.fields
+                && self.resp == other.resp && self.timeout_ms == other.timeout_ms
+                && self.bounded == other.bounded && self.latest_key == other.latest_key
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for RouteInfo {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {
+            let _: ::core::cmp::AssertParamIsEq<&'static str>;
+            let _: ::core::cmp::AssertParamIsEq<&'static [(&'static str, &'static str)]>;
+            let _: ::core::cmp::AssertParamIsEq<Option<&'static str>>;
+            let _: ::core::cmp::AssertParamIsEq<bool>;
+            let _: ::core::cmp::AssertParamIsEq<Option<u64>>;
+            let _: ::core::cmp::AssertParamIsEq<Option<usize>>;
+            let _: ::core::cmp::AssertParamIsEq<Option<&'static str>>;
+        }
+    }
+    struct ActorShared {
+        id: ActorId,
+        close_reason: std::sync::atomic::AtomicU8,
+        restarts: std::sync::atomic::AtomicUsize,
+        handles: std::sync::atomic::AtomicUsize,
+        stopping: std::sync::atomic::AtomicBool,
+        stop: std::sync::Mutex<StopState>,
+        abandoned: std::sync::atomic::AtomicUsize,
+        panic: std::sync::Mutex<Option<ActorPanic>>,
+        ready: std::sync::Mutex<
+            Option<
+                ::tokio::sync::oneshot::Sender<
+                    Result<(), Box<dyn std::error::Error + Send + Sync>>,
+                >,
+            >,
+        >,
+    }
+    #[automatically_derived]
+    impl ::core::default::Default for ActorShared {
+        #[inline]
+        fn default() -> ActorShared {
+            ActorShared {
+                id: ::core::default::Default::default(),
+                close_reason: ::core::default::Default::default(),
+                restarts: ::core::default::Default::default(),
+                handles: ::core::default::Default::default(),
+                stopping: ::core::default::Default::default(),
+                stop: ::core::default::Default::default(),
+                abandoned: ::core::default::Default::default(),
+                panic: ::core::default::Default::default(),
+                ready: ::core::default::Default::default(),
+            }
+        }
+    }
+    trait IntoStarted {
+        fn into_started(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    }
+    impl IntoStarted for () {
+        fn into_started(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            return Ok(());
+        }
+    }
+    impl<E: Into<Box<dyn std::error::Error + Send + Sync>>> IntoStarted
+    for Result<(), E> {
+        fn into_started(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            return self.map_err(Into::into);
+        }
+    }
+    enum StopState {
+        #[default]
+        Running,
+        Requested {
+            deadline: ::tokio::time::Instant,
+            done: Vec<::tokio::sync::oneshot::Sender<usize>>,
+        },
+        Exited,
+    }
+    #[automatically_derived]
+    impl ::core::default::Default for StopState {
+        #[inline]
+        fn default() -> StopState {
+            Self::Running
+        }
+    }
+    struct StopGuard(std::sync::Arc<ActorShared>);
+    impl Drop for StopGuard {
+        fn drop(&mut self) {
+            let state = match self.0.stop.lock() {
+                Ok(mut stop) => std::mem::replace(&mut *stop, StopState::Exited),
+                Err(_e) => return,
+            };
+            if let StopState::Requested { done, .. } = state {
+                let abandoned = self
+                    .0
+                    .abandoned
+                    .load(std::sync::atomic::Ordering::SeqCst);
+                for done in done {
+                    let _ = done.send(abandoned);
+                }
+            }
+        }
+    }
+    impl ActorShared {
+        fn close(&self, reason: CloseReason) {
+            self.close_reason.store(reason as u8, std::sync::atomic::Ordering::SeqCst);
+        }
+        #[allow(dead_code)]
+        fn ready(
+            &self,
+        ) -> ::tokio::sync::oneshot::Receiver<
+            Result<(), Box<dyn std::error::Error + Send + Sync>>,
+        > {
+            let (s, r) = ::tokio::sync::oneshot::channel();
+            *self.ready.lock().unwrap() = Some(s);
+            return r;
+        }
+        fn started(&self, result: Result<(), Box<dyn std::error::Error + Send + Sync>>) {
+            if let Some(ready) = self.ready.lock().unwrap().take() {
+                let _ = ready.send(result);
+            }
+        }
+        fn close_reason(&self) -> CloseReason {
+            match self.close_reason.load(std::sync::atomic::Ordering::SeqCst) {
+                v if v == CloseReason::Idle as u8 => CloseReason::Idle,
+                v if v == CloseReason::Stopped as u8 => CloseReason::Stopped,
+                v if v == CloseReason::StartFailed as u8 => CloseReason::StartFailed,
+                v if v == CloseReason::Failed as u8 => CloseReason::Failed,
+                v if v == CloseReason::Panicked as u8 => CloseReason::Panicked,
+                _ => CloseReason::Exited,
+            }
+        }
+        fn accepting(&self) -> Result<(), ActorError> {
+            if !self.stopping.load(std::sync::atomic::Ordering::SeqCst) {
+                return Ok(());
+            }
+            let reason = match (self.close_reason(), &*self.stop.lock().unwrap()) {
+                (CloseReason::Failed, _) => CloseReason::Failed,
+                (_, StopState::Exited) => CloseReason::Stopped,
+                _ => CloseReason::Stopping,
+            };
+            return Err(ActorError::MailboxClosed {
+                reason,
+            });
+        }
+        fn request_stop(
+            &self,
+            deadline: ::tokio::time::Instant,
+        ) -> Result<::tokio::sync::oneshot::Receiver<usize>, usize> {
+            let (s, r) = ::tokio::sync::oneshot::channel();
+            let mut stop = self.stop.lock().unwrap();
+            match &mut *stop {
+                StopState::Running => {
+                    *stop = StopState::Requested {
+                        deadline,
+                        done: ::alloc::boxed::box_assume_init_into_vec_unsafe(
+                            ::alloc::intrinsics::write_box_via_move(
+                                ::alloc::boxed::Box::new_uninit(),
+                                [s],
+                            ),
+                        ),
+                    };
+                }
+                StopState::Requested { deadline: earliest, done } => {
+                    *earliest = deadline.min(*earliest);
+                    done.push(s);
+                }
+                StopState::Exited => {
+                    return Err(self.abandoned.load(std::sync::atomic::Ordering::SeqCst));
+                }
+            }
+            self.stopping.store(true, std::sync::atomic::Ordering::SeqCst);
+            return Ok(r);
+        }
+        #[allow(dead_code)]
+        fn panicked(&self, payload: &(dyn std::any::Any + Send)) -> ActorPanic {
+            let panic = ActorPanic::from_payload(payload);
+            if let Ok(mut last) = self.panic.lock() {
+                *last = Some(panic.clone());
+            }
+            return panic;
+        }
+        #[allow(dead_code)]
+        fn failed(&self) {
+            self.close(CloseReason::Failed);
+            let _ = self.request_stop(::tokio::time::Instant::now());
+        }
+        fn stop_deadline(&self) -> Option<::tokio::time::Instant> {
+            if !self.stopping.load(std::sync::atomic::Ordering::Relaxed) {
+                return None;
+            }
+            return match &*self.stop.lock().unwrap() {
+                StopState::Requested { deadline, .. } => Some(*deadline),
+                _ => None,
+            };
+        }
+    }
+    pub struct AskFuture<T> {
+        state: AskState<T>,
+        timeout: Option<AskDeadline>,
+        variant: &'static str,
+        panicked: Option<::tokio::sync::oneshot::Receiver<ActorPanic>>,
+        propagate: Option<fn(ActorPanic) -> T>,
+    }
+    struct AskDeadline {
+        sleep: std::pin::Pin<Box<::tokio::time::Sleep>>,
+        start: ::tokio::time::Instant,
+        limit: std::time::Duration,
+    }
+    impl AskDeadline {
+        fn new(limit: std::time::Duration) -> Self {
+            return Self {
+                sleep: Box::pin(::tokio::time::sleep(limit)),
+                start: ::tokio::time::Instant::now(),
+                limit,
+            };
+        }
+    }
+    impl<T> AskFuture<T> {
+        #[allow(dead_code)]
+        fn variant(mut self, _actor: &'static str, variant: &'static str) -> Self {
+            self.variant = variant;
+            return self;
+        }
+    }
+    enum AskState<T> {
+        Sending(
+            std::pin::Pin<
+                Box<
+                    dyn std::future::Future<
+                        Output = Result<::tokio::sync::oneshot::Receiver<T>, ActorError>,
+                    > + Send,
+                >,
+            >,
+        ),
+        Waiting(::tokio::sync::oneshot::Receiver<T>),
+        Dropped(::tokio::sync::oneshot::Receiver<ActorPanic>),
+        Failed(Option<ActorError>),
+    }
+    impl<T> AskFuture<T> {
+        fn sending(
+            f: std::pin::Pin<
+                Box<
+                    dyn std::future::Future<
+                        Output = Result<::tokio::sync::oneshot::Receiver<T>, ActorError>,
+                    > + Send,
+                >,
+            >,
+        ) -> Self {
+            return Self {
+                state: AskState::Sending(f),
+                timeout: None,
+                variant: "",
+                panicked: None,
+                propagate: None,
+            };
+        }
+        fn waiting(r: ::tokio::sync::oneshot::Receiver<T>) -> Self {
+            return Self {
+                state: AskState::Waiting(r),
+                timeout: None,
+                variant: "",
+                panicked: None,
+                propagate: None,
+            };
+        }
+        fn failed(e: ActorError) -> Self {
+            return Self {
+                state: AskState::Failed(Some(e)),
+                timeout: None,
+                variant: "",
+                panicked: None,
+                propagate: None,
+            };
+        }
+        #[allow(dead_code)]
+        fn default_timeout(mut self, dur: std::time::Duration) -> Self {
+            self.timeout = Some(AskDeadline::new(dur));
+            return self;
+        }
+        #[allow(dead_code)]
+        fn watching(
+            mut self,
+            panicked: ::tokio::sync::oneshot::Receiver<ActorPanic>,
+        ) -> Self {
+            self.panicked = Some(panicked);
+            return self;
+        }
+        #[allow(dead_code)]
+        fn propagating(mut self, propagate: fn(ActorPanic) -> T) -> Self {
+            self.propagate = Some(propagate);
+            return self;
+        }
+        #[allow(dead_code)]
+        async fn enqueued(mut self) -> Self {
+            if let AskState::Sending(f) = &mut self.state {
+                self.state = match f.await {
+                    Ok(r) => AskState::Waiting(r),
+                    Err(e) => AskState::Failed(Some(e)),
+                };
+            }
+            return self;
+        }
+        pub fn with_timeout(mut self, dur: std::time::Duration) -> AskTimeout<T> {
+            self.timeout = None;
+            return AskTimeout {
+                inner: self,
+                deadline: AskDeadline::new(dur),
+            };
+        }
+        fn expire(&mut self, deadline: &AskDeadline) -> ActorError {
+            let state = std::mem::replace(&mut self.state, AskState::Failed(None));
+            drop(state);
+            return ActorError::ResponseTimeout {
+                waited: deadline.start.elapsed(),
+                limit: deadline.limit,
+                variant: self.variant,
+            };
+        }
+        pub fn map_err_into<E: From<ActorError>>(self) -> AskMapErr<T, E> {
+            return AskMapErr {
+                inner: self,
+                _marker: std::marker::PhantomData,
+            };
+        }
+    }
+    impl<T> AskFuture<Box<T>> {
+        pub fn unboxed(self) -> AskUnboxed<T> {
+            return AskUnboxed { inner: self };
+        }
+    }
+    impl<T, E: From<ActorError>> AskFuture<Result<T, E>> {
+        pub fn flatten(self) -> AskFlatten<T, E> {
+            return AskFlatten { inner: self };
+        }
+    }
+    impl<T> std::future::Future for AskFuture<T> {
+        type Output = Result<T, ActorError>;
+        fn poll(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Self::Output> {
+            let this = self.get_mut();
+            loop {
+                match &mut this.state {
+                    AskState::Sending(f) => {
+                        match f.as_mut().poll(cx) {
+                            std::task::Poll::Ready(Ok(r)) => {
+                                this.state = AskState::Waiting(r);
+                            }
+                            std::task::Poll::Ready(Err(e)) => {
+                                this.state = AskState::Failed(Some(e));
+                            }
+                            std::task::Poll::Pending => break,
+                        }
+                    }
+                    AskState::Waiting(r) => {
+                        match std::pin::Pin::new(r).poll(cx) {
+                            std::task::Poll::Ready(Ok(v)) => {
+                                return std::task::Poll::Ready(Ok(v));
+                            }
+                            std::task::Poll::Ready(Err(_e)) => {
+                                match this.panicked.take() {
+                                    Some(panicked) => this.state = AskState::Dropped(panicked),
+                                    None => {
+                                        return std::task::Poll::Ready(
+                                            Err(ActorError::ResponseDropped),
+                                        );
+                                    }
+                                }
+                            }
+                            std::task::Poll::Pending => break,
+                        }
+                    }
+                    AskState::Dropped(panicked) => {
+                        match std::pin::Pin::new(panicked).poll(cx) {
+                            std::task::Poll::Ready(Ok(panic)) => {
+                                return std::task::Poll::Ready(
+                                    match this.propagate {
+                                        Some(propagate) => Ok(propagate(panic)),
+                                        None => Err(ActorError::Panicked),
+                                    },
+                                );
+                            }
+                            std::task::Poll::Ready(Err(_e)) => {
+                                return std::task::Poll::Ready(
+                                    Err(ActorError::ResponseDropped),
+                                );
+                            }
+                            std::task::Poll::Pending => break,
+                        }
+                    }
+                    AskState::Failed(e) => {
+                        return std::task::Poll::Ready(
+                            Err(e.take().expect("AskFuture polled after completion")),
+                        );
+                    }
+                }
+            }
+            if let Some(deadline) = &mut this.timeout {
+                if deadline.sleep.as_mut().poll(cx).is_ready() {
+                    let deadline = this.timeout.take().unwrap();
+                    return std::task::Poll::Ready(Err(this.expire(&deadline)));
+                }
+            }
+            return std::task::Poll::Pending;
+        }
+    }
+    pub struct AskTimeout<T> {
+        inner: AskFuture<T>,
+        deadline: AskDeadline,
+    }
+    impl<T> std::future::Future for AskTimeout<T> {
+        type Output = Result<T, ActorError>;
+        fn poll(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Self::Output> {
+            let this = self.get_mut();
+            if let std::task::Poll::Ready(v) = std::pin::Pin::new(&mut this.inner)
+                .poll(cx)
+            {
+                return std::task::Poll::Ready(v);
+            }
+            return this
+                .deadline
+                .sleep
+                .as_mut()
+                .poll(cx)
+                .map(|_| Err(this.inner.expire(&this.deadline)));
+        }
+    }
+    pub struct AskMapErr<T, E> {
+        inner: AskFuture<T>,
+        _marker: std::marker::PhantomData<fn() -> E>,
+    }
+    impl<T, E: From<ActorError>> std::future::Future for AskMapErr<T, E> {
+        type Output = Result<T, E>;
+        fn poll(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Self::Output> {
+            return std::pin::Pin::new(&mut self.get_mut().inner)
+                .poll(cx)
+                .map(|v| v.map_err(E::from));
+        }
+    }
+    pub struct AskFlatten<T, E> {
+        inner: AskFuture<Result<T, E>>,
+    }
+    impl<T, E: From<ActorError>> std::future::Future for AskFlatten<T, E> {
+        type Output = Result<T, E>;
+        fn poll(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Self::Output> {
+            return std::pin::Pin::new(&mut self.get_mut().inner)
+                .poll(cx)
+                .map(|v| match v {
+                    Ok(v) => v,
+                    Err(e) => Err(E::from(e)),
+                });
+        }
+    }
+    pub struct AskUnboxed<T> {
+        inner: AskFuture<Box<T>>,
+    }
+    impl<T> std::future::Future for AskUnboxed<T> {
+        type Output = Result<T, ActorError>;
+        fn poll(
+            self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Self::Output> {
+            return std::pin::Pin::new(&mut self.get_mut().inner)
+                .poll(cx)
+                .map(|v| v.map(|boxed| *boxed));
+        }
+    }
+    #[must_use = "the caller waits until `ack()` is called"]
+    pub struct Ack {
+        sender: ::tokio::sync::oneshot::Sender<()>,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for Ack {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_struct_field1_finish(
+                f,
+                "Ack",
+                "sender",
+                &&self.sender,
+            )
+        }
+    }
+    #[must_use = "the caller waits until `respond_with(..)` is called"]
+    pub struct Responder<I, T> {
+        sender: ::tokio::sync::oneshot::Sender<T>,
+        convert: fn(I) -> T,
+    }
+    impl<I, T> Responder<I, T> {
+        pub fn respond_with(self, internal: I) -> Result<(), T> {
+            return self.sender.send((self.convert)(internal));
+        }
+        pub fn send(self, v: T) -> Result<(), T> {
+            return self.sender.send(v);
+        }
+    }
+    impl Ack {
+        pub fn ack(self) {
+            let _ = self.sender.send(());
+        }
+        pub fn send(self, v: ()) -> Result<(), ()> {
+            return self.sender.send(v);
+        }
+    }
+    pub struct Emitter<E> {
+        sender: std::sync::Arc<
+            std::sync::Mutex<Option<::tokio::sync::broadcast::Sender<E>>>,
+        >,
+    }
+    impl<E> Clone for Emitter<E> {
+        fn clone(&self) -> Self {
+            return Self {
+                sender: self.sender.clone(),
+            };
+        }
+    }
+    impl<E: Clone> Emitter<E> {
+        pub fn new(capacity: usize) -> Self {
+            let (sender, _receiver) = ::tokio::sync::broadcast::channel(capacity);
+            return Self {
+                sender: std::sync::Arc::new(std::sync::Mutex::new(Some(sender))),
+            };
+        }
+        pub fn emit(&self, event: E) -> usize {
+            return match &*self.sender.lock().unwrap() {
+                Some(sender) => sender.send(event).unwrap_or(0),
+                None => 0,
+            };
+        }
+        pub fn subscribe(&self) -> ::tokio::sync::broadcast::Receiver<E> {
+            return match &*self.sender.lock().unwrap() {
+                Some(sender) => sender.subscribe(),
+                None => ::tokio::sync::broadcast::channel(1).1,
+            };
+        }
+        fn close(&self) {
+            self.sender.lock().unwrap().take();
+        }
+    }
+    pub struct EventBus<E> {
+        subscribers: std::sync::Arc<std::sync::Mutex<EventSubscribers<E>>>,
+    }
+    type EventDelivery<E> = Box<dyn Fn(&E) -> bool + Send + Sync>;
+    struct EventSubscribers<E> {
+        next: u64,
+        list: Vec<(SubscriptionId, EventDelivery<E>)>,
+    }
+    pub struct SubscriptionId(u64);
+    #[automatically_derived]
+    impl ::core::fmt::Debug for SubscriptionId {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_tuple_field1_finish(
+                f,
+                "SubscriptionId",
+                &&self.0,
+            )
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for SubscriptionId {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for SubscriptionId {
+        #[inline]
+        fn clone(&self) -> SubscriptionId {
+            let _: ::core::clone::AssertParamIsClone<u64>;
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for SubscriptionId {}
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for SubscriptionId {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for SubscriptionId {
+        #[inline]
+        fn eq(&self, other: &SubscriptionId) -> bool {
+            self.0 == other.0
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for SubscriptionId {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {
+            let _: ::core::cmp::AssertParamIsEq<u64>;
+        }
+    }
+    #[automatically_derived]
+    impl ::core::hash::Hash for SubscriptionId {
+        #[inline]
+        fn hash<__H: ::core::hash::Hasher>(&self, state: &mut __H) {
+            ::core::hash::Hash::hash(&self.0, state)
+        }
+    }
+    impl<E> Clone for EventBus<E> {
+        fn clone(&self) -> Self {
+            return Self {
+                subscribers: self.subscribers.clone(),
+            };
+        }
+    }
+    impl<E> Default for EventBus<E> {
+        fn default() -> Self {
+            return Self::new();
+        }
+    }
+    impl<E> EventBus<E> {
+        pub fn new() -> Self {
+            return Self {
+                subscribers: std::sync::Arc::new(
+                    std::sync::Mutex::new(EventSubscribers {
+                        next: 0,
+                        list: ::alloc::vec::Vec::new(),
+                    }),
+                ),
+            };
+        }
+        pub fn subscribe<M, S: EventSink<M>>(
+            &self,
+            sink: &S,
+            map: impl Fn(&E) -> Option<M> + Send + Sync + 'static,
+        ) -> SubscriptionId {
+            let sink = sink.clone();
+            let delivery: EventDelivery<E> = Box::new(move |event| {
+                if sink.handle_count() <= 1 {
+                    return false;
+                }
+                return match map(event) {
+                    Some(msg) => {
+                        !#[allow(non_exhaustive_omitted_patterns)]
+                        match sink.try_tell(msg) {
+                            Err(ActorError::MailboxClosed { .. }) => true,
+                            _ => false,
+                        }
+                    }
+                    None => true,
+                };
+            });
+            let mut subscribers = self.subscribers.lock().unwrap();
+            let id = SubscriptionId(subscribers.next);
+            subscribers.next += 1;
+            subscribers.list.push((id, delivery));
+            return id;
+        }
+        pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+            let mut subscribers = self.subscribers.lock().unwrap();
+            let before = subscribers.list.len();
+            subscribers.list.retain(|(v, _delivery)| *v != id);
+            return subscribers.list.len() < before;
+        }
+        pub fn emit(&self, event: E) -> usize {
+            let mut subscribers = self.subscribers.lock().unwrap();
+            subscribers.list.retain(|(_id, delivery)| delivery(&event));
+            return subscribers.list.len();
+        }
+        pub fn subscriber_count(&self) -> usize {
+            return self.subscribers.lock().unwrap().list.len();
+        }
+    }
+    pub trait EventSink<M>: Clone + Send + Sync + 'static {
+        fn try_tell(&self, msg: M) -> Result<(), ActorError>;
+        fn handle_count(&self) -> usize;
+    }
+    pub trait Handles<R> {
+        type Response;
+        fn call(&self, req: R) -> AskFuture<Self::Response>;
+    }
+    pub trait AnyActor: Send + Sync {
+        fn id(&self) -> ActorId;
+        fn is_closed(&self) -> bool;
+        fn stop(&self);
+        fn ping(
+            &self,
+        ) -> std::pin::Pin<
+            Box<
+                dyn std::future::Future<
+                    Output = Result<std::time::Duration, ActorError>,
+                > + Send + '_,
+            >,
+        >;
+    }
+    pub trait MsgSource<M>: Send + 'static {
+        fn next_msg(
+            &mut self,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Option<M>> + Send + '_>>;
+    }
+    impl<M: Send + 'static> MsgSource<M> for ::tokio::sync::mpsc::Receiver<M> {
+        fn next_msg(
+            &mut self,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Option<M>> + Send + '_>,
+        > {
+            return Box::pin(self.recv());
+        }
+    }
+    impl<M: Send + 'static> MsgSource<M> for ::tokio::sync::mpsc::UnboundedReceiver<M> {
+        fn next_msg(
+            &mut self,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Option<M>> + Send + '_>,
+        > {
+            return Box::pin(self.recv());
+        }
+    }
+    trait MailboxFeed<M>: Send {
+        fn poll_feed(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<M>>;
+    }
+    struct SourceFeed<M, S> {
+        next: std::pin::Pin<
+            Box<dyn std::future::Future<Output = (Option<M>, S)> + Send>,
+        >,
+    }
+    impl<M: Send + 'static, S: MsgSource<M>> SourceFeed<M, S> {
+        fn new(source: S) -> Self {
+            return Self { next: Self::next(source) };
+        }
+        fn next(
+            mut source: S,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = (Option<M>, S)> + Send>,
+        > {
+            return Box::pin(async move {
+                let msg = source.next_msg().await;
+                return (msg, source);
+            });
+        }
+    }
+    impl<M: Send + 'static, S: MsgSource<M>> MailboxFeed<M> for SourceFeed<M, S> {
+        fn poll_feed(
+            &mut self,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Option<M>> {
+            let (msg, source) = match self.next.as_mut().poll(cx) {
+                std::task::Poll::Ready(v) => v,
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            };
+            if msg.is_some() {
+                self.next = Self::next(source);
+            }
+            return std::task::Poll::Ready(msg);
+        }
+    }
+    impl<M: Clone + Send + 'static> MsgSource<M>
+    for ::tokio::sync::broadcast::Receiver<M> {
+        fn next_msg(
+            &mut self,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Option<M>> + Send + '_>,
+        > {
+            return Box::pin(async move {
+                loop {
+                    match self.recv().await {
+                        Ok(v) => return Some(v),
+                        Err(::tokio::sync::broadcast::error::RecvError::Lagged(_n)) => {
+                            continue;
+                        }
+                        Err(::tokio::sync::broadcast::error::RecvError::Closed) => {
+                            return None;
+                        }
+                    }
+                }
+            });
+        }
+    }
+    pub trait PipeSink<M>: Clone + Send + Sync + 'static {
+        fn pipe_send(
+            &self,
+            msg: M,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<(), ActorError>> + Send + '_>,
+        >;
+    }
+    pub struct PipeStats {
+        pub items: u64,
+        pub errors: u64,
+        pub sink_closed: bool,
+    }
+    #[automatically_derived]
+    impl ::core::fmt::Debug for PipeStats {
+        #[inline]
+        fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+            ::core::fmt::Formatter::debug_struct_field3_finish(
+                f,
+                "PipeStats",
+                "items",
+                &self.items,
+                "errors",
+                &self.errors,
+                "sink_closed",
+                &&self.sink_closed,
+            )
+        }
+    }
+    #[automatically_derived]
+    #[doc(hidden)]
+    unsafe impl ::core::clone::TrivialClone for PipeStats {}
+    #[automatically_derived]
+    impl ::core::clone::Clone for PipeStats {
+        #[inline]
+        fn clone(&self) -> PipeStats {
+            let _: ::core::clone::AssertParamIsClone<u64>;
+            let _: ::core::clone::AssertParamIsClone<bool>;
+            *self
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::Copy for PipeStats {}
+    #[automatically_derived]
+    impl ::core::default::Default for PipeStats {
+        #[inline]
+        fn default() -> PipeStats {
+            PipeStats {
+                items: ::core::default::Default::default(),
+                errors: ::core::default::Default::default(),
+                sink_closed: ::core::default::Default::default(),
+            }
+        }
+    }
+    #[automatically_derived]
+    impl ::core::marker::StructuralPartialEq for PipeStats {}
+    #[automatically_derived]
+    impl ::core::cmp::PartialEq for PipeStats {
+        #[inline]
+        fn eq(&self, other: &PipeStats) -> bool {
+            self.items == other.items && self.errors == other.errors
+                && self.sink_closed == other.sink_closed
+        }
+    }
+    #[automatically_derived]
+    impl ::core::cmp::Eq for PipeStats {
+        #[doc(hidden)]
+        #[coverage(off)]
+        fn assert_fields_are_eq(&self) {
+            let _: ::core::cmp::AssertParamIsEq<u64>;
+            let _: ::core::cmp::AssertParamIsEq<bool>;
+        }
+    }
+}
+fn main() {}
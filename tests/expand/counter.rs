@@ -0,0 +1,29 @@
+use tokio_actor::actors;
+
+#[actors]
+mod counter {
+    pub enum CounterMsg {
+        Add { n: u64, resp: u64 },
+        Reset,
+    }
+
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { n, resp } => {
+                    self.total += n;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+                CounterMsg::Reset => self.total = 0,
+            }
+        }
+    }
+}
+
+fn main() {}
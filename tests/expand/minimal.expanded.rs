@@ -0,0 +1,67 @@
+use tokio_actor::actors;
+mod counter {
+    pub enum CounterMsg {
+        Add { by: u64, resp: Option<::tokio::sync::oneshot::Sender<u64>> },
+        Reset { resp: Option<::tokio::sync::oneshot::Sender<()>> },
+        Log(String),
+    }
+    pub struct Counter {
+        pub total: u64,
+    }
+    impl Counter {
+        pub async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { by, resp } => {
+                    self.total += by;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+                CounterMsg::Reset { resp } => {
+                    self.total = 0;
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+                CounterMsg::Log(_line) => {}
+            }
+        }
+    }
+    pub trait ActorRequest {
+        type Msg;
+        type Response;
+        const VARIANT: &'static str;
+        fn into_msg(
+            self,
+            resp: ::tokio::sync::oneshot::Sender<Self::Response>,
+        ) -> Self::Msg;
+    }
+    pub struct CounterAddRequest {
+        pub by: u64,
+    }
+    #[allow(deprecated)]
+    impl ActorRequest for CounterAddRequest {
+        type Msg = CounterMsg;
+        type Response = u64;
+        const VARIANT: &'static str = "CounterMsg::Add";
+        fn into_msg(self, resp: ::tokio::sync::oneshot::Sender<u64>) -> CounterMsg {
+            return CounterMsg::Add {
+                by: self.by,
+                resp: Some(resp),
+            };
+        }
+    }
+    pub struct CounterResetRequest {}
+    #[allow(deprecated)]
+    impl ActorRequest for CounterResetRequest {
+        type Msg = CounterMsg;
+        type Response = ();
+        const VARIANT: &'static str = "CounterMsg::Reset";
+        fn into_msg(self, resp: ::tokio::sync::oneshot::Sender<()>) -> CounterMsg {
+            return CounterMsg::Reset {
+                resp: Some(resp),
+            };
+        }
+    }
+}
+fn main() {}
@@ -0,0 +1,38 @@
+use tokio_actor::actors;
+
+// only the `resp` rewrite and a request struct per ask variant, nothing else
+#[actors(minimal)]
+mod counter {
+    pub enum CounterMsg {
+        Add { by: u64, resp: u64 },
+        Reset { resp: () },
+        Log(String),
+    }
+
+    // left as written, no handle or run loop is generated for it
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        pub async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { by, resp } => {
+                    self.total += by;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+                CounterMsg::Reset { resp } => {
+                    self.total = 0;
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+                CounterMsg::Log(_line) => {}
+            }
+        }
+    }
+}
+
+fn main() {}
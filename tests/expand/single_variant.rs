@@ -0,0 +1,28 @@
+use tokio_actor::actors;
+
+// with one variant every match on the msg is exhaustive, no `_` arm is generated
+#[actors]
+mod ping {
+    pub enum PingMsg {
+        Ping { resp: u64 },
+    }
+
+    pub struct Ping {
+        pub count: u64,
+    }
+
+    impl Ping {
+        async fn process(&mut self, msg: PingMsg) {
+            match msg {
+                PingMsg::Ping { resp } => {
+                    self.count += 1;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.count);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn main() {}
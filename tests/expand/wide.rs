@@ -0,0 +1,115 @@
+use tokio_actor::actors;
+
+// 16 ask variants, see `tests/expansion_size.rs`
+#[actors]
+mod wide {
+    pub enum WideMsg {
+        Get0 { n: u64, resp: u64 },
+        Get1 { n: u64, resp: u64 },
+        Get2 { n: u64, resp: u64 },
+        Get3 { n: u64, resp: u64 },
+        Get4 { n: u64, resp: u64 },
+        Get5 { n: u64, resp: u64 },
+        Get6 { n: u64, resp: u64 },
+        Get7 { n: u64, resp: u64 },
+        Get8 { n: u64, resp: u64 },
+        Get9 { n: u64, resp: u64 },
+        Get10 { n: u64, resp: u64 },
+        Get11 { n: u64, resp: u64 },
+        Get12 { n: u64, resp: u64 },
+        Get13 { n: u64, resp: u64 },
+        Get14 { n: u64, resp: u64 },
+        Get15 { n: u64, resp: u64 },
+    }
+
+    pub struct Wide {}
+
+    impl Wide {
+        async fn process(&mut self, msg: WideMsg) {
+            match msg {
+                WideMsg::Get0 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 0);
+                    }
+                }
+                WideMsg::Get1 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 1);
+                    }
+                }
+                WideMsg::Get2 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 2);
+                    }
+                }
+                WideMsg::Get3 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 3);
+                    }
+                }
+                WideMsg::Get4 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 4);
+                    }
+                }
+                WideMsg::Get5 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 5);
+                    }
+                }
+                WideMsg::Get6 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 6);
+                    }
+                }
+                WideMsg::Get7 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 7);
+                    }
+                }
+                WideMsg::Get8 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 8);
+                    }
+                }
+                WideMsg::Get9 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 9);
+                    }
+                }
+                WideMsg::Get10 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 10);
+                    }
+                }
+                WideMsg::Get11 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 11);
+                    }
+                }
+                WideMsg::Get12 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 12);
+                    }
+                }
+                WideMsg::Get13 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 13);
+                    }
+                }
+                WideMsg::Get14 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 14);
+                    }
+                }
+                WideMsg::Get15 { n, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(n + 15);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+// what one more variant costs in the expansion, as pinned by `tests/expand`. the
+// per-variant methods only check the variant and plug in a channel, the sending
+// and waiting is shared per actor; duplicating it again shows up here
+fn lines(case: &str) -> usize {
+    let path = format!(
+        "{}/tests/expand/{}.expanded.rs",
+        env!("CARGO_MANIFEST_DIR"),
+        case
+    );
+    std::fs::read_to_string(path).unwrap().lines().count()
+}
+
+#[test]
+fn each_ask_variant_stays_small() {
+    // both with only ask variants, one against 16
+    let per_variant = (lines("wide") - lines("single_variant")) / 15;
+    assert!(per_variant <= 100, "{} lines per variant", per_variant);
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::sync::watch;
+use tokio_actor::actors;
+
+#[actors(bounded = 2)]
+mod lights {
+    use tokio::sync::watch;
+
+    pub enum LampMsg {
+        Hold,
+        Toggle(u32),
+        #[latest_only(key = "lamp")]
+        Dim {
+            lamp: u32,
+            level: u8,
+        },
+        Log {
+            resp: Vec<String>,
+        },
+    }
+
+    // stuck on `Hold` until the gate opens, notes every other msg
+    pub struct Lamp {
+        pub gate: watch::Receiver<bool>,
+        pub log: Vec<String>,
+    }
+
+    impl Lamp {
+        async fn process(&mut self, msg: LampMsg) {
+            match msg {
+                LampMsg::Hold => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                }
+                LampMsg::Toggle(lamp) => self.log.push(format!("toggle {}", lamp)),
+                LampMsg::Dim { lamp, level } => self.log.push(format!("dim {} {}", lamp, level)),
+                LampMsg::Log { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.log.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use lights::prelude::*;
+use lights::Lamp;
+
+// a lamp stuck on a `Hold`, its mailbox empty
+async fn stalled() -> (ActorLamp, watch::Sender<bool>) {
+    let (open, gate) = watch::channel(false);
+    let lamp = ActorLamp::spawn(Lamp { gate, log: vec![] });
+    lamp.send_into(LampMsg::Hold).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    (lamp, open)
+}
+
+async fn log(lamp: &mut ActorLamp, open: watch::Sender<bool>) -> Vec<String> {
+    open.send(true).unwrap();
+    within(lamp.log(LampMsg::Log { resp: None })).await.unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn queues_what_fits_and_drops_the_rest() {
+    let (mut lamp, open) = stalled().await;
+    lamp.extend((1..=4).map(LampMsg::Toggle));
+    assert_eq!(log(&mut lamp, open).await, ["toggle 1", "toggle 2"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn coalesces_latest_only_msgs_still_queued() {
+    let (mut lamp, open) = stalled().await;
+    lamp.extend([
+        LampMsg::Dim { lamp: 1, level: 10 },
+        LampMsg::Dim { lamp: 1, level: 20 },
+        LampMsg::Dim { lamp: 1, level: 30 },
+    ]);
+    assert_eq!(lamp.replaced(), 2);
+    assert_eq!(log(&mut lamp, open).await, ["dim 1 30"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_latest_only_msg_a_full_mailbox_dropped_frees_its_key() {
+    let (mut lamp, open) = stalled().await;
+    lamp.extend([
+        LampMsg::Toggle(1),
+        LampMsg::Toggle(2),
+        LampMsg::Dim { lamp: 1, level: 10 },
+    ]);
+    open.send(true).unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // not swallowed by the slot the dropped msg took
+    lamp.extend([LampMsg::Dim { lamp: 1, level: 20 }]);
+    assert_eq!(lamp.replaced(), 0);
+    assert_eq!(
+        log(&mut lamp, open).await,
+        ["toggle 1", "toggle 2", "dim 1 20"]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn leaves_the_rest_alone_once_the_mailbox_closed() {
+    let (open, gate) = watch::channel(true);
+    let mut lamp = ActorLamp::spawn(Lamp { gate, log: vec![] });
+    within(lamp.clone().stop_within(Duration::ZERO))
+        .await
+        .unwrap();
+    drop(open);
+    lamp.extend([LampMsg::Toggle(1), LampMsg::Dim { lamp: 1, level: 10 }]);
+    assert_closed!(lamp.log(LampMsg::Log { resp: None }).await, Stopped);
+    assert_closed!(
+        lamp.send_into(LampMsg::Dim { lamp: 1, level: 20 }).await,
+        Stopped
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+#[path = "../examples/fetcher.rs"]
+#[allow(dead_code)]
+mod example;
+
+use std::time::Duration;
+
+use common::{drain, within};
+use example::fetching::{ActorError, CloseReason, FetchError, LimiterMsg};
+use example::{fetch, spawn};
+use tokio::time::Instant;
+
+const EVERY: Duration = Duration::from_millis(100);
+
+#[tokio::test(start_paused = true)]
+async fn fetches_go_through_the_limiter() {
+    let (mut fetcher, mut limiter) = spawn(EVERY);
+    let start = Instant::now();
+    let asks: Vec<_> = (0..5)
+        .map(|_i| fetcher.fetch(fetch("https://example.com/a")))
+        .collect();
+    for ask in asks {
+        assert_eq!(within(ask).await, Ok("alpha".to_string()));
+    }
+    // the first permit is free, every later one waits for its turn
+    assert_eq!(start.elapsed(), EVERY * 4);
+    let granted = limiter.granted(LimiterMsg::Granted { resp: None });
+    assert_eq!(within(granted).await, Ok(5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn unknown_urls_fail_with_the_handlers_error() {
+    let (mut fetcher, _limiter) = spawn(EVERY);
+    assert_eq!(
+        within(fetcher.fetch(fetch("https://example.com/nope"))).await,
+        Err(FetchError::NotFound("https://example.com/nope".into()))
+    );
+    assert_eq!(
+        within(fetcher.fetch(fetch("https://example.com/b"))).await,
+        Ok("beta".to_string())
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_stopped_limiter_fails_fetches_instead_of_hanging() {
+    let (mut fetcher, limiter) = spawn(EVERY);
+    assert_eq!(drain(limiter.stop_within(EVERY)).await, 0);
+    assert_eq!(
+        within(fetcher.fetch(fetch("https://example.com/a"))).await,
+        Err(FetchError::Actor(ActorError::MailboxClosed {
+            reason: CloseReason::Stopped
+        }))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_stopped_fetcher_refuses_fetches() {
+    let (mut fetcher, _limiter) = spawn(EVERY);
+    assert_eq!(drain(fetcher.stop_within(EVERY)).await, 0);
+    assert_eq!(
+        within(fetcher.fetch(fetch("https://example.com/a"))).await,
+        Err(FetchError::Actor(ActorError::MailboxClosed {
+            reason: CloseReason::Stopped
+        }))
+    );
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::sync::{broadcast, mpsc};
+use tokio_actor::actors;
+
+#[actors]
+mod tally {
+    use tokio::sync::mpsc;
+
+    pub enum TallyMsg {
+        Add(u64),
+        Total { resp: u64 },
+    }
+
+    // for a broadcast receiver, which hands out clones. a clone
+    // doesn't take the asker's reply with it
+    impl Clone for TallyMsg {
+        fn clone(&self) -> Self {
+            match self {
+                TallyMsg::Add(n) => TallyMsg::Add(*n),
+                TallyMsg::Total { .. } => TallyMsg::Total { resp: None },
+            }
+        }
+    }
+
+    // reports its total on `done` when it stops
+    pub struct Tally {
+        pub total: u64,
+        pub done: mpsc::UnboundedSender<u64>,
+    }
+
+    impl Tally {
+        async fn process(&mut self, msg: TallyMsg) {
+            match msg {
+                TallyMsg::Add(n) => self.total += n,
+                TallyMsg::Total { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.done.send(self.total);
+        }
+    }
+}
+
+use tally::prelude::*;
+use tally::Tally;
+
+#[tokio::test(start_paused = true)]
+async fn takes_from_the_receiver_without_holding_a_handle() {
+    let (done, mut stopped) = mpsc::unbounded_channel();
+    let (tx, rx) = mpsc::channel(8);
+    let mut tally = ActorTally::new_from_receiver(rx, Tally { total: 0, done });
+    assert_eq!(tally.handle_count(), 1);
+
+    for n in 1..=4 {
+        tx.send(TallyMsg::Add(n)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(
+        within(tally.total(TallyMsg::Total { resp: None })).await,
+        Ok(10)
+    );
+    assert_eq!(tally.handle_count(), 1);
+
+    // the receiver ending alone doesn't stop the actor, its handle still sends
+    drop(tx);
+    tally.send_into(TallyMsg::Add(5)).await.unwrap();
+    assert_eq!(
+        within(tally.total(TallyMsg::Total { resp: None })).await,
+        Ok(15)
+    );
+
+    drop(tally);
+    assert_eq!(within(stopped.recv()).await, Some(15));
+}
+
+#[tokio::test(start_paused = true)]
+async fn keeps_running_while_the_receiver_is_open() {
+    let (done, mut stopped) = mpsc::unbounded_channel();
+    let (tx, rx) = mpsc::unbounded_channel();
+    drop(ActorTally::new_from_receiver(rx, Tally { total: 0, done }));
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    tx.send(TallyMsg::Add(3)).unwrap();
+    tx.send(TallyMsg::Add(4)).unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(stopped.try_recv().is_err());
+
+    drop(tx);
+    assert_eq!(within(stopped.recv()).await, Some(7));
+}
+
+#[tokio::test(start_paused = true)]
+async fn skips_what_a_broadcast_receiver_lagged_behind_on() {
+    let (done, mut stopped) = mpsc::unbounded_channel();
+    let (tx, rx) = broadcast::channel(2);
+    // sent before the actor first polls, the oldest two are pushed out
+    for n in [1, 2, 3, 4] {
+        let _ = tx.send(TallyMsg::Add(n));
+    }
+    drop(ActorTally::new_from_receiver(rx, Tally { total: 0, done }));
+    drop(tx);
+    assert_eq!(within(stopped.recv()).await, Some(7));
+}
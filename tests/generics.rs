@@ -0,0 +1,123 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+// keeps the last `N` values
+#[actors]
+mod window {
+    pub enum WindowMsg {
+        Push { v: u64, resp: usize },
+        Sum { resp: u64 },
+    }
+
+    pub struct Window<const N: usize> {
+        pub values: Vec<u64>,
+    }
+
+    impl<const N: usize> Window<N> {
+        async fn process(&mut self, msg: WindowMsg) {
+            match msg {
+                WindowMsg::Push { v, resp } => {
+                    if self.values.len() == N {
+                        self.values.remove(0);
+                    }
+                    self.values.push(v);
+                    if let Some(r) = resp {
+                        let _ = r.send(self.values.len());
+                    }
+                }
+                WindowMsg::Sum { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.values.iter().sum());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// `T` of the enum is the struct's, `L` of the enum falls back to its default
+#[actors]
+mod store {
+    pub enum StoreMsg<T = String, L = u8> {
+        Put { v: T, resp: usize },
+        Last { resp: Option<T> },
+        Level { level: L, resp: L },
+    }
+
+    pub struct Store<T: Clone + Send + 'static = String> {
+        pub items: Vec<T>,
+    }
+
+    impl<T: Clone + Send + 'static> Store<T> {
+        async fn process(&mut self, msg: StoreMsg<T>) {
+            match msg {
+                StoreMsg::Put { v, resp } => {
+                    self.items.push(v);
+                    if let Some(r) = resp {
+                        let _ = r.send(self.items.len());
+                    }
+                }
+                StoreMsg::Last { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.items.last().cloned());
+                    }
+                }
+                StoreMsg::Level { level, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(level);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn const_generic_actors_keep_their_parameter() {
+    use window::{ActorWindow, Window, WindowMsg};
+    let mut small: ActorWindow<2> = ActorWindow::spawn(Window { values: vec![] });
+    let mut large = ActorWindow::spawn(Window::<4> { values: vec![] });
+    for v in 1..=4 {
+        let len = within(small.push(WindowMsg::Push { v, resp: None })).await;
+        assert_eq!(len, Ok(v.min(2) as usize));
+        within(large.push(WindowMsg::Push { v, resp: None }))
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        within(small.sum(WindowMsg::Sum { resp: None })).await,
+        Ok(7)
+    );
+    assert_eq!(
+        within(large.sum(WindowMsg::Sum { resp: None })).await,
+        Ok(10)
+    );
+}
+
+#[tokio::test]
+async fn defaulted_type_parameters_apply_when_left_out() {
+    use store::{ActorStore, Store, StoreMsg};
+    let mut names: ActorStore = ActorStore::spawn(Store { items: vec![] });
+    let put = StoreMsg::Put {
+        v: "a".to_string(),
+        resp: None,
+    };
+    assert_eq!(within(names.put(put)).await, Ok(1));
+    let last = within(names.last(StoreMsg::Last { resp: None })).await;
+    assert_eq!(last, Ok(Some("a".to_string())));
+    let level = within(names.level(StoreMsg::Level {
+        level: 3u8,
+        resp: None,
+    }))
+    .await;
+    assert_eq!(level, Ok(3));
+
+    let mut numbers = ActorStore::spawn(Store::<u32> { items: vec![] });
+    within(numbers.put(StoreMsg::Put { v: 7, resp: None }))
+        .await
+        .unwrap();
+    let last = within(numbers.last(StoreMsg::Last { resp: None })).await;
+    assert_eq!(last, Ok(Some(7)));
+}
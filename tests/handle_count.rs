@@ -0,0 +1,72 @@
+mod common;
+
+use common::within;
+use tokio::sync::mpsc;
+use tokio_actor::actors;
+
+#[actors]
+mod leaky {
+    use tokio::sync::mpsc;
+
+    pub enum CacheMsg {
+        Weak { resp: ActorCacheWeak },
+    }
+
+    // reports on `stopped` once its last handle is gone
+    pub struct Cache {
+        pub stopped: mpsc::UnboundedSender<()>,
+    }
+
+    impl Cache {
+        async fn process(&mut self, msg: CacheMsg, ctx: &ActorCacheCtx) {
+            let CacheMsg::Weak { resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(ctx.handle());
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.stopped.send(());
+        }
+    }
+}
+
+use leaky::{ActorCache, Cache, CacheMsg};
+
+#[tokio::test(start_paused = true)]
+async fn follows_clones_and_drops() {
+    let (stopped, mut gone) = mpsc::unbounded_channel();
+    let cache = ActorCache::spawn(Cache { stopped });
+    assert_eq!(cache.handle_count(), 1);
+    let clones: Vec<_> = (0..3).map(|_| cache.clone()).collect();
+    assert_eq!(cache.handle_count(), 4);
+    assert_eq!(clones[0].handle_count(), 4);
+    // a clone held by a task counts until the task is done with it
+    let held = cache.clone();
+    let task = tokio::spawn(async move { held.handle_count() });
+    assert_eq!(within(task).await.unwrap(), 5);
+    drop(clones);
+    assert_eq!(cache.handle_count(), 1);
+
+    drop(cache);
+    assert_eq!(within(gone.recv()).await, Some(()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn weak_handles_are_not_counted() {
+    let (stopped, mut gone) = mpsc::unbounded_channel();
+    let mut cache = ActorCache::spawn(Cache { stopped });
+    let weak = within(cache.weak(CacheMsg::Weak { resp: None }))
+        .await
+        .unwrap();
+    assert_eq!(cache.handle_count(), 1);
+    let upgraded = weak.upgrade().unwrap();
+    assert_eq!(cache.handle_count(), 2);
+    drop(upgraded);
+    assert_eq!(cache.handle_count(), 1);
+
+    // the weak handle alone doesn't keep the actor alive
+    drop(cache);
+    assert_eq!(within(gone.recv()).await, Some(()));
+    assert!(weak.upgrade().is_none());
+}
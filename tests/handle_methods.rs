@@ -0,0 +1,75 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod account {
+    pub enum AccountMsg {
+        Deposit { amount: u64, resp: u64 },
+        Withdraw { amount: u64, resp: Option<u64> },
+        Balance { resp: u64 },
+    }
+
+    pub struct Account {
+        pub balance: u64,
+    }
+
+    impl Account {
+        async fn process(&mut self, msg: AccountMsg) {
+            match msg {
+                AccountMsg::Deposit { amount, resp } => {
+                    self.balance += amount;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.balance);
+                    }
+                }
+                AccountMsg::Withdraw { amount, resp } => {
+                    let left = self.balance.checked_sub(amount);
+                    if let Some(left) = left {
+                        self.balance = left;
+                    }
+                    if let Some(r) = resp {
+                        let _ = r.send(left);
+                    }
+                }
+                AccountMsg::Balance { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.balance);
+                    }
+                }
+            }
+        }
+    }
+
+    // next to the generated methods, checked by the macro
+    impl ActorAccount {
+        pub async fn balance_now(&mut self) -> Result<u64, ActorError> {
+            self.balance(AccountMsg::Balance { resp: None }).await
+        }
+    }
+}
+
+use account::{Account, AccountMsg, ActorAccount};
+
+// anywhere else in the crate, out of the macro's sight
+impl ActorAccount {
+    async fn transfer(&mut self, to: &mut ActorAccount, amount: u64) -> bool {
+        let withdraw = AccountMsg::Withdraw { amount, resp: None };
+        if self.withdraw(withdraw).await.unwrap().is_none() {
+            return false;
+        }
+        let deposit = AccountMsg::Deposit { amount, resp: None };
+        to.deposit(deposit).await.is_ok()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn own_methods_sit_next_to_the_generated_ones() {
+    let mut from = ActorAccount::spawn(Account { balance: 10 });
+    let mut to = ActorAccount::spawn(Account { balance: 0 });
+    assert!(within(from.transfer(&mut to, 7)).await);
+    assert!(!within(from.transfer(&mut to, 7)).await);
+    assert_eq!(within(from.balance_now()).await.unwrap(), 3);
+    assert_eq!(within(to.balance_now()).await.unwrap(), 7);
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod writers {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    pub enum WriterMsg {
+        Write(u64),
+        Pending { resp: usize },
+    }
+
+    // batches writes, flushed whenever the mailbox runs dry
+    pub struct Writer {
+        pub pending: Vec<u64>,
+        pub flushes: Arc<AtomicUsize>,
+        pub flushed: Arc<AtomicUsize>,
+    }
+
+    impl Writer {
+        async fn process(&mut self, msg: WriterMsg) {
+            match msg {
+                WriterMsg::Write(n) => {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    self.pending.push(n);
+                }
+                WriterMsg::Pending { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.pending.len());
+                    }
+                }
+            }
+        }
+
+        async fn on_idle(&mut self) {
+            self.flushes.fetch_add(1, Ordering::SeqCst);
+            self.flushed.fetch_add(self.pending.len(), Ordering::SeqCst);
+            self.pending.clear();
+        }
+    }
+
+    pub enum DebouncedMsg {
+        Write(u64),
+        Seen { resp: u64 },
+    }
+
+    #[actor(idle_debounce = "20ms")]
+    pub struct Debounced {
+        pub seen: u64,
+        pub flushes: Arc<AtomicUsize>,
+    }
+
+    impl Debounced {
+        async fn process(&mut self, msg: DebouncedMsg) {
+            match msg {
+                DebouncedMsg::Write(_n) => self.seen += 1,
+                DebouncedMsg::Seen { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.seen);
+                    }
+                }
+            }
+        }
+
+        async fn on_idle(&mut self) {
+            self.flushes.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+use writers::prelude::*;
+use writers::{Debounced, Writer};
+
+fn counter() -> Arc<AtomicUsize> {
+    Arc::new(AtomicUsize::new(0))
+}
+
+#[tokio::test(start_paused = true)]
+async fn on_idle_runs_once_per_burst() {
+    let (flushes, flushed) = (counter(), counter());
+    let mut writer = ActorWriter::spawn(Writer {
+        pending: vec![],
+        flushes: flushes.clone(),
+        flushed: flushed.clone(),
+    });
+    for n in 0..10 {
+        writer.send_into(WriterMsg::Write(n)).await.unwrap();
+    }
+    // pings are control msgs, not a burst of their own
+    within(writer.ping()).await.unwrap();
+    within(writer.ping()).await.unwrap();
+    assert_eq!(flushes.load(Ordering::SeqCst), 1);
+    assert_eq!(flushed.load(Ordering::SeqCst), 10);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(flushes.load(Ordering::SeqCst), 1);
+
+    for n in 0..3 {
+        writer.send_into(WriterMsg::Write(n)).await.unwrap();
+    }
+    within(writer.ping()).await.unwrap();
+    assert_eq!(flushes.load(Ordering::SeqCst), 2);
+    assert_eq!(flushed.load(Ordering::SeqCst), 13);
+    let pending = within(writer.pending(WriterMsg::Pending { resp: None })).await;
+    assert_eq!(pending, Ok(0));
+}
+
+#[tokio::test(start_paused = true)]
+async fn idle_debounce_waits_for_a_quiet_mailbox() {
+    let flushes = counter();
+    let mut debounced = ActorDebounced::spawn(Debounced {
+        seen: 0,
+        flushes: flushes.clone(),
+    });
+    // each write arrives before the debounce ran out
+    for n in 0..5 {
+        debounced.send_into(DebouncedMsg::Write(n)).await.unwrap();
+        within(debounced.ping()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(flushes.load(Ordering::SeqCst), 0);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(flushes.load(Ordering::SeqCst), 1);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(flushes.load(Ordering::SeqCst), 1);
+    let seen = within(debounced.seen(DebouncedMsg::Seen { resp: None })).await;
+    assert_eq!(seen, Ok(5));
+}
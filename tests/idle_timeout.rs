@@ -0,0 +1,89 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::sync::mpsc;
+use tokio_actor::actors;
+
+#[actors]
+mod sessions {
+    use tokio::sync::mpsc;
+
+    pub enum SessionMsg {
+        Touch { resp: u64 },
+    }
+
+    // reports how many touches it saw when it stops
+    #[actor(idle_timeout = "10m")]
+    pub struct Session {
+        pub touches: u64,
+        pub stopped: mpsc::UnboundedSender<u64>,
+    }
+
+    impl Session {
+        async fn process(&mut self, msg: SessionMsg) {
+            let SessionMsg::Touch { resp } = msg;
+            self.touches += 1;
+            if let Some(r) = resp {
+                let _ = r.send(self.touches);
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.stopped.send(self.touches);
+        }
+    }
+}
+
+use sessions::{ActorError, ActorSession, CloseReason, Session, SessionMsg};
+
+const IDLE: Duration = Duration::from_secs(600);
+
+fn touch() -> SessionMsg {
+    SessionMsg::Touch { resp: None }
+}
+
+fn spawn() -> (ActorSession, mpsc::UnboundedReceiver<u64>) {
+    let (stopped, on_stop) = mpsc::unbounded_channel();
+    let session = ActorSession::spawn(Session {
+        touches: 0,
+        stopped,
+    });
+    (session, on_stop)
+}
+
+#[tokio::test(start_paused = true)]
+async fn stops_once_idle_for_the_timeout() {
+    let (mut session, mut on_stop) = spawn();
+    assert_eq!(within(session.touch(touch())).await, Ok(1));
+
+    tokio::time::sleep(IDLE - Duration::from_secs(1)).await;
+    assert!(on_stop.try_recv().is_err());
+    tokio::time::sleep(Duration::from_secs(2)).await;
+    assert_eq!(within(on_stop.recv()).await, Some(1));
+    assert_closed!(session.touch(touch()).await, Idle);
+    assert_closed!(session.touch_no_wait(touch()).await, Idle);
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_msg_restarts_the_timer() {
+    let (mut session, mut on_stop) = spawn();
+    // ten touches 9 minutes apart keep it alive for 90 minutes
+    for n in 1..=10 {
+        tokio::time::sleep(IDLE - Duration::from_secs(60)).await;
+        assert_eq!(within(session.touch(touch())).await, Ok(n));
+    }
+    assert!(on_stop.try_recv().is_err());
+    tokio::time::sleep(IDLE).await;
+    assert_eq!(within(on_stop.recv()).await, Some(10));
+}
+
+#[tokio::test(start_paused = true)]
+async fn holding_a_handle_does_not_count_as_activity() {
+    let (session, mut on_stop) = spawn();
+    let clone = session.clone();
+    tokio::time::sleep(IDLE + Duration::from_secs(1)).await;
+    assert_eq!(within(on_stop.recv()).await, Some(0));
+    drop((session, clone));
+}
@@ -0,0 +1,59 @@
+use tokio_actor::actors;
+
+#[actors]
+pub mod jobs {
+    pub enum JobMsg {
+        Submit {
+            name: String,
+            resp: u64,
+        },
+        // put back by the retry logic of this crate only
+        #[internal]
+        Requeue {
+            id: u64,
+            resp: usize,
+        },
+    }
+
+    #[derive(Default)]
+    pub struct Job {
+        pub queue: Vec<u64>,
+        pub next_id: u64,
+    }
+
+    impl Job {
+        async fn process(&mut self, msg: JobMsg) {
+            match msg {
+                JobMsg::Submit { name: _, resp } => {
+                    self.next_id += 1;
+                    self.queue.push(self.next_id);
+                    if let Some(r) = resp {
+                        let _ = r.send(self.next_id);
+                    }
+                }
+                JobMsg::Requeue { id, resp } => {
+                    self.queue.push(id);
+                    if let Some(r) = resp {
+                        let _ = r.send(self.queue.len());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// a sibling of `jobs`, sending its internal msgs
+pub mod retry {
+    use crate::jobs::prelude::*;
+    use crate::jobs::JobRequeueRequest;
+
+    // puts `id` back in the queue, returning how long it is now
+    pub async fn requeue(jobs: &mut ActorJob, id: u64) -> Result<usize, ActorError> {
+        jobs.requeue(JobMsg::Requeue { id, resp: None }).await
+    }
+
+    // the same through the request struct
+    pub async fn requeue_request(jobs: &ActorJob, id: u64) -> Result<usize, ActorError> {
+        Handles::call(jobs, JobRequeueRequest { id }).await
+    }
+}
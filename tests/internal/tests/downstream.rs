@@ -0,0 +1,22 @@
+use internal_msgs::jobs::prelude::*;
+use internal_msgs::jobs::Job;
+use internal_msgs::retry;
+
+#[tokio::test]
+async fn internal_msgs_go_through_the_crate_that_owns_them() {
+    let mut jobs = ActorJob::spawn(Job::default());
+    let submit = JobMsg::Submit {
+        name: "report".to_string(),
+        resp: None,
+    };
+    assert_eq!(jobs.submit(submit).await, Ok(1));
+    assert_eq!(retry::requeue(&mut jobs, 1).await, Ok(2));
+    assert_eq!(retry::requeue_request(&jobs, 1).await, Ok(3));
+}
+
+// the methods and request struct of `#[internal]` variants aren't visible here
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
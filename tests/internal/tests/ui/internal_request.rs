@@ -0,0 +1,10 @@
+use internal_msgs::jobs::prelude::*;
+use internal_msgs::jobs::{Job, JobRequeueRequest};
+
+#[tokio::main]
+async fn main() {
+    let mut jobs = ActorJob::spawn(Job::default());
+    let _ = jobs.requeue(JobMsg::Requeue { id: 1, resp: None }).await;
+    let _ = jobs.requeue_no_wait(JobMsg::Requeue { id: 1, resp: None }).await;
+    let _ = JobRequeueRequest { id: 1 };
+}
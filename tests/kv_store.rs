@@ -0,0 +1,61 @@
+mod common;
+
+#[path = "../examples/kv_store.rs"]
+#[allow(dead_code)]
+mod example;
+
+use common::{drain, within};
+use example::kv::{ActorError, ActorKv, CloseReason, Kv, KvMsg};
+use example::{get, set};
+
+fn len() -> KvMsg {
+    KvMsg::Len { resp: None }
+}
+
+#[tokio::test]
+async fn asks_read_their_own_writes() {
+    let mut kv = ActorKv::spawn(Kv::default());
+    assert_eq!(within(kv.set(set("a", "1"))).await, Ok(None));
+    assert_eq!(within(kv.set(set("a", "2"))).await, Ok(Some("1".into())));
+    assert_eq!(within(kv.get(get("a"))).await, Ok(Some("2".into())));
+    assert_eq!(within(kv.get(get("b"))).await, Ok(None));
+    let deleted = kv.delete(KvMsg::Delete {
+        key: "a".into(),
+        resp: None,
+    });
+    assert_eq!(within(deleted).await, Ok(Some("2".into())));
+    assert_eq!(within(kv.len(len())).await, Ok(0));
+}
+
+#[tokio::test]
+async fn tells_are_processed_in_order() {
+    let mut kv = ActorKv::spawn(Kv::default());
+    for i in 0..100 {
+        within(kv.set_no_wait(set(&format!("k{}", i % 10), &i.to_string())))
+            .await
+            .unwrap();
+    }
+    assert_eq!(within(kv.len(len())).await, Ok(10));
+    assert_eq!(within(kv.get(get("k3"))).await, Ok(Some("93".into())));
+}
+
+#[tokio::test]
+async fn shutdown_finishes_queued_msgs_then_refuses_new_ones() {
+    let mut kv = ActorKv::spawn(Kv::default());
+    let pending: Vec<_> = (0..10).map(|i| kv.set(set(&i.to_string(), "v"))).collect();
+    assert_eq!(drain(kv.stop_within(common::PATIENCE)).await, 0);
+    for ask in pending {
+        assert_eq!(within(ask).await, Ok(None));
+    }
+    assert_closed!(within(kv.get(get("0"))).await, Stopped);
+    assert_closed!(within(kv.set_no_wait(set("0", "v"))).await, Stopped);
+}
+
+#[tokio::test]
+async fn dropping_every_handle_ends_the_actor() {
+    let (kv, run) = ActorKv::start(Kv::default());
+    let task = tokio::spawn(run);
+    drop(kv);
+    let state = within(task).await.unwrap();
+    assert!(state.entries.is_empty());
+}
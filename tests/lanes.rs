@@ -0,0 +1,96 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::time::Instant;
+use tokio_actor::actors;
+
+#[actors]
+mod sampling {
+    pub enum SamplerMsg {
+        // the firehose, held back once two are queued
+        #[bounded(2)]
+        Sample(u64),
+        #[bounded(2)]
+        Samples {
+            resp: Vec<u64>,
+        },
+        // control, never waits behind samples
+        Count {
+            resp: usize,
+        },
+    }
+
+    #[derive(Default)]
+    pub struct Sampler {
+        pub samples: Vec<u64>,
+    }
+
+    impl Sampler {
+        async fn process(&mut self, msg: SamplerMsg) {
+            match msg {
+                SamplerMsg::Sample(n) => {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    self.samples.push(n);
+                }
+                SamplerMsg::Samples { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.samples.clone());
+                    }
+                }
+                SamplerMsg::Count { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.samples.len());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use sampling::{ActorSampler, Sampler, SamplerMsg};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_full_lane_holds_up_its_variants_only() {
+    let mut sampler = ActorSampler::spawn(Sampler::default());
+    let start = Instant::now();
+    // one being processed, two queued
+    for n in 0..3 {
+        sampler.send_into(SamplerMsg::Sample(n)).await.unwrap();
+    }
+    assert!(
+        tokio::time::timeout(ms(1), sampler.send_into(SamplerMsg::Sample(3)))
+            .await
+            .is_err()
+    );
+    // answered as soon as the sample in progress is done, ahead of the queued ones
+    let count = within(sampler.count(SamplerMsg::Count { resp: None })).await;
+    assert_eq!(count, Ok(1));
+    assert_eq!(start.elapsed(), ms(10));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_lane_keeps_its_order() {
+    let mut sampler = ActorSampler::spawn(Sampler::default());
+    for n in 0..5 {
+        within(sampler.send_into(SamplerMsg::Sample(n)))
+            .await
+            .unwrap();
+    }
+    let samples = within(sampler.samples(SamplerMsg::Samples { resp: None })).await;
+    assert_eq!(samples, Ok(vec![0, 1, 2, 3, 4]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stopping_drains_the_lane_too() {
+    let sampler = ActorSampler::spawn(Sampler::default());
+    for n in 0..3 {
+        sampler.send_into(SamplerMsg::Sample(n)).await.unwrap();
+    }
+    assert_eq!(common::drain(sampler.stop_within(ms(100))).await, 0);
+}
@@ -0,0 +1,206 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::sync::watch;
+use tokio_actor::actors;
+
+#[actors]
+mod telemetry {
+    use tokio::sync::watch;
+
+    pub enum GaugeMsg {
+        Hold,
+        #[latest_only(key = "device")]
+        Reading {
+            device: u32,
+            value: u64,
+        },
+        Seen {
+            resp: Vec<(u32, u64)>,
+        },
+    }
+
+    // stuck on `Hold` until the gate opens
+    pub struct Gauge {
+        pub gate: watch::Receiver<bool>,
+        pub seen: Vec<(u32, u64)>,
+    }
+
+    impl Gauge {
+        async fn process(&mut self, msg: GaugeMsg) {
+            match msg {
+                GaugeMsg::Hold => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                }
+                GaugeMsg::Reading { device, value } => self.seen.push((device, value)),
+                GaugeMsg::Seen { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.seen.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// the same gauge with room for one queued msg, stopping after an idle hour
+#[actors(bounded = 1)]
+mod cramped {
+    use tokio::sync::watch;
+
+    pub enum MeterMsg {
+        Hold,
+        #[latest_only(key = "device")]
+        Reading {
+            device: u32,
+            value: u64,
+        },
+        Seen {
+            resp: Vec<(u32, u64)>,
+        },
+    }
+
+    #[actor(idle_timeout = "1h")]
+    pub struct Meter {
+        pub gate: watch::Receiver<bool>,
+        pub seen: Vec<(u32, u64)>,
+    }
+
+    impl Meter {
+        async fn process(&mut self, msg: MeterMsg) {
+            match msg {
+                MeterMsg::Hold => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                }
+                MeterMsg::Reading { device, value } => self.seen.push((device, value)),
+                MeterMsg::Seen { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.seen.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use cramped::{ActorMeter, Meter, MeterMsg};
+use telemetry::{ActorGauge, Gauge, GaugeMsg};
+
+fn spawn() -> (ActorGauge, watch::Sender<bool>) {
+    let (open, gate) = watch::channel(false);
+    let gauge = ActorGauge::spawn(Gauge { gate, seen: vec![] });
+    (gauge, open)
+}
+
+async fn stall(gauge: &ActorGauge) {
+    gauge.send_into(GaugeMsg::Hold).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_hundred_updates_to_one_key_coalesce_into_the_last() {
+    let (mut gauge, open) = spawn();
+    stall(&gauge).await;
+    for value in 1..=100 {
+        let reading = GaugeMsg::Reading { device: 7, value };
+        gauge.send_into(reading).await.unwrap();
+    }
+    assert_eq!(gauge.replaced(), 99);
+    open.send(true).unwrap();
+    let seen = within(gauge.seen(GaugeMsg::Seen { resp: None })).await;
+    assert_eq!(seen.unwrap(), [(7, 100)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn keys_coalesce_separately_in_the_place_of_their_oldest() {
+    let (mut gauge, open) = spawn();
+    stall(&gauge).await;
+    for (device, value) in [(1, 10), (2, 20), (1, 11), (3, 30), (2, 21), (1, 12)] {
+        let reading = GaugeMsg::Reading { device, value };
+        gauge.send_into(reading).await.unwrap();
+    }
+    assert_eq!(gauge.replaced(), 3);
+    open.send(true).unwrap();
+    let seen = within(gauge.seen(GaugeMsg::Seen { resp: None })).await;
+    assert_eq!(seen.unwrap(), [(1, 12), (2, 21), (3, 30)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn an_update_after_the_last_was_taken_is_queued_again() {
+    let (mut gauge, open) = spawn();
+    open.send(true).unwrap();
+    for value in [1, 2] {
+        let reading = GaugeMsg::Reading { device: 7, value };
+        gauge.send_into(reading).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(gauge.replaced(), 0);
+    let seen = within(gauge.seen(GaugeMsg::Seen { resp: None })).await;
+    assert_eq!(seen.unwrap(), [(7, 1), (7, 2)]);
+}
+
+// a `Hold` in `process` and a reading for device 2 taking the only free place
+async fn crammed() -> (ActorMeter, watch::Sender<bool>) {
+    let (open, gate) = watch::channel(false);
+    let meter = ActorMeter::spawn(Meter { gate, seen: vec![] });
+    meter.send_into(MeterMsg::Hold).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let reading = MeterMsg::Reading {
+        device: 2,
+        value: 0,
+    };
+    meter.send_into(reading).await.unwrap();
+    (meter, open)
+}
+
+// once the gate opened, a reading for device 1 still gets through on its own
+async fn delivers_device_one(mut meter: ActorMeter, open: watch::Sender<bool>) {
+    open.send(true).unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let reading = MeterMsg::Reading {
+        device: 1,
+        value: 2,
+    };
+    meter.send_into(reading).await.unwrap();
+    let seen = within(meter.seen(MeterMsg::Seen { resp: None })).await;
+    assert_eq!(seen.unwrap(), [(2, 0), (1, 2)]);
+    assert_eq!(meter.replaced(), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_reading_extend_dropped_leaves_its_key_usable() {
+    let (mut meter, open) = crammed().await;
+    meter.extend([MeterMsg::Reading {
+        device: 1,
+        value: 1,
+    }]);
+    delivers_device_one(meter, open).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_cancelled_send_leaves_its_key_usable() {
+    let (meter, open) = crammed().await;
+    let reading = MeterMsg::Reading {
+        device: 1,
+        value: 1,
+    };
+    let sent = tokio::time::timeout(Duration::from_millis(10), meter.send_into(reading)).await;
+    assert!(sent.is_err());
+    delivers_device_one(meter, open).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_send_after_the_actor_closed_fails() {
+    use cramped::{ActorError, CloseReason};
+    let (open, gate) = watch::channel(true);
+    let meter = ActorMeter::spawn(Meter { gate, seen: vec![] });
+    drop(open);
+    tokio::time::sleep(Duration::from_secs(3601)).await;
+    for value in [1, 2] {
+        let reading = MeterMsg::Reading { device: 1, value };
+        assert_closed!(meter.send_into(reading).await, Idle);
+    }
+    assert_eq!(meter.replaced(), 0);
+}
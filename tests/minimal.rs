@@ -0,0 +1,78 @@
+mod common;
+
+use common::within;
+use tokio::sync::{mpsc, oneshot};
+use tokio_actor::actors;
+
+#[actors(minimal)]
+mod counter {
+    pub enum CounterMsg {
+        Add { by: u64, resp: u64 },
+        Reset { resp: () },
+    }
+
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        pub async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { by, resp } => {
+                    self.total += by;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+                CounterMsg::Reset { resp } => {
+                    self.total = 0;
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use counter::{ActorRequest, Counter, CounterAddRequest, CounterMsg, CounterResetRequest};
+
+// the hand written handle and loop `minimal` leaves to its users
+struct Handle(mpsc::UnboundedSender<CounterMsg>);
+
+impl Handle {
+    fn spawn(mut counter: Counter) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Some(msg) = receiver.recv().await {
+                counter.process(msg).await;
+            }
+        });
+        Handle(sender)
+    }
+
+    async fn ask<R: ActorRequest<Msg = CounterMsg>>(&self, req: R) -> Option<R::Response> {
+        let (s, r) = oneshot::channel();
+        self.0.send(req.into_msg(s)).ok()?;
+        r.await.ok()
+    }
+}
+
+#[tokio::test]
+async fn request_structs_drive_a_hand_written_loop() {
+    let counter = Handle::spawn(Counter { total: 0 });
+    assert_eq!(
+        within(counter.ask(CounterAddRequest { by: 2 })).await,
+        Some(2)
+    );
+    assert_eq!(
+        within(counter.ask(CounterAddRequest { by: 3 })).await,
+        Some(5)
+    );
+    assert_eq!(within(counter.ask(CounterResetRequest {})).await, Some(()));
+    assert_eq!(
+        within(counter.ask(CounterAddRequest { by: 1 })).await,
+        Some(1)
+    );
+    assert_eq!(CounterAddRequest::VARIANT, "CounterMsg::Add");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod counting {
+    #[msg_for(inner::deep::Counter)]
+    pub enum Tally {
+        Add(u64),
+        Get { resp: u64 },
+    }
+
+    pub mod inner {
+        pub mod deep {
+            use super::super::Tally;
+
+            pub struct Counter {
+                pub count: u64,
+            }
+
+            impl Counter {
+                pub(in crate::counting) async fn process(&mut self, msg: Tally) {
+                    match msg {
+                        Tally::Add(n) => self.count += n,
+                        Tally::Get { resp } => {
+                            if let Some(r) = resp {
+                                let _ = r.send(self.count);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // a hook next to the handle rather than the struct
+    impl inner::deep::Counter {
+        async fn on_start(&mut self) {
+            self.count = 100;
+        }
+    }
+}
+
+use counting::inner::deep::Counter;
+use counting::{ActorCounter, Tally};
+
+#[tokio::test(start_paused = true)]
+async fn a_nested_actor_gets_its_handle_at_the_module_root() {
+    let mut counter = ActorCounter::spawn(Counter { count: 0 });
+    counter.send_into(Tally::Add(2)).await.unwrap();
+    counter.send_into(Tally::Add(3)).await.unwrap();
+    assert_eq!(
+        within(counter.get(Tally::Get { resp: None })).await,
+        Ok(105)
+    );
+    assert_eq!(Tally::ROUTES.len(), 2);
+}
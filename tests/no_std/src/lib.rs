@@ -0,0 +1,56 @@
+// the payload side builds without std, the actor side only with `runtime`
+#![cfg_attr(not(feature = "runtime"), no_std)]
+
+use tokio_actor::actors;
+
+#[actors(cfg(feature = "runtime"))]
+pub mod sensor {
+    #[wire(derive(Debug, Clone, Copy, PartialEq))]
+    pub enum SensorMsg {
+        Sample { channel: u8, value: i32 },
+        Calibrate { offset: i32, resp: i32 },
+        Read { channel: u8, resp: Option<i32> },
+    }
+
+    #[cfg(feature = "runtime")]
+    #[derive(Default)]
+    pub struct Sensor {
+        pub offset: i32,
+        pub last: [Option<i32>; 4],
+    }
+
+    #[cfg(feature = "runtime")]
+    impl Sensor {
+        async fn process(&mut self, msg: SensorMsg) {
+            match msg {
+                SensorMsg::Sample { channel, value } => {
+                    self.last[channel as usize % 4] = Some(value + self.offset);
+                }
+                SensorMsg::Calibrate { offset, resp } => {
+                    self.offset = offset;
+                    if let Some(r) = resp {
+                        let _ = r.send(offset);
+                    }
+                }
+                SensorMsg::Read { channel, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.last[channel as usize % 4]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub use sensor::prelude::*;
+
+// what a firmware build does with the payload: no tokio, no allocation
+pub fn encode(msg: &SensorMsgWire) -> [u8; 6] {
+    let (tag, channel, value) = match *msg {
+        SensorMsgWire::Sample { channel, value } => (0, channel, value),
+        SensorMsgWire::Calibrate { offset } => (1, 0, offset),
+        SensorMsgWire::Read { channel } => (2, channel, 0),
+    };
+    let v = value.to_le_bytes();
+    [tag, channel, v[0], v[1], v[2], v[3]]
+}
@@ -0,0 +1,46 @@
+use no_std_payload::{encode, SensorMsgWire};
+
+#[test]
+fn payloads_encode_without_the_runtime() {
+    let msg = SensorMsgWire::Sample {
+        channel: 2,
+        value: -1,
+    };
+    assert_eq!(encode(&msg), [0, 2, 0xff, 0xff, 0xff, 0xff]);
+    assert_eq!(
+        encode(&SensorMsgWire::Read { channel: 3 }),
+        [2, 3, 0, 0, 0, 0]
+    );
+}
+
+#[cfg(feature = "runtime")]
+#[tokio::test]
+async fn the_runtime_side_still_answers() {
+    use no_std_payload::sensor::Sensor;
+    use no_std_payload::{ActorSensor, SensorMsg};
+
+    let mut sensor = ActorSensor::spawn(Sensor::default());
+    let offset = sensor.calibrate(SensorMsg::Calibrate {
+        offset: 10,
+        resp: None,
+    });
+    assert_eq!(offset.await, Ok(10));
+    let sample = SensorMsg::Sample {
+        channel: 1,
+        value: 5,
+    };
+    // the wire copy of a live msg, as sent to the firmware
+    assert_eq!(
+        encode(&SensorMsgWire::from(SensorMsg::Sample {
+            channel: 1,
+            value: 5
+        })),
+        [0, 1, 5, 0, 0, 0]
+    );
+    sensor.send_into(sample).await.unwrap();
+    let read = sensor.read(SensorMsg::Read {
+        channel: 1,
+        resp: None,
+    });
+    assert_eq!(read.await, Ok(Some(15)));
+}
@@ -0,0 +1,221 @@
+mod common;
+
+use common::within;
+use tokio::sync::mpsc;
+use tokio_actor::actors;
+
+// one actor per policy, each failing on a zero without answering it, and
+// noting every hook that ran on `log`
+#[actors]
+mod policies {
+    use tokio::sync::mpsc;
+
+    pub enum KeepMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    #[actor(on_error = "continue")]
+    pub struct Keep {
+        pub total: u64,
+        pub log: mpsc::UnboundedSender<String>,
+    }
+
+    impl Keep {
+        async fn process(&mut self, msg: KeepMsg) -> Result<(), String> {
+            let KeepMsg::Add { n, resp } = msg;
+            if n == 0 {
+                return Err("zero".to_string());
+            }
+            self.total += n;
+            if let Some(r) = resp {
+                let _ = r.send(self.total);
+            }
+            Ok(())
+        }
+
+        async fn on_error(&mut self, error: String) {
+            let _ = self.log.send(format!("error {}", error));
+        }
+    }
+
+    pub enum HaltMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    #[actor(on_error = "stop")]
+    pub struct Halt {
+        pub total: u64,
+        pub log: mpsc::UnboundedSender<String>,
+    }
+
+    impl Halt {
+        async fn process(&mut self, msg: HaltMsg) -> Result<(), String> {
+            let HaltMsg::Add { n, resp } = msg;
+            if n == 0 {
+                return Err("zero".to_string());
+            }
+            self.total += n;
+            if let Some(r) = resp {
+                let _ = r.send(self.total);
+            }
+            Ok(())
+        }
+
+        async fn on_error(&mut self, error: String) {
+            let _ = self.log.send(format!("error {}", error));
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.log.send(format!("stopped at {}", self.total));
+        }
+    }
+
+    pub enum ResetMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    #[actor(on_error = "restart")]
+    pub struct Reset {
+        pub total: u64,
+        pub log: mpsc::UnboundedSender<String>,
+    }
+
+    impl Reset {
+        async fn process(&mut self, msg: ResetMsg) -> Result<(), String> {
+            let ResetMsg::Add { n, resp } = msg;
+            if n == 0 {
+                return Err("zero".to_string());
+            }
+            self.total += n;
+            if let Some(r) = resp {
+                let _ = r.send(self.total);
+            }
+            Ok(())
+        }
+
+        async fn on_error(&mut self, error: String) {
+            let _ = self.log.send(format!("error {}", error));
+        }
+
+        async fn on_restart(&mut self) {
+            let _ = self.log.send("restarted".to_string());
+            self.total = 0;
+        }
+    }
+}
+
+// errors and panics both restarting the actor
+#[actors]
+mod guarded {
+    pub enum GuardMsg {
+        Check { n: u64, resp: u64 },
+    }
+
+    #[actor(supervised, on_error = "restart")]
+    pub struct Guard {}
+
+    impl Guard {
+        async fn process(&mut self, msg: GuardMsg) -> Result<(), String> {
+            let GuardMsg::Check { n, resp } = msg;
+            match n {
+                0 => return Err("zero".to_string()),
+                1 => panic!("one"),
+                _ => {}
+            }
+            if let Some(r) = resp {
+                let _ = r.send(n);
+            }
+            Ok(())
+        }
+    }
+}
+
+use policies::prelude::*;
+use policies::{Halt, Keep, Reset};
+
+fn logged(log: &mut mpsc::UnboundedReceiver<String>) -> Vec<String> {
+    let mut lines = vec![];
+    while let Ok(line) = log.try_recv() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[tokio::test(start_paused = true)]
+async fn continue_goes_on_with_the_next_msg() {
+    let (log, mut logged_to) = mpsc::unbounded_channel();
+    let mut keep = ActorKeep::spawn(Keep { total: 0, log });
+    assert_eq!(
+        within(keep.add(KeepMsg::Add { n: 2, resp: None })).await,
+        Ok(2)
+    );
+
+    // the failed msg's caller isn't left waiting
+    let failed = within(keep.add(KeepMsg::Add { n: 0, resp: None })).await;
+    assert!(matches!(failed, Err(ActorError::ResponseDropped)));
+    assert_eq!(
+        within(keep.add(KeepMsg::Add { n: 3, resp: None })).await,
+        Ok(5)
+    );
+    assert_eq!(logged(&mut logged_to), ["error zero"]);
+    assert!(!keep.is_closed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn stop_drops_whats_queued_and_closes_the_mailbox() {
+    let (log, mut logged_to) = mpsc::unbounded_channel();
+    let mut halt = ActorHalt::spawn(Halt { total: 0, log });
+    assert_eq!(
+        within(halt.add(HaltMsg::Add { n: 2, resp: None })).await,
+        Ok(2)
+    );
+
+    let failed = halt.add(HaltMsg::Add { n: 0, resp: None });
+    let queued = halt.add(HaltMsg::Add { n: 3, resp: None });
+    assert!(matches!(
+        within(failed).await,
+        Err(ActorError::ResponseDropped)
+    ));
+    assert!(matches!(
+        within(queued).await,
+        Err(ActorError::ResponseDropped)
+    ));
+
+    assert_closed!(halt.add(HaltMsg::Add { n: 1, resp: None }).await, Failed);
+    assert_eq!(logged(&mut logged_to), ["error zero", "stopped at 2"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn restart_runs_on_restart_and_carries_on() {
+    let (log, mut logged_to) = mpsc::unbounded_channel();
+    let mut reset = ActorReset::spawn(Reset { total: 0, log });
+    assert_eq!(
+        within(reset.add(ResetMsg::Add { n: 2, resp: None })).await,
+        Ok(2)
+    );
+
+    let failed = within(reset.add(ResetMsg::Add { n: 0, resp: None })).await;
+    assert!(matches!(failed, Err(ActorError::ResponseDropped)));
+    // `on_restart` started the total over
+    assert_eq!(
+        within(reset.add(ResetMsg::Add { n: 3, resp: None })).await,
+        Ok(3)
+    );
+    assert_eq!(reset.restart_count(), 1);
+    assert_eq!(logged(&mut logged_to), ["error zero", "restarted"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn errors_and_panics_count_as_restarts_alike() {
+    use guarded::{ActorError, ActorGuard, Guard, GuardMsg};
+    let mut guard = ActorGuard::spawn(Guard {});
+    let failed = within(guard.check(GuardMsg::Check { n: 0, resp: None })).await;
+    assert!(matches!(failed, Err(ActorError::ResponseDropped)));
+    let panicked = within(guard.check(GuardMsg::Check { n: 1, resp: None })).await;
+    assert!(matches!(panicked, Err(ActorError::Panicked)));
+    assert_eq!(
+        within(guard.check(GuardMsg::Check { n: 2, resp: None })).await,
+        Ok(2)
+    );
+    assert_eq!(guard.restart_count(), 2);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+// the generated items live in `handles`, while user code on both sides of it
+// names them: a msg field holding a handle and `process` answering with an error
+#[actors(output_mod = handles)]
+mod pipeline {
+    pub enum SinkMsg {
+        Push { n: u64 },
+        Total { resp: u64 },
+    }
+
+    pub struct Sink {
+        pub total: u64,
+    }
+
+    impl Sink {
+        async fn process(&mut self, msg: SinkMsg) {
+            match msg {
+                SinkMsg::Push { n } => self.total += n,
+                SinkMsg::Total { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum SourceMsg {
+        Emit {
+            n: u64,
+            to: handles::ActorSink,
+            resp: Result<(), handles::ActorError>,
+        },
+    }
+
+    pub struct Source {}
+
+    impl Source {
+        async fn process(&mut self, msg: SourceMsg) {
+            let SourceMsg::Emit { n, to, resp } = msg;
+            let sent = to.send_into(SinkMsg::Push { n }).await;
+            if let Some(r) = resp {
+                let _ = r.send(sent);
+            }
+        }
+    }
+}
+
+use pipeline::handles::{ActorSink, ActorSource};
+use pipeline::{Sink, SinkMsg, SourceMsg};
+
+#[tokio::test]
+async fn generated_items_are_nested_and_reachable_from_user_code() {
+    let mut sink = ActorSink::spawn(Sink { total: 0 });
+    let mut source = ActorSource::new().await;
+    for n in 1..=3 {
+        let emit = SourceMsg::Emit {
+            n,
+            to: sink.clone(),
+            resp: None,
+        };
+        assert_eq!(within(source.emit(emit)).await, Ok(Ok(())));
+    }
+    assert_eq!(
+        within(sink.total(SinkMsg::Total { resp: None })).await,
+        Ok(6)
+    );
+}
+
+#[tokio::test]
+async fn the_prelude_follows_output_mod() {
+    use pipeline::prelude::{ActorError, ActorSink, CloseReason};
+    let sink: pipeline::handles::ActorSink = ActorSink::spawn(Sink { total: 0 });
+    let error: pipeline::handles::ActorError = ActorError::MailboxClosed {
+        reason: CloseReason::Exited,
+    };
+    assert_eq!(sink.handle_count(), 1);
+    assert!(error.to_string().contains("closed"), "{}", error);
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod parser {
+    #[derive(Debug, PartialEq)]
+    pub enum ParseError {
+        Empty,
+        Panicked(String),
+    }
+
+    impl From<ActorPanic> for ParseError {
+        fn from(panic: ActorPanic) -> Self {
+            ParseError::Panicked(panic.message)
+        }
+    }
+
+    pub enum ParserMsg {
+        #[propagate_panic]
+        Parse {
+            text: String,
+            resp: Result<u64, ParseError>,
+        },
+        Count {
+            resp: usize,
+        },
+    }
+
+    #[derive(Default)]
+    pub struct Parser {
+        pub parsed: usize,
+    }
+
+    impl Parser {
+        async fn process(&mut self, msg: ParserMsg) {
+            match msg {
+                ParserMsg::Parse { text, resp } => {
+                    let parsed = match text.as_str() {
+                        "" => Err(ParseError::Empty),
+                        text => Ok(text
+                            .parse()
+                            .unwrap_or_else(|_e| panic!("not a number: {}", text))),
+                    };
+                    self.parsed += 1;
+                    if let Some(r) = resp {
+                        let _ = r.send(parsed);
+                    }
+                }
+                ParserMsg::Count { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.parsed);
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum CounterMsg {
+        Inc { by: u64, resp: u64 },
+    }
+
+    #[actor(supervised)]
+    #[derive(Default)]
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Inc { by, resp } => {
+                    self.total = self.total.checked_add(by).expect("counter overflowed");
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use parser::{
+    ActorCounter, ActorError, ActorParser, CloseReason, Counter, CounterMsg, ParseError, Parser,
+    ParserMsg,
+};
+
+fn parse(text: &str) -> ParserMsg {
+    ParserMsg::Parse {
+        text: text.to_string(),
+        resp: None,
+    }
+}
+
+#[tokio::test]
+async fn the_caller_gets_the_panic_message() {
+    let mut parser = ActorParser::spawn(Parser::default());
+    assert_eq!(within(parser.parse(parse("42"))).await, Ok(Ok(42)));
+    assert_eq!(
+        within(parser.parse(parse("4x2"))).await,
+        Ok(Err(ParseError::Panicked("not a number: 4x2".to_string())))
+    );
+    // not supervised, the actor is gone and says why
+    assert_eq!(
+        within(parser.count(ParserMsg::Count { resp: None })).await,
+        Err(ActorError::MailboxClosed {
+            reason: CloseReason::Panicked
+        })
+    );
+    assert_eq!(
+        parser.last_panic().map(|panic| panic.message),
+        Some("not a number: 4x2".to_string())
+    );
+}
+
+#[tokio::test]
+async fn other_resp_types_find_the_message_on_the_handle() {
+    let mut counter = ActorCounter::spawn(Counter::default());
+    assert_eq!(counter.last_panic(), None);
+    assert_eq!(
+        within(counter.inc(CounterMsg::Inc {
+            by: u64::MAX,
+            resp: None
+        }))
+        .await,
+        Ok(u64::MAX)
+    );
+    assert_eq!(
+        within(counter.inc(CounterMsg::Inc { by: 1, resp: None })).await,
+        Err(ActorError::Panicked)
+    );
+    let panic = counter.last_panic().unwrap();
+    assert_eq!(panic.message, "counter overflowed");
+    assert_eq!(panic.to_string(), "handler panicked: counter overflowed");
+}
@@ -0,0 +1,64 @@
+use std::time::{Duration, Instant};
+
+use tokio_actor::actors;
+
+#[actors]
+mod report {
+    use std::time::Duration;
+
+    pub enum ReportMsg {
+        // builds for as long as it's told, on tokio's clock
+        #[timeout("1h")]
+        Build { takes: Duration, resp: u64 },
+    }
+
+    #[actor(idle_timeout = "24h")]
+    pub struct Report {
+        pub built: u64,
+    }
+
+    impl Report {
+        async fn process(&mut self, msg: ReportMsg) {
+            let ReportMsg::Build { takes, resp } = msg;
+            tokio::time::sleep(takes).await;
+            self.built += 1;
+            if let Some(r) = resp {
+                let _ = r.send(self.built);
+            }
+        }
+    }
+}
+
+use report::prelude::*;
+use report::Report;
+
+const HOUR: Duration = Duration::from_secs(3600);
+
+fn build(takes: Duration) -> ReportMsg {
+    ReportMsg::Build { takes, resp: None }
+}
+
+// hours of tokio time pass in an instant of wall time
+#[tokio::test(start_paused = true)]
+async fn timers_follow_the_paused_clock() {
+    let wall = Instant::now();
+    let started = tokio::time::Instant::now();
+    let mut report = ActorReport::spawn(Report { built: 0 });
+
+    // no `within` here, its 5s run on the same clock
+    assert_eq!(report.build(build(HOUR / 2)).await, Ok(1));
+    let late = report.build(build(HOUR * 2)).await;
+    match late {
+        Err(ActorError::ResponseTimeout { waited, limit, .. }) => {
+            assert_eq!((waited, limit), (HOUR, HOUR));
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(started.elapsed(), HOUR * 3 / 2);
+
+    // idle for a day once the late build is done, then gone
+    tokio::time::sleep(HOUR * 26).await;
+    let stopped = report.build(build(Duration::ZERO)).await;
+    assert!(matches!(stopped, Err(ActorError::MailboxClosed { .. })));
+    assert!(wall.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors(bounded = 2)]
+mod store {
+    pub enum StoreMsg {
+        Put { key: String, value: Vec<u8> },
+        Len { key: String, resp: usize },
+        Clear { resp: () },
+    }
+
+    pub struct Store {
+        pub entries: std::collections::HashMap<String, Vec<u8>>,
+    }
+
+    impl Store {
+        async fn process(&mut self, msg: StoreMsg) {
+            match msg {
+                StoreMsg::Put { key, value } => {
+                    self.entries.insert(key, value);
+                }
+                StoreMsg::Len { key, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.entries.get(&key).map_or(0, |v| v.len()));
+                    }
+                }
+                StoreMsg::Clear { resp } => {
+                    self.entries.clear();
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use store::prelude::*;
+use store::Store;
+
+fn spawn() -> ActorStore {
+    ActorStore::spawn(Store {
+        entries: Default::default(),
+    })
+}
+
+#[tokio::test(start_paused = true)]
+async fn builds_the_msg_once_a_slot_is_reserved() {
+    let store = spawn();
+    let permit = within(store.reserve()).await.unwrap();
+    // only built now that it's sure to fit
+    let value = vec![7; 1024];
+    permit
+        .send(StoreMsg::Put {
+            key: "a".to_string(),
+            value,
+        })
+        .unwrap();
+
+    let permit = within(store.reserve()).await.unwrap();
+    let len = permit.len(StoreMsg::Len {
+        key: "a".to_string(),
+        resp: None,
+    });
+    assert_eq!(within(len).await, Ok(1024));
+
+    let permit = within(store.reserve()).await.unwrap();
+    permit
+        .clear_no_wait(StoreMsg::Clear { resp: None })
+        .unwrap();
+    let len = within(store.reserve()).await.unwrap().len(StoreMsg::Len {
+        key: "a".to_string(),
+        resp: None,
+    });
+    assert_eq!(within(len).await, Ok(0));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_dropped_permit_gives_its_slot_back() {
+    let store = spawn();
+    let first = within(store.reserve()).await.unwrap();
+    let _second = within(store.reserve()).await.unwrap();
+    assert!(matches!(
+        store.reserve_timeout(Duration::from_secs(1)).await,
+        Err(ActorError::EnqueueTimeout)
+    ));
+
+    drop(first);
+    let third = within(store.reserve_timeout(Duration::from_secs(1))).await;
+    assert!(third.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_permit_wrong_for_the_variant_turns_the_msg_down() {
+    let store = spawn();
+    let permit = within(store.reserve()).await.unwrap();
+    let len = permit.len(StoreMsg::Clear { resp: None });
+    assert!(matches!(within(len).await, Err(ActorError::InvalidMsgType)));
+    // the slot came back with the msg
+    let _first = within(store.reserve()).await.unwrap();
+    let _second = within(store.reserve()).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_stop_requested_meanwhile_is_checked_when_spending() {
+    let store = spawn();
+    let permit = within(store.reserve()).await.unwrap();
+    within(store.clone().stop_within(Duration::ZERO))
+        .await
+        .unwrap();
+
+    let sent = permit.send(StoreMsg::Put {
+        key: "a".to_string(),
+        value: vec![],
+    });
+    assert_closed!(sent, Stopped);
+    assert_closed!(store.reserve().await.map(|_permit| ()), Stopped);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_held_permit_doesnt_hold_up_a_stop_past_its_deadline() {
+    let store = spawn();
+    let _permit = within(store.reserve()).await.unwrap();
+    let started = tokio::time::Instant::now();
+    assert_eq!(
+        within(store.clone().stop_within(Duration::from_millis(100))).await,
+        Ok(0)
+    );
+    assert_eq!(started.elapsed(), Duration::from_millis(100));
+}
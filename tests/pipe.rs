@@ -0,0 +1,113 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod numbers {
+    pub enum CountMsg {
+        #[stream]
+        Upto { n: u64, resp: u64 },
+    }
+
+    pub struct Count {}
+
+    impl Count {
+        async fn process(&mut self, msg: CountMsg) {
+            let CountMsg::Upto { n, resp } = msg;
+            if let Some(items) = resp {
+                for i in 1..=n {
+                    let _ = items.send(i);
+                }
+            }
+        }
+    }
+
+    // stops on its own once it has taken `limit` numbers
+    pub enum SumMsg {
+        Add(u64),
+        Sum { resp: (u64, u64) },
+    }
+
+    pub struct Sum {
+        pub limit: u64,
+        pub taken: u64,
+        pub sum: u64,
+    }
+
+    impl Sum {
+        async fn process(&mut self, msg: SumMsg, ctx: &ActorSumCtx) {
+            match msg {
+                SumMsg::Add(n) => {
+                    self.taken += 1;
+                    self.sum += n;
+                    if self.taken == self.limit {
+                        ctx.stop();
+                    }
+                }
+                SumMsg::Sum { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send((self.taken, self.sum));
+                    }
+                }
+            }
+        }
+    }
+}
+
+use numbers::prelude::*;
+use numbers::{Count, Sum};
+
+#[tokio::test(start_paused = true)]
+async fn pipes_every_item_into_the_sink() {
+    let count = ActorCount::spawn(Count {});
+    let mut sum = ActorSum::spawn(Sum {
+        limit: u64::MAX,
+        taken: 0,
+        sum: 0,
+    });
+    let upto = CountMsg::Upto {
+        n: 1000,
+        resp: None,
+    };
+    let stats = within(count.pipe_upto_to(upto, &sum, SumMsg::Add)).await;
+    let stats = stats.unwrap();
+    assert_eq!((stats.items, stats.errors), (1000, 0));
+    assert!(!stats.sink_closed);
+    let total = within(sum.sum(SumMsg::Sum { resp: None })).await;
+    assert_eq!(total, Ok((1000, 500500)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stops_once_the_sink_is_gone() {
+    let count = ActorCount::spawn(Count {});
+    let sum = ActorSum::spawn(Sum {
+        limit: 10,
+        taken: 0,
+        sum: 0,
+    });
+    let upto = CountMsg::Upto {
+        n: 1000,
+        resp: None,
+    };
+    let stats = within(count.pipe_upto_to(upto, &sum, SumMsg::Add)).await;
+    let stats = stats.unwrap();
+    assert!(stats.sink_closed);
+    assert_eq!(stats.errors, 1);
+    assert!((10..1000).contains(&stats.items));
+}
+
+#[tokio::test(start_paused = true)]
+async fn maps_items_into_any_msg_of_the_sink() {
+    let count = ActorCount::spawn(Count {});
+    let mut sum = ActorSum::spawn(Sum {
+        limit: u64::MAX,
+        taken: 0,
+        sum: 0,
+    });
+    let upto = CountMsg::Upto { n: 3, resp: None };
+    let doubled = count.pipe_upto_to(upto, &sum, |n| SumMsg::Add(n * 2));
+    assert_eq!(within(doubled).await.unwrap().items, 3);
+    let total = within(sum.sum(SumMsg::Sum { resp: None })).await;
+    assert_eq!(total, Ok((3, 12)));
+}
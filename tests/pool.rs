@@ -0,0 +1,129 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{drain, within};
+use tokio::sync::mpsc;
+use tokio_actor::actors;
+
+#[actors]
+mod work {
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+
+    pub enum TaskMsg {
+        // takes `ms` and reports `(worker, id)` on `done`
+        Task { id: u32, ms: u64 },
+        Flush { resp: () },
+    }
+
+    #[actor(pool, shared_queue)]
+    pub struct Task {
+        pub worker: usize,
+        pub done: mpsc::UnboundedSender<(usize, u32)>,
+        pub stopped: mpsc::UnboundedSender<usize>,
+    }
+
+    impl Task {
+        async fn process(&mut self, msg: TaskMsg) {
+            match msg {
+                TaskMsg::Task { id, ms } => {
+                    tokio::time::sleep(Duration::from_millis(ms)).await;
+                    let _ = self.done.send((self.worker, id));
+                }
+                TaskMsg::Flush { resp } => {
+                    if let Some(ack) = resp {
+                        ack.ack();
+                    }
+                }
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.stopped.send(self.worker);
+        }
+    }
+}
+
+use work::prelude::*;
+use work::Task;
+
+struct Pool {
+    pool: ActorTask,
+    done: mpsc::UnboundedReceiver<(usize, u32)>,
+    stopped: mpsc::UnboundedReceiver<usize>,
+}
+
+fn spawn(workers: usize) -> Pool {
+    let (done, done_rx) = mpsc::unbounded_channel();
+    let (stopped, stopped_rx) = mpsc::unbounded_channel();
+    let states = (0..workers).map(|worker| Task {
+        worker,
+        done: done.clone(),
+        stopped: stopped.clone(),
+    });
+    Pool {
+        pool: ActorTask::spawn_pool(states.collect::<Vec<_>>()),
+        done: done_rx,
+        stopped: stopped_rx,
+    }
+}
+
+async fn task(pool: &ActorTask, id: u32, ms: u64) {
+    pool.send_into(TaskMsg::Task { id, ms }).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_slow_msg_strands_nothing_behind_it() {
+    let Pool { pool, mut done, .. } = spawn(2);
+    task(&pool, 0, 1000).await;
+    for id in 1..=5 {
+        task(&pool, id, 10).await;
+    }
+    // the other worker takes every quick one while the first is busy
+    let mut order = vec![];
+    for _ in 0..6 {
+        order.push(within(done.recv()).await.unwrap());
+    }
+    let (slow, _) = order.iter().copied().find(|(_, id)| *id == 0).unwrap();
+    assert_eq!(order.last(), Some(&(slow, 0)));
+    assert!(order[..5].iter().all(|(worker, _)| *worker != slow));
+}
+
+#[tokio::test(start_paused = true)]
+async fn back_to_back_msgs_may_finish_out_of_order() {
+    let Pool { pool, mut done, .. } = spawn(2);
+    task(&pool, 1, 50).await;
+    task(&pool, 2, 10).await;
+    assert_eq!(within(done.recv()).await.map(|(_, id)| id), Some(2));
+    assert_eq!(within(done.recv()).await.map(|(_, id)| id), Some(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn stop_drains_the_shared_queue_then_stops_every_worker() {
+    let Pool {
+        mut pool,
+        mut done,
+        mut stopped,
+    } = spawn(3);
+    for id in 0..9 {
+        task(&pool, id, 10).await;
+    }
+    within(pool.flush(TaskMsg::Flush { resp: None }))
+        .await
+        .unwrap();
+    assert_eq!(drain(pool.stop_within(Duration::from_secs(1))).await, 0);
+    let mut finished = vec![];
+    while let Ok((_, id)) = done.try_recv() {
+        finished.push(id);
+    }
+    finished.sort();
+    assert_eq!(finished, (0..9).collect::<Vec<_>>());
+    let mut workers = vec![];
+    for _ in 0..3 {
+        workers.push(within(stopped.recv()).await.unwrap());
+    }
+    workers.sort();
+    assert_eq!(workers, [0, 1, 2]);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod shop {
+    #[wire(derive(Debug, PartialEq))]
+    pub enum CartMsg {
+        Add { item: String },
+        Count { resp: usize },
+    }
+
+    pub struct Cart {
+        pub items: Vec<String>,
+    }
+
+    impl Cart {
+        async fn process(&mut self, msg: CartMsg) {
+            match msg {
+                CartMsg::Add { item } => self.items.push(item),
+                CartMsg::Count { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.items.len());
+                    }
+                }
+            }
+        }
+    }
+}
+
+// downstream code naming the generated types through the prelude alone
+mod checkout {
+    use super::shop::prelude::*;
+
+    pub async fn fill(cart: &ActorCart, items: &[&str]) -> Result<(), ActorError> {
+        for item in items {
+            let item = item.to_string();
+            cart.send_into(CartMsg::Add { item }).await?;
+        }
+        Ok(())
+    }
+
+    pub fn count(cart: &mut ActorCart) -> AskFuture<usize> {
+        cart.count(CartMsg::Count { resp: None })
+    }
+
+    pub fn whose(cart: &ActorCart) -> ActorId {
+        cart.id()
+    }
+
+    pub fn routes() -> &'static [RouteInfo] {
+        CartMsg::ROUTES
+    }
+
+    pub fn logged(msg: CartMsg) -> CartMsgWire {
+        msg.into()
+    }
+
+    pub fn gone(reason: Option<CloseReason>) -> bool {
+        reason.is_some()
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_consumer_needs_only_the_prelude() {
+    let mut cart = shop::ActorCart::spawn(shop::Cart { items: vec![] });
+    checkout::fill(&cart, &["pear", "fig"]).await.unwrap();
+    assert_eq!(within(checkout::count(&mut cart)).await, Ok(2));
+    assert_eq!(checkout::whose(&cart), cart.id());
+    assert_eq!(checkout::routes().len(), 2);
+    let wire = checkout::logged(shop::CartMsg::Add { item: "fig".into() });
+    assert_eq!(wire, shop::CartMsgWire::Add { item: "fig".into() });
+    assert!(!checkout::gone(None));
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::within;
+use tokio_actor::{actors, actors_profile};
+
+actors_profile! {
+    tight = { bounded = 2, batch = 4 };
+    roomy = { bounded = 64 }
+}
+
+// as defined by the profile
+#[actors(profile = "tight")]
+mod plain {
+    pub enum EchoMsg {
+        Echo { n: u64, resp: u64 },
+    }
+
+    pub struct Echo {}
+
+    impl Echo {
+        async fn process(&mut self, msg: EchoMsg) {
+            let EchoMsg::Echo { n, resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(n);
+            }
+        }
+    }
+}
+
+// the module's own argument wins over the profile's
+#[actors(profile = "tight", bounded = 16)]
+mod overridden {
+    pub enum EchoMsg {
+        Echo { n: u64, resp: u64 },
+    }
+
+    pub struct Echo {}
+
+    impl Echo {
+        async fn process(&mut self, msg: EchoMsg) {
+            let EchoMsg::Echo { n, resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(n);
+            }
+        }
+    }
+}
+
+#[actors(bounded = 16, profile = "roomy")]
+mod overridden_first {
+    pub enum EchoMsg {
+        Echo { n: u64, resp: u64 },
+    }
+
+    pub struct Echo {}
+
+    impl Echo {
+        async fn process(&mut self, msg: EchoMsg) {
+            let EchoMsg::Echo { n, resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(n);
+            }
+        }
+    }
+}
+
+#[test]
+fn module_arguments_override_the_profile() {
+    assert_eq!(plain::EchoMsg::ROUTES[0].bounded, Some(2));
+    assert_eq!(overridden::EchoMsg::ROUTES[0].bounded, Some(16));
+    // wherever the module puts `profile`
+    assert_eq!(overridden_first::EchoMsg::ROUTES[0].bounded, Some(16));
+}
+
+#[tokio::test(start_paused = true)]
+async fn profiled_actors_run_as_usual() {
+    let mut echo = plain::ActorEcho::spawn(plain::Echo {});
+    let msg = plain::EchoMsg::Echo { n: 7, resp: None };
+    assert_eq!(within(echo.echo(msg)).await, Ok(7));
+    let mut echo = overridden::ActorEcho::spawn(overridden::Echo {});
+    let msg = overridden::EchoMsg::Echo { n: 8, resp: None };
+    assert_eq!(within(echo.echo(msg)).await, Ok(8));
+    let mut echo = overridden_first::ActorEcho::spawn(overridden_first::Echo {});
+    let msg = overridden_first::EchoMsg::Echo { n: 9, resp: None };
+    assert_eq!(within(echo.echo(msg)).await, Ok(9));
+}
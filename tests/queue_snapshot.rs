@@ -0,0 +1,93 @@
+#![cfg(feature = "debug")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::sync::watch;
+use tokio_actor::actors;
+
+#[actors]
+mod wedged {
+    use tokio::sync::watch;
+
+    pub enum ExportMsg {
+        Export(Vec<u8>),
+        Compact,
+        Status { resp: usize },
+    }
+
+    // stuck in `process` until the gate opens
+    pub struct Export {
+        pub gate: watch::Receiver<bool>,
+        pub exported: usize,
+    }
+
+    impl Export {
+        async fn process(&mut self, msg: ExportMsg) {
+            match msg {
+                ExportMsg::Export(bytes) => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                    self.exported += bytes.len();
+                }
+                ExportMsg::Compact => {}
+                ExportMsg::Status { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.exported);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use wedged::prelude::*;
+use wedged::Export;
+
+#[tokio::test(start_paused = true)]
+async fn lists_what_a_stalled_actor_has_queued() {
+    let (open, gate) = watch::channel(false);
+    let mut export = ActorExport::spawn(Export { gate, exported: 0 });
+    export
+        .send_into(ExportMsg::Export(vec![1; 3]))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    // the first export is in `process`, everything after it waits
+    export.send_into(ExportMsg::Compact).await.unwrap();
+    export
+        .send_into(ExportMsg::Export(vec![2; 4]))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let status = export.status(ExportMsg::Status { resp: None });
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    let queued = export.queue_snapshot();
+    let variants: Vec<&str> = queued.iter().map(|q| q.variant).collect();
+    assert_eq!(variants, ["Compact", "Export", "Status"]);
+    assert_eq!(queued[0].age(), Duration::from_millis(55));
+    assert_eq!(queued[2].age(), Duration::from_millis(5));
+
+    open.send(true).unwrap();
+    assert_eq!(within(status).await, Ok(7));
+    assert!(export.queue_snapshot().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn keeps_the_newest_msgs_only() {
+    let (_open, gate) = watch::channel(false);
+    let export = ActorExport::spawn(Export { gate, exported: 0 });
+    export.send_into(ExportMsg::Export(vec![])).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    for _ in 0..100 {
+        export.send_into(ExportMsg::Compact).await.unwrap();
+    }
+    export.send_into(ExportMsg::Export(vec![])).await.unwrap();
+
+    let queued = export.queue_snapshot();
+    assert_eq!(queued.len(), 64);
+    assert_eq!(queued.last().unwrap().variant, "Export");
+    assert!(queued[..63].iter().all(|q| q.variant == "Compact"));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use tokio_actor::actors;
+
+#[actors]
+mod login {
+    #[derive(Debug)]
+    #[wire(derive(Debug, Clone, PartialEq))]
+    pub enum LoginMsg {
+        #[trace(fields(user, password))]
+        Login {
+            user: String,
+            #[redact]
+            password: String,
+            resp: bool,
+        },
+        Rotate(String, #[redact(keep)] String),
+        Logout,
+    }
+
+    #[derive(Default)]
+    pub struct Login {
+        pub logins: u64,
+    }
+
+    impl Login {
+        async fn process(&mut self, msg: LoginMsg) {
+            if let LoginMsg::Login { password, resp, .. } = msg {
+                self.logins += 1;
+                if let Some(r) = resp {
+                    let _ = r.send(password == "hunter2");
+                }
+            }
+        }
+    }
+}
+
+use login::{ActorLogin, Login, LoginMsg, LoginMsgWire};
+
+fn login() -> LoginMsg {
+    LoginMsg::Login {
+        user: "ann".into(),
+        password: "hunter2".into(),
+        resp: None,
+    }
+}
+
+#[test]
+fn debug_masks_redacted_fields() {
+    assert_eq!(
+        format!("{:?}", login()),
+        r#"Login { user: "ann", password: ***, resp: None }"#
+    );
+    let rotate = LoginMsg::Rotate("ann".into(), "s3cret".into());
+    assert_eq!(format!("{:?}", rotate), r#"Rotate("ann", ***)"#);
+    assert_eq!(format!("{:?}", LoginMsg::Logout), "Logout");
+    let pretty = format!("{:#?}", login());
+    assert!(!pretty.contains("hunter2"), "{}", pretty);
+}
+
+#[test]
+fn wire_mirrors_leave_redacted_fields_out_unless_kept() {
+    assert_eq!(
+        LoginMsgWire::from(login()),
+        LoginMsgWire::Login { user: "ann".into() }
+    );
+    assert_eq!(
+        LoginMsgWire::from(LoginMsg::Rotate("ann".into(), "s3cret".into())),
+        LoginMsgWire::Rotate("ann".into(), "s3cret".into())
+    );
+}
+
+#[tokio::test]
+async fn redaction_leaves_the_msg_itself_alone() {
+    let mut handle = ActorLogin::spawn(Login::default());
+    assert_eq!(handle.login(login()).await, Ok(true));
+}
+
+#[cfg(feature = "tracing")]
+#[tokio::test]
+async fn spans_record_redacted_fields_as_stars() {
+    use common::SpanFields;
+
+    let spans = SpanFields::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    let (mut handle, run) = ActorLogin::start(Login::default());
+    let ask = handle.login(login());
+    let (answer, _state) = tokio::join!(ask, async move {
+        drop(handle);
+        run.await
+    });
+    assert_eq!(answer, Ok(true));
+    let fields = spans.fields();
+    assert!(
+        fields.contains(&r#"user="ann""#.to_string()),
+        "{:?}",
+        fields
+    );
+    assert!(
+        fields.contains(&r#"password="***""#.to_string()),
+        "{:?}",
+        fields
+    );
+    assert!(
+        !fields.iter().any(|f| f.contains("hunter2")),
+        "{:?}",
+        fields
+    );
+}
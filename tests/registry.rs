@@ -0,0 +1,142 @@
+mod common;
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors(registry, ready_timeout = "1s")]
+mod services {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    pub enum StorageMsg {
+        Loaded { resp: bool },
+    }
+
+    // loads its snapshot in `on_start`
+    pub struct Storage {
+        pub load: Duration,
+        pub fail: bool,
+        pub loaded: Arc<AtomicBool>,
+    }
+
+    impl Storage {
+        async fn on_start(&mut self) -> Result<(), String> {
+            tokio::time::sleep(self.load).await;
+            if self.fail {
+                return Err("snapshot is corrupt".to_string());
+            }
+            self.loaded.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn process(&mut self, msg: StorageMsg) {
+            match msg {
+                StorageMsg::Loaded { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.loaded.load(Ordering::SeqCst));
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum IndexerMsg {
+        Index(u64),
+        // each indexed doc, with whether the snapshot was loaded by then
+        Indexed { resp: Vec<(u64, bool)> },
+    }
+
+    #[actor(await_ready(Storage))]
+    pub struct Indexer {
+        pub storage_loaded: Arc<AtomicBool>,
+        pub indexed: Vec<(u64, bool)>,
+    }
+
+    impl Indexer {
+        async fn process(&mut self, msg: IndexerMsg) {
+            match msg {
+                IndexerMsg::Index(doc) => {
+                    let loaded = self.storage_loaded.load(Ordering::SeqCst);
+                    self.indexed.push((doc, loaded));
+                }
+                IndexerMsg::Indexed { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.indexed.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use services::prelude::*;
+use services::{Indexer, Storage};
+
+fn states(load: Duration, fail: bool) -> (Storage, Indexer) {
+    let loaded = Arc::new(AtomicBool::new(false));
+    let storage = Storage {
+        load,
+        fail,
+        loaded: loaded.clone(),
+    };
+    let indexer = Indexer {
+        storage_loaded: loaded,
+        indexed: vec![],
+    };
+    (storage, indexer)
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_dependent_processes_nothing_before_its_dependency_is_ready() {
+    let (storage, indexer) = states(Duration::from_millis(200), false);
+    let (mut actors, ready) = Actors::start_all(storage, indexer);
+    // queued while the snapshot loads
+    actors
+        .indexer
+        .send_into(IndexerMsg::Index(1))
+        .await
+        .unwrap();
+    actors
+        .indexer
+        .send_into(IndexerMsg::Index(2))
+        .await
+        .unwrap();
+    within(ready).await.unwrap().unwrap();
+    let indexed = within(actors.indexer.indexed(IndexerMsg::Indexed { resp: None })).await;
+    assert_eq!(indexed, Ok(vec![(1, true), (2, true)]));
+    let loaded = within(actors.storage.loaded(StorageMsg::Loaded { resp: None })).await;
+    assert_eq!(loaded, Ok(true));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_dependency_that_isnt_ready_in_time_fails_startup() {
+    let (storage, indexer) = states(Duration::from_secs(5), false);
+    let e = match within(Actors::spawn_all(storage, indexer)).await {
+        Ok(_actors) => panic!("started with storage still loading"),
+        Err(e) => e,
+    };
+    assert_eq!(e.actor, "Storage");
+    let not_ready = e.error.downcast_ref::<NotReady>().unwrap();
+    assert_eq!(not_ready.limit, Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_dependent_never_starts_when_its_dependency_failed() {
+    let (storage, indexer) = states(Duration::from_millis(10), true);
+    let (mut actors, ready) = Actors::start_all(storage, indexer);
+    actors
+        .indexer
+        .send_into(IndexerMsg::Index(1))
+        .await
+        .unwrap();
+    let e = within(ready).await.unwrap().unwrap_err();
+    assert_eq!(e.actor, "Storage");
+    assert_eq!(e.error.to_string(), "snapshot is corrupt");
+    let indexed = within(actors.indexer.indexed(IndexerMsg::Indexed { resp: None })).await;
+    assert!(matches!(indexed, Err(ActorError::MailboxClosed { .. })));
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::within;
+use tokio::sync::mpsc;
+use tokio_actor::actors;
+
+#[actors(registry)]
+mod system {
+    use tokio::sync::mpsc;
+
+    pub enum CacheMsg {
+        Warm { resp: bool },
+    }
+
+    pub struct Cache {
+        pub stopped: mpsc::UnboundedSender<&'static str>,
+    }
+
+    impl Cache {
+        async fn on_start(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn process(&mut self, msg: CacheMsg) {
+            let CacheMsg::Warm { resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(true);
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.stopped.send("Cache");
+        }
+    }
+
+    pub enum DbMsg {
+        Open { resp: bool },
+    }
+
+    // fails to connect when `up` is false
+    pub struct Db {
+        pub up: bool,
+    }
+
+    impl Db {
+        async fn on_start(&mut self) -> Result<(), String> {
+            match self.up {
+                true => Ok(()),
+                false => Err("connection refused".to_string()),
+            }
+        }
+
+        async fn process(&mut self, msg: DbMsg) {
+            let DbMsg::Open { resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(true);
+            }
+        }
+    }
+
+    pub enum HttpMsg {
+        Serving { resp: bool },
+    }
+
+    pub struct Http {
+        pub stopped: mpsc::UnboundedSender<&'static str>,
+    }
+
+    impl Http {
+        async fn on_start(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn process(&mut self, msg: HttpMsg) {
+            let HttpMsg::Serving { resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(true);
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.stopped.send("Http");
+        }
+    }
+}
+
+use system::prelude::*;
+use system::{Cache, Db, Http};
+
+fn states(up: bool) -> (Cache, Db, Http, mpsc::UnboundedReceiver<&'static str>) {
+    let (stopped, on_stop) = mpsc::unbounded_channel();
+    let cache = Cache {
+        stopped: stopped.clone(),
+    };
+    let http = Http { stopped };
+    (cache, Db { up }, http, on_stop)
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_failed_start_names_the_actor_and_stops_the_others_in_reverse() {
+    let (cache, db, http, mut on_stop) = states(false);
+    let e = match within(Actors::spawn_all(cache, db, http)).await {
+        Ok(_actors) => panic!("started without a database"),
+        Err(e) => e,
+    };
+    assert_eq!(e.actor, "Db");
+    assert_eq!(e.error.to_string(), "connection refused");
+    assert_eq!(within(on_stop.recv()).await, Some("Http"));
+    assert_eq!(within(on_stop.recv()).await, Some("Cache"));
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_actor_is_ready_once_spawn_all_resolves() {
+    let (cache, db, http, mut on_stop) = states(true);
+    let mut actors = within(Actors::spawn_all(cache, db, http)).await.unwrap();
+    let warm = within(actors.cache.warm(CacheMsg::Warm { resp: None })).await;
+    let open = within(actors.db.open(DbMsg::Open { resp: None })).await;
+    let serving = within(actors.http.serving(HttpMsg::Serving { resp: None })).await;
+    assert_eq!((warm, open, serving), (Ok(true), Ok(true), Ok(true)));
+    assert!(on_stop.try_recv().is_err());
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod squares {
+    // collects whatever it's pushed
+    pub enum SinkMsg {
+        Push(u64),
+        Drain { resp: Vec<u64> },
+    }
+
+    pub struct Sink {
+        pub got: Vec<u64>,
+    }
+
+    impl Sink {
+        async fn process(&mut self, msg: SinkMsg) {
+            match msg {
+                SinkMsg::Push(n) => self.got.push(n),
+                SinkMsg::Drain { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(std::mem::take(&mut self.got));
+                    }
+                }
+            }
+        }
+    }
+
+    // squares each number and pushes the result to the handle in the request
+    #[wire(derive(Debug, PartialEq))]
+    pub enum SquareMsg {
+        Square { n: u64, reply_to: ActorSink },
+        Squared { resp: u64 },
+    }
+
+    pub struct Square {
+        pub done: u64,
+    }
+
+    impl Square {
+        async fn process(&mut self, msg: SquareMsg) {
+            match msg {
+                SquareMsg::Square { n, reply_to } => {
+                    self.done += 1;
+                    let _ = reply_to.send_into(SinkMsg::Push(n * n)).await;
+                }
+                SquareMsg::Squared { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.done);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use squares::prelude::*;
+use squares::{Sink, Square};
+
+#[tokio::test(start_paused = true)]
+async fn results_go_to_the_sink_in_the_request() {
+    let mut sink = ActorSink::spawn(Sink { got: vec![] });
+    let mut square = ActorSquare::spawn(Square { done: 0 });
+    for n in 1..=4 {
+        let msg = SquareMsg::Square {
+            n,
+            reply_to: sink.clone(),
+        };
+        square.send_into(msg).await.unwrap();
+    }
+    let squared = within(square.squared(SquareMsg::Squared { resp: None })).await;
+    assert_eq!(squared, Ok(4));
+    let got = within(sink.drain(SinkMsg::Drain { resp: None })).await;
+    assert_eq!(got, Ok(vec![1, 4, 9, 16]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn wire_copies_leave_the_handle_out() {
+    let sink = ActorSink::spawn(Sink { got: vec![] });
+    let msg = SquareMsg::Square {
+        n: 3,
+        reply_to: sink,
+    };
+    let wire: SquareMsgWire = msg.into();
+    assert_eq!(wire, SquareMsgWire::Square { n: 3 });
+}
@@ -0,0 +1,102 @@
+use tokio_actor::actors;
+
+// never spawned, only its routes are looked at
+#[actors]
+#[allow(dead_code)]
+mod catalog {
+    #[derive(Debug, Clone)]
+    pub struct Item {
+        pub sku: String,
+        pub price: u64,
+    }
+
+    pub enum CatalogMsg {
+        #[timeout(ms = 250)]
+        Get { sku: String, resp: Option<Item> },
+        #[latest_only(key = "sku")]
+        Reprice { sku: String, price: u64 },
+        #[bounded(16)]
+        Import(Vec<Item>, bool),
+        #[stream]
+        Scan { prefix: &'static str, resp: Item },
+        #[deprecated]
+        Clear,
+        #[cfg(any())]
+        Gone,
+    }
+
+    #[derive(Default)]
+    pub struct Catalog {
+        pub items: Vec<Item>,
+    }
+
+    impl Catalog {
+        #[allow(deprecated)]
+        async fn process(&mut self, msg: CatalogMsg) {
+            match msg {
+                CatalogMsg::Get { sku, resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.items.iter().find(|i| i.sku == sku).cloned());
+                    }
+                }
+                CatalogMsg::Reprice { sku, price } => {
+                    for item in self.items.iter_mut().filter(|i| i.sku == sku) {
+                        item.price = price;
+                    }
+                }
+                CatalogMsg::Import(items, replace) => {
+                    if replace {
+                        self.items.clear();
+                    }
+                    self.items.extend(items);
+                }
+                CatalogMsg::Scan { prefix, resp } => {
+                    if let Some(r) = resp {
+                        for item in self.items.iter().filter(|i| i.sku.starts_with(prefix)) {
+                            let _ = r.send(item.clone());
+                        }
+                    }
+                }
+                CatalogMsg::Clear => self.items.clear(),
+            }
+        }
+    }
+}
+
+use catalog::prelude::*;
+
+#[test]
+fn the_routes_list_every_variant_in_order() {
+    let names: Vec<&str> = CatalogMsg::ROUTES.iter().map(|r| r.variant).collect();
+    assert_eq!(names, ["Get", "Reprice", "Import", "Scan", "Clear"]);
+}
+
+#[test]
+fn the_routes_match_the_enum() {
+    let routes = CatalogMsg::ROUTES;
+    assert_eq!(
+        routes[0],
+        RouteInfo {
+            variant: "Get",
+            fields: &[("sku", "String")],
+            resp: Some("Option<Item>"),
+            stream: false,
+            sink: false,
+            timeout_ms: Some(250),
+            bounded: None,
+            latest_key: None,
+            deprecated: false,
+            internal: false,
+        }
+    );
+    assert_eq!(routes[1].fields, &[("sku", "String"), ("price", "u64")]);
+    assert_eq!(routes[1].resp, None);
+    assert_eq!(routes[1].latest_key, Some("sku"));
+    assert_eq!(routes[2].fields, &[("0", "Vec<Item>"), ("1", "bool")]);
+    assert_eq!(routes[2].bounded, Some(16));
+    assert_eq!(routes[3].fields, &[("prefix", "&'static str")]);
+    assert_eq!(routes[3].resp, Some("Item"));
+    assert!(routes[3].stream);
+    assert!(routes[4].fields.is_empty());
+    assert!(routes[4].deprecated);
+}
@@ -0,0 +1,78 @@
+use tokio::runtime::{Builder, Runtime};
+use tokio_actor::actors;
+
+#[actors(runtime_check)]
+mod cache {
+    pub enum CacheMsg {
+        Get { resp: u64 },
+    }
+
+    #[derive(Default)]
+    pub struct Cache {
+        pub hits: u64,
+    }
+
+    impl Cache {
+        async fn process(&mut self, msg: CacheMsg) {
+            match msg {
+                CacheMsg::Get { resp } => {
+                    self.hits += 1;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.hits);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use cache::{ActorCache, ActorError, Cache, CacheMsg, CloseReason};
+
+fn runtime() -> Runtime {
+    Builder::new_current_thread().enable_all().build().unwrap()
+}
+
+fn get() -> CacheMsg {
+    CacheMsg::Get { resp: None }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn asking_across_a_shut_down_runtime_fails_fast() {
+    // like a `#[tokio::test]` runtime, gone once its test is over
+    let first = runtime();
+    let mut cache = first.block_on(async { ActorCache::spawn(Cache::default()) });
+    assert_eq!(first.block_on(cache.get(get())), Ok(1));
+    drop(first);
+    let second = runtime();
+    assert_eq!(
+        second.block_on(cache.get(get())),
+        Err(ActorError::RuntimeGone)
+    );
+}
+
+#[test]
+fn an_actor_that_stopped_is_just_closed() {
+    let first = runtime();
+    let mut cache = first.block_on(async {
+        let cache = ActorCache::spawn(Cache::default());
+        cache.stop_within(std::time::Duration::ZERO).await.unwrap();
+        cache
+    });
+    drop(first);
+    let second = runtime();
+    assert!(matches!(
+        second.block_on(cache.get(get())),
+        Err(ActorError::MailboxClosed {
+            reason: CloseReason::Stopped | CloseReason::Stopping
+        })
+    ));
+}
+
+#[test]
+fn the_same_runtime_keeps_working() {
+    let first = runtime();
+    let mut cache = first.block_on(async { ActorCache::spawn(Cache::default()) });
+    assert_eq!(first.block_on(cache.get(get())), Ok(1));
+    assert_eq!(first.block_on(cache.get(get())), Ok(2));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use futures::stream::{FuturesUnordered, StreamExt};
+use tokio_actor::actors;
+
+// two replicas answering the same lookup after their own delay
+#[actors]
+mod replicas {
+    use std::time::Duration;
+
+    pub enum ReplicaMsg {
+        Lookup { key: u64, resp: String },
+    }
+
+    pub struct Replica {
+        pub name: &'static str,
+        pub delay: Duration,
+    }
+
+    impl Replica {
+        async fn process(&mut self, msg: ReplicaMsg) {
+            let ReplicaMsg::Lookup { key, resp } = msg;
+            tokio::time::sleep(self.delay).await;
+            if let Some(r) = resp {
+                let _ = r.send(format!("{}:{}", self.name, key));
+            }
+        }
+    }
+}
+
+use replicas::{ActorReplica, Replica, ReplicaMsg};
+
+fn spawn(name: &'static str, ms: u64) -> ActorReplica {
+    ActorReplica::spawn(Replica {
+        name,
+        delay: Duration::from_millis(ms),
+    })
+}
+
+fn lookup(key: u64) -> ReplicaMsg {
+    ReplicaMsg::Lookup { key, resp: None }
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_faster_replica_wins_the_select() {
+    let (mut east, mut west) = (spawn("east", 30), spawn("west", 10));
+    let answer = tokio::select! {
+        v = east.lookup(lookup(1)) => v,
+        v = west.lookup(lookup(1)) => v,
+        _ = tokio::time::sleep(Duration::from_millis(50)) => panic!("both replicas too slow"),
+    };
+    assert_eq!(answer, Ok("west:1".to_string()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_sleep_wins_over_slow_replicas() {
+    let (mut east, mut west) = (spawn("east", 80), spawn("west", 90));
+    let mut east_ask = east.lookup(lookup(2));
+    let mut west_ask = west.lookup(lookup(2));
+    let timed_out = tokio::select! {
+        _ = &mut east_ask => false,
+        _ = &mut west_ask => false,
+        _ = tokio::time::sleep(Duration::from_millis(50)) => true,
+    };
+    assert!(timed_out);
+    // the asks were sent when created, losing the select doesn't cancel them
+    assert_eq!(within(east_ask).await, Ok("east:2".to_string()));
+    assert_eq!(within(west_ask).await, Ok("west:2".to_string()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn replies_come_in_as_they_are_ready() {
+    let (mut east, mut west) = (spawn("east", 20), spawn("west", 10));
+    let mut asks: FuturesUnordered<_> = [east.lookup(lookup(3)), west.lookup(lookup(4))]
+        .into_iter()
+        .collect();
+    let mut answers = vec![];
+    while let Some(answer) = within(asks.next()).await {
+        answers.push(answer.unwrap());
+    }
+    assert_eq!(answers, ["west:4", "east:3"]);
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use std::sync::OnceLock;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod shared {
+    pub enum CounterMsg {
+        Add(u64),
+        Get { resp: u64 },
+    }
+
+    pub struct Counter {
+        pub count: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add(n) => self.count += n,
+                CounterMsg::Get { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.count);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use shared::prelude::*;
+use shared::Counter;
+
+fn send_sync<T: Send + Sync + 'static>() {}
+
+#[test]
+fn handles_are_send_and_sync() {
+    send_sync::<ActorCounter>();
+    send_sync::<ActorError>();
+}
+
+#[test]
+fn one_handle_serves_several_threads() {
+    static COUNTER: OnceLock<ActorCounter> = OnceLock::new();
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let counter = COUNTER.get_or_init(|| {
+        let _guard = rt.enter();
+        ActorCounter::spawn(Counter { count: 0 })
+    });
+    // threads outside the runtime, sharing the handle by reference
+    std::thread::scope(|s| {
+        for n in 1..=4 {
+            let rt = rt.handle();
+            s.spawn(move || {
+                let add = counter.send_into(CounterMsg::Add(n));
+                rt.block_on(add).unwrap();
+            });
+        }
+    });
+    let total = rt.block_on(within(counter.clone().get(CounterMsg::Get { resp: None })));
+    assert_eq!(total, Ok(10));
+}
@@ -0,0 +1,134 @@
+#![cfg(feature = "tower")]
+
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use futures::FutureExt;
+use tokio::sync::watch;
+use tokio_actor::actors;
+use tower::{Service, ServiceExt};
+
+#[actors]
+mod calc {
+    pub enum AdderMsg {
+        #[service]
+        Add {
+            a: u64,
+            b: u64,
+            resp: u64,
+        },
+        Reset,
+    }
+
+    pub struct Adder {
+        pub calls: u64,
+    }
+
+    impl Adder {
+        async fn process(&mut self, msg: AdderMsg) {
+            match msg {
+                AdderMsg::Add { a, b, resp } => {
+                    self.calls += 1;
+                    if let Some(r) = resp {
+                        let _ = r.send(a + b);
+                    }
+                }
+                AdderMsg::Reset => self.calls = 0,
+            }
+        }
+    }
+}
+
+#[actors(bounded = 2)]
+mod gated {
+    use tokio::sync::watch;
+
+    pub enum StepMsg {
+        #[service]
+        Step { n: u64, resp: u64 },
+    }
+
+    // holds every msg until the gate opens
+    pub struct Step {
+        pub gate: watch::Receiver<bool>,
+    }
+
+    impl Step {
+        async fn process(&mut self, msg: StepMsg) {
+            match msg {
+                StepMsg::Step { n, resp } => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                    if let Some(r) = resp {
+                        let _ = r.send(n * 2);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn calls_answer_through_the_service_variant() {
+    use calc::prelude::*;
+    let mut adder = ActorAdder::spawn(calc::Adder { calls: 0 });
+    let msg = AdderMsg::Add {
+        a: 2,
+        b: 3,
+        resp: None,
+    };
+    let sum = within(adder.ready().await.unwrap().call(msg)).await;
+    assert_eq!(sum, Ok(5));
+    let sums = within(adder.clone().oneshot(AdderMsg::Add {
+        a: 1,
+        b: 1,
+        resp: None,
+    }))
+    .await;
+    assert_eq!(sums, Ok(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn other_variants_are_turned_down() {
+    use calc::prelude::*;
+    let mut adder = ActorAdder::spawn(calc::Adder { calls: 0 });
+    let reset = within(adder.ready().await.unwrap().call(AdderMsg::Reset)).await;
+    assert!(matches!(reset, Err(ActorError::InvalidMsgType)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn poll_ready_fails_once_the_mailbox_is_closed() {
+    use calc::prelude::*;
+    let mut adder = ActorAdder::spawn(calc::Adder { calls: 0 });
+    within(adder.stop_within(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    let ready = within(adder.ready()).await;
+    assert!(matches!(ready, Err(ActorError::MailboxClosed { .. })));
+}
+
+#[tokio::test(start_paused = true)]
+async fn poll_ready_waits_for_room_in_a_bounded_mailbox() {
+    use gated::prelude::*;
+    let (open, gate) = watch::channel(false);
+    let mut step = ActorStep::spawn(gated::Step { gate });
+    let mut answers = vec![];
+    // one msg in `process` and two in the mailbox
+    for n in 0..3 {
+        let svc = within(step.ready()).await.unwrap();
+        answers.push(svc.call(StepMsg::Step { n, resp: None }));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(step.ready().now_or_never().is_none());
+
+    open.send(true).unwrap();
+    let svc = within(step.ready()).await.unwrap();
+    answers.push(svc.call(StepMsg::Step { n: 3, resp: None }));
+    let mut doubled = vec![];
+    for answer in answers {
+        doubled.push(within(answer).await.unwrap());
+    }
+    assert_eq!(doubled, [0, 2, 4, 6]);
+}
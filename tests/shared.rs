@@ -0,0 +1,76 @@
+mod common;
+
+use std::sync::Arc;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod fanout {
+    use std::sync::Arc;
+
+    pub enum ReaderMsg {
+        Load {
+            #[shared]
+            payload: Vec<u8>,
+            #[shared]
+            name: Arc<str>,
+        },
+        Held {
+            resp: Option<Arc<Vec<u8>>>,
+        },
+    }
+
+    pub struct Reader {
+        pub held: Option<Arc<Vec<u8>>>,
+    }
+
+    impl Reader {
+        async fn process(&mut self, msg: ReaderMsg) {
+            match msg {
+                ReaderMsg::Load { payload, name } => {
+                    assert_eq!(&*name, "blob");
+                    self.held = Some(payload);
+                }
+                ReaderMsg::Held { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.held.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use fanout::prelude::*;
+use fanout::Reader;
+
+#[tokio::test(start_paused = true)]
+async fn every_recipient_gets_the_same_payload() {
+    let mut readers: Vec<_> = (0..10)
+        .map(|_| ActorReader::spawn(Reader { held: None }))
+        .collect();
+    let payload: Arc<Vec<u8>> = vec![7u8; 1 << 20].into();
+    let name: Arc<str> = "blob".into();
+    for reader in &readers {
+        let load = ReaderMsg::Load {
+            payload: payload.clone(),
+            name: name.clone(),
+        };
+        reader.send_into(load).await.unwrap();
+    }
+    for reader in &mut readers {
+        let held = within(reader.held(ReaderMsg::Held { resp: None })).await;
+        assert!(Arc::ptr_eq(&held.unwrap().unwrap(), &payload));
+    }
+    // ours and one per reader, the payload was never copied
+    assert_eq!(Arc::strong_count(&payload), 11);
+}
+
+#[test]
+fn routes_list_shared_fields_as_their_arc() {
+    let fields = ReaderMsg::ROUTES[0].fields;
+    assert_eq!(fields[0], ("payload", "std::sync::Arc<Vec<u8>>"));
+    // already an `Arc`, kept as written
+    assert_eq!(fields[1], ("name", "Arc<str>"));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod quoting {
+    use std::time::Duration;
+
+    pub enum PricerMsg {
+        Price { item: String, resp: u64 },
+    }
+
+    // takes 100ms per price
+    pub struct Pricer {}
+
+    impl Pricer {
+        async fn process(&mut self, msg: PricerMsg) {
+            let PricerMsg::Price { item, resp } = msg;
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            if let Some(r) = resp {
+                let _ = r.send(item.len() as u64 * 10);
+            }
+        }
+    }
+
+    pub enum QuoteMsg {
+        Request { item: String, pricer: ActorPricer },
+        Note { text: String },
+        Log { resp: Vec<String> },
+    }
+
+    pub struct Quote {
+        pub log: Vec<String>,
+    }
+
+    impl Quote {
+        async fn process(&mut self, msg: QuoteMsg, ctx: &ActorQuoteCtx) {
+            match msg {
+                QuoteMsg::Request { item, mut pricer } => {
+                    let ask = pricer.price(PricerMsg::Price {
+                        item: item.clone(),
+                        resp: None,
+                    });
+                    ctx.spawn_ask(ask, move |price, this: &mut Quote| {
+                        this.log.push(format!("{} costs {:?}", item, price));
+                    });
+                }
+                QuoteMsg::Note { text } => self.log.push(text),
+                QuoteMsg::Log { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.log.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use quoting::{ActorPricer, ActorQuote, Pricer, Quote, QuoteMsg};
+
+fn note(text: &str) -> QuoteMsg {
+    QuoteMsg::Note { text: text.into() }
+}
+
+#[tokio::test(start_paused = true)]
+async fn other_msgs_are_processed_while_the_ask_is_pending() {
+    let pricer = ActorPricer::new().await;
+    let mut quote = ActorQuote::spawn(Quote { log: vec![] });
+    let request = QuoteMsg::Request {
+        item: "tea".into(),
+        pricer,
+    };
+    quote.send_into(request).await.unwrap();
+    for text in ["first", "second"] {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        quote.send_into(note(text)).await.unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let log = within(quote.log(QuoteMsg::Log { resp: None })).await;
+    assert_eq!(log.unwrap(), ["first", "second"]);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    quote.send_into(note("third")).await.unwrap();
+    let log = within(quote.log(QuoteMsg::Log { resp: None })).await;
+    assert_eq!(
+        log.unwrap(),
+        ["first", "second", "tea costs Ok(30)", "third"]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn continuations_run_in_the_order_their_asks_complete() {
+    let mut quote = ActorQuote::spawn(Quote { log: vec![] });
+    // one pricer per item, so both are priced at the same time
+    for item in ["coffee", "tea"] {
+        let pricer = ActorPricer::spawn(Pricer {});
+        let request = QuoteMsg::Request {
+            item: item.into(),
+            pricer,
+        };
+        quote.send_into(request).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let log = within(quote.log(QuoteMsg::Log { resp: None })).await;
+    assert_eq!(log.unwrap(), ["coffee costs Ok(60)", "tea costs Ok(30)"]);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod shards {
+    pub enum PartitionMsg {
+        Index { resp: usize },
+    }
+
+    pub struct Partition {
+        pub index: usize,
+    }
+
+    impl Partition {
+        async fn process(&mut self, msg: PartitionMsg) {
+            match msg {
+                PartitionMsg::Index { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.index);
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum WorkerMsg {
+        Index { resp: usize },
+    }
+
+    #[actor(pool, shared_queue)]
+    pub struct Worker {
+        pub index: usize,
+    }
+
+    impl Worker {
+        async fn process(&mut self, msg: WorkerMsg) {
+            match msg {
+                WorkerMsg::Index { resp } => {
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.index);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use shards::{
+    ActorError, ActorPartition, ActorWorker, CloseReason, Partition, PartitionMsg, Worker,
+    WorkerMsg,
+};
+
+fn index() -> PartitionMsg {
+    PartitionMsg::Index { resp: None }
+}
+
+#[tokio::test]
+async fn every_instance_gets_its_index() {
+    let mut partitions = ActorPartition::spawn_many(8, |index| Partition { index });
+    assert_eq!(partitions.len(), 8);
+    for (i, partition) in partitions.iter_mut().enumerate() {
+        assert_eq!(within(partition.index(index())).await, Ok(i));
+    }
+    let ids: Vec<_> = partitions.iter().map(|p| p.id()).collect();
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    for (i, id) in ids.iter().enumerate() {
+        assert_eq!(id.index(), Some(i));
+        assert_eq!(
+            id.to_string(),
+            format!("spawn_many::shards::Partition[{}]#{}", i, id.value())
+        );
+    }
+    let single = ActorPartition::spawn(Partition { index: 0 }).id();
+    assert_eq!(single.index(), None);
+    assert_eq!(
+        single.to_string(),
+        format!("spawn_many::shards::Partition#{}", single.value())
+    );
+}
+
+#[tokio::test]
+async fn one_call_stops_them_all() {
+    let mut partitions = ActorPartition::spawn_many(8, |index| Partition { index });
+    assert_eq!(
+        within(ActorPartition::stop_all(
+            &partitions,
+            Duration::from_secs(1)
+        ))
+        .await,
+        Ok(0)
+    );
+    for partition in partitions.iter_mut() {
+        assert_eq!(
+            within(partition.index(index())).await,
+            Err(ActorError::MailboxClosed {
+                reason: CloseReason::Stopped
+            })
+        );
+    }
+    // stopping again answers with the earlier count
+    assert_eq!(
+        within(ActorPartition::stop_all(&partitions, Duration::ZERO)).await,
+        Ok(0)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_pool_of_many() {
+    let mut pool = ActorWorker::spawn_pool_many(4, |index| Worker { index });
+    let asks: Vec<_> = (0..4)
+        .map(|_| pool.index(WorkerMsg::Index { resp: None }))
+        .collect();
+    let start = tokio::time::Instant::now();
+    let mut answered = vec![];
+    for ask in asks {
+        answered.push(within(ask).await.unwrap());
+    }
+    answered.sort();
+    assert_eq!(answered, vec![0, 1, 2, 3]);
+    // side by side, one msg per worker
+    assert_eq!(start.elapsed(), Duration::from_millis(10));
+}
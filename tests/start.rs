@@ -0,0 +1,88 @@
+mod common;
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll};
+
+use common::within;
+use futures::task::noop_waker_ref;
+use tokio_actor::actors;
+
+#[actors]
+mod counting {
+    pub enum CounterMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            let CounterMsg::Add { n, resp } = msg;
+            self.total += n;
+            if let Some(r) = resp {
+                let _ = r.send(self.total);
+            }
+        }
+    }
+}
+
+use counting::{ActorCounter, Counter, CounterMsg};
+
+fn add(n: u64) -> CounterMsg {
+    CounterMsg::Add { n, resp: None }
+}
+
+#[tokio::test]
+async fn msgs_are_only_processed_while_the_future_is_polled() {
+    let (mut counter, run) = ActorCounter::start(Counter { total: 0 });
+    let mut run = pin!(run);
+    let mut cx = Context::from_waker(noop_waker_ref());
+
+    let mut first = counter.add(add(1));
+    let mut second = counter.add(add(2));
+    tokio::task::yield_now().await;
+    // nothing polls the actor, so nothing is answered
+    assert!(futures::poll!(&mut first).is_pending());
+
+    assert!(run.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(futures::poll!(&mut first), Poll::Ready(Ok(1)));
+    assert_eq!(futures::poll!(&mut second), Poll::Ready(Ok(3)));
+
+    let mut third = counter.add(add(3));
+    assert!(futures::poll!(&mut third).is_pending());
+    assert!(run.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(futures::poll!(&mut third), Poll::Ready(Ok(6)));
+
+    // once every handle is gone the future ends with the final state
+    drop(counter);
+    match run.as_mut().poll(&mut cx) {
+        Poll::Ready(state) => assert_eq!(state.total, 6),
+        Poll::Pending => panic!("still running without handles"),
+    }
+}
+
+#[tokio::test]
+async fn dropping_the_future_stops_the_actor() {
+    let (mut counter, run) = ActorCounter::start(Counter { total: 0 });
+    let ask = counter.add(add(1));
+    drop(run);
+    assert!(within(ask).await.is_err());
+    assert!(within(counter.add(add(1))).await.is_err());
+}
+
+#[tokio::test]
+async fn runs_inside_a_select_next_to_other_work() {
+    let (mut counter, run) = ActorCounter::start(Counter { total: 0 });
+    let mut run = pin!(run);
+    let total = tokio::select! {
+        _ = &mut run => unreachable!("the handle is still alive"),
+        total = async {
+            counter.add(add(4)).await.unwrap();
+            counter.add(add(5)).await
+        } => total,
+    };
+    assert_eq!(total, Ok(9));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod metered {
+    pub enum MeterMsg {
+        Work(u64),
+        Stats { resp: ActorStats },
+    }
+
+    #[derive(Default)]
+    pub struct Meter {
+        #[actor_stats]
+        pub stats: ActorStats,
+    }
+
+    impl Meter {
+        async fn process(&mut self, msg: MeterMsg) {
+            match msg {
+                MeterMsg::Work(ms) => {
+                    tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+                }
+                MeterMsg::Stats { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.stats);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use metered::{ActorMeter, ActorStats, Meter, MeterMsg};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[tokio::test(start_paused = true)]
+async fn counters_advance_with_every_msg() {
+    let mut meter = ActorMeter::spawn(Meter::default());
+    let first = within(meter.stats(MeterMsg::Stats { resp: None }))
+        .await
+        .unwrap();
+    assert_eq!(first.processed, 1);
+    assert_eq!(first.queued, 0);
+    assert!(first.started.is_some());
+    assert!(first.last_msg.is_some());
+
+    within(meter.send_into(MeterMsg::Work(10))).await.unwrap();
+    within(meter.send_into(MeterMsg::Work(10))).await.unwrap();
+    let second = within(meter.stats(MeterMsg::Stats { resp: None }))
+        .await
+        .unwrap();
+    assert_eq!(second.processed, 4);
+    assert_eq!(second.started, first.started);
+    assert_eq!(second.last_msg.unwrap() - first.last_msg.unwrap(), ms(20));
+    assert_eq!(second.uptime(), ms(20));
+}
+
+#[tokio::test(start_paused = true)]
+async fn queued_counts_what_waits_behind_the_current_msg() {
+    let meter = ActorMeter::spawn(Meter::default());
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    within(meter.send_into(MeterMsg::Work(10))).await.unwrap();
+    within(meter.send_into(MeterMsg::Stats { resp: Some(tx) }))
+        .await
+        .unwrap();
+    within(meter.send_into(MeterMsg::Work(10))).await.unwrap();
+    // taken out while the second work msg is still queued
+    let stats = within(rx).await.unwrap();
+    assert_eq!(stats.processed, 2);
+    assert_eq!(stats.queued, 1);
+}
+
+#[test]
+fn stats_start_empty() {
+    let stats = ActorStats::default();
+    assert_eq!(stats.processed, 0);
+    assert_eq!(stats.uptime(), Duration::ZERO);
+}
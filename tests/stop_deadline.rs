@@ -0,0 +1,95 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{drain, within};
+use tokio::sync::mpsc;
+use tokio_actor::actors;
+
+#[actors]
+mod queue {
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+
+    pub enum JobMsg {
+        Job { n: u64, resp: u64 },
+    }
+
+    // 10ms per job, reports how many it finished when it stops
+    pub struct Job {
+        pub done: u64,
+        pub stopped: mpsc::UnboundedSender<u64>,
+    }
+
+    impl Job {
+        async fn process(&mut self, msg: JobMsg) {
+            let JobMsg::Job { n, resp } = msg;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.done += 1;
+            if let Some(r) = resp {
+                let _ = r.send(n);
+            }
+        }
+
+        async fn on_stop(&mut self) {
+            let _ = self.stopped.send(self.done);
+        }
+    }
+}
+
+use queue::prelude::*;
+use queue::Job;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[tokio::test(start_paused = true)]
+async fn drops_what_the_deadline_leaves() {
+    let (stopped, mut on_stop) = mpsc::unbounded_channel();
+    let mut job = ActorJob::spawn(Job { done: 0, stopped });
+    let started = tokio::time::Instant::now();
+    let asks: Vec<_> = (0..100)
+        .map(|n| tokio::spawn(job.job(JobMsg::Job { n, resp: None })))
+        .collect();
+    tokio::task::yield_now().await;
+
+    // jobs start every 10ms, the one started at 250ms is finished
+    assert_eq!(drain(job.stop_within(ms(255))).await, 74);
+    assert_eq!(started.elapsed(), ms(260));
+    assert_eq!(within(on_stop.recv()).await, Some(26));
+
+    let mut answered = 0;
+    for ask in asks {
+        match within(ask).await.unwrap() {
+            Ok(_n) => answered += 1,
+            Err(ActorError::ResponseDropped) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+    assert_eq!(answered, 26);
+    // the dropped ones failed by then, no need to wait any longer
+    assert_eq!(started.elapsed(), ms(260));
+}
+
+#[tokio::test(start_paused = true)]
+async fn refuses_new_msgs_while_draining() {
+    let (stopped, _on_stop) = mpsc::unbounded_channel();
+    let mut job = ActorJob::spawn(Job { done: 0, stopped });
+    let first = tokio::spawn(job.job(JobMsg::Job { n: 1, resp: None }));
+    tokio::task::yield_now().await;
+    let stop = tokio::spawn({
+        let job = job.clone();
+        async move { job.stop_within(ms(100)).await }
+    });
+    tokio::task::yield_now().await;
+    let late = within(job.job(JobMsg::Job { n: 2, resp: None })).await;
+    assert_closed!(late, Stopping);
+    assert_eq!(within(first).await.unwrap(), Ok(1));
+    assert_eq!(within(stop).await.unwrap(), Ok(0));
+    assert_closed!(
+        within(job.job(JobMsg::Job { n: 3, resp: None })).await,
+        Stopped
+    );
+}
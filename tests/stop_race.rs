@@ -0,0 +1,121 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{drain, within};
+use tokio_actor::actors;
+
+#[actors]
+mod queue {
+    use std::time::Duration;
+
+    pub enum WorkMsg {
+        Work { n: u64, resp: u64 },
+    }
+
+    // 1ms per msg, so a backlog builds up behind the askers
+    pub struct Work {}
+
+    impl Work {
+        async fn process(&mut self, msg: WorkMsg) {
+            let WorkMsg::Work { n, resp } = msg;
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            if let Some(r) = resp {
+                let _ = r.send(n);
+            }
+        }
+    }
+}
+
+use queue::prelude::*;
+use queue::Work;
+
+// how each ask of one asker ended, in order
+#[derive(Debug, Default)]
+struct Outcomes {
+    answered: usize,
+    dropped: usize,
+    closed: usize,
+}
+
+// asks until the mailbox turns it down, every failure has to be one a stop explains
+async fn ask_until_closed(mut work: ActorWork, n: u64) -> Outcomes {
+    let mut outcomes = Outcomes::default();
+    loop {
+        match within(work.work(WorkMsg::Work { n, resp: None })).await {
+            Ok(m) => {
+                assert_eq!(m, n);
+                assert_eq!(outcomes.closed, 0, "answered after being turned down");
+                outcomes.answered += 1;
+            }
+            Err(ActorError::ResponseDropped) => {
+                assert_eq!(outcomes.closed, 0, "accepted after being turned down");
+                outcomes.dropped += 1;
+            }
+            Err(ActorError::MailboxClosed {
+                reason: CloseReason::Stopping | CloseReason::Stopped,
+            }) => {
+                outcomes.closed += 1;
+                return outcomes;
+            }
+            Err(e) => panic!("an ask racing a stop failed with {:?}", e),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn asks_racing_a_stop_never_hang() {
+    for _ in 0..20 {
+        let mut work = ActorWork::spawn(Work {});
+        let askers: Vec<_> = (0..64)
+            .map(|n| tokio::spawn(ask_until_closed(work.clone(), n)))
+            .collect();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        let abandoned = drain(work.stop_within(Duration::from_millis(20))).await;
+        // the actor exited, sends now say so
+        assert_closed!(work.work(WorkMsg::Work { n: 0, resp: None }).await, Stopped);
+
+        let mut dropped = 0;
+        for asker in askers {
+            let outcomes = within(asker).await.unwrap();
+            assert_eq!(outcomes.closed, 1);
+            dropped += outcomes.dropped;
+        }
+        // every msg the stop dropped is an ask told so, and nothing else was
+        assert_eq!(dropped, abandoned);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn sends_after_the_stop_request_fail_fast() {
+    let mut work = ActorWork::spawn(Work {});
+    let queued: Vec<_> = (0..10)
+        .map(|n| tokio::spawn(work.work(WorkMsg::Work { n, resp: None })))
+        .collect();
+    tokio::task::yield_now().await;
+
+    let stopper = work.clone();
+    let stop = tokio::spawn(async move { stopper.stop_within(Duration::from_secs(1)).await });
+    tokio::task::yield_now().await;
+
+    // still draining, turned down without being queued
+    assert_closed!(
+        work.work(WorkMsg::Work { n: 99, resp: None }).await,
+        Stopping
+    );
+    assert_closed!(
+        work.send_into(WorkMsg::Work { n: 99, resp: None }).await,
+        Stopping
+    );
+    assert!(work.is_closed());
+
+    assert_eq!(within(stop).await.unwrap(), Ok(0));
+    for (n, ask) in queued.into_iter().enumerate() {
+        assert_eq!(within(ask).await.unwrap(), Ok(n as u64));
+    }
+    assert_closed!(
+        work.work(WorkMsg::Work { n: 99, resp: None }).await,
+        Stopped
+    );
+}
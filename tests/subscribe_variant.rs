@@ -0,0 +1,82 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+use tokio_stream::StreamExt;
+
+#[actors]
+mod ledger {
+    #[derive(Clone, Debug)]
+    pub enum LedgerEvent {
+        Added(u64),
+        Moved { from: u32, to: u32 },
+        Cleared,
+    }
+
+    pub enum LedgerMsg {
+        Add(u64),
+        Move(u32, u32),
+        Clear,
+        Total { resp: u64 },
+    }
+
+    pub struct Ledger {
+        #[events]
+        pub events: Emitter<LedgerEvent>,
+        pub total: u64,
+    }
+
+    impl Ledger {
+        async fn process(&mut self, msg: LedgerMsg) {
+            match msg {
+                LedgerMsg::Add(n) => {
+                    self.total += n;
+                    self.events.emit(LedgerEvent::Added(n));
+                }
+                LedgerMsg::Move(from, to) => {
+                    self.events.emit(LedgerEvent::Moved { from, to });
+                }
+                LedgerMsg::Clear => {
+                    self.total = 0;
+                    self.events.emit(LedgerEvent::Cleared);
+                }
+                LedgerMsg::Total { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use ledger::prelude::*;
+use ledger::Ledger;
+
+#[tokio::test(start_paused = true)]
+async fn each_stream_yields_its_variant_only() {
+    let mut ledger = ActorLedger::spawn(Ledger {
+        events: Emitter::new(16),
+        total: 0,
+    });
+    let added = ledger.subscribe_added();
+    let moved = ledger.subscribe_moved();
+    let cleared = ledger.subscribe_cleared();
+    for msg in [
+        LedgerMsg::Add(2),
+        LedgerMsg::Move(1, 2),
+        LedgerMsg::Clear,
+        LedgerMsg::Add(5),
+    ] {
+        ledger.send_into(msg).await.unwrap();
+    }
+    let total = within(ledger.total(LedgerMsg::Total { resp: None })).await;
+    assert_eq!(total, Ok(5));
+    // the streams end once the actor is gone
+    drop(ledger);
+    assert_eq!(within(added.collect::<Vec<_>>()).await, [2, 5]);
+    assert_eq!(within(moved.collect::<Vec<_>>()).await, [(1, 2)]);
+    assert_eq!(within(cleared.collect::<Vec<_>>()).await, [()]);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod flaky {
+    pub enum StepMsg {
+        Step(i64),
+        Sum { resp: i64 },
+        Restarts { resp: u64 },
+    }
+
+    // panics on negative steps, which leaves `pending` half updated
+    #[actor(supervised)]
+    pub struct Step {
+        pub sum: i64,
+        pub pending: Vec<i64>,
+        pub restarts: u64,
+    }
+
+    impl Step {
+        async fn process(&mut self, msg: StepMsg) {
+            match msg {
+                StepMsg::Step(n) => {
+                    self.pending.push(n);
+                    assert!(n >= 0, "negative step {}", n);
+                    self.sum += self.pending.drain(..).sum::<i64>();
+                }
+                StepMsg::Sum { resp } => {
+                    let sum = self.sum;
+                    if sum < 0 {
+                        panic!("sum went negative");
+                    }
+                    if let Some(r) = resp {
+                        let _ = r.send(sum);
+                    }
+                }
+                StepMsg::Restarts { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.restarts);
+                    }
+                }
+            }
+        }
+
+        async fn on_restart(&mut self) {
+            self.pending.clear();
+            self.restarts += 1;
+        }
+    }
+}
+
+use flaky::prelude::*;
+use flaky::Step;
+
+fn spawn() -> ActorStep {
+    ActorStep::spawn(Step {
+        sum: 0,
+        pending: vec![],
+        restarts: 0,
+    })
+}
+
+#[tokio::test]
+async fn carries_on_with_the_next_msg_after_a_panic() {
+    let mut step = spawn();
+    for n in [1, -4, 2] {
+        step.send_into(StepMsg::Step(n)).await.unwrap();
+    }
+    // `on_restart` threw away the half applied step
+    assert_eq!(within(step.sum(StepMsg::Sum { resp: None })).await, Ok(3));
+    let restarts = within(step.restarts(StepMsg::Restarts { resp: None })).await;
+    assert_eq!(restarts, Ok(1));
+    assert_eq!(step.restart_count(), 1);
+    assert_eq!(step.last_panic().unwrap().message, "negative step -4");
+}
+
+#[tokio::test]
+async fn only_the_asker_of_the_panicking_msg_fails() {
+    // panics answering `Sum`, with the reply in hand
+    let mut step = ActorStep::spawn(Step {
+        sum: -1,
+        pending: vec![],
+        restarts: 0,
+    });
+    let mut other = step.clone();
+    let sum = step.sum(StepMsg::Sum { resp: None });
+    let restarts = other.restarts(StepMsg::Restarts { resp: None });
+    assert_eq!(within(sum).await, Err(ActorError::Panicked));
+    assert_eq!(within(restarts).await, Ok(1));
+    assert!(!step.is_closed());
+}
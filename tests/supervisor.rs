@@ -0,0 +1,100 @@
+mod common;
+
+#[path = "../examples/supervisor.rs"]
+#[allow(dead_code)]
+mod example;
+
+use common::{drain, spawn_many, within};
+use example::run;
+use example::supervise::{
+    ActorError, ActorSupervisor, ActorWorker, CloseReason, Job, Supervisor, SupervisorMsg, Worker,
+    WorkerMsg,
+};
+
+fn restarts() -> SupervisorMsg {
+    SupervisorMsg::Restarts { resp: None }
+}
+
+#[tokio::test]
+async fn jobs_are_spread_over_the_workers() {
+    let mut supervisor = ActorSupervisor::spawn(Supervisor::new(3));
+    let asks: Vec<_> = (1..=9)
+        .map(|n| supervisor.run(run(Job::Square(n))))
+        .collect();
+    for (n, ask) in (1..=9u64).zip(asks) {
+        assert_eq!(within(ask).await, Ok(Ok(n * n)));
+    }
+    assert_eq!(within(supervisor.restarts(restarts())).await, Ok(0));
+}
+
+#[tokio::test]
+async fn a_panicking_job_only_fails_its_caller() {
+    let mut supervisor = ActorSupervisor::spawn(Supervisor::new(1));
+    assert_eq!(
+        within(supervisor.run(run(Job::Panic))).await,
+        Ok(Err(ActorError::Panicked))
+    );
+    assert_eq!(within(supervisor.run(run(Job::Square(2)))).await, Ok(Ok(4)));
+    assert_eq!(within(supervisor.restarts(restarts())).await, Ok(1));
+}
+
+#[tokio::test]
+async fn a_worker_stopped_by_an_error_is_replaced() {
+    let mut supervisor = ActorSupervisor::spawn(Supervisor::new(1));
+    assert_eq!(
+        within(supervisor.run(run(Job::Invalid))).await,
+        Ok(Err(ActorError::ResponseDropped))
+    );
+    assert_eq!(within(supervisor.run(run(Job::Square(3)))).await, Ok(Ok(9)));
+    assert_eq!(within(supervisor.restarts(restarts())).await, Ok(1));
+}
+
+#[tokio::test]
+async fn a_failed_worker_drops_what_was_queued_behind_the_error() {
+    let mut worker = ActorWorker::spawn(Worker::default());
+    let failing = worker.run(WorkerMsg::Run {
+        job: Job::Invalid,
+        resp: None,
+    });
+    let queued: Vec<_> = (0..3)
+        .map(|n| {
+            worker.run(WorkerMsg::Run {
+                job: Job::Square(n),
+                resp: None,
+            })
+        })
+        .collect();
+    assert_eq!(within(failing).await, Err(ActorError::ResponseDropped));
+    for ask in queued {
+        assert_eq!(within(ask).await, Err(ActorError::ResponseDropped));
+    }
+    let after = worker.run(WorkerMsg::Run {
+        job: Job::Square(1),
+        resp: None,
+    });
+    assert_closed!(within(after).await, Failed);
+}
+
+#[tokio::test]
+async fn shutdown_stops_every_worker() {
+    let mut workers = spawn_many(2, |_i| ActorWorker::spawn(Worker::default()));
+    let mut supervisor = ActorSupervisor::spawn(Supervisor {
+        workers: workers.clone(),
+        next: 0,
+        replaced: 0,
+    });
+    assert_eq!(
+        within(supervisor.run(run(Job::Square(5)))).await,
+        Ok(Ok(25))
+    );
+    let shutdown = supervisor.shutdown(SupervisorMsg::Shutdown { resp: None });
+    assert_eq!(within(shutdown).await, Ok(0));
+    for worker in workers.iter_mut() {
+        let ask = worker.run(WorkerMsg::Run {
+            job: Job::Square(1),
+            resp: None,
+        });
+        assert_closed!(within(ask).await, Stopped);
+    }
+    assert_eq!(drain(supervisor.stop_within(common::PATIENCE)).await, 0);
+}
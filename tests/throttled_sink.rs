@@ -0,0 +1,169 @@
+#![cfg(feature = "sink")]
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::within;
+use futures::StreamExt;
+use tokio::sync::watch;
+use tokio_actor::actors;
+
+#[actors(bounded = 4)]
+mod pipeline {
+    use tokio::sync::watch;
+
+    pub enum StageMsg {
+        Item(u64),
+        Total { resp: u64 },
+    }
+
+    // a downstream stage that can be held up
+    pub struct Stage {
+        pub gate: watch::Receiver<bool>,
+        pub total: u64,
+    }
+
+    impl Stage {
+        async fn process(&mut self, msg: StageMsg) {
+            match msg {
+                StageMsg::Item(n) => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                    self.total += n;
+                }
+                StageMsg::Total { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+
+    pub enum WorkerMsg {
+        Item(u64),
+        Total { resp: u64 },
+    }
+
+    #[actor(pool, shared_queue)]
+    pub struct Worker {
+        pub gate: watch::Receiver<bool>,
+        pub total: u64,
+    }
+
+    impl Worker {
+        async fn process(&mut self, msg: WorkerMsg) {
+            match msg {
+                WorkerMsg::Item(n) => {
+                    let _ = self.gate.wait_for(|open| *open).await;
+                    self.total += n;
+                }
+                WorkerMsg::Total { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use pipeline::prelude::*;
+use pipeline::{Stage, Worker};
+
+// 1..=20, counting how many items were taken from the source so far
+fn source<M>(
+    make: fn(u64) -> M,
+) -> (
+    Arc<AtomicUsize>,
+    impl futures::Stream<Item = Result<M, ActorError>>,
+) {
+    let pulled = Arc::new(AtomicUsize::new(0));
+    let counter = pulled.clone();
+    let items = futures::stream::iter(1..=20u64).map(move |n| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(make(n))
+    });
+    (pulled, items)
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_source_pauses_while_the_mailbox_is_full() {
+    let (open, gate) = watch::channel(false);
+    let mut stage = ActorStage::spawn(Stage { gate, total: 0 });
+    let (pulled, items) = source(StageMsg::Item);
+    let forward = tokio::spawn(items.forward(stage.throttled_sink()));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    // one item in `process`, four in the mailbox and one `forward` holds on to
+    assert_eq!(pulled.load(Ordering::SeqCst), 6);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(pulled.load(Ordering::SeqCst), 6);
+
+    open.send(true).unwrap();
+    within(forward).await.unwrap().unwrap();
+    let total = within(stage.total(StageMsg::Total { resp: None })).await;
+    assert_eq!(total, Ok(210));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_shard_that_is_behind_holds_up_the_source() {
+    let (open, gate) = watch::channel(false);
+    let (_always, ready) = watch::channel(true);
+    let shards = ActorStage::spawn_many(2, |index| Stage {
+        gate: if index == 1 {
+            gate.clone()
+        } else {
+            ready.clone()
+        },
+        total: 0,
+    });
+    let (pulled, items) = source(StageMsg::Item);
+    let sink = ActorStage::throttled_shard_sink(shards.clone(), |msg| match msg {
+        StageMsg::Item(n) => *n as usize,
+        _ => 0,
+    });
+    let forward = tokio::spawn(items.forward(sink));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let paused = pulled.load(Ordering::SeqCst);
+    assert!(paused < 20, "pulled {}", paused);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(pulled.load(Ordering::SeqCst), paused);
+
+    open.send(true).unwrap();
+    within(forward).await.unwrap().unwrap();
+    let mut totals = vec![];
+    for mut shard in shards {
+        totals.push(
+            within(shard.total(StageMsg::Total { resp: None }))
+                .await
+                .unwrap(),
+        );
+    }
+    // even items on shard 0, odd ones on shard 1
+    assert_eq!(totals, [110, 100]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_pool_pauses_the_source_once_its_queue_is_full() {
+    let (open, gate) = watch::channel(false);
+    let mut pool = ActorWorker::spawn_pool_many(2, |_index| Worker {
+        gate: gate.clone(),
+        total: 0,
+    });
+    let (pulled, items) = source(WorkerMsg::Item);
+    let forward = tokio::spawn(items.forward(pool.throttled_sink()));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let paused = pulled.load(Ordering::SeqCst);
+    assert!(paused < 20, "pulled {}", paused);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(pulled.load(Ordering::SeqCst), paused);
+
+    open.send(true).unwrap();
+    within(forward).await.unwrap().unwrap();
+    assert_eq!(pulled.load(Ordering::SeqCst), 20);
+    assert!(within(pool.total(WorkerMsg::Total { resp: None }))
+        .await
+        .is_ok());
+}
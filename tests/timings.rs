@@ -0,0 +1,87 @@
+#![cfg(any(feature = "metrics", feature = "tracing"))]
+
+mod common;
+
+use std::time::Duration;
+
+use common::within;
+use tokio::time::Instant;
+use tokio_actor::actors;
+
+#[actors(bounded = 1)]
+mod napping {
+    pub enum NapperMsg {
+        Nap { ms: u64, resp: u64 },
+    }
+
+    #[derive(Default)]
+    pub struct Napper {
+        pub napped: u64,
+    }
+
+    impl Napper {
+        async fn process(&mut self, msg: NapperMsg) {
+            let NapperMsg::Nap { ms, resp } = msg;
+            tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
+            self.napped += ms;
+            if let Some(r) = resp {
+                let _ = r.send(self.napped);
+            }
+        }
+    }
+}
+
+use napping::{ActorNapper, AskTimings, Napper, NapperMsg};
+
+fn nap(ms: u64) -> NapperMsg {
+    NapperMsg::Nap { ms, resp: None }
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[tokio::test(start_paused = true)]
+async fn an_idle_actor_only_spends_processing_time() {
+    let mut napper = ActorNapper::spawn(Napper::default());
+    let start = Instant::now();
+    let (napped, timings) = within(napper.nap_traced(nap(50))).await.unwrap();
+    assert_eq!(napped, 50);
+    assert_eq!(
+        timings,
+        AskTimings {
+            send: ms(0),
+            queued: ms(0),
+            processing: ms(50),
+        }
+    );
+    assert_eq!(timings.total(), start.elapsed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiting_behind_another_msg_is_queue_time() {
+    let mut napper = ActorNapper::spawn(Napper::default());
+    let start = Instant::now();
+    napper.nap_no_wait(nap(100)).await.unwrap();
+    let (napped, timings) = within(napper.nap_traced(nap(50))).await.unwrap();
+    assert_eq!(napped, 150);
+    assert_eq!(timings.send, ms(0));
+    assert_eq!(timings.queued, ms(100));
+    assert_eq!(timings.processing, ms(50));
+    assert_eq!(timings.total(), start.elapsed());
+}
+
+#[tokio::test(start_paused = true)]
+async fn waiting_for_a_mailbox_slot_is_send_time() {
+    let mut napper = ActorNapper::spawn(Napper::default());
+    let start = Instant::now();
+    // the first is taken off the mailbox right away, the second fills it
+    napper.nap_no_wait(nap(100)).await.unwrap();
+    napper.nap_no_wait(nap(100)).await.unwrap();
+    let (napped, timings) = within(napper.nap_traced(nap(50))).await.unwrap();
+    assert_eq!(napped, 250);
+    assert_eq!(timings.send, ms(100));
+    assert_eq!(timings.queued, ms(100));
+    assert_eq!(timings.processing, ms(50));
+    assert_eq!(timings.total(), start.elapsed());
+}
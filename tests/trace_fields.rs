@@ -0,0 +1,127 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use common::SpanFields;
+use tokio_actor::actors;
+
+#[actors]
+mod upload {
+    pub enum StoreMsg {
+        #[trace(fields(key, size, payload))]
+        #[instrument(fields(key, payload))]
+        Put {
+            key: String,
+            #[trace(display)]
+            size: u64,
+            #[trace(skip)]
+            payload: Vec<u8>,
+            resp: usize,
+        },
+        #[trace(skip_all)]
+        Purge {
+            key: String,
+            resp: (),
+        },
+        Touch {
+            key: String,
+            resp: (),
+        },
+    }
+
+    #[derive(Default)]
+    pub struct Store {
+        pub stored: usize,
+    }
+
+    impl Store {
+        async fn process(&mut self, msg: StoreMsg) {
+            match msg {
+                StoreMsg::Put { payload, resp, .. } => {
+                    self.stored += payload.len();
+                    if let Some(r) = resp {
+                        let _ = r.send(self.stored);
+                    }
+                }
+                StoreMsg::Purge { key, resp } | StoreMsg::Touch { key, resp } => {
+                    debug_assert!(!key.is_empty());
+                    if let Some(r) = resp {
+                        let _ = r.send(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+use upload::{ActorStore, Store, StoreMsg};
+
+// runs `msgs` against a fresh actor on this thread, under `spans`
+async fn traced(spans: &SpanFields, msgs: Vec<StoreMsg>) {
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    let mut store = ActorStore::spawn(Store::default());
+    for msg in msgs {
+        match msg {
+            msg @ StoreMsg::Put { .. } => drop(store.put(msg).await.unwrap()),
+            msg @ StoreMsg::Purge { .. } => store.purge(msg).await.unwrap(),
+            msg @ StoreMsg::Touch { .. } => store.touch(msg).await.unwrap(),
+        }
+    }
+}
+
+fn put() -> StoreMsg {
+    StoreMsg::Put {
+        key: "avatar".into(),
+        size: 3,
+        payload: vec![0xAB; 3],
+        resp: None,
+    }
+}
+
+#[tokio::test]
+async fn skipped_fields_never_reach_a_span() {
+    let spans = SpanFields::default();
+    traced(&spans, vec![put()]).await;
+    let fields = spans.fields();
+    assert_eq!(spans.spans(), ["request", "process"]);
+    assert_eq!(
+        fields.iter().filter(|f| *f == r#"key="avatar""#).count(),
+        2,
+        "{:?}",
+        fields
+    );
+    assert!(fields.contains(&"size=3".to_string()), "{:?}", fields);
+    assert!(
+        !fields.iter().any(|f| f.starts_with("payload")),
+        "{:?}",
+        fields
+    );
+    assert!(!fields.iter().any(|f| f.contains("171")), "{:?}", fields);
+}
+
+#[tokio::test]
+async fn skip_all_and_unlisted_variants_record_their_name_only() {
+    let spans = SpanFields::default();
+    let purge = StoreMsg::Purge {
+        key: "secret".into(),
+        resp: None,
+    };
+    let touch = StoreMsg::Touch {
+        key: "secret".into(),
+        resp: None,
+    };
+    traced(&spans, vec![purge, touch]).await;
+    let fields = spans.fields();
+    assert_eq!(spans.spans(), ["process", "process"]);
+    assert!(
+        fields.contains(&r#"variant="Purge""#.to_string()),
+        "{:?}",
+        fields
+    );
+    assert!(
+        fields.contains(&r#"variant="Touch""#.to_string()),
+        "{:?}",
+        fields
+    );
+    assert!(!fields.iter().any(|f| f.contains("secret")), "{:?}", fields);
+}
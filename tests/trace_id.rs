@@ -0,0 +1,50 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use common::{within, SpanFields};
+use tokio_actor::actors;
+
+#[actors]
+mod echo {
+    pub enum EchoMsg {
+        Echo { n: u64, resp: u64 },
+    }
+
+    pub struct Echo {}
+
+    impl Echo {
+        async fn process(&mut self, msg: EchoMsg) {
+            let EchoMsg::Echo { n, resp } = msg;
+            if let Some(r) = resp {
+                let _ = r.send(n);
+            }
+        }
+    }
+}
+
+use echo::{ActorEcho, Echo, EchoMsg};
+
+#[tokio::test]
+async fn process_spans_carry_the_actor_id() {
+    let spans = SpanFields::default();
+    let _guard = tracing::subscriber::set_default(spans.clone());
+    let mut first = ActorEcho::spawn(Echo {});
+    let mut second = ActorEcho::spawn(Echo {});
+    assert_ne!(first.id(), second.id());
+    for echo in [&mut first, &mut second] {
+        let n = within(echo.echo(EchoMsg::Echo { n: 1, resp: None })).await;
+        assert_eq!(n, Ok(1));
+    }
+    let fields = spans.fields();
+    assert_eq!(spans.spans(), ["process", "process"]);
+    for id in [first.id(), second.id()] {
+        let field = format!("actor_id={}", id);
+        assert!(fields.contains(&field), "{:?}", fields);
+    }
+    assert!(
+        fields.contains(&r#"actor="Echo""#.to_string()),
+        "{:?}",
+        fields
+    );
+}
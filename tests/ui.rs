@@ -0,0 +1,12 @@
+// diagnostics of `#[actors]`, each case in `tests/ui` pinned by the `.stderr`
+// next to it. after an intended change:
+// TRYBUILD=overwrite cargo test --test ui
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+    // cases erroring inside the generated code, which `tracing`, `stream` and
+    // `metrics` add more errors to. pinned as built without them
+    #[cfg(not(any(feature = "tracing", feature = "stream", feature = "metrics")))]
+    t.compile_fail("tests/ui/featureless/*.rs");
+}
@@ -0,0 +1,26 @@
+use tokio_actor::actors;
+
+#[actors(registry)]
+mod a {
+    pub enum HttpServerMsg {
+        Get { resp: u64 },
+    }
+
+    pub enum HTTPServerMsg {
+        Get { resp: u64 },
+    }
+
+    pub struct HttpServer {}
+
+    impl HttpServer {
+        async fn process(&mut self, _msg: HttpServerMsg) {}
+    }
+
+    pub struct HTTPServer {}
+
+    impl HTTPServer {
+        async fn process(&mut self, _msg: HTTPServerMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use tokio_actor::actors;
+
+#[actors(registry)]
+mod m {
+    pub enum FooMsg {
+        Get { resp: u64 },
+    }
+
+    #[actor(await_ready(Bar))]
+    pub struct Foo;
+
+    impl Foo {
+        async fn process(&mut self, _msg: FooMsg) {}
+    }
+
+    pub enum BarMsg {
+        Get { resp: u64 },
+    }
+
+    #[actor(await_ready(Foo))]
+    pub struct Bar;
+
+    impl Bar {
+        async fn process(&mut self, _msg: BarMsg) {}
+    }
+}
+
+fn main() {}
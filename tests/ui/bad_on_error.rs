@@ -0,0 +1,19 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum CounterMsg {
+        Get { resp: u64 },
+    }
+
+    #[actor(on_error = "retry")]
+    pub struct Counter {}
+
+    impl Counter {
+        async fn process(&mut self, _msg: CounterMsg) -> Result<(), String> {
+            return Ok(());
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum LoginMsg {
+        Login {
+            #[redact(hash)]
+            password: String,
+            resp: bool,
+        },
+    }
+
+    pub struct Login {}
+
+    impl Login {
+        async fn process(&mut self, _msg: LoginMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use tokio_actor::actors;
+
+#[actors(rename_all = "kebab-case")]
+mod a {
+    pub enum CounterMsg {
+        Get { resp: u64 },
+    }
+
+    pub struct Counter {}
+
+    impl Counter {
+        async fn process(&mut self, _msg: CounterMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum CounterMsg {
+        // a unit is needed in a string, bare milliseconds are an integer
+        #[timeout("90")]
+        Get { resp: u64 },
+    }
+
+    pub struct Counter {}
+
+    impl Counter {
+        async fn process(&mut self, _msg: CounterMsg) {}
+    }
+}
+
+fn main() {}
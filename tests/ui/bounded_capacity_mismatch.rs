@@ -0,0 +1,20 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum FeedMsg {
+        #[bounded(64)]
+        Tick(u64),
+        #[bounded(128)]
+        Frame(Vec<u8>),
+        Stats { resp: u64 },
+    }
+
+    pub struct Feed {}
+
+    impl Feed {
+        async fn process(&mut self, _msg: FeedMsg) {}
+    }
+}
+
+fn main() {}
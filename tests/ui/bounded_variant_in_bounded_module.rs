@@ -0,0 +1,18 @@
+use tokio_actor::actors;
+
+#[actors(bounded = 16)]
+mod a {
+    pub enum FeedMsg {
+        #[bounded(64)]
+        Tick(u64),
+        Stats { resp: u64 },
+    }
+
+    pub struct Feed {}
+
+    impl Feed {
+        async fn process(&mut self, _msg: FeedMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum CounterMsg {
+        Get { resp: u64 },
+    }
+
+    #[actor(idle_timeout = "18446744073709551615h")]
+    pub struct Counter {}
+
+    impl Counter {
+        async fn process(&mut self, _msg: CounterMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum CounterMsg {}
+
+    pub struct Counter {}
+
+    impl Counter {
+        async fn process(&mut self, _msg: CounterMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use tokio_actor::actors;
+
+#[actors]
+mod m {
+    use std::rc::Rc;
+
+    pub enum ConfigMsg {
+        Get { resp: Rc<String> },
+    }
+
+    pub struct Config {
+        pub value: String,
+    }
+
+    impl Config {
+        async fn process(&mut self, msg: ConfigMsg) {
+            match msg {
+                ConfigMsg::Get { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(Rc::new(self.value.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum CounterMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    pub struct Counter {}
+
+    impl Counter {
+        async fn process(&mut self, _msg: CounterMsg) {}
+    }
+
+    impl ActorCounter {
+        fn __ask(&self) {}
+    }
+}
+
+fn main() {}
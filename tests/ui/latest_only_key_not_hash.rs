@@ -0,0 +1,21 @@
+use tokio_actor::actors;
+
+#[actors]
+mod m {
+    #[derive(Clone, PartialEq)]
+    pub struct Position(pub f64, pub f64);
+
+    pub enum FooMsg {
+        #[latest_only(key = "at")]
+        Move { at: Position },
+        Get { resp: u64 },
+    }
+
+    pub struct Foo;
+
+    impl Foo {
+        async fn process(&mut self, _msg: FooMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,18 @@
+use tokio_actor::actors;
+
+#[actors(max_variants = 2)]
+mod a {
+    pub enum CounterMsg {
+        Add(u64),
+        Sub(u64),
+        Get { resp: u64 },
+    }
+
+    pub struct Counter {}
+
+    impl Counter {
+        async fn process(&mut self, _msg: CounterMsg) {}
+    }
+}
+
+fn main() {}
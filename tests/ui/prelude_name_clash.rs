@@ -0,0 +1,30 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum CounterMsg {
+        Add { n: u64, resp: u64 },
+    }
+
+    pub struct Counter {
+        pub total: u64,
+    }
+
+    // taken by the generated one
+    pub struct Responder;
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add { n, resp } => {
+                    self.total += n;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.total);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use tokio_actor::actors;
+
+#[actors]
+mod m {
+    pub enum FooMsg {
+        #[propagate_panic]
+        Get { resp: u64 },
+    }
+
+    pub struct Foo;
+
+    impl Foo {
+        async fn process(&mut self, _msg: FooMsg) {}
+    }
+}
+
+fn main() {}
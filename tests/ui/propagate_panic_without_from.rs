@@ -0,0 +1,20 @@
+use tokio_actor::actors;
+
+#[actors]
+mod m {
+    #[derive(Debug)]
+    pub struct LoadError;
+
+    pub enum FooMsg {
+        #[propagate_panic]
+        Load { resp: Result<u64, LoadError> },
+    }
+
+    pub struct Foo;
+
+    impl Foo {
+        async fn process(&mut self, _msg: FooMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use tokio_actor::actors;
+
+#[actors]
+mod m {
+    pub fn count(rows: Vec<u8>) {
+        let _ = rows;
+    }
+
+    pub enum FooMsg {
+        #[resp(from = Vec<u8>, map = count)]
+        Touch { resp: () },
+    }
+
+    pub struct Foo;
+
+    impl Foo {
+        async fn process(&mut self, _msg: FooMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum ListMsg {
+        List { resp: dyn Iterator<Item = u32> },
+    }
+
+    pub struct List {}
+
+    impl List {
+        async fn process(&mut self, _msg: ListMsg) {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use tokio_actor::actors;
+
+#[actors]
+mod m {
+    pub struct Rows(pub Vec<u64>);
+
+    pub enum FooMsg {
+        #[resp(try_from = Rows)]
+        First { resp: u64 },
+    }
+
+    pub struct Foo;
+
+    impl Foo {
+        async fn process(&mut self, _msg: FooMsg) {}
+    }
+}
+
+fn main() {}
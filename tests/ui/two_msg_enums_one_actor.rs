@@ -0,0 +1,21 @@
+use tokio_actor::actors;
+
+#[actors]
+mod a {
+    pub enum OrderMsg {
+        Place { resp: u64 },
+    }
+
+    #[msg_for(Order)]
+    pub enum Refund {
+        Issue { resp: u64 },
+    }
+
+    pub struct Order {}
+
+    impl Order {
+        async fn process(&mut self, _msg: OrderMsg) {}
+    }
+}
+
+fn main() {}
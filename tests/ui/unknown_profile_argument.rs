@@ -0,0 +1,7 @@
+use tokio_actor::actors_profile;
+
+actors_profile! {
+    production = { bounded = 1024, bached = 32 }
+}
+
+fn main() {}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod counter {
+    #[derive(Debug)]
+    pub enum CounterMsg {
+        Add(u64),
+        Reset,
+        Get { resp: u64 },
+    }
+
+    pub struct Counter {
+        pub count: u64,
+    }
+
+    impl Counter {
+        async fn process(&mut self, msg: CounterMsg) {
+            match msg {
+                CounterMsg::Add(n) => self.count += n,
+                CounterMsg::Reset => self.count = 0,
+                CounterMsg::Get { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.count);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use counter::{ActorCounter, Counter, CounterMsg};
+
+// exhaustive without a `_` arm: the control msgs of the run loop (stops, flushes,
+// `wait_until_idle`) travel next to the user's enum, never inside it
+#[deny(unreachable_patterns)]
+fn describe(msg: &CounterMsg) -> &'static str {
+    match msg {
+        CounterMsg::Add(_) => "add",
+        CounterMsg::Reset => "reset",
+        CounterMsg::Get { .. } => "get",
+    }
+}
+
+#[test]
+fn the_enum_keeps_its_own_variants_only() {
+    let msgs = [
+        CounterMsg::Add(1),
+        CounterMsg::Reset,
+        CounterMsg::Get { resp: None },
+    ];
+    let names: Vec<_> = msgs.iter().map(describe).collect();
+    assert_eq!(names, ["add", "reset", "get"]);
+    assert_eq!(CounterMsg::ROUTES.len(), msgs.len());
+    // the user's derives still apply
+    assert_eq!(format!("{:?}", msgs[0]), "Add(1)");
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_handle_takes_the_plain_enum() {
+    let mut counter = ActorCounter::spawn(Counter { count: 0 });
+    for msg in [CounterMsg::Add(2), CounterMsg::Reset, CounterMsg::Add(3)] {
+        counter.send_into(msg).await.unwrap();
+    }
+    let count = within(counter.get(CounterMsg::Get { resp: None })).await;
+    assert_eq!(count, Ok(3));
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod bank {
+    pub enum AccMsg {
+        #[validate(check_deposit)]
+        Deposit {
+            amount: i64,
+            resp: i64,
+        },
+        #[validate(check_memo)]
+        Memo {
+            text: String,
+        },
+        // every msg `process` saw, rejected ones never show up
+        Seen {
+            resp: usize,
+        },
+    }
+
+    fn check_deposit(req: &AccDepositRequest) -> Result<(), ValidationError> {
+        match req.amount > 0 {
+            true => Ok(()),
+            false => Err(ValidationError("amount must be positive")),
+        }
+    }
+
+    fn check_memo(req: &AccMemoRequest) -> Result<(), ValidationError> {
+        match req.text.len() <= 8 {
+            true => Ok(()),
+            false => Err(ValidationError("memo too long")),
+        }
+    }
+
+    pub struct Acc {
+        pub balance: i64,
+        pub seen: usize,
+    }
+
+    impl Acc {
+        async fn process(&mut self, msg: AccMsg) {
+            self.seen += 1;
+            match msg {
+                AccMsg::Deposit { amount, resp } => {
+                    self.balance += amount;
+                    if let Some(r) = resp {
+                        let _ = r.send(self.balance);
+                    }
+                }
+                AccMsg::Memo { text } => debug_assert!(text.len() <= 8),
+                AccMsg::Seen { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.seen);
+                    }
+                }
+            }
+        }
+    }
+}
+
+use bank::prelude::*;
+use bank::Acc;
+
+fn deposit(amount: i64) -> AccMsg {
+    AccMsg::Deposit { amount, resp: None }
+}
+
+fn rejected<T: std::fmt::Debug>(result: Result<T, ActorError>, why: &str) {
+    match result {
+        Err(ActorError::Rejected(ValidationError(reason))) => assert_eq!(reason, why),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn rejected_msgs_never_reach_the_actor() {
+    let mut acc = ActorAcc::spawn(Acc {
+        balance: 0,
+        seen: 0,
+    });
+    rejected(
+        within(acc.deposit(deposit(-5))).await,
+        "amount must be positive",
+    );
+    rejected(acc.send_into(deposit(0)).await, "amount must be positive");
+    let memo = AccMsg::Memo {
+        text: "far too long".into(),
+    };
+    rejected(acc.send_into(memo).await, "memo too long");
+    let seen = within(acc.seen(AccMsg::Seen { resp: None })).await;
+    assert_eq!(seen, Ok(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn accepted_msgs_go_through_as_usual() {
+    let mut acc = ActorAcc::spawn(Acc {
+        balance: 0,
+        seen: 0,
+    });
+    assert_eq!(within(acc.deposit(deposit(5))).await, Ok(5));
+    acc.send_into(deposit(2)).await.unwrap();
+    let memo = AccMsg::Memo {
+        text: "rent".into(),
+    };
+    acc.send_into(memo).await.unwrap();
+    let seen = within(acc.seen(AccMsg::Seen { resp: None })).await;
+    assert_eq!(seen, Ok(4));
+    assert_eq!(within(acc.deposit(deposit(1))).await, Ok(8));
+}
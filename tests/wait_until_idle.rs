@@ -0,0 +1,91 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::within;
+use tokio_actor::actors;
+
+#[actors]
+mod slow {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    pub enum WorkMsg {
+        Work(u64),
+        Quit,
+        Done { resp: u64 },
+    }
+
+    // takes 10ms per msg, `done` is read without asking it
+    pub struct Work {
+        pub done: Arc<AtomicU64>,
+    }
+
+    impl Work {
+        async fn process(&mut self, msg: WorkMsg, ctx: &ActorWorkCtx) {
+            match msg {
+                WorkMsg::Work(n) => {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    self.done.fetch_add(n, Ordering::SeqCst);
+                }
+                WorkMsg::Quit => ctx.stop(),
+                WorkMsg::Done { resp } => {
+                    if let Some(r) = resp {
+                        let _ = r.send(self.done.load(Ordering::SeqCst));
+                    }
+                }
+            }
+        }
+    }
+}
+
+use slow::prelude::*;
+use slow::Work;
+
+fn spawn() -> (ActorWork, Arc<AtomicU64>) {
+    let done = Arc::new(AtomicU64::new(0));
+    let work = ActorWork::spawn(Work { done: done.clone() });
+    (work, done)
+}
+
+#[tokio::test(start_paused = true)]
+async fn resolves_once_everything_queued_is_processed() {
+    let (mut work, done) = spawn();
+    for n in 1..=5 {
+        work.send_into(WorkMsg::Work(n)).await.unwrap();
+    }
+    assert_eq!(done.load(Ordering::SeqCst), 0);
+    within(work.wait_until_idle()).await.unwrap();
+    assert_eq!(done.load(Ordering::SeqCst), 15);
+    // and right away when there's nothing to wait for
+    within(work.wait_until_idle()).await.unwrap();
+    let asked = within(work.done(WorkMsg::Done { resp: None })).await;
+    assert_eq!(asked, Ok(15));
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_for_msgs_sent_while_waiting() {
+    let (work, done) = spawn();
+    work.send_into(WorkMsg::Work(1)).await.unwrap();
+    let sender = work.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        sender.send_into(WorkMsg::Work(2)).await.unwrap();
+    });
+    within(work.wait_until_idle()).await.unwrap();
+    assert_eq!(done.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn fails_when_the_actor_stops_first() {
+    let (work, _done) = spawn();
+    work.send_into(WorkMsg::Quit).await.unwrap();
+    work.send_into(WorkMsg::Work(1)).await.unwrap();
+    let idle = within(work.wait_until_idle()).await;
+    assert!(matches!(idle, Err(ActorError::MailboxClosed { .. })));
+}
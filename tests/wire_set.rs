@@ -0,0 +1,92 @@
+use std::collections::{BTreeSet, HashSet};
+
+use tokio_actor::actors;
+
+#[actors]
+mod events {
+    #[wire(derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord))]
+    pub enum AuditMsg {
+        Login { user: String },
+        Logout { user: String },
+        Lookup { user: String, resp: bool },
+    }
+
+    pub struct Audit {}
+
+    impl Audit {
+        async fn process(&mut self, msg: AuditMsg) {
+            if let AuditMsg::Lookup { resp: Some(r), .. } = msg {
+                let _ = r.send(true);
+            }
+        }
+    }
+}
+
+use events::{AuditMsg, AuditMsgWire};
+
+fn emitted(users: &[&str]) -> Vec<AuditMsg> {
+    let mut msgs = vec![];
+    for user in users {
+        msgs.push(AuditMsg::Login {
+            user: user.to_string(),
+        });
+        msgs.push(AuditMsg::Lookup {
+            user: user.to_string(),
+            resp: None,
+        });
+        msgs.push(AuditMsg::Logout {
+            user: user.to_string(),
+        });
+    }
+    msgs
+}
+
+#[test]
+fn wire_copies_compare_as_sets_regardless_of_order() {
+    let forward: BTreeSet<AuditMsgWire> = emitted(&["ann", "bob"])
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let backward: BTreeSet<AuditMsgWire> = emitted(&["bob", "ann"])
+        .into_iter()
+        .rev()
+        .map(Into::into)
+        .collect();
+    assert_eq!(forward, backward);
+    let hashed: HashSet<AuditMsgWire> = forward.iter().cloned().collect();
+    assert_eq!(hashed.len(), 6);
+}
+
+#[test]
+fn wire_copies_order_by_variant_then_fields() {
+    let sorted: Vec<AuditMsgWire> = emitted(&["bob", "ann"])
+        .into_iter()
+        .map(Into::into)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let login = |user: &str| AuditMsgWire::Login { user: user.into() };
+    let logout = |user: &str| AuditMsgWire::Logout { user: user.into() };
+    let lookup = |user: &str| AuditMsgWire::Lookup { user: user.into() };
+    assert_eq!(
+        sorted,
+        [
+            login("ann"),
+            login("bob"),
+            logout("ann"),
+            logout("bob"),
+            lookup("ann"),
+            lookup("bob"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn deriving_on_the_copy_leaves_the_live_enum_usable() {
+    let mut audit = events::ActorAudit::new().await;
+    let lookup = AuditMsg::Lookup {
+        user: "ann".into(),
+        resp: None,
+    };
+    assert_eq!(audit.lookup(lookup).await, Ok(true));
+}